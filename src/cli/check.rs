@@ -0,0 +1,59 @@
+use std::path::Path;
+
+use crate::config::{Config, SourceMap};
+
+use super::hook::{config_paths, note_untrusted};
+
+/// Execute the check subcommand: load a config and report its errors and
+/// lint warnings on stdout.
+///
+/// The config is found the same way as for `hook`, from the current
+/// directory. Exits 1 when the config does not load (or none is found), 0
+/// otherwise, warnings or not.
+pub fn run(config_path: Option<&Path>) {
+    let cwd = std::env::current_dir().unwrap_or_default();
+    note_untrusted(config_path, &cwd);
+    let paths = config_paths(config_path, &cwd);
+    if paths.is_empty() {
+        println!("no config file found");
+        std::process::exit(1);
+    }
+    let name = display_paths(&paths);
+    match Config::load_with_warnings(&paths) {
+        Ok((config, warnings)) => {
+            for warning in &warnings {
+                println!(
+                    "{}: warning: {}",
+                    located(&config.sources, warning.line),
+                    warning.message
+                );
+            }
+            match warnings.len() {
+                0 => println!("{name}: ok"),
+                1 => println!("{name}: ok, 1 warning"),
+                n => println!("{name}: ok, {n} warnings"),
+            }
+        }
+        Err(e) => {
+            println!("{name}: error: {e}");
+            std::process::exit(1);
+        }
+    }
+}
+
+/// The config files, as given, for summary lines.
+pub(super) fn display_paths(paths: &[std::path::PathBuf]) -> String {
+    paths
+        .iter()
+        .map(|p| p.display().to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// `path:line` for a document line, naming the file it is in.
+pub(super) fn located(sources: &SourceMap, line: usize) -> String {
+    match sources.locate(line) {
+        (Some(path), line) => format!("{}:{line}", path.display()),
+        (None, line) => format!("line {line}"),
+    }
+}
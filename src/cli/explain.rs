@@ -0,0 +1,87 @@
+use std::path::Path;
+
+use serde_json::{json, Value};
+
+use crate::config::Config;
+use crate::decision;
+use crate::protocol::HookInput;
+
+use super::hook::{config_paths, note_untrusted};
+
+/// What to explain: a tool call described on the command line.
+pub struct ExplainRequest<'a> {
+    /// Tool name, `Bash` when not given.
+    pub tool: Option<&'a str>,
+    /// The command for Bash; joined with spaces.
+    pub command: &'a [String],
+    /// The path for file tools.
+    pub path: Option<&'a str>,
+    /// Permission mode as Claude Code names it (`default`, `dontAsk`, ...).
+    pub mode: Option<&'a str>,
+    /// Working directory, the current one when not given.
+    pub cwd: Option<&'a Path>,
+}
+
+/// Execute the explain subcommand: trace how the hook would decide a tool
+/// call and print each step to stdout.
+///
+/// The config is found the same way as for `hook`, from the explained call's
+/// working directory. Unlike `hook`, errors are reported on stderr with a
+/// non-zero exit, since a person is reading.
+pub fn run(config_path: Option<&Path>, request: &ExplainRequest) {
+    let input = match build_input(request) {
+        Ok(input) => input,
+        Err(e) => fail(&e),
+    };
+    note_untrusted(config_path, Path::new(&input.cwd));
+    let paths = config_paths(config_path, Path::new(&input.cwd));
+    let config = match (!paths.is_empty()).then(|| Config::load_layers(&paths)) {
+        Some(Ok(config)) => Some(config),
+        Some(Err(e)) => fail(&format!("Config error: {e}")),
+        None => None,
+    };
+    print!("{}", decision::explain(&input, config.as_ref()));
+}
+
+/// Synthesize the PreToolUse input Claude Code would send for the request.
+fn build_input(request: &ExplainRequest) -> Result<HookInput, String> {
+    let tool = request.tool.unwrap_or("Bash");
+    let tool_input = match (tool, request.path) {
+        ("Bash", _) if request.command.is_empty() => {
+            return Err("Bash needs a command after `--`".to_string())
+        }
+        ("Bash", _) => json!({ "command": request.command.join(" ") }),
+        (_, Some(path)) => json!({ path_field(tool): path }),
+        (_, None) => json!({}),
+    };
+    let cwd = match request.cwd {
+        Some(cwd) => cwd.to_path_buf(),
+        None => std::env::current_dir().map_err(|e| format!("Error: {e}"))?,
+    };
+    let mut input = json!({
+        "session_id": "explain",
+        "transcript_path": "",
+        "cwd": cwd.to_string_lossy(),
+        "hook_event_name": "PreToolUse",
+        "tool_name": tool,
+        "tool_input": tool_input,
+    });
+    if let Some(mode) = request.mode {
+        input["permission_mode"] = Value::from(mode);
+    }
+    serde_json::from_value(input).map_err(|e| format!("Error: {e}"))
+}
+
+/// The `tool_input` field a file tool reads its path from.
+fn path_field(tool: &str) -> &'static str {
+    match tool {
+        "NotebookEdit" => "notebook_path",
+        "Glob" | "Grep" | "LS" => "path",
+        _ => "file_path",
+    }
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("{message}");
+    std::process::exit(2);
+}
@@ -1,18 +1,73 @@
 use std::io::Read;
 use std::path::{Path, PathBuf};
 
+use serde::Serialize;
+
+use crate::config::trust::TrustStore;
 use crate::config::Config;
 use crate::decision;
-use crate::protocol::HookOutput;
+use crate::protocol::{HookInput, HookOutput};
+
+use super::log::{self, LogRecord};
+
+/// System-wide config layer, shared by every user on the machine.
+const SYSTEM_CONFIG: &str = "/etc/claude-permissions-hook/config.kdl";
+
+/// Project config layer, looked up in the working directory's ancestors.
+const PROJECT_CONFIG: &str = ".claude/permissions.kdl";
 
-/// Discover config path when `--config` is not provided.
+/// The config files to load: the `--config` path alone, or the discovered
+/// layers.
 ///
-/// Checks in order:
-/// 1. `$CLAUDE_PERMISSIONS_HOOK_CONFIG` environment variable
-/// 2. `~/.config/claude-permissions-hook/config.kdl`
+/// Without `--config`, layers are checked in order, least specific first:
+/// 1. `/etc/claude-permissions-hook/config.kdl` (system)
+/// 2. `$CLAUDE_PERMISSIONS_HOOK_CONFIG`, or else
+///    `~/.config/claude-permissions-hook/config.kdl` (user)
+/// 3. `.claude/permissions.kdl` in `cwd` or its nearest ancestor with one
+///    (project), only if the user trusts that project
 ///
-/// Returns `None` if no config is found (no-config mode: ask for everything).
-fn discover_config() -> Option<PathBuf> {
+/// Returns an empty list if no config is found (no-config mode: ask for
+/// everything).
+pub(super) fn config_paths(config_path: Option<&Path>, cwd: &Path) -> Vec<PathBuf> {
+    if let Some(path) = config_path {
+        return vec![path.to_path_buf()];
+    }
+    let system = Some(PathBuf::from(SYSTEM_CONFIG)).filter(|p| p.exists());
+    let project = project_config(cwd).filter(|p| is_trusted(p));
+    [system, user_config(), project]
+        .into_iter()
+        .flatten()
+        .collect()
+}
+
+/// The nearest `.claude/permissions.kdl` in `cwd` or its ancestors.
+pub(super) fn project_config(cwd: &Path) -> Option<PathBuf> {
+    cwd.ancestors()
+        .map(|dir| dir.join(PROJECT_CONFIG))
+        .find(|p| p.exists())
+}
+
+/// The project root a project config belongs to: the directory holding
+/// its `.claude` directory.
+pub(super) fn project_root(project_config: &Path) -> &Path {
+    project_config.ancestors().nth(2).unwrap_or(Path::new("/"))
+}
+
+/// Whether the user's trust store lets a project config load. An unreadable
+/// store trusts nothing.
+fn is_trusted(project_config: &Path) -> bool {
+    trust_store_path()
+        .and_then(|path| TrustStore::load(&path).ok())
+        .is_some_and(|store| store.trusts(project_root(project_config), project_config))
+}
+
+/// The user's trust store, next to the XDG config.
+pub(super) fn trust_store_path() -> Option<PathBuf> {
+    home_dir().map(|home| home.join(".config/claude-permissions-hook/trusted.kdl"))
+}
+
+/// The user's config layer: the environment variable, or the XDG path.
+fn user_config() -> Option<PathBuf> {
     // 1. Environment variable
     if let Ok(path) = std::env::var("CLAUDE_PERMISSIONS_HOOK_CONFIG") {
         let p = PathBuf::from(path);
@@ -36,54 +91,94 @@ fn home_dir() -> Option<PathBuf> {
     std::env::var_os("HOME").map(PathBuf::from)
 }
 
+/// Tell a person, on stderr, about a project config discovery skipped
+/// because the project is not trusted.
+pub(super) fn note_untrusted(config_path: Option<&Path>, cwd: &Path) {
+    if config_path.is_some() {
+        return;
+    }
+    if let Some(project) = project_config(cwd).filter(|p| !is_trusted(p)) {
+        eprintln!(
+            "note: ignoring untrusted project config {}; run `claude-permissions-hook trust` in {} to load it",
+            project.display(),
+            project_root(&project).display()
+        );
+    }
+}
+
 /// Execute the hook subcommand: read JSON from stdin, evaluate, write JSON to stdout.
 ///
-/// Loads config from the `--config` path, or auto-discovers it from well-known
-/// locations. Without config, all tools receive an "ask" decision prompting
-/// the user to configure the hook.
+/// Loads config from the `--config` path, or layers the configs discovered
+/// in well-known locations and the input's working directory. Without
+/// config, all tools receive an "ask" decision prompting the user to
+/// configure the hook.
 ///
 /// All runtime errors (bad stdin, config errors, parse failures) produce valid
-/// JSON on stdout. Panics only on invariant violations (e.g., broken Serialize
-/// derive), which indicate programming bugs rather than runtime conditions.
-pub fn run(config_path: Option<&Path>) {
-    let discovered = config_path.is_none().then(discover_config).flatten();
-    let effective_path = config_path.or(discovered.as_deref());
-    let config = effective_path.map(Config::load);
-
-    // Handle config load error: output ask with error message
-    let config_ref = match &config {
-        Some(Ok(cfg)) => Some(cfg),
-        Some(Err(e)) => {
-            output_json(&HookOutput::ask(format!("Config error: {e}")));
+/// JSON on stdout. Unreadable input gets a PreToolUse "ask"; config errors
+/// fail closed in the shape of the input's event. Panics only on invariant
+/// violations (e.g., broken Serialize derive), which indicate programming bugs
+/// rather than runtime conditions.
+///
+/// Each evaluated input is appended to the decision log at `log_path`, or
+/// the config's `log` path when the flag is absent. Unreadable input is not
+/// logged, and a failed write never changes the output.
+pub fn run(config_path: Option<&Path>, log_path: Option<&Path>) {
+    let hook_input = read_stdin();
+    let cwd = match &hook_input {
+        Ok(input) => PathBuf::from(&input.cwd),
+        Err(_) => std::env::current_dir().unwrap_or_default(),
+    };
+    let paths = config_paths(config_path, &cwd);
+    let config = (!paths.is_empty()).then(|| Config::load_layers(&paths));
+
+    let hook_input = match hook_input {
+        Ok(input) => input,
+        Err(e) => {
+            let reason = match &config {
+                Some(Err(config_err)) => format!("Config error: {config_err}"),
+                _ => format!("Error: {e}"),
+            };
+            output_json(&HookOutput::ask(reason));
             return;
         }
-        None => None,
     };
 
-    match execute_from_stdin(config_ref) {
-        Ok(Some(output)) => output_json(&output),
-        Ok(None) => println!("{{}}"),
-        Err(e) => output_json(&HookOutput::ask(format!("Error: {e}"))),
+    let (output, items) = match &config {
+        Some(Ok(cfg)) => decision::evaluate_audited(&hook_input, Some(cfg)),
+        Some(Err(e)) => (
+            decision::fail_closed(&hook_input, format!("Config error: {e}")),
+            vec![],
+        ),
+        None => decision::evaluate_audited(&hook_input, None),
+    };
+    match &output {
+        Some(output) => output_json(output),
+        None => println!("{{}}"),
+    }
+
+    let config = config.as_ref().and_then(|c| c.as_ref().ok());
+    let configured_log = config
+        .and_then(|c| c.log.as_ref())
+        .map(|l| l.path.as_path());
+    if let Some(path) = log_path.or(configured_log) {
+        log::append(path, &LogRecord::new(&hook_input, output.as_ref(), items));
     }
 }
 
-fn execute_from_stdin(
-    config: Option<&Config>,
-) -> Result<Option<HookOutput>, Box<dyn std::error::Error>> {
+fn read_stdin() -> Result<HookInput, Box<dyn std::error::Error>> {
     let mut input = String::new();
     std::io::stdin().read_to_string(&mut input)?;
-    let hook_input = serde_json::from_str(&input)?;
-    Ok(decision::evaluate(&hook_input, config))
+    Ok(serde_json::from_str(&input)?)
 }
 
-/// Serialize a HookOutput to JSON and print to stdout.
+/// Serialize a hook output to JSON and print to stdout.
 ///
 /// # Panics
 ///
 /// Panics if serialization fails, which cannot happen with the derived
 /// `Serialize` impl on strings and enums. This is an invariant, not a
 /// runtime error — failure here indicates a programming bug.
-fn output_json(output: &HookOutput) {
-    let json = serde_json::to_string(output).expect("HookOutput serialization cannot fail");
+fn output_json(output: &impl Serialize) {
+    let json = serde_json::to_string(output).expect("hook output serialization cannot fail");
     println!("{json}");
 }
@@ -0,0 +1,145 @@
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+
+use crate::decision::AuditItem;
+use crate::protocol::output::Behavior;
+use crate::protocol::{Decision, EventOutput, HookInput, PermissionMode};
+
+/// One line of the decision log: what was evaluated and what was answered.
+#[derive(Debug, Serialize)]
+pub(crate) struct LogRecord<'a> {
+    /// UTC, RFC 3339 with seconds (`2025-01-31T09:15:02Z`).
+    timestamp: String,
+    session_id: &'a str,
+    tool_use_id: &'a str,
+    cwd: &'a str,
+    event: &'a str,
+    tool: &'a str,
+    mode: &'a PermissionMode,
+    items: Vec<AuditItem>,
+    /// `allow`, `ask`, `deny`, or `block`; `null` when the hook had no opinion.
+    decision: Option<&'static str>,
+    reason: Option<&'a str>,
+}
+
+impl<'a> LogRecord<'a> {
+    /// Describe the evaluation of `input` that produced `output` after
+    /// looking up `items`.
+    pub(crate) fn new(
+        input: &'a HookInput,
+        output: Option<&'a EventOutput>,
+        items: Vec<AuditItem>,
+    ) -> Self {
+        let (decision, reason) = match output {
+            Some(output) => {
+                let (decision, reason) = summarize(output);
+                (Some(decision), reason)
+            }
+            None => (None, None),
+        };
+        Self {
+            timestamp: format_timestamp(SystemTime::now()),
+            session_id: &input.session_id,
+            tool_use_id: &input.tool_use_id,
+            cwd: &input.cwd,
+            event: &input.hook_event_name,
+            tool: &input.tool_name,
+            mode: &input.permission_mode,
+            items,
+            decision,
+            reason,
+        }
+    }
+}
+
+/// The final decision and reason carried by an event output.
+fn summarize(output: &EventOutput) -> (&'static str, Option<&str>) {
+    match output {
+        EventOutput::PreToolUse(output) => {
+            let specific = &output.hook_specific_output;
+            let decision = match specific.permission_decision {
+                Decision::Allow => "allow",
+                Decision::Ask => "ask",
+                Decision::Deny => "deny",
+            };
+            (decision, Some(&specific.permission_decision_reason))
+        }
+        EventOutput::PermissionRequest(output) => {
+            let decision = &output.hook_specific_output.decision;
+            let behavior = match decision.behavior {
+                Behavior::Allow => "allow",
+                Behavior::Deny => "deny",
+            };
+            (behavior, decision.message.as_deref())
+        }
+        EventOutput::Block(output) => ("block", Some(&output.reason)),
+    }
+}
+
+/// Append `record` to the log at `path` as one JSON line.
+///
+/// The file is opened in append mode and the line goes out in a single
+/// write, so concurrent hook processes do not interleave records. Failures
+/// are ignored: logging must never change what the hook prints.
+pub(crate) fn append(path: &Path, record: &LogRecord) {
+    let Ok(mut line) = serde_json::to_string(record) else {
+        return;
+    };
+    line.push('\n');
+    if let Ok(mut file) = OpenOptions::new().create(true).append(true).open(path) {
+        let _ = file.write_all(line.as_bytes());
+    }
+}
+
+/// Format a time as RFC 3339 UTC, to the second.
+fn format_timestamp(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs());
+    let (year, month, day) = civil_from_days((secs / 86_400) as i64);
+    let rem = secs % 86_400;
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        rem / 3600,
+        rem % 3600 / 60,
+        rem % 60
+    )
+}
+
+/// Convert days since 1970-01-01 to a proleptic Gregorian (year, month, day).
+///
+/// Howard Hinnant's `civil_from_days` algorithm.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn at(secs: u64) -> String {
+        format_timestamp(UNIX_EPOCH + Duration::from_secs(secs))
+    }
+
+    #[test]
+    fn timestamps_are_rfc3339_utc() {
+        assert_eq!(at(0), "1970-01-01T00:00:00Z");
+        assert_eq!(at(951_782_400), "2000-02-29T00:00:00Z");
+        assert_eq!(at(1_738_314_902), "2025-01-31T09:15:02Z");
+        assert_eq!(at(4_107_542_399), "2100-02-28T23:59:59Z");
+    }
+}
@@ -1 +1,6 @@
+pub mod check;
+pub mod explain;
 pub mod hook;
+mod log;
+pub mod test_cases;
+pub mod trust;
@@ -0,0 +1,166 @@
+use std::path::Path;
+
+use serde_json::{json, Value};
+
+use crate::config::{Config, TestCase};
+use crate::decision;
+use crate::protocol::{Decision, EventOutput, HookInput};
+
+use super::check::{display_paths, located};
+use super::hook::{config_paths, note_untrusted};
+
+/// Execute the test subcommand: run the config's `tests` cases and report
+/// the ones that fail on stdout.
+///
+/// The config is found the same way as for `hook`, from the current
+/// directory. Exits 1 when the config does not load or any case fails.
+pub fn run(config_path: Option<&Path>) {
+    let current_dir = std::env::current_dir().unwrap_or_default();
+    note_untrusted(config_path, &current_dir);
+    let paths = config_paths(config_path, &current_dir);
+    if paths.is_empty() {
+        println!("no config file found");
+        std::process::exit(1);
+    }
+    let name = display_paths(&paths);
+    let config = match Config::load_layers(&paths) {
+        Ok(config) => config,
+        Err(e) => {
+            println!("{name}: error: {e}");
+            std::process::exit(1);
+        }
+    };
+    let cwd = current_dir.to_string_lossy();
+
+    let mut failed = 0;
+    for case in &config.tests {
+        let (actual, reason) = run_case(case, &config, &cwd);
+        if actual == case.expected {
+            continue;
+        }
+        failed += 1;
+        println!(
+            "{}: FAIL {}: expected {}, got {}",
+            located(&config.sources, case.line),
+            case.label,
+            decision_name(case.expected.as_ref()),
+            decision_name(actual.as_ref())
+        );
+        if let Some(reason) = reason {
+            println!("    reason: {reason}");
+        }
+    }
+    let passed = config.tests.len() - failed;
+    println!("{name}: {passed} passed, {failed} failed");
+    if failed > 0 {
+        std::process::exit(1);
+    }
+}
+
+/// Evaluate one case as a `PreToolUse` call; returns the decision and reason.
+pub(crate) fn run_case(
+    case: &TestCase,
+    config: &Config,
+    default_cwd: &str,
+) -> (Option<Decision>, Option<String>) {
+    let cwd = case.cwd.as_deref().unwrap_or(default_cwd);
+    let input: HookInput = serde_json::from_value(json!({
+        "session_id": "test",
+        "transcript_path": "",
+        "cwd": cwd,
+        "permission_mode": case.mode.as_str(),
+        "hook_event_name": "PreToolUse",
+        "tool_name": case.tool_name,
+        "tool_input": substitute_cwd(&case.tool_input, cwd),
+    }))
+    .expect("synthesized hook input always deserializes");
+    match decision::evaluate(&input, Some(config)) {
+        Some(EventOutput::PreToolUse(output)) => {
+            let specific = output.hook_specific_output;
+            (
+                Some(specific.permission_decision),
+                Some(specific.permission_decision_reason),
+            )
+        }
+        _ => (None, None),
+    }
+}
+
+/// Replace `<cwd>` in every string of a tool input.
+fn substitute_cwd(value: &Value, cwd: &str) -> Value {
+    match value {
+        Value::String(s) => Value::String(s.replace("<cwd>", cwd)),
+        Value::Array(items) => items.iter().map(|v| substitute_cwd(v, cwd)).collect(),
+        Value::Object(fields) => fields
+            .iter()
+            .map(|(k, v)| (k.clone(), substitute_cwd(v, cwd)))
+            .collect(),
+        other => other.clone(),
+    }
+}
+
+fn decision_name(decision: Option<&Decision>) -> &'static str {
+    match decision {
+        Some(Decision::Allow) => "allow",
+        Some(Decision::Ask) => "ask",
+        Some(Decision::Deny) => "deny",
+        None => "no opinion",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run_all(source: &str) -> Vec<(Option<Decision>, Option<Decision>)> {
+        let config = Config::parse(source).unwrap();
+        config
+            .tests
+            .iter()
+            .map(|case| (case.expected.clone(), run_case(case, &config, "/work").0))
+            .collect()
+    }
+
+    #[test]
+    fn cases_evaluate_against_the_config() {
+        let results = run_all(
+            r#"
+            bash {
+                allow "git"
+                deny "git push --force"
+            }
+            files {
+                "<cwd>/**" { allow "read"; }
+            }
+            tests {
+                expect "deny" bash="git push --force origin main"
+                expect "allow" bash="git status"
+                expect "none" bash="make"
+                expect "allow" read="<cwd>/src/lib.rs" cwd="/repo"
+                expect "allow" read="/work/README.md"
+                expect "deny" bash="git push" mode="dontAsk" // fails: allowed
+            }
+            "#,
+        );
+        assert_eq!(
+            results,
+            vec![
+                (Some(Decision::Deny), Some(Decision::Deny)),
+                (Some(Decision::Allow), Some(Decision::Allow)),
+                (None, None),
+                (Some(Decision::Allow), Some(Decision::Allow)),
+                (Some(Decision::Allow), Some(Decision::Allow)),
+                (Some(Decision::Deny), Some(Decision::Allow)),
+            ]
+        );
+    }
+
+    #[test]
+    fn substitute_cwd_reaches_nested_strings() {
+        let value = json!({"a": ["<cwd>/x", 1], "b": {"c": "<cwd>"}});
+        assert_eq!(
+            substitute_cwd(&value, "/repo"),
+            json!({"a": ["/repo/x", 1], "b": {"c": "/repo"}})
+        );
+    }
+}
@@ -0,0 +1,65 @@
+use std::path::Path;
+
+use crate::config::trust::{project_digest, TrustStore};
+
+use super::hook::{project_config, project_root, trust_store_path};
+
+/// Execute the trust subcommand: let the hook load the project config
+/// found from `dir` (the current directory by default), or with `remove`,
+/// stop loading it.
+///
+/// With `pin`, trust lasts only while the project config and the files it
+/// includes are unchanged. Exits 1 when there is nothing to trust or the
+/// trust store cannot be updated.
+pub fn run(dir: Option<&Path>, pin: bool, remove: bool) {
+    let dir = match dir {
+        Some(dir) => dir.to_path_buf(),
+        None => std::env::current_dir().unwrap_or_default(),
+    };
+    let Some(store_path) = trust_store_path() else {
+        fail("cannot find the trust store: HOME is not set");
+    };
+    let mut store = match TrustStore::load(&store_path) {
+        Ok(store) => store,
+        Err(e) => fail(&format!("{}: error: {e}", store_path.display())),
+    };
+    let project = project_config(&dir);
+
+    let message = if remove {
+        let root = project.as_deref().map_or(dir.as_path(), project_root);
+        if store.remove(root) {
+            format!("no longer trusted: {}", root.display())
+        } else {
+            println!("not trusted: {}", root.display());
+            return;
+        }
+    } else {
+        let Some(project) = project else {
+            fail(&format!(
+                "no .claude/permissions.kdl in {} or its parents",
+                dir.display()
+            ));
+        };
+        let root = project_root(&project);
+        if pin {
+            let digest = match project_digest(&project) {
+                Ok(digest) => digest,
+                Err(e) => fail(&format!("{}: error: {e}", project.display())),
+            };
+            store.trust(root, Some(digest.clone()));
+            format!("trusted: {} (pinned to sha256 {digest})", root.display())
+        } else {
+            store.trust(root, None);
+            format!("trusted: {}", root.display())
+        }
+    };
+    if let Err(e) = store.save(&store_path) {
+        fail(&format!("{}: error: {e}", store_path.display()));
+    }
+    println!("{message}");
+}
+
+fn fail(message: &str) -> ! {
+    println!("{message}");
+    std::process::exit(1);
+}
@@ -0,0 +1,357 @@
+//! The working directory as `cd`, `pushd`, and `popd` change it.
+//!
+//! The walker in [`super`] carries a [`DirState`] through lists, pipelines,
+//! and compound commands, and tags every segment and redirection with the
+//! [`WorkingDir`] it runs in, so `cd /etc && rm passwd` is seen to remove
+//! `/etc/passwd`. Where a `cd` may have failed or run conditionally, every
+//! directory the command could be in is kept.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use super::{strip_outer_quotes, CommandSegment, Unwrap};
+
+/// Where a command runs, relative to the directory the whole command
+/// starts in.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) enum WorkingDir {
+    /// Any one of these directories. Each is absolute, `~`-relative, or
+    /// relative to the starting directory, which is `.` itself.
+    Known(Vec<String>),
+    /// A directory only running the shell can tell (`cd "$DIR"`, `cd -`).
+    Unknown,
+}
+
+/// More candidate directories than this collapse to [`WorkingDir::Unknown`].
+const MAX_CANDIDATES: usize = 8;
+
+impl Default for WorkingDir {
+    /// The starting directory.
+    fn default() -> Self {
+        WorkingDir::Known(vec![".".to_string()])
+    }
+}
+
+impl std::fmt::Display for WorkingDir {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WorkingDir::Known(dirs) => f.write_str(&dirs.join(" or ")),
+            WorkingDir::Unknown => f.write_str("an unknown directory"),
+        }
+    }
+}
+
+impl WorkingDir {
+    /// Whether this is the starting directory.
+    pub(crate) fn is_start(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// A path as seen from each candidate directory.
+    ///
+    /// Absolute and `~` paths, and paths only the shell can expand, come
+    /// back unchanged. Returns `None` for a relative path in an unknown
+    /// directory.
+    pub(crate) fn resolve(&self, path: &str) -> Option<Vec<String>> {
+        if path.starts_with(['/', '~']) || path.contains(['$', '`']) {
+            return Some(vec![path.to_string()]);
+        }
+        match self {
+            WorkingDir::Known(dirs) => Some(dirs.iter().map(|dir| join(dir, path)).collect()),
+            WorkingDir::Unknown => None,
+        }
+    }
+
+    /// `inner`, a directory relative to this one, relative to the start.
+    pub(super) fn within(&self, inner: &WorkingDir) -> WorkingDir {
+        let WorkingDir::Known(dirs) = inner else {
+            return WorkingDir::Unknown;
+        };
+        let mut resolved = Vec::new();
+        for dir in dirs {
+            match self.resolve(dir) {
+                Some(candidates) => resolved.extend(candidates),
+                None => return WorkingDir::Unknown,
+            }
+        }
+        WorkingDir::from_candidates(resolved)
+    }
+
+    /// Add the candidates of `other`.
+    fn merge(&mut self, other: &WorkingDir) {
+        *self = match (&*self, other) {
+            (WorkingDir::Known(ours), WorkingDir::Known(theirs)) => {
+                WorkingDir::from_candidates(ours.iter().chain(theirs).cloned().collect())
+            }
+            _ => WorkingDir::Unknown,
+        };
+    }
+
+    /// The directory `cd` into `target` leads to.
+    fn cd(&self, target: &str) -> WorkingDir {
+        let target = strip_outer_quotes(target);
+        if target.is_empty() {
+            return self.clone();
+        }
+        if target == "-" || target.contains(['$', '`', '*', '?', '[']) {
+            return WorkingDir::Unknown;
+        }
+        self.resolve(&target)
+            .map_or(WorkingDir::Unknown, WorkingDir::from_candidates)
+    }
+
+    fn from_candidates(candidates: Vec<String>) -> WorkingDir {
+        let mut unique: Vec<String> = Vec::new();
+        for candidate in candidates {
+            if !unique.contains(&candidate) {
+                unique.push(candidate);
+            }
+        }
+        if unique.len() > MAX_CANDIDATES {
+            return WorkingDir::Unknown;
+        }
+        WorkingDir::Known(unique)
+    }
+}
+
+fn join(dir: &str, path: &str) -> String {
+    if dir == "." {
+        return path.to_string();
+    }
+    if path == "." {
+        return dir.to_string();
+    }
+    format!("{}/{path}", dir.trim_end_matches('/'))
+}
+
+/// The working directory and `pushd` stack at one point of the walk.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub(super) struct DirState {
+    pub(super) cwd: WorkingDir,
+    /// Directories `pushd` saved, most recent last.
+    stack: Vec<WorkingDir>,
+    /// Shared by every state of one walk.
+    loops: LoopExits,
+}
+
+/// The state each loop leaves the shell in, by loop and entry state.
+///
+/// Finding where a loop leaves the shell walks its body until no new
+/// directory turns up, and a loop nested in it is walked on every one of
+/// those passes. Remembering each answer keeps nested loops from costing
+/// twice as much with each level. Not part of the state itself: it never makes
+/// two states differ.
+#[derive(Clone, Default)]
+struct LoopExits(Rc<RefCell<HashMap<(usize, DirState), DirState>>>);
+
+impl PartialEq for LoopExits {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for LoopExits {}
+
+impl std::hash::Hash for LoopExits {
+    fn hash<H: std::hash::Hasher>(&self, _: &mut H) {}
+}
+
+impl std::fmt::Debug for LoopExits {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("LoopExits")
+    }
+}
+
+impl DirState {
+    /// The state a loop, identified by `body`, left the shell in when
+    /// entered in this state, if already worked out.
+    pub(super) fn loop_exit(&self, body: usize) -> Option<DirState> {
+        self.loops.0.borrow().get(&(body, self.clone())).cloned()
+    }
+
+    /// Remember the state a loop leaves the shell in when entered in this
+    /// state.
+    pub(super) fn record_loop_exit(&self, body: usize, exit: &DirState) {
+        self.loops
+            .0
+            .borrow_mut()
+            .insert((body, self.clone()), exit.clone());
+    }
+
+    /// Add the possibilities of `other`, reached along another path.
+    ///
+    /// Stacks of different depths cannot be lined up and are dropped, so a
+    /// later `popd` leads to an unknown directory.
+    pub(super) fn merge(&mut self, other: &DirState) {
+        self.cwd.merge(&other.cwd);
+        if self.stack.len() == other.stack.len() {
+            for (ours, theirs) in self.stack.iter_mut().zip(&other.stack) {
+                ours.merge(theirs);
+            }
+        } else {
+            self.stack.clear();
+        }
+    }
+
+    /// Lose track of the directory and the stack.
+    pub(super) fn forget(&mut self) {
+        self.cwd = WorkingDir::Unknown;
+        self.stack.clear();
+    }
+
+    /// Follow the directory change of one simple command, given its
+    /// segments and the wrappers unwrapped to reach them.
+    ///
+    /// `builtin cd` and `command cd` change directory like `cd`; `env cd`
+    /// and `sudo cd` cannot run the builtin and change nothing. A `cd` run
+    /// through `eval` or `time` loses track of the directory.
+    pub(super) fn follow(&mut self, segments: &[CommandSegment], unwraps: &[Unwrap]) {
+        let Some(first) = segments.first() else {
+            return;
+        };
+        let program = first.program.as_str();
+        let direct = unwraps
+            .iter()
+            .all(|unwrap| matches!(unwrap.wrapper.as_str(), "builtin" | "command"));
+        if changes_directory(program) && direct {
+            self.change_directory(program, &first.args);
+        } else if matches!(program, "eval" | "time")
+            && segments
+                .iter()
+                .any(|segment| changes_directory(segment.program.as_str()))
+        {
+            self.forget();
+        }
+    }
+
+    /// Apply `cd`, `pushd`, or `popd` with its expanded arguments.
+    ///
+    /// `CDPATH` is not consulted. Stack rotation (`pushd +1`), `-n`, and
+    /// other options lose track of the directory.
+    fn change_directory(&mut self, program: &str, args: &[String]) {
+        let (options, operands) = split_options(args);
+        match program {
+            "cd" => {
+                if !options.iter().all(|o| ["-L", "-P", "-e", "-@"].contains(o)) {
+                    self.cwd = WorkingDir::Unknown;
+                    return;
+                }
+                self.cwd = match operands {
+                    [] => self.cwd.cd("~"),
+                    [target] => self.cwd.cd(target),
+                    _ => WorkingDir::Unknown,
+                };
+            }
+            "pushd" => match (options.is_empty(), operands) {
+                (true, [target]) if !target.starts_with('+') => {
+                    let target = self.cwd.cd(target);
+                    let previous = std::mem::replace(&mut self.cwd, target);
+                    self.stack.push(previous);
+                }
+                (true, []) => match self.stack.pop() {
+                    Some(top) => {
+                        let previous = std::mem::replace(&mut self.cwd, top);
+                        self.stack.push(previous);
+                    }
+                    None => self.forget(),
+                },
+                _ => self.forget(),
+            },
+            _ => match (options.is_empty() && operands.is_empty(), self.stack.pop()) {
+                (true, Some(top)) => self.cwd = top,
+                _ => self.forget(),
+            },
+        }
+    }
+}
+
+/// Whether a program is one of the directory builtins.
+fn changes_directory(program: &str) -> bool {
+    matches!(program, "cd" | "pushd" | "popd")
+}
+
+/// Split arguments into options and operands at the first operand or `--`.
+fn split_options(args: &[String]) -> (Vec<&str>, &[String]) {
+    let mut options = Vec::new();
+    for (i, arg) in args.iter().enumerate() {
+        if arg == "--" {
+            return (options, &args[i + 1..]);
+        }
+        if !arg.starts_with('-') || arg == "-" {
+            return (options, &args[i..]);
+        }
+        options.push(arg.as_str());
+    }
+    (options, &[])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dirs(dirs: &[&str]) -> WorkingDir {
+        WorkingDir::Known(dirs.iter().map(|dir| dir.to_string()).collect())
+    }
+
+    fn after(commands: &[(&str, &[&str])]) -> DirState {
+        let mut state = DirState::default();
+        for (program, args) in commands {
+            let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+            state.change_directory(program, &args);
+        }
+        state
+    }
+
+    #[test]
+    fn cd_joins_relative_targets() {
+        assert_eq!(
+            after(&[("cd", &["src"]), ("cd", &["lib"])]).cwd,
+            dirs(&["src/lib"])
+        );
+        assert_eq!(
+            after(&[("cd", &["src"]), ("cd", &["/etc"])]).cwd,
+            dirs(&["/etc"])
+        );
+        assert_eq!(after(&[("cd", &["-P", "'/tmp'"])]).cwd, dirs(&["/tmp"]));
+        assert_eq!(after(&[("cd", &[])]).cwd, dirs(&["~"]));
+    }
+
+    #[test]
+    fn dynamic_cd_targets_are_unknown() {
+        for target in ["$DIR", "-", "/tmp/*", "`pwd`"] {
+            assert_eq!(after(&[("cd", &[target])]).cwd, WorkingDir::Unknown);
+        }
+        assert_eq!(after(&[("cd", &["a", "b"])]).cwd, WorkingDir::Unknown);
+    }
+
+    #[test]
+    fn pushd_and_popd_use_the_stack() {
+        let state = after(&[("pushd", &["/etc"]), ("pushd", &["ssh"])]);
+        assert_eq!(state.cwd, dirs(&["/etc/ssh"]));
+        assert_eq!(
+            after(&[("pushd", &["/etc"]), ("popd", &[])]).cwd,
+            dirs(&["."])
+        );
+        assert_eq!(
+            after(&[("pushd", &["/etc"]), ("pushd", &["/tmp"]), ("pushd", &[])]).cwd,
+            dirs(&["/etc"])
+        );
+        // Nothing to pop: the shell's own stack is not known.
+        assert_eq!(after(&[("popd", &[])]).cwd, WorkingDir::Unknown);
+        assert_eq!(after(&[("pushd", &["+1"])]).cwd, WorkingDir::Unknown);
+    }
+
+    #[test]
+    fn merge_keeps_every_candidate() {
+        let mut state = after(&[("cd", &["/etc"])]);
+        state.merge(&DirState::default());
+        assert_eq!(state.cwd, dirs(&["/etc", "."]));
+        assert_eq!(
+            state.cwd.resolve("passwd"),
+            Some(vec!["/etc/passwd".to_string(), "passwd".to_string()])
+        );
+        assert_eq!(state.cwd.resolve("/abs"), Some(vec!["/abs".to_string()]));
+        assert_eq!(WorkingDir::Unknown.resolve("passwd"), None);
+    }
+}
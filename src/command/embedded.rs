@@ -0,0 +1,381 @@
+//! Commands embedded in another program's arguments.
+//!
+//! Some programs run a command given to them as arguments: `bash -c 'cmd'`,
+//! `eval cmd`, `find -exec cmd {} ;`, `parallel cmd ::: args`, `watch cmd`,
+//! and `git -c alias.x='!cmd' x`. This module finds those command strings
+//! in the program's raw words so the parser can evaluate them as segments.
+
+use super::{expand_flags, strip_outer_quotes};
+
+/// Shell interpreters whose `-c` argument is a command string to parse.
+const SHELL_INTERPRETERS: &[&str] = &["bash", "sh", "zsh", "dash", "ksh"];
+
+/// Shell interpreter options that consume a following argument
+/// (`bash -o pipefail -c "..."`).
+const SHELL_CONSUMING_OPTIONS: &[&str] = &["-o", "+o", "-O", "+O", "--rcfile", "--init-file"];
+
+/// `find` actions that run a command terminated by `;` or `{} +`.
+const FIND_EXEC_ACTIONS: &[&str] = &["-exec", "-execdir", "-ok", "-okdir"];
+
+/// GNU parallel options that consume a following argument.
+const PARALLEL_CONSUMING_OPTIONS: &[&str] = &[
+    "-j",
+    "--jobs",
+    "-P",
+    "--max-procs",
+    "-S",
+    "--sshlogin",
+    "--sshloginfile",
+    "--slf",
+    "-a",
+    "--arg-file",
+    "-d",
+    "--delimiter",
+    "-C",
+    "--colsep",
+    "-I",
+    "-E",
+    "-n",
+    "--max-args",
+    "-N",
+    "-L",
+    "--max-lines",
+    "--joblog",
+    "--results",
+    "--tmpdir",
+    "--workdir",
+    "--wd",
+    "--timeout",
+    "--delay",
+    "--retries",
+    "--halt",
+    "--tagstring",
+];
+
+/// Separators between a GNU parallel command template and its input sources.
+const PARALLEL_SEPARATORS: &[&str] = &[":::", "::::", ":::+", "::::+"];
+
+/// `watch` options that consume a following argument.
+const WATCH_CONSUMING_OPTIONS: &[&str] = &["-n", "--interval", "-q", "--equexit"];
+
+/// `git` global options that consume a following argument.
+const GIT_CONSUMING_OPTIONS: &[&str] = &["-C", "-c"];
+
+/// Find the command strings a program will run from its raw argument words.
+///
+/// `program` is the basename; `words` are the raw suffix words, still quoted
+/// as written. Returns one shell command string per embedded command.
+pub(super) fn embedded_commands(program: &str, words: &[String]) -> Vec<String> {
+    match program {
+        "eval" => eval_command(words).into_iter().collect(),
+        "find" => find_exec_commands(words),
+        "parallel" => parallel_commands(words),
+        "watch" => watch_command(words).into_iter().collect(),
+        "git" => git_alias_commands(words),
+        p if SHELL_INTERPRETERS.contains(&p) => shell_command(words).into_iter().collect(),
+        _ => vec![],
+    }
+}
+
+/// Find the command string in a shell interpreter's arguments.
+///
+/// With `-c` among the options, the first operand is the command string;
+/// without it, the operands name a script file and there is nothing to parse.
+fn shell_command(words: &[String]) -> Option<String> {
+    let mut has_command_option = false;
+    let mut words = words.iter();
+    while let Some(word) = words.next() {
+        match word.as_str() {
+            "--" => break,
+            opt if SHELL_CONSUMING_OPTIONS.contains(&opt) => {
+                words.next();
+            }
+            opt if opt.starts_with("--") || opt.starts_with('+') => {}
+            opt if opt.starts_with('-') => {
+                // Clustered flags like `-euo pipefail` or `-lc`
+                let flags = expand_flags(opt);
+                has_command_option |= flags.iter().any(|f| f == "-c");
+                if flags
+                    .last()
+                    .is_some_and(|f| SHELL_CONSUMING_OPTIONS.contains(&f.as_str()))
+                {
+                    words.next();
+                }
+            }
+            _ => return has_command_option.then(|| strip_outer_quotes(word)),
+        }
+    }
+    if has_command_option {
+        words.next().map(|word| strip_outer_quotes(word))
+    } else {
+        None
+    }
+}
+
+/// `eval` joins its arguments with spaces and runs the result.
+fn eval_command(words: &[String]) -> Option<String> {
+    if words.is_empty() {
+        return None;
+    }
+    Some(unquote_join(words))
+}
+
+/// Collect the commands run by `find -exec`, `-execdir`, `-ok`, and `-okdir`.
+///
+/// Each action runs the words up to a `;` (usually written `\;` or `';'`), or
+/// up to `{} +`. The words keep their quoting and are joined back into a
+/// command string.
+fn find_exec_commands(words: &[String]) -> Vec<String> {
+    let mut commands = Vec::new();
+    let mut words = words.iter();
+    while let Some(word) = words.next() {
+        if !FIND_EXEC_ACTIONS.contains(&word.as_str()) {
+            continue;
+        }
+        let mut command: Vec<&str> = Vec::new();
+        for word in words.by_ref() {
+            let unquoted = strip_outer_quotes(word);
+            if unquoted == ";" || unquoted == "\\;" {
+                break;
+            }
+            if unquoted == "+" && command.last().is_some_and(|w| *w == "{}") {
+                break;
+            }
+            command.push(word);
+        }
+        if !command.is_empty() {
+            commands.push(command.join(" "));
+        }
+    }
+    commands
+}
+
+/// Collect the commands run by GNU parallel.
+///
+/// The words before the first `:::`-style separator form the command
+/// template. Without a template, each `:::` argument is itself a command.
+fn parallel_commands(words: &[String]) -> Vec<String> {
+    let mut words = words.iter().peekable();
+    while let Some(word) = words.next_if(|w| w.starts_with('-') && w.as_str() != "-") {
+        if PARALLEL_CONSUMING_OPTIONS.contains(&word.as_str()) {
+            words.next();
+        }
+    }
+
+    let template: Vec<&str> =
+        std::iter::from_fn(|| words.next_if(|w| !PARALLEL_SEPARATORS.contains(&w.as_str())))
+            .map(String::as_str)
+            .collect();
+    if !template.is_empty() {
+        return vec![template.join(" ")];
+    }
+
+    // No template: `parallel ::: 'cmd one' 'cmd two'` runs each argument
+    if words.next().is_some_and(|sep| sep == ":::") {
+        return words
+            .take_while(|w| !PARALLEL_SEPARATORS.contains(&w.as_str()))
+            .map(|w| strip_outer_quotes(w))
+            .collect();
+    }
+    vec![]
+}
+
+/// Find the command run by `watch`.
+///
+/// `watch` joins its arguments and runs them with `sh -c`, so the words are
+/// unquoted before joining. With `-x`/`--exec` the words are run directly and
+/// keep their quoting.
+fn watch_command(words: &[String]) -> Option<String> {
+    let mut exec = false;
+    let mut words = words.iter();
+    let mut command = Vec::new();
+    while let Some(word) = words.next() {
+        match word.as_str() {
+            "--" => break,
+            "-x" | "--exec" => exec = true,
+            opt if WATCH_CONSUMING_OPTIONS.contains(&opt) => {
+                words.next();
+            }
+            opt if opt.starts_with('-') => {}
+            _ => {
+                command.push(word.clone());
+                break;
+            }
+        }
+    }
+    command.extend(words.cloned());
+    if command.is_empty() {
+        None
+    } else if exec {
+        Some(command.join(" "))
+    } else {
+        Some(unquote_join(&command))
+    }
+}
+
+/// Collect shell aliases defined on the command line with `git -c`.
+///
+/// `git -c alias.x='!rm -rf /' x` runs `rm -rf /` through the shell. Only
+/// the global options before the git subcommand are inspected.
+fn git_alias_commands(words: &[String]) -> Vec<String> {
+    let mut commands = Vec::new();
+    let mut words = words.iter();
+    while let Some(word) = words.next() {
+        match word.as_str() {
+            "-c" => {
+                if let Some(command) = words.next().and_then(|w| git_alias_command(w)) {
+                    commands.push(command);
+                }
+            }
+            opt if GIT_CONSUMING_OPTIONS.contains(&opt) => {
+                words.next();
+            }
+            opt if opt.starts_with('-') => {}
+            _ => break,
+        }
+    }
+    commands
+}
+
+/// Extract the shell command from an `alias.<name>=!<command>` setting.
+fn git_alias_command(setting: &str) -> Option<String> {
+    let setting = strip_outer_quotes(setting);
+    let (key, value) = setting.split_once('=')?;
+    if !key.to_ascii_lowercase().starts_with("alias.") {
+        return None;
+    }
+    strip_outer_quotes(value)
+        .strip_prefix('!')
+        .map(|command| command.to_string())
+}
+
+fn unquote_join(words: &[String]) -> String {
+    words
+        .iter()
+        .map(|word| strip_outer_quotes(word))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn embedded(program: &str, words: &[&str]) -> Vec<String> {
+        let words: Vec<String> = words.iter().map(|w| w.to_string()).collect();
+        embedded_commands(program, &words)
+    }
+
+    #[test]
+    fn shell_command_string_after_c() {
+        assert_eq!(embedded("bash", &["-c", "'git pull'"]), vec!["git pull"]);
+    }
+
+    #[test]
+    fn shell_script_file_has_no_command() {
+        assert!(embedded("sh", &["deploy.sh", "-c"]).is_empty());
+    }
+
+    #[test]
+    fn shell_consuming_option_skipped() {
+        assert_eq!(
+            embedded("bash", &["-o", "pipefail", "-c", "ls"]),
+            vec!["ls"]
+        );
+    }
+
+    #[test]
+    fn eval_words_unquoted_and_joined() {
+        assert_eq!(embedded("eval", &["'rm -rf'", "/"]), vec!["rm -rf /"]);
+    }
+
+    #[test]
+    fn find_exec_semicolon_terminated() {
+        assert_eq!(
+            embedded("find", &[".", "-name", "'*.o'", "-exec", "rm", "{}", "\\;"]),
+            vec!["rm {}"]
+        );
+    }
+
+    #[test]
+    fn find_exec_plus_terminated() {
+        assert_eq!(
+            embedded("find", &[".", "-exec", "rm", "-f", "{}", "+", "-print"]),
+            vec!["rm -f {}"]
+        );
+    }
+
+    #[test]
+    fn find_multiple_actions() {
+        assert_eq!(
+            embedded(
+                "find",
+                &[".", "-execdir", "chmod", "600", "{}", "';'", "-ok", "rm", "{}", "\";\""]
+            ),
+            vec!["chmod 600 {}", "rm {}"]
+        );
+    }
+
+    #[test]
+    fn find_unterminated_exec_takes_rest() {
+        assert_eq!(embedded("find", &[".", "-exec", "rm", "{}"]), vec!["rm {}"]);
+    }
+
+    #[test]
+    fn parallel_template_before_separator() {
+        assert_eq!(
+            embedded(
+                "parallel",
+                &["-j", "4", "gzip", "-9", "{}", ":::", "a", "b"]
+            ),
+            vec!["gzip -9 {}"]
+        );
+    }
+
+    #[test]
+    fn parallel_without_template_runs_arguments() {
+        assert_eq!(
+            embedded("parallel", &[":::", "'make test'", "'rm -rf /'"]),
+            vec!["make test", "rm -rf /"]
+        );
+    }
+
+    #[test]
+    fn parallel_reading_stdin_has_no_command() {
+        assert!(embedded("parallel", &["-j", "2"]).is_empty());
+    }
+
+    #[test]
+    fn watch_command_unquoted() {
+        assert_eq!(
+            embedded("watch", &["-n", "1", "'ls | wc -l'"]),
+            vec!["ls | wc -l"]
+        );
+    }
+
+    #[test]
+    fn watch_exec_keeps_quoting() {
+        assert_eq!(
+            embedded("watch", &["-x", "git", "log", "'-1'"]),
+            vec!["git log '-1'"]
+        );
+    }
+
+    #[test]
+    fn git_shell_alias_extracted() {
+        assert_eq!(
+            embedded("git", &["-c", "alias.x='!rm -rf /'", "x"]),
+            vec!["rm -rf /"]
+        );
+    }
+
+    #[test]
+    fn git_non_shell_alias_ignored() {
+        assert!(embedded("git", &["-c", "alias.st=status", "st"]).is_empty());
+    }
+
+    #[test]
+    fn git_config_after_subcommand_ignored() {
+        assert!(embedded("git", &["commit", "-c", "alias.x=!rm"]).is_empty());
+    }
+}
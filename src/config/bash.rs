@@ -2,47 +2,78 @@
 //!
 //! Struct and lookup logic. Parsing is in [`crate::config::parse::bash`].
 
+use super::path_args::PathArgsTable;
+use super::rewrite::RewriteRule;
 use super::rule;
 use super::section::{ToolConfig, ToolSection};
-use super::ConfigError;
+use super::{ConfigError, RuleMatch};
 use crate::command::CommandSegment;
-use crate::protocol::Decision;
 
 /// Bash-specific configuration: rules for allow, deny, or ask decisions.
-#[derive(Debug, Default)]
+#[derive(Debug)]
 pub struct BashConfig {
     pub allow: Vec<rule::BashRule>,
     pub deny: Vec<rule::BashRule>,
     pub ask: Vec<rule::BashRule>,
+    /// How many levels of nested command strings (substitutions, `bash -c`,
+    /// `eval`, `env -S`) are parsed before the command fails closed to ask.
+    pub max_nesting_depth: usize,
+    /// Which arguments of known programs name files, for checking them
+    /// against `files` rules. Built-in entries plus `path-args` nodes.
+    pub path_args: PathArgsTable,
+    /// Flag edits applied to allowed or asked commands, sent back to
+    /// Claude Code as `updatedInput`.
+    pub(crate) rewrites: Vec<RewriteRule>,
+}
+
+impl Default for BashConfig {
+    fn default() -> Self {
+        BashConfig {
+            allow: Vec::new(),
+            deny: Vec::new(),
+            ask: Vec::new(),
+            max_nesting_depth: crate::command::DEFAULT_MAX_NESTING_DEPTH,
+            path_args: PathArgsTable::default(),
+            rewrites: Vec::new(),
+        }
+    }
 }
 
 impl ToolConfig for BashConfig {
     const SECTION: &'static str = "bash";
 
     fn from_section(section: ToolSection) -> Result<Self, ConfigError> {
+        let schema = super::parse::bash::parse_flag_schema(&section.settings)?;
         Ok(BashConfig {
-            allow: super::parse::bash::parse_rules(section.allow)?,
-            deny: super::parse::bash::parse_rules(section.deny)?,
-            ask: super::parse::bash::parse_rules(section.ask)?,
+            allow: super::parse::bash::parse_rules(section.allow, &schema)?,
+            deny: super::parse::bash::parse_rules(section.deny, &schema)?,
+            ask: super::parse::bash::parse_rules(section.ask, &schema)?,
+            max_nesting_depth: super::parse::bash::parse_max_nesting_depth(&section.settings)?,
+            path_args: super::parse::bash::parse_path_args(&section.settings)?,
+            rewrites: super::parse::bash::parse_rewrites(&section.settings, &schema)?,
         })
     }
 }
 
 impl BashConfig {
-    /// Look up a command segment and return its configured decision.
+    /// Look up a command segment and return its configured decision, with
+    /// the rule that decided it.
     ///
     /// Uses `BashRule::matches()` for full condition evaluation (program name,
     /// flags, subcommands, positionals, required arguments).
     /// Precedence: deny > ask > allow. Returns `None` for unlisted programs.
-    pub(crate) fn lookup(&self, segment: &CommandSegment) -> Option<Decision> {
-        if self.deny.iter().any(|r| r.matches(segment)) {
-            Some(Decision::Deny)
-        } else if self.ask.iter().any(|r| r.matches(segment)) {
-            Some(Decision::Ask)
-        } else if self.allow.iter().any(|r| r.matches(segment)) {
-            Some(Decision::Allow)
-        } else {
-            None
-        }
+    pub(crate) fn lookup_match(&self, segment: &CommandSegment) -> Option<RuleMatch> {
+        super::match_rule::first_match(
+            &self.deny,
+            &self.ask,
+            &self.allow,
+            |r| (&r.raw, r.line),
+            |r| r.matches(segment),
+        )
+    }
+
+    /// Find the first rewrite rule that applies to a command segment.
+    pub(crate) fn rewrite(&self, segment: &CommandSegment) -> Option<&RewriteRule> {
+        self.rewrites.iter().find(|r| r.matches(segment))
     }
 }
@@ -0,0 +1,91 @@
+//! Named lists (`define`) and path variables (`path-var`).
+//!
+//! Parsing is in [`crate::config::parse::define`]. References are expanded
+//! while the sections that use them are parsed, so rules never see them.
+
+use std::collections::HashMap;
+
+use super::ConfigError;
+
+/// Every `define` and `path-var` in a config, with references between them
+/// already resolved.
+#[derive(Debug, Default)]
+pub(crate) struct Definitions {
+    /// `define` name → values, for `@name` in rule lists.
+    pub lists: HashMap<String, Vec<String>>,
+    /// `path-var` name → patterns, for `$name` in file patterns.
+    pub paths: HashMap<String, Vec<String>>,
+}
+
+impl Definitions {
+    /// Expand a rule value: `@name` becomes the named list's values; any
+    /// other value stands for itself.
+    pub(crate) fn expand_list(&self, value: &str, line: usize) -> Result<Vec<String>, ConfigError> {
+        match list_reference(value) {
+            Some(name) => self
+                .lists
+                .get(name)
+                .cloned()
+                .ok_or_else(|| undefined("list", &format!("@{name}"), name, &self.lists, line)),
+            None => Ok(vec![value.to_string()]),
+        }
+    }
+
+    /// Expand a file pattern: `$name` (alone or followed by `/…`) becomes
+    /// one pattern per value of the path variable; any other pattern stands
+    /// for itself.
+    pub(crate) fn expand_path(
+        &self,
+        pattern: &str,
+        line: usize,
+    ) -> Result<Vec<String>, ConfigError> {
+        match path_reference(pattern) {
+            Some((name, rest)) => {
+                let values = self.paths.get(name).ok_or_else(|| {
+                    undefined(
+                        "path variable",
+                        &format!("${name}"),
+                        name,
+                        &self.paths,
+                        line,
+                    )
+                })?;
+                Ok(values
+                    .iter()
+                    .map(|value| format!("{value}{rest}"))
+                    .collect())
+            }
+            None => Ok(vec![pattern.to_string()]),
+        }
+    }
+}
+
+/// The name in an `@name` list reference.
+pub(crate) fn list_reference(value: &str) -> Option<&str> {
+    value.strip_prefix('@').filter(|name| !name.is_empty())
+}
+
+/// The name in a `$name` or `$name/…` path reference, and the rest of the
+/// pattern after it.
+pub(crate) fn path_reference(pattern: &str) -> Option<(&str, &str)> {
+    let reference = pattern.strip_prefix('$')?;
+    let end = reference.find('/').unwrap_or(reference.len());
+    let (name, rest) = reference.split_at(end);
+    (!name.is_empty()).then_some((name, rest))
+}
+
+fn undefined(
+    kind: &str,
+    reference: &str,
+    name: &str,
+    defined: &HashMap<String, Vec<String>>,
+    line: usize,
+) -> ConfigError {
+    let names: Vec<&str> = defined.keys().map(String::as_str).collect();
+    let hint = super::lint::did_you_mean(name, &names)
+        .map(|name| format!("; did you mean \"{}{name}\"?", &reference[..1]))
+        .unwrap_or_default();
+    ConfigError::ParseError(format!(
+        "line {line}: undefined {kind} \"{reference}\"{hint}"
+    ))
+}
@@ -3,35 +3,141 @@
 //! `ConfigDocument`, `ConfigSection`, and `ParseNode` wrap the `kdl` crate
 //! types so the rest of the config module never touches KDL directly.
 
-/// Parsed KDL document paired with its source text.
+use std::path::{Path, PathBuf};
+
+/// Parsed KDL document: one or more files (or a single string) whose
+/// sections are read as one.
 ///
 /// Provides section lookup and node iteration that return [`ParseNode`]
-/// wrappers carrying source context for line-number error reporting.
+/// wrappers carrying source context for line-number error reporting. Lines
+/// are numbered across all parts in order, so every line in the document is
+/// distinct; [`SourceMap`] maps them back to a file and its own line.
 pub(super) struct ConfigDocument {
+    parts: Vec<Part>,
+}
+
+/// One parsed file (or string) of a document.
+struct Part {
     doc: kdl::KdlDocument,
     source: String,
+    /// File the part was read from; `None` when parsed from a string.
+    path: Option<PathBuf>,
+    /// Lines in the parts before this one.
+    line_offset: usize,
+    /// Which of the layered config files the part belongs to: the file
+    /// itself or one it includes.
+    layer: usize,
+}
+
+/// Source context shared by every node of one part.
+#[derive(Clone, Copy)]
+struct PartRef<'a> {
+    source: &'a str,
+    line_offset: usize,
+    index: usize,
+    layer: usize,
+}
+
+/// Files a config was loaded from, for naming where a document line is.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct SourceMap {
+    /// Each file with the lines in the files before it.
+    files: Vec<(PathBuf, usize)>,
+}
+
+impl SourceMap {
+    /// The file a document line is in, and its line within that file.
+    ///
+    /// Configs parsed from a string have no file; their lines are unchanged.
+    pub(crate) fn locate(&self, line: usize) -> (Option<&Path>, usize) {
+        match self.files.iter().rev().find(|(_, offset)| line > *offset) {
+            Some((path, offset)) => (Some(path.as_path()), line - offset),
+            None => (None, line),
+        }
+    }
+
+    /// Where a document line is, for messages: `config.kdl:14`, or
+    /// `line 14` without a file.
+    pub(crate) fn location(&self, line: usize) -> String {
+        match self.locate(line) {
+            (Some(path), line) => match path.file_name() {
+                Some(name) => format!("{}:{line}", name.to_string_lossy()),
+                None => format!("{}:{line}", path.display()),
+            },
+            (None, line) => format!("line {line}"),
+        }
+    }
+}
+
+/// A single entry value, detached from the KDL types.
+#[derive(Debug, Clone, PartialEq)]
+pub(super) enum EntryValue<'a> {
+    String(&'a str),
+    Integer(i128),
+    Float(f64),
+    Bool(bool),
+    Null,
+}
+
+impl<'a> EntryValue<'a> {
+    fn from_kdl(value: &'a kdl::KdlValue) -> Self {
+        match value {
+            kdl::KdlValue::String(s) => EntryValue::String(s),
+            kdl::KdlValue::Integer(i) => EntryValue::Integer(*i),
+            kdl::KdlValue::Float(f) => EntryValue::Float(*f),
+            kdl::KdlValue::Bool(b) => EntryValue::Bool(*b),
+            kdl::KdlValue::Null => EntryValue::Null,
+        }
+    }
 }
 
 /// Single KDL node with source context for line-number reporting.
+#[derive(Clone)]
 pub(super) struct ParseNode<'a> {
     node: &'a kdl::KdlNode,
-    source: &'a str,
+    part: PartRef<'a>,
 }
 
 impl ConfigDocument {
     /// Parse a KDL source string into a document.
+    ///
+    /// `include` needs a file to resolve against, so it is rejected here.
     pub(super) fn parse(source: &str) -> Result<Self, super::ConfigError> {
-        let doc: kdl::KdlDocument = source
-            .parse()
-            .map_err(|e: kdl::KdlError| super::ConfigError::ParseError(e.to_string()))?;
-        Ok(Self {
-            doc,
-            source: source.to_string(),
-        })
+        let doc = parse_kdl(source)?;
+        if let Some(node) = doc.get("include") {
+            let line = line_of(source, node);
+            return Err(super::ConfigError::ParseError(format!(
+                "line {line}: include is only supported in config files"
+            )));
+        }
+        let mut document = Self { parts: Vec::new() };
+        document.push(doc, source.to_string(), None, 0);
+        Ok(document)
+    }
+
+    /// Load and parse config files, in order, with the files they include.
+    ///
+    /// Each file's includes are read before the file itself, relative to
+    /// its directory. A file already loaded is skipped, and an include that
+    /// leads back to a file being loaded is an error. Once more than one
+    /// file is involved, errors name the file they came from.
+    pub(super) fn load(paths: &[PathBuf]) -> Result<Self, super::ConfigError> {
+        let mut document = Self { parts: Vec::new() };
+        let mut loaded = Vec::new();
+        for (layer, path) in paths.iter().enumerate() {
+            document.load_file(path, layer, &mut Vec::new(), &mut loaded, paths.len() > 1)?;
+        }
+        Ok(document)
     }
 
-    /// Load and parse a KDL config file.
-    pub(super) fn load(path: &std::path::Path) -> Result<Self, super::ConfigError> {
+    fn load_file(
+        &mut self,
+        path: &Path,
+        layer: usize,
+        stack: &mut Vec<PathBuf>,
+        loaded: &mut Vec<PathBuf>,
+        name_file: bool,
+    ) -> Result<(), super::ConfigError> {
         let content = std::fs::read_to_string(path).map_err(|e| {
             if e.kind() == std::io::ErrorKind::NotFound {
                 super::ConfigError::NotFound(path.to_path_buf())
@@ -39,58 +145,279 @@ impl ConfigDocument {
                 super::ConfigError::ReadError(e)
             }
         })?;
-        Self::parse(&content)
+        let canonical = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        if stack.contains(&canonical) {
+            let cycle: Vec<String> = stack
+                .iter()
+                .chain(std::iter::once(&canonical))
+                .map(|p| p.display().to_string())
+                .collect();
+            return Err(super::ConfigError::ParseError(format!(
+                "include cycle: {}",
+                cycle.join(" -> ")
+            )));
+        }
+        if loaded.contains(&canonical) {
+            return Ok(());
+        }
+        loaded.push(canonical.clone());
+
+        let name_file = name_file || !stack.is_empty();
+        let located = |e| if name_file { in_file(path, e) } else { e };
+        let doc = parse_kdl(&content).map_err(located)?;
+        let includes = includes(&doc, &content, path).map_err(located)?;
+        stack.push(canonical);
+        for include in includes {
+            self.load_file(&include, layer, stack, loaded, true)?;
+        }
+        stack.pop();
+        self.push(doc, content, Some(path.to_path_buf()), layer);
+        Ok(())
+    }
+
+    fn push(&mut self, doc: kdl::KdlDocument, source: String, path: Option<PathBuf>, layer: usize) {
+        let line_offset = self
+            .parts
+            .last()
+            .map_or(0, |p| p.line_offset + p.source.matches('\n').count() + 1);
+        self.parts.push(Part {
+            doc,
+            source,
+            path,
+            line_offset,
+            layer,
+        });
+    }
+
+    /// The files this document was loaded from.
+    pub(super) fn sources(&self) -> SourceMap {
+        SourceMap {
+            files: self
+                .parts
+                .iter()
+                .filter_map(|p| p.path.clone().map(|path| (path, p.line_offset)))
+                .collect(),
+        }
+    }
+
+    /// The text of every part, in load order.
+    pub(super) fn contents(&self) -> impl Iterator<Item = &str> {
+        self.parts.iter().map(|p| p.source.as_str())
+    }
+
+    /// Point a `line N: …` error at where it is: the column of the entry
+    /// it quotes, or else of the node on that line, and the file it is in
+    /// when the document has more than one.
+    pub(super) fn locate_error(&self, error: super::ConfigError) -> super::ConfigError {
+        let super::ConfigError::ParseError(msg) = &error else {
+            return error;
+        };
+        let Some((line, rest)) = msg
+            .strip_prefix("line ")
+            .and_then(|rest| rest.split_once(':'))
+            .and_then(|(line, rest)| Some((line.parse::<usize>().ok()?, rest)))
+        else {
+            return error;
+        };
+        let column = self
+            .column(line, rest)
+            .map(|column| format!(", column {column}"))
+            .unwrap_or_default();
+        let located = match self.sources().locate(line) {
+            (Some(path), local) if self.parts.len() > 1 => {
+                format!("{}: line {local}{column}", path.display())
+            }
+            _ => format!("line {line}{column}"),
+        };
+        super::ConfigError::ParseError(format!("{located}:{rest}"))
+    }
+
+    /// 1-based column of the entry on a document line whose string value
+    /// `message` quotes, or of the first node on the line.
+    fn column(&self, line: usize, message: &str) -> Option<usize> {
+        let part = self.parts.iter().rev().find(|p| line > p.line_offset)?;
+        let mut nodes = Vec::new();
+        nodes_on_line(&part.doc, &part.source, line - part.line_offset, &mut nodes);
+        let quoted = |value: &str| {
+            !value.is_empty()
+                && (message.contains(&format!("'{value}'"))
+                    || message.contains(&format!("\"{value}\"")))
+        };
+        let offset = nodes
+            .iter()
+            .flat_map(|node| node.entries())
+            .find(|entry| entry.value().as_string().is_some_and(quoted))
+            .map(|entry| entry.span().offset())
+            .or_else(|| nodes.first().map(|node| node.span().offset()))?;
+        let start = part.source[offset..]
+            .find(|c: char| !c.is_whitespace())
+            .map_or(offset, |skipped| offset + skipped);
+        Some(line_column(&part.source, start).1)
+    }
+
+    fn part_refs(&self) -> impl Iterator<Item = (&kdl::KdlDocument, PartRef<'_>)> {
+        self.parts.iter().enumerate().map(|(index, part)| {
+            (
+                &part.doc,
+                PartRef {
+                    source: &part.source,
+                    line_offset: part.line_offset,
+                    index,
+                    layer: part.layer,
+                },
+            )
+        })
+    }
+
+    /// Iterate over the top-level nodes (the sections) of every part.
+    pub(super) fn nodes(&self) -> Vec<ParseNode<'_>> {
+        self.part_refs()
+            .flat_map(|(doc, part)| doc.nodes().iter().map(move |node| ParseNode { node, part }))
+            .collect()
     }
 
     /// Get a named top-level section's children as a borrowed `ConfigSection`.
     ///
-    /// `section("bash")` returns the contents of the `bash { … }` block.
+    /// `section("bash")` returns the contents of every `bash { … }` block,
+    /// across all parts, in order.
     pub(super) fn section(&self, name: &str) -> Option<ConfigSection<'_>> {
-        self.doc
-            .get(name)
-            .and_then(|n| n.children())
-            .map(|doc| ConfigSection {
-                doc,
-                source: &self.source,
+        let blocks: Vec<_> = self
+            .part_refs()
+            .flat_map(|(doc, part)| {
+                doc.nodes()
+                    .iter()
+                    .filter(move |n| n.name().value() == name)
+                    .filter_map(move |n| n.children().map(|children| (children, part)))
             })
+            .collect();
+        (!blocks.is_empty()).then_some(ConfigSection { blocks })
     }
 }
 
+fn parse_kdl(source: &str) -> Result<kdl::KdlDocument, super::ConfigError> {
+    source
+        .parse()
+        .map_err(|e: kdl::KdlError| super::ConfigError::ParseError(describe_error(&e, source)))
+}
+
+/// The files a parsed file includes, resolved against its directory.
+fn includes(
+    doc: &kdl::KdlDocument,
+    source: &str,
+    path: &Path,
+) -> Result<Vec<PathBuf>, super::ConfigError> {
+    doc.nodes()
+        .iter()
+        .filter(|node| node.name().value() == "include")
+        .map(|node| {
+            let line = line_of(source, node);
+            let raw = match node.entries() {
+                [entry] if entry.name().is_none() && node.children().is_none() => {
+                    entry.value().as_string()
+                }
+                _ => None,
+            }
+            .ok_or_else(|| {
+                super::ConfigError::ParseError(format!(
+                    "line {line}: include requires a single quoted file path"
+                ))
+            })?;
+            let expanded = super::normalize::files::expand_home(raw).map_err(|e| {
+                super::ConfigError::ParseError(format!(
+                    "line {line}: cannot expand include path '{raw}': {e}"
+                ))
+            })?;
+            let base = path.parent().unwrap_or(Path::new(""));
+            Ok(base.join(expanded))
+        })
+        .collect()
+}
+
+/// Prefix an error with the file it came from.
+fn in_file(path: &Path, error: super::ConfigError) -> super::ConfigError {
+    match error {
+        super::ConfigError::ParseError(msg) => {
+            super::ConfigError::ParseError(format!("{}: {msg}", path.display()))
+        }
+        other => other,
+    }
+}
+
+/// Every node, at any depth, that starts on a line of its source.
+fn nodes_on_line<'a>(
+    doc: &'a kdl::KdlDocument,
+    source: &str,
+    line: usize,
+    found: &mut Vec<&'a kdl::KdlNode>,
+) {
+    for node in doc.nodes() {
+        if line_of(source, node) == line {
+            found.push(node);
+        }
+        if let Some(children) = node.children() {
+            nodes_on_line(children, source, line, found);
+        }
+    }
+}
+
+/// 1-based line number of a node within its own source.
+fn line_of(source: &str, node: &kdl::KdlNode) -> usize {
+    line_column(source, node.span().offset()).0
+}
+
 /// Borrowed view into a KDL section (children block of a top-level node).
 ///
-/// Provides node iteration for parsing tool sections.
+/// A section named in several parts reads as one, in part order. Provides
+/// node iteration for parsing tool sections.
 pub(super) struct ConfigSection<'a> {
-    doc: &'a kdl::KdlDocument,
-    source: &'a str,
+    blocks: Vec<(&'a kdl::KdlDocument, PartRef<'a>)>,
 }
 
 impl<'a> ConfigSection<'a> {
     /// Iterate over child nodes whose name matches `name`.
     pub(super) fn nodes_named(&self, name: &str) -> Vec<ParseNode<'a>> {
-        self.doc
-            .nodes()
-            .iter()
-            .filter(|n| n.name().value() == name)
-            .map(|node| ParseNode {
-                node,
-                source: self.source,
-            })
+        self.nodes()
+            .into_iter()
+            .filter(|n| n.name() == name)
             .collect()
     }
 
     /// Iterate over all child nodes.
     pub(super) fn nodes(&self) -> Vec<ParseNode<'a>> {
-        self.doc
-            .nodes()
+        self.blocks
             .iter()
-            .map(|node| ParseNode {
-                node,
-                source: self.source,
+            .flat_map(|(doc, part)| {
+                doc.nodes()
+                    .iter()
+                    .map(|node| ParseNode { node, part: *part })
             })
             .collect()
     }
 }
 
+/// Describe a KDL syntax error by its first problem, with line and column.
+///
+/// Later diagnostics are mostly the parser recovering from the first one.
+fn describe_error(error: &kdl::KdlError, source: &str) -> String {
+    match error.diagnostics.first() {
+        Some(diagnostic) => {
+            let (line, column) = line_column(source, diagnostic.span.offset());
+            format!("line {line}, column {column}: {diagnostic}")
+        }
+        None => error.to_string(),
+    }
+}
+
+/// 1-based line and column of a byte offset in the source.
+fn line_column(source: &str, offset: usize) -> (usize, usize) {
+    let before = source.get(..offset).unwrap_or(source);
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    (
+        before.matches('\n').count() + 1,
+        before[line_start..].chars().count() + 1,
+    )
+}
+
 impl<'a> ParseNode<'a> {
     /// The node's identifier (e.g. `"deny"`, `"required-flags"`).
     pub(super) fn name(&self) -> &str {
@@ -106,6 +433,37 @@ impl<'a> ParseNode<'a> {
             .collect()
     }
 
+    /// Collect all integer-valued entries from this node.
+    pub(super) fn integer_values(&self) -> Vec<i128> {
+        self.node
+            .entries()
+            .iter()
+            .filter_map(|e| e.value().as_integer())
+            .collect()
+    }
+
+    /// Positional (unnamed) entries, in order.
+    pub(super) fn arguments(&self) -> Vec<EntryValue<'a>> {
+        self.node
+            .entries()
+            .iter()
+            .filter(|e| e.name().is_none())
+            .map(|e| EntryValue::from_kdl(e.value()))
+            .collect()
+    }
+
+    /// Named entries (`key=value`), in order.
+    pub(super) fn properties(&self) -> Vec<(&'a str, EntryValue<'a>)> {
+        self.node
+            .entries()
+            .iter()
+            .filter_map(|e| {
+                e.name()
+                    .map(|name| (name.value(), EntryValue::from_kdl(e.value())))
+            })
+            .collect()
+    }
+
     /// Total number of entries (all types, not just strings).
     pub(super) fn entry_count(&self) -> usize {
         self.node.entries().len()
@@ -119,18 +477,291 @@ impl<'a> ParseNode<'a> {
     /// Get the children block as a borrowed `ConfigSection` (preserving source).
     pub(super) fn children(&self) -> Option<ConfigSection<'a>> {
         self.node.children().map(|doc| ConfigSection {
-            doc,
-            source: self.source,
+            blocks: vec![(doc, self.part)],
         })
     }
 
-    /// 1-based line number of this node in the original source.
+    /// 1-based line number of this node in the document.
+    ///
+    /// Lines count across parts; see [`SourceMap::locate`].
     pub(super) fn line(&self) -> usize {
-        let offset = self.node.span().offset();
-        self.source[..offset.min(self.source.len())]
-            .bytes()
-            .filter(|&b| b == b'\n')
-            .count()
-            + 1
+        self.part.line_offset + line_of(self.part.source, self.node)
+    }
+
+    /// Index of the part (file) this node is in, in load order.
+    pub(super) fn part(&self) -> usize {
+        self.part.index
+    }
+
+    /// Index of the layer this node is in: the layered config file it was
+    /// loaded from, directly or through an include.
+    pub(super) fn layer(&self) -> usize {
+        self.part.layer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use crate::config::{Config, ConfigError};
+    use crate::protocol::FileOperation;
+
+    fn write(dir: &Path, name: &str, content: &str) -> PathBuf {
+        let path = dir.join(name);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    fn load_error(paths: &[PathBuf]) -> String {
+        match Config::load_layers(paths) {
+            Err(ConfigError::ParseError(msg)) => msg,
+            other => panic!("expected a parse error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn include_resolves_against_including_file() {
+        let dir = tempfile::tempdir().unwrap();
+        write(
+            dir.path(),
+            "shared/team-base.kdl",
+            "bash {\n    deny \"rm\"\n}\n",
+        );
+        let main = write(
+            dir.path(),
+            "config.kdl",
+            "include \"shared/team-base.kdl\"\nbash {\n    allow \"git\"\n}\n",
+        );
+        let config = Config::load(&main).unwrap();
+        let bash = config.bash.unwrap();
+        assert_eq!(bash.deny[0].raw, "rm");
+        assert_eq!(bash.allow[0].raw, "git");
+        assert_eq!(
+            config.sources.location(bash.deny[0].line),
+            "team-base.kdl:2"
+        );
+        assert_eq!(config.sources.location(bash.allow[0].line), "config.kdl:3");
+    }
+
+    #[test]
+    fn include_cycle_is_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        write(dir.path(), "b.kdl", "include \"a.kdl\"\n");
+        let a = write(dir.path(), "a.kdl", "include \"b.kdl\"\n");
+        let msg = load_error(&[a]);
+        assert!(msg.contains("include cycle:"), "{msg}");
+        assert!(msg.contains("a.kdl -> "), "{msg}");
+        assert!(msg.ends_with("a.kdl"), "{msg}");
+    }
+
+    #[test]
+    fn file_included_twice_is_loaded_once() {
+        let dir = tempfile::tempdir().unwrap();
+        write(
+            dir.path(),
+            "base.kdl",
+            "log {\n    path \"/tmp/a.jsonl\"\n}\n",
+        );
+        write(dir.path(), "extra.kdl", "include \"base.kdl\"\n");
+        let main = write(
+            dir.path(),
+            "config.kdl",
+            "include \"base.kdl\"\ninclude \"extra.kdl\"\n",
+        );
+        assert!(Config::load(&main).unwrap().log.is_some());
+    }
+
+    #[test]
+    fn missing_include_is_not_found() {
+        let dir = tempfile::tempdir().unwrap();
+        let main = write(dir.path(), "config.kdl", "include \"missing.kdl\"\n");
+        assert!(matches!(
+            Config::load(&main),
+            Err(ConfigError::NotFound(path)) if path.ends_with("missing.kdl")
+        ));
+    }
+
+    #[test]
+    fn include_needs_a_single_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let main = write(
+            dir.path(),
+            "config.kdl",
+            "bash {}\ninclude \"a.kdl\" \"b.kdl\"\n",
+        );
+        assert_eq!(
+            load_error(&[main]),
+            "line 2: include requires a single quoted file path"
+        );
+    }
+
+    #[test]
+    fn include_in_string_config_is_rejected() {
+        assert!(matches!(
+            Config::parse("include \"base.kdl\""),
+            Err(ConfigError::ParseError(msg)) if msg == "line 1: include is only supported in config files"
+        ));
+    }
+
+    #[test]
+    fn errors_name_the_included_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let base = write(
+            dir.path(),
+            "team-base.kdl",
+            "bash {\n    allow \"git\"\n    deny \"sudo rm\"\n}\n",
+        );
+        let main = write(dir.path(), "config.kdl", "include \"team-base.kdl\"\n");
+        let msg = load_error(&[main]);
+        assert!(
+            msg.starts_with(&format!("{}: line 3, column 10: ", base.display())),
+            "{msg}"
+        );
+    }
+
+    #[test]
+    fn syntax_errors_name_the_included_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let base = write(dir.path(), "team-base.kdl", "bash {\n");
+        let main = write(dir.path(), "config.kdl", "include \"team-base.kdl\"\n");
+        let msg = load_error(&[main]);
+        assert!(
+            msg.starts_with(&format!("{}: line ", base.display())),
+            "{msg}"
+        );
+    }
+
+    #[test]
+    fn single_file_errors_name_no_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let main = write(
+            dir.path(),
+            "config.kdl",
+            "bash {\n    deny \"sudo rm\"\n}\n",
+        );
+        assert!(load_error(&[main]).starts_with("line 2, column 10: "));
+    }
+
+    #[test]
+    fn semantic_errors_point_at_the_entry() {
+        assert!(matches!(
+            Config::parse("bash {\n    allow \"ls\" {\n        positionals \"[\"\n    }\n}"),
+            Err(ConfigError::ParseError(msg)) if msg.starts_with("line 3, column 21: invalid glob pattern '['")
+        ));
+    }
+
+    #[test]
+    fn later_layer_replaces_single_valued_settings() {
+        let dir = tempfile::tempdir().unwrap();
+        let system = write(
+            dir.path(),
+            "system.kdl",
+            "bash {\n    max-nesting-depth 5\n    deny \"rm\"\n}\n",
+        );
+        let project = write(
+            dir.path(),
+            "project.kdl",
+            "bash {\n    max-nesting-depth 2\n    allow \"git\"\n}\n",
+        );
+        let config = Config::load_layers(&[system, project]).unwrap();
+        let bash = config.bash.unwrap();
+        assert_eq!(bash.max_nesting_depth, 2);
+        assert_eq!(bash.deny.len(), 1);
+        assert_eq!(bash.allow.len(), 1);
+    }
+
+    #[test]
+    fn later_layer_adds_to_path_args() {
+        let dir = tempfile::tempdir().unwrap();
+        let system = write(
+            dir.path(),
+            "system.kdl",
+            "bash {\n    path-args \"upload\" { read \"positionals\"; }\n}\n",
+        );
+        let project = write(
+            dir.path(),
+            "project.kdl",
+            "bash {\n    path-args \"cat\" { ignore \"positionals\"; }\n    \
+             path-args \"upload\" { ignore \"positionals\"; write \"-o\"; }\n}\n",
+        );
+        let config = Config::load_layers(&[system, project]).unwrap();
+        let path_args = config.bash.unwrap().path_args;
+        let args = |args: &[&str]| args.iter().map(|a| a.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            path_args.accesses("cat", &args(&["~/.ssh/id_rsa"])),
+            vec![("~/.ssh/id_rsa".to_string(), FileOperation::Read)]
+        );
+        assert_eq!(
+            path_args.accesses("upload", &args(&["-o", "out", "in"])),
+            vec![
+                ("out".to_string(), FileOperation::Read),
+                ("in".to_string(), FileOperation::Read),
+                ("out".to_string(), FileOperation::Write),
+            ]
+        );
+    }
+
+    #[test]
+    fn first_layer_replaces_builtin_path_args() {
+        let dir = tempfile::tempdir().unwrap();
+        write(
+            dir.path(),
+            "team-base.kdl",
+            "bash {\n    path-args \"cat\" { write \"positionals\"; }\n}\n",
+        );
+        let main = write(dir.path(), "config.kdl", "include \"team-base.kdl\"\n");
+        let config = Config::load(&main).unwrap();
+        assert_eq!(
+            config
+                .bash
+                .unwrap()
+                .path_args
+                .accesses("cat", &["x".to_string()]),
+            vec![("x".to_string(), FileOperation::Write)]
+        );
+    }
+
+    #[test]
+    fn later_layer_cannot_move_log_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let user = write(
+            dir.path(),
+            "user.kdl",
+            "log {\n    path \"/tmp/user.jsonl\"\n}\n",
+        );
+        let project = write(
+            dir.path(),
+            "project.kdl",
+            "log {\n    path \"/tmp/project.jsonl\"\n}\n",
+        );
+        let config = Config::load_layers(&[user, project.clone()]).unwrap();
+        assert_eq!(config.log.unwrap().path, PathBuf::from("/tmp/user.jsonl"));
+
+        // With no earlier layer setting it, a later layer's path is used
+        let config = Config::load_layers(&[project]).unwrap();
+        assert_eq!(
+            config.log.unwrap().path,
+            PathBuf::from("/tmp/project.jsonl")
+        );
+    }
+
+    #[test]
+    fn setting_twice_in_one_layer_is_still_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let system = write(dir.path(), "system.kdl", "bash {\n    allow \"ls\"\n}\n");
+        let project = write(
+            dir.path(),
+            "project.kdl",
+            "bash {\n    max-nesting-depth 2\n    max-nesting-depth 3\n}\n",
+        );
+        assert_eq!(
+            load_error(&[system, project.clone()]),
+            format!(
+                "{}: line 3, column 5: max-nesting-depth is set more than once",
+                project.display()
+            )
+        );
     }
 }
@@ -6,6 +6,9 @@
 
 use std::collections::HashSet;
 
+use regex::Regex;
+
+use super::RuleMatch;
 use crate::domain::PathError;
 use crate::protocol::Decision;
 use crate::protocol::FileOperation;
@@ -16,12 +19,15 @@ pub struct FilesConfig {
     pub deny: Vec<FileRule>,
     pub ask: Vec<FileRule>,
     pub allow: Vec<FileRule>,
+    /// Path rewrites, in config order; the first match wins.
+    pub rewrites: Vec<FileRewrite>,
 }
 
 /// A single file rule binding a path pattern to a set of operations.
 #[derive(Debug)]
 pub struct FileRule {
-    /// Raw glob pattern (may contain `<cwd>`, `<home>`, `~`).
+    /// Raw glob pattern (may contain `<cwd>`, `<home>`, `~`), or a regex
+    /// with a `re:` prefix.
     #[allow(dead_code)]
     pub raw_pattern: String,
     /// Pattern with `~` and `<home>` expanded at load time.
@@ -29,24 +35,98 @@ pub struct FileRule {
     /// `Err` when `$HOME` is not set and the pattern requires it.
     /// `<cwd>` is **not** expanded here — that happens at match time.
     pub home_expanded_pattern: Result<String, PathError>,
+    /// The compiled regex of a `re:` pattern; `None` for globs.
+    pub regex: Option<PathRegex>,
     /// Which file operations this rule applies to.
     pub operations: HashSet<FileOperation>,
+    /// Patterns from `except` children: the rule does not match a path any
+    /// of these match.
+    pub exceptions: Vec<FileRule>,
     /// 1-based line number in the source file.
-    #[allow(dead_code)]
     pub line: usize,
 }
 
+/// A path rewrite: `rewrite "~/.env" "read" { path "<cwd>/.env.example"; }`.
+///
+/// A file tool call whose path and operation match `rule` gets its path
+/// replaced by the target through `updatedInput`.
+#[derive(Debug)]
+pub struct FileRewrite {
+    /// Which paths and operations the rewrite applies to.
+    pub rule: FileRule,
+    /// Replacement path with `~` and `<home>` expanded at load time.
+    ///
+    /// `<cwd>` is expanded at match time, by [`FileRewrite::target`].
+    pub home_expanded_target: Result<String, PathError>,
+}
+
+impl FileRewrite {
+    /// The replacement path for a call made from `cwd`, or `None` when it
+    /// needs `$HOME` and `$HOME` is not set.
+    pub(crate) fn target(&self, cwd: &str) -> Option<String> {
+        let target = self.home_expanded_target.as_ref().ok()?;
+        Some(target.replace("<cwd>", cwd))
+    }
+}
+
+/// A `re:` file pattern, compiled at load time.
+#[derive(Debug)]
+pub struct PathRegex {
+    pub(crate) regex: Regex,
+    /// The pattern started with `<cwd>`: the rest of the regex must match
+    /// the path after the working directory, from its start.
+    pub(crate) after_cwd: bool,
+}
+
+impl PathRegex {
+    /// Whether the regex matches somewhere in a normalized path.
+    pub(crate) fn is_match(&self, normalized_path: &str, cwd: &str) -> bool {
+        if self.after_cwd {
+            normalized_path
+                .strip_prefix(cwd)
+                .is_some_and(|rest| self.regex.is_match(rest))
+        } else {
+            self.regex.is_match(normalized_path)
+        }
+    }
+}
+
 impl FilesConfig {
-    /// Look up a normalized path and operation against file rules.
+    /// Look up a normalized path and operation against file rules, returning
+    /// the decision and the rule that decided it.
     ///
-    /// Delegates to [`super::match_rule::files::lookup`].
+    /// Delegates to [`super::match_rule::files::lookup_match`].
     /// Precedence: deny > ask > allow. Returns `None` if no rule matches.
-    pub fn lookup(
+    pub(crate) fn lookup_match(
+        &self,
+        normalized_path: &str,
+        operation: FileOperation,
+        cwd: &str,
+    ) -> Option<RuleMatch> {
+        super::match_rule::files::lookup_match(self, normalized_path, operation, cwd)
+    }
+
+    /// The first rewrite whose rule matches a normalized path and operation.
+    ///
+    /// Delegates to [`super::match_rule::files::rewrite`].
+    pub(crate) fn rewrite(
+        &self,
+        normalized_path: &str,
+        operation: FileOperation,
+        cwd: &str,
+    ) -> Option<&FileRewrite> {
+        super::match_rule::files::rewrite(self, normalized_path, operation, cwd)
+    }
+
+    /// Every rule covering `operation`, with its tier and whether it matches.
+    ///
+    /// Delegates to [`super::match_rule::files::considered`].
+    pub(crate) fn considered(
         &self,
         normalized_path: &str,
         operation: FileOperation,
         cwd: &str,
-    ) -> Option<Decision> {
-        super::match_rule::files::lookup(self, normalized_path, operation, cwd)
+    ) -> Vec<(Decision, &FileRule, bool)> {
+        super::match_rule::files::considered(self, normalized_path, operation, cwd)
     }
 }
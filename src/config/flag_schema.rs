@@ -0,0 +1,500 @@
+//! Flag schemas: which flags of a program take a value, and which flags
+//! are the same flag.
+//!
+//! Lets rule matching tell `git -C /tmp push` apart from a `/tmp`
+//! subcommand, and see `rm --recursive --force` as `rm -rf`. Built-in
+//! entries cover common tools; `flags-with-values` and `flag-aliases` nodes
+//! in the `bash` section add more. Parsing is in
+//! [`crate::config::parse::bash`].
+
+use std::collections::HashMap;
+
+use crate::domain::Flag;
+
+/// Flag semantics for every known program, keyed by program basename.
+#[derive(Debug)]
+pub struct FlagSchema {
+    programs: HashMap<String, ProgramFlags>,
+}
+
+/// What one program's flags mean.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ProgramFlags {
+    /// Flags that take a separate value (`-C DIR`).
+    pub(crate) value_flags: Vec<Flag>,
+    /// Groups of flags that mean the same thing (`-f`, `--force`); the
+    /// first of each group is its canonical name.
+    pub(crate) aliases: Vec<Vec<Flag>>,
+    /// The program accepts an unambiguous prefix of a long option
+    /// (`--forc` for `--force`), as GNU tools and git do.
+    pub(crate) abbreviations: bool,
+}
+
+impl Default for FlagSchema {
+    /// The built-in schema.
+    fn default() -> Self {
+        FlagSchema {
+            programs: builtin_programs(),
+        }
+    }
+}
+
+impl FlagSchema {
+    /// The flag semantics of a program; empty for unknown programs.
+    pub(crate) fn for_program(&self, program: &str) -> ProgramFlags {
+        self.programs.get(program).cloned().unwrap_or_default()
+    }
+
+    /// Add flags that take a value to a program's entry.
+    pub(crate) fn extend_values(&mut self, program: &str, flags: impl IntoIterator<Item = Flag>) {
+        let entry = self.programs.entry(program.to_string()).or_default();
+        for flag in flags {
+            if !entry.value_flags.contains(&flag) {
+                entry.value_flags.push(flag);
+            }
+        }
+    }
+
+    /// Declare flags of a program equivalent.
+    ///
+    /// A group sharing a flag with an existing one is merged into it, and
+    /// keeps the existing group's canonical name.
+    pub(crate) fn add_aliases(&mut self, program: &str, group: Vec<Flag>) {
+        let entry = self.programs.entry(program.to_string()).or_default();
+        let (overlapping, mut rest): (Vec<_>, Vec<_>) = std::mem::take(&mut entry.aliases)
+            .into_iter()
+            .partition(|existing| existing.iter().any(|flag| group.contains(flag)));
+        let mut merged: Vec<Flag> = overlapping.into_iter().flatten().collect();
+        for flag in group {
+            if !merged.contains(&flag) {
+                merged.push(flag);
+            }
+        }
+        rest.push(merged);
+        entry.aliases = rest;
+    }
+}
+
+impl ProgramFlags {
+    /// The canonical name of a flag: its alias group's first flag, or the
+    /// long option an abbreviation stands for. Other flags are their own
+    /// canonical name.
+    ///
+    /// An abbreviation that fits several long options is left alone, as
+    /// the program itself rejects it.
+    pub(crate) fn canonical<'a>(&'a self, flag: &'a str) -> &'a str {
+        if let Some(canonical) = self.alias_of(flag) {
+            return canonical;
+        }
+        if !self.abbreviations || !flag.starts_with("--") || flag.len() < 3 || self.knows(flag) {
+            return flag;
+        }
+        let mut expansions = self
+            .long_flags()
+            .filter(|long| long.starts_with(flag))
+            .map(|long| self.alias_of(long).unwrap_or(long));
+        match expansions.next() {
+            Some(first) if expansions.all(|other| other == first) => first,
+            _ => flag,
+        }
+    }
+
+    /// Whether two flags are the same flag.
+    pub(crate) fn same(&self, a: &str, b: &str) -> bool {
+        self.canonical(a) == self.canonical(b)
+    }
+
+    /// Whether a flag takes a separate value.
+    pub(crate) fn takes_value(&self, flag: &str) -> bool {
+        self.value_flags
+            .iter()
+            .any(|value_flag| self.same(value_flag.as_str(), flag))
+    }
+
+    fn alias_of(&self, flag: &str) -> Option<&str> {
+        self.aliases
+            .iter()
+            .find(|group| group.iter().any(|f| f == flag))
+            .map(|group| group[0].as_str())
+    }
+
+    fn knows(&self, flag: &str) -> bool {
+        self.long_flags().any(|long| long == flag)
+    }
+
+    fn long_flags(&self) -> impl Iterator<Item = &str> {
+        self.aliases
+            .iter()
+            .flatten()
+            .chain(&self.value_flags)
+            .map(Flag::as_str)
+            .filter(|flag| flag.starts_with("--"))
+    }
+}
+
+// ---- Built-in table ----
+
+/// One built-in entry: value flags and alias groups.
+struct Builtin {
+    programs: &'static [&'static str],
+    abbreviations: bool,
+    value_flags: &'static [&'static str],
+    aliases: &'static [&'static [&'static str]],
+}
+
+/// Only flags that mean the same in every subcommand are listed: `docker
+/// rm -f` is a switch while `docker build -f` takes a file, so `-f` is
+/// neither a value flag nor an alias for docker. Long options are listed
+/// even without a short form, so an abbreviation of one is not taken for
+/// another (`git push --force` is not `--force-with-lease`).
+const BUILTINS: &[Builtin] = &[
+    Builtin {
+        programs: &["rm"],
+        abbreviations: true,
+        value_flags: &[],
+        aliases: &[
+            &["-r", "-R", "--recursive"],
+            &["-f", "--force"],
+            &["-i", "--interactive"],
+            &["-d", "--dir"],
+            &["-v", "--verbose"],
+            &["--one-file-system"],
+            &["--preserve-root"],
+            &["--no-preserve-root"],
+        ],
+    },
+    Builtin {
+        programs: &["cp"],
+        abbreviations: true,
+        value_flags: &[],
+        aliases: &[
+            &["-r", "-R", "--recursive"],
+            &["-f", "--force"],
+            &["-i", "--interactive"],
+            &["-n", "--no-clobber"],
+            &["-a", "--archive"],
+            &["-u", "--update"],
+            &["-l", "--link"],
+            &["-s", "--symbolic-link"],
+            &["-t", "--target-directory"],
+            &["-v", "--verbose"],
+            &["--remove-destination"],
+        ],
+    },
+    Builtin {
+        programs: &["mv"],
+        abbreviations: true,
+        value_flags: &[],
+        aliases: &[
+            &["-f", "--force"],
+            &["-i", "--interactive"],
+            &["-n", "--no-clobber"],
+            &["-u", "--update"],
+            &["-t", "--target-directory"],
+            &["-v", "--verbose"],
+        ],
+    },
+    Builtin {
+        programs: &["ln"],
+        abbreviations: true,
+        value_flags: &[],
+        aliases: &[
+            &["-s", "--symbolic"],
+            &["-f", "--force"],
+            &["-i", "--interactive"],
+            &["-n", "--no-dereference"],
+            &["-t", "--target-directory"],
+            &["-v", "--verbose"],
+        ],
+    },
+    Builtin {
+        programs: &["chmod", "chown", "chgrp"],
+        abbreviations: true,
+        value_flags: &[],
+        aliases: &[
+            &["-R", "--recursive"],
+            &["-v", "--verbose"],
+            &["-c", "--changes"],
+            &["-f", "--silent", "--quiet"],
+            &["--reference"],
+        ],
+    },
+    Builtin {
+        programs: &["mkdir"],
+        abbreviations: true,
+        value_flags: &[],
+        aliases: &[
+            &["-p", "--parents"],
+            &["-m", "--mode"],
+            &["-v", "--verbose"],
+        ],
+    },
+    Builtin {
+        programs: &["sed"],
+        abbreviations: true,
+        value_flags: &[],
+        aliases: &[
+            &["-i", "--in-place"],
+            &["-e", "--expression"],
+            &["-f", "--file"],
+            &["-n", "--quiet", "--silent"],
+            &["-E", "-r", "--regexp-extended"],
+        ],
+    },
+    Builtin {
+        programs: &["git"],
+        abbreviations: true,
+        value_flags: &[
+            "-C",
+            "-c",
+            "--git-dir",
+            "--work-tree",
+            "--namespace",
+            "--config-env",
+        ],
+        aliases: &[
+            &["-f", "--force"],
+            &["--force-with-lease"],
+            &["--force-if-includes"],
+            &["-q", "--quiet"],
+            &["-v", "--verbose"],
+            &["--all"],
+            &["--mirror"],
+            &["--tags"],
+            &["--delete"],
+            &["--hard"],
+            &["--soft"],
+            &["--mixed"],
+            &["--no-verify"],
+            &["--dry-run"],
+        ],
+    },
+    Builtin {
+        programs: &["docker"],
+        abbreviations: false,
+        value_flags: &[
+            "-H",
+            "--host",
+            "--context",
+            "--config",
+            "--log-level",
+            "-e",
+            "--env",
+            "--env-file",
+            "-v",
+            "--volume",
+            "--mount",
+            "-p",
+            "--publish",
+            "--name",
+            "-w",
+            "--workdir",
+            "-u",
+            "--user",
+            "--network",
+            "--entrypoint",
+            "--platform",
+            "--label",
+            "--tag",
+        ],
+        aliases: &[
+            &["-H", "--host"],
+            &["-e", "--env"],
+            &["-v", "--volume"],
+            &["-p", "--publish"],
+            &["-w", "--workdir"],
+            &["-u", "--user"],
+        ],
+    },
+    Builtin {
+        programs: &["kubectl"],
+        abbreviations: false,
+        value_flags: &[
+            "-n",
+            "--namespace",
+            "--context",
+            "--cluster",
+            "--user",
+            "--kubeconfig",
+            "-s",
+            "--server",
+            "-l",
+            "--selector",
+            "--field-selector",
+            "-o",
+            "--output",
+            "-f",
+            "--filename",
+            "-c",
+            "--container",
+        ],
+        aliases: &[
+            &["-n", "--namespace"],
+            &["-s", "--server"],
+            &["-l", "--selector"],
+            &["-o", "--output"],
+            &["-f", "--filename"],
+            &["-c", "--container"],
+            &["-A", "--all-namespaces"],
+            &["-R", "--recursive"],
+        ],
+    },
+    Builtin {
+        programs: &["npm"],
+        abbreviations: false,
+        value_flags: &[
+            "--prefix",
+            "-w",
+            "--workspace",
+            "--registry",
+            "--tag",
+            "--cache",
+            "--userconfig",
+        ],
+        aliases: &[
+            &["-w", "--workspace"],
+            &["-g", "--global"],
+            &["-D", "--save-dev"],
+            &["-f", "--force"],
+        ],
+    },
+    Builtin {
+        programs: &["cargo"],
+        abbreviations: false,
+        value_flags: &[
+            "-p",
+            "--package",
+            "--manifest-path",
+            "--target",
+            "--target-dir",
+            "-F",
+            "--features",
+            "--bin",
+            "--example",
+            "--test",
+            "--bench",
+            "-j",
+            "--jobs",
+            "--profile",
+            "--config",
+            "--color",
+            "-Z",
+        ],
+        aliases: &[
+            &["-p", "--package"],
+            &["-F", "--features"],
+            &["-j", "--jobs"],
+            &["-r", "--release"],
+            &["-q", "--quiet"],
+            &["-v", "--verbose"],
+        ],
+    },
+    Builtin {
+        programs: &["curl"],
+        abbreviations: false,
+        value_flags: &[
+            "-X",
+            "--request",
+            "-o",
+            "--output",
+            "-d",
+            "--data",
+            "-T",
+            "--upload-file",
+            "-H",
+            "--header",
+            "-u",
+            "--user",
+            "-F",
+            "--form",
+        ],
+        aliases: &[
+            &["-X", "--request"],
+            &["-o", "--output"],
+            &["-d", "--data"],
+            &["-T", "--upload-file"],
+            &["-H", "--header"],
+            &["-u", "--user"],
+            &["-F", "--form"],
+            &["-k", "--insecure"],
+            &["-L", "--location"],
+        ],
+    },
+];
+
+fn builtin_programs() -> HashMap<String, ProgramFlags> {
+    let flags = |names: &[&str]| names.iter().map(|name| Flag::new(name)).collect();
+    let mut table = HashMap::new();
+    for builtin in BUILTINS {
+        let program_flags = ProgramFlags {
+            value_flags: flags(builtin.value_flags),
+            aliases: builtin.aliases.iter().map(|group| flags(group)).collect(),
+            abbreviations: builtin.abbreviations,
+        };
+        for program in builtin.programs {
+            table.insert(program.to_string(), program_flags.clone());
+        }
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_lists_global_git_options() {
+        let schema = FlagSchema::default();
+        assert!(schema.for_program("git").takes_value("-C"));
+        assert_eq!(schema.for_program("unknown-tool"), ProgramFlags::default());
+    }
+
+    #[test]
+    fn extend_values_adds_to_builtin_entry() {
+        let mut schema = FlagSchema::default();
+        schema.extend_values("git", [Flag::new("-C"), Flag::new("--exec-path")]);
+        let git = schema.for_program("git");
+        assert_eq!(git.value_flags.iter().filter(|f| *f == "-C").count(), 1);
+        assert!(git.takes_value("--exec-path"));
+    }
+
+    #[test]
+    fn aliases_share_a_canonical_name() {
+        let rm = FlagSchema::default().for_program("rm");
+        assert_eq!(rm.canonical("--recursive"), "-r");
+        assert_eq!(rm.canonical("-R"), "-r");
+        assert!(rm.same("--force", "-f"));
+        assert_eq!(rm.canonical("-x"), "-x");
+    }
+
+    #[test]
+    fn unambiguous_abbreviation_expands() {
+        let rm = FlagSchema::default().for_program("rm");
+        assert_eq!(rm.canonical("--forc"), "-f");
+        assert_eq!(rm.canonical("--rec"), "-r");
+        // --preserve-root or --no-preserve-root? Only the first fits.
+        assert_eq!(rm.canonical("--pre"), "--preserve-root");
+        // Ambiguous prefixes stay as written.
+        let git = FlagSchema::default().for_program("git");
+        assert_eq!(git.canonical("--force"), "-f");
+        assert_eq!(git.canonical("--force-w"), "--force-with-lease");
+        assert_eq!(git.canonical("--for"), "--for");
+    }
+
+    #[test]
+    fn no_abbreviation_without_support() {
+        let kubectl = FlagSchema::default().for_program("kubectl");
+        assert_eq!(kubectl.canonical("--names"), "--names");
+        assert_eq!(kubectl.canonical("--namespace"), "-n");
+    }
+
+    #[test]
+    fn overlapping_alias_groups_merge() {
+        let mut schema = FlagSchema::default();
+        schema.add_aliases("rm", vec![Flag::new("--force"), Flag::new("--yes")]);
+        schema.add_aliases("mytool", vec![Flag::new("-y"), Flag::new("--yes")]);
+        let rm = schema.for_program("rm");
+        assert_eq!(rm.canonical("--yes"), "-f");
+        assert_eq!(schema.for_program("mytool").canonical("--yes"), "-y");
+    }
+}
@@ -0,0 +1,587 @@
+//! Config lints: mistakes in a config that loads but probably does not do
+//! what was meant.
+//!
+//! Lints never change how a config is evaluated. They are reported by the
+//! `check` subcommand alongside load errors.
+
+use std::fmt;
+
+use super::document::ConfigDocument;
+use super::files::FileRule;
+use super::rule::BashRule;
+use super::{BashConfig, Config, FilesConfig, SourceMap};
+
+/// Top-level sections the config understands.
+const SECTIONS: &[&str] = &[
+    "bash", "files", "web", "mcp", "tools", "prompt", "log", "tests",
+];
+
+/// Top-level nodes that are not sections.
+const TOP_LEVEL_NODES: &[&str] = &["include", "define", "path-var"];
+
+/// Nodes the `bash` section understands besides its tiers.
+const BASH_SETTINGS: &[&str] = &[
+    "max-nesting-depth",
+    "path-args",
+    "flags-with-values",
+    "flag-aliases",
+    "rewrite",
+];
+
+/// A lint finding, tied to the config line it is about.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Warning {
+    /// 1-based line number in the source file.
+    pub(crate) line: usize,
+    pub(crate) message: String,
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl Warning {
+    fn new(line: usize, message: String) -> Self {
+        Warning { line, message }
+    }
+}
+
+/// Lint a parsed config against the document it came from, in line order.
+pub(super) fn lint(doc: &ConfigDocument, config: &Config) -> Vec<Warning> {
+    let mut warnings = unknown_nodes(doc);
+    if let Some(bash) = &config.bash {
+        warnings.extend(bash_never_match(bash));
+        warnings.extend(bash_shadowed(bash, &config.sources));
+    }
+    if let Some(files) = &config.files {
+        warnings.extend(files_never_match(files));
+        warnings.extend(files_shadowed(files, &config.sources));
+    }
+    warnings.sort_by_key(|w| w.line);
+    warnings
+}
+
+/// Suggest the candidate a misspelled name was probably meant to be.
+///
+/// Matches within two edits, so short names don't suggest everything.
+pub(crate) fn did_you_mean<'a>(name: &str, candidates: &[&'a str]) -> Option<&'a str> {
+    candidates
+        .iter()
+        .map(|candidate| (edit_distance(name, candidate), *candidate))
+        .filter(|(distance, _)| *distance <= 2 && *distance < name.len())
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// ` did you mean "x"?`, or nothing when no candidate is close.
+fn suggestion(name: &str, candidates: &[&str]) -> String {
+    did_you_mean(name, candidates)
+        .map(|c| format!("; did you mean \"{c}\"?"))
+        .unwrap_or_default()
+}
+
+/// Levenshtein distance between two names.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = if ca == *cb {
+                diagonal
+            } else {
+                1 + diagonal.min(above).min(row[j])
+            };
+            diagonal = above;
+        }
+    }
+    row[b.len()]
+}
+
+/// Sections and `bash` settings the parser silently ignores.
+fn unknown_nodes(doc: &ConfigDocument) -> Vec<Warning> {
+    let mut warnings = Vec::new();
+    for node in doc.nodes() {
+        if !TOP_LEVEL_NODES.contains(&node.name()) && !SECTIONS.contains(&node.name()) {
+            warnings.push(Warning::new(
+                node.line(),
+                format!(
+                    "unknown section \"{}\" is ignored{}",
+                    node.name(),
+                    suggestion(node.name(), SECTIONS)
+                ),
+            ));
+        }
+    }
+    let Some(bash) = doc.section("bash") else {
+        return warnings;
+    };
+    for node in bash.nodes() {
+        let name = node.name();
+        if !matches!(name, "allow" | "deny" | "ask") && !BASH_SETTINGS.contains(&name) {
+            let known = [&["allow", "deny", "ask"], BASH_SETTINGS].concat();
+            warnings.push(Warning::new(
+                node.line(),
+                format!(
+                    "unknown node \"{name}\" in bash section is ignored{}",
+                    suggestion(name, &known)
+                ),
+            ));
+        }
+    }
+    warnings
+}
+
+/// Bash rules with a condition no command can satisfy.
+fn bash_never_match(bash: &BashConfig) -> Vec<Warning> {
+    let mut warnings = Vec::new();
+    for (tier, rule) in
+        bash_tiers(bash).flat_map(|(tier, rules)| rules.iter().map(move |r| (tier, r)))
+    {
+        let conditions = &rule.conditions;
+        if conditions.required_flags.iter().any(|f| f.as_str() == "--") {
+            warnings.push(Warning::new(
+                rule.line,
+                format!(
+                    "{tier} \"{}\" can never match: \"--\" ends options and is not a flag",
+                    rule.raw
+                ),
+            ));
+        }
+        let mut contradictions: Vec<&str> = conditions
+            .required_flags
+            .intersection(&conditions.forbidden_flags)
+            .map(|f| f.as_str())
+            .collect();
+        contradictions.sort_unstable();
+        for flag in contradictions {
+            warnings.push(Warning::new(
+                rule.line,
+                format!(
+                    "{tier} \"{}\" can never match: \"{flag}\" is both required and forbidden",
+                    rule.raw
+                ),
+            ));
+        }
+        let literal_globs = conditions
+            .subcommand
+            .iter()
+            .chain(conditions.subcommands.iter().flatten())
+            .filter(|word| word.contains(['*', '?', '[']));
+        for word in literal_globs {
+            warnings.push(Warning::new(
+                rule.line,
+                format!(
+                    "{tier} \"{}\" only matches a literal \"{word}\": subcommands are not \
+                     patterns; use a positionals child instead",
+                    rule.raw
+                ),
+            ));
+        }
+    }
+    warnings
+}
+
+/// Bash rules that a rule in a stricter tier (or an earlier identical rule
+/// in the same tier) always decides first.
+fn bash_shadowed(bash: &BashConfig, sources: &SourceMap) -> Vec<Warning> {
+    let tiers: Vec<_> = bash_tiers(bash).collect();
+    let mut warnings = Vec::new();
+    for (position, (tier, rules)) in tiers.iter().enumerate() {
+        for (index, rule) in rules.iter().enumerate() {
+            let stricter = tiers[..position]
+                .iter()
+                .find_map(|(stricter_tier, stricter_rules)| {
+                    stricter_rules
+                        .iter()
+                        .find(|s| bash_covers(s, rule))
+                        .map(|s| (*stricter_tier, s))
+                });
+            if let Some((stricter_tier, by)) = stricter {
+                warnings.push(Warning::new(
+                    rule.line,
+                    shadowed_message(
+                        tier,
+                        &rule.raw,
+                        stricter_tier,
+                        &by.raw,
+                        &sources.location(by.line),
+                    ),
+                ));
+            } else if let Some(first) = rules[..index]
+                .iter()
+                .find(|earlier| bash_covers(earlier, rule) && bash_covers(rule, earlier))
+            {
+                warnings.push(Warning::new(
+                    rule.line,
+                    duplicate_message(tier, &rule.raw, first.line, rule.line, sources),
+                ));
+            }
+        }
+    }
+    warnings
+}
+
+fn bash_tiers(bash: &BashConfig) -> impl Iterator<Item = (&'static str, &[BashRule])> {
+    [
+        ("deny", bash.deny.as_slice()),
+        ("ask", bash.ask.as_slice()),
+        ("allow", bash.allow.as_slice()),
+    ]
+    .into_iter()
+}
+
+/// Whether `broad` matches every command `narrow` matches.
+///
+/// Only decides the clear case: `broad` has no conditions beyond required
+/// flags and an inline subcommand, both implied by `narrow`'s own, and no
+/// carve-outs.
+fn bash_covers(broad: &BashRule, narrow: &BashRule) -> bool {
+    let b = &broad.conditions;
+    let n = &narrow.conditions;
+    broad.program == narrow.program
+        && b.optional_flags.is_empty()
+        && b.forbidden_flags.is_empty()
+        && b.exceptions.is_empty()
+        && b.positionals.is_empty()
+        && b.required_arguments.is_empty()
+        && b.subcommands.is_empty()
+        && b.required_flags.is_subset(&n.required_flags)
+        && n.subcommand.starts_with(&b.subcommand)
+}
+
+/// Glob file rules whose pattern cannot match a normalized path.
+fn files_never_match(files: &FilesConfig) -> Vec<Warning> {
+    let mut warnings = Vec::new();
+    for (tier, rule) in file_tiers(files)
+        .flat_map(|(tier, rules)| rules.iter().map(move |r| (tier, r)))
+        .filter(|(_, rule)| rule.regex.is_none())
+    {
+        let pattern = &rule.raw_pattern;
+        let absolute = ["/", "~", "<cwd>", "<home>", "*"]
+            .iter()
+            .any(|prefix| pattern.starts_with(prefix));
+        if !absolute {
+            warnings.push(Warning::new(
+                rule.line,
+                format!(
+                    "{tier} \"{pattern}\" can never match: paths are matched as absolute \
+                     paths; start it with /, ~, or <cwd>"
+                ),
+            ));
+        } else if pattern.len() > 1 && pattern.ends_with('/') {
+            warnings.push(Warning::new(
+                rule.line,
+                format!(
+                    "{tier} \"{pattern}\" can never match: normalized paths have no \
+                     trailing slash; use \"{pattern}**\""
+                ),
+            ));
+        }
+    }
+    warnings
+}
+
+/// File rules that a rule in a stricter tier (or an earlier rule in the
+/// same tier) always decides first.
+fn files_shadowed(files: &FilesConfig, sources: &SourceMap) -> Vec<Warning> {
+    let tiers: Vec<_> = file_tiers(files).collect();
+    let mut warnings = Vec::new();
+    for (position, (tier, rules)) in tiers.iter().enumerate() {
+        for (index, rule) in rules.iter().enumerate() {
+            let stricter = tiers[..position]
+                .iter()
+                .find_map(|(stricter_tier, stricter_rules)| {
+                    stricter_rules
+                        .iter()
+                        .find(|s| file_covers(s, rule))
+                        .map(|s| (*stricter_tier, s))
+                });
+            if let Some((stricter_tier, by)) = stricter {
+                warnings.push(Warning::new(
+                    rule.line,
+                    shadowed_message(
+                        tier,
+                        &rule.raw_pattern,
+                        stricter_tier,
+                        &by.raw_pattern,
+                        &sources.location(by.line),
+                    ),
+                ));
+            } else if let Some(first) = rules[..index].iter().find(|earlier| {
+                earlier.raw_pattern == rule.raw_pattern
+                    && earlier.exceptions.is_empty()
+                    && rule.operations.is_subset(&earlier.operations)
+            }) {
+                warnings.push(Warning::new(
+                    rule.line,
+                    duplicate_message(tier, &rule.raw_pattern, first.line, rule.line, sources),
+                ));
+            }
+        }
+    }
+    warnings
+}
+
+fn file_tiers(files: &FilesConfig) -> impl Iterator<Item = (&'static str, &[FileRule])> {
+    [
+        ("deny", files.deny.as_slice()),
+        ("ask", files.ask.as_slice()),
+        ("allow", files.allow.as_slice()),
+    ]
+    .into_iter()
+}
+
+/// Whether `broad` matches every path and operation `narrow` matches.
+///
+/// Only decides the clear cases: the same pattern, or `broad` is a literal
+/// directory followed by `/**` that `narrow` lies within, and `broad` has no
+/// `except` patterns.
+fn file_covers(broad: &FileRule, narrow: &FileRule) -> bool {
+    if !narrow.operations.is_subset(&broad.operations) || !broad.exceptions.is_empty() {
+        return false;
+    }
+    if broad.raw_pattern == narrow.raw_pattern {
+        return true;
+    }
+    broad
+        .raw_pattern
+        .strip_suffix("/**")
+        .filter(|dir| !dir.contains(['*', '?', '[', '{']))
+        .is_some_and(|dir| {
+            narrow
+                .raw_pattern
+                .strip_prefix(dir)
+                .is_some_and(|rest| rest.starts_with('/'))
+        })
+}
+
+/// Where the first copy of a duplicate rule is, or that both copies are
+/// on the line the warning already names.
+fn duplicate_message(
+    tier: &str,
+    raw: &str,
+    first_line: usize,
+    line: usize,
+    sources: &SourceMap,
+) -> String {
+    if first_line == line {
+        format!("duplicate {tier} \"{raw}\" (listed twice on this line)")
+    } else {
+        format!(
+            "duplicate {tier} \"{raw}\" (already at {})",
+            sources.location(first_line)
+        )
+    }
+}
+
+fn shadowed_message(
+    tier: &str,
+    raw: &str,
+    by_tier: &str,
+    by_raw: &str,
+    by_location: &str,
+) -> String {
+    let effect = if tier == "allow" {
+        "can never allow anything"
+    } else {
+        "can never apply"
+    };
+    format!("{tier} \"{raw}\" {effect}: {by_tier} \"{by_raw}\" ({by_location}) matches everything it does first")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn warnings(source: &str) -> Vec<String> {
+        let (_, warnings) = Config::parse_with_warnings(source).expect("config should load");
+        warnings.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn clean_config_has_no_warnings() {
+        let found = warnings(
+            r#"
+            define "readonly" "ls" "cat"
+            path-var "keys" "~/.ssh/**"
+            bash {
+                allow "git" "cargo" "@readonly"
+                deny "git push --force"
+                ask "git push"
+                max-nesting-depth 3
+            }
+            files {
+                deny "$keys" "read"
+                "<cwd>/**" { allow "read" "write"; }
+            }
+            "#,
+        );
+        assert!(found.is_empty(), "{found:?}");
+    }
+
+    #[test]
+    fn unknown_section_suggests_known_one() {
+        assert_eq!(
+            warnings("bsh {\n    allow \"git\"\n}"),
+            vec![r#"line 1: unknown section "bsh" is ignored; did you mean "bash"?"#]
+        );
+    }
+
+    #[test]
+    fn unknown_bash_node_is_reported() {
+        assert_eq!(
+            warnings("bash {\n    alow \"git\"\n}"),
+            vec![
+                r#"line 2: unknown node "alow" in bash section is ignored; did you mean "allow"?"#
+            ]
+        );
+    }
+
+    #[test]
+    fn allow_shadowed_by_deny_is_unreachable() {
+        assert_eq!(
+            warnings("bash {\n    deny \"git\"\n    allow \"git status\"\n}"),
+            vec![
+                r#"line 3: allow "git status" can never allow anything: deny "git" (line 2) matches everything it does first"#
+            ]
+        );
+    }
+
+    #[test]
+    fn ask_shadowed_by_broader_deny() {
+        assert_eq!(
+            warnings("bash {\n    deny \"rm -r\"\n    ask \"rm -rf /tmp\"\n}"),
+            vec![
+                r#"line 3: ask "rm -rf /tmp" can never apply: deny "rm -r" (line 2) matches everything it does first"#
+            ]
+        );
+    }
+
+    #[test]
+    fn narrower_stricter_rule_does_not_shadow() {
+        let found = warnings(
+            r#"bash {
+                deny "git push --force"
+                ask "git" { positionals "main"; }
+                allow "git"
+            }"#,
+        );
+        assert!(found.is_empty(), "{found:?}");
+    }
+
+    #[test]
+    fn duplicate_bash_rule_is_reported() {
+        assert_eq!(
+            warnings("bash {\n    allow \"git\"\n    allow \"cargo\" \"git\"\n}"),
+            vec![r#"line 3: duplicate allow "git" (already at line 2)"#]
+        );
+    }
+
+    #[test]
+    fn duplicate_on_one_line_does_not_point_back_to_it() {
+        assert_eq!(
+            warnings("bash {\n    allow \"ls\" \"ls\"\n}"),
+            vec![r#"line 2: duplicate allow "ls" (listed twice on this line)"#]
+        );
+    }
+
+    #[test]
+    fn double_dash_flag_never_matches() {
+        assert_eq!(
+            warnings("bash {\n    deny \"git --\"\n}"),
+            vec![r#"line 2: deny "git --" can never match: "--" ends options and is not a flag"#]
+        );
+    }
+
+    #[test]
+    fn flag_both_required_and_forbidden_never_matches() {
+        assert_eq!(
+            warnings("bash {\n    deny \"git push --force\" { forbidden-flags \"--force\"; }\n}"),
+            vec![
+                r#"line 2: deny "git push --force" can never match: "--force" is both required and forbidden"#
+            ]
+        );
+    }
+
+    #[test]
+    fn rule_with_except_does_not_shadow() {
+        let found = warnings(
+            r#"bash {
+                deny "git" { except "git status"; }
+                allow "git status"
+            }"#,
+        );
+        assert!(found.is_empty(), "{found:?}");
+    }
+
+    #[test]
+    fn glob_in_subcommand_is_literal() {
+        assert_eq!(
+            warnings("bash {\n    allow \"git log*\"\n}"),
+            vec![
+                r#"line 2: allow "git log*" only matches a literal "log*": subcommands are not patterns; use a positionals child instead"#
+            ]
+        );
+    }
+
+    #[test]
+    fn relative_and_trailing_slash_file_patterns_never_match() {
+        assert_eq!(
+            warnings("files {\n    deny \"secrets/**\" \"read\"\n    deny \"/etc/\" \"read\"\n}"),
+            vec![
+                r#"line 2: deny "secrets/**" can never match: paths are matched as absolute paths; start it with /, ~, or <cwd>"#,
+                r#"line 3: deny "/etc/" can never match: normalized paths have no trailing slash; use "/etc/**""#,
+            ]
+        );
+    }
+
+    #[test]
+    fn regex_file_patterns_are_not_globs() {
+        let found = warnings("files {\n    deny \"re:\\\\.env$\" \"read\"\n}");
+        assert!(found.is_empty(), "{found:?}");
+    }
+
+    #[test]
+    fn file_rule_inside_stricter_directory_is_shadowed() {
+        assert_eq!(
+            warnings("files {\n    deny \"/etc/**\" \"read\" \"write\"\n    allow \"/etc/hosts\" \"read\"\n}"),
+            vec![r#"line 3: allow "/etc/hosts" can never allow anything: deny "/etc/**" (line 2) matches everything it does first"#]
+        );
+    }
+
+    #[test]
+    fn file_rule_with_except_does_not_shadow() {
+        let found = warnings(
+            "files {\n    deny \"/etc/**\" \"read\" { except \"/etc/hosts\"; }\n    allow \"/etc/hosts\" \"read\"\n}",
+        );
+        assert!(found.is_empty(), "{found:?}");
+    }
+
+    #[test]
+    fn file_rule_for_other_operations_is_not_shadowed() {
+        let found = warnings(
+            "files {\n    deny \"/etc/**\" \"write\"\n    allow \"/etc/hosts\" \"read\"\n}",
+        );
+        assert!(found.is_empty(), "{found:?}");
+    }
+
+    #[test]
+    fn duplicate_file_rule_is_reported() {
+        assert_eq!(
+            warnings("files {\n    allow \"/tmp/**\" \"read\" \"write\"\n    allow \"/tmp/**\" \"read\"\n}"),
+            vec![r#"line 3: duplicate allow "/tmp/**" (already at line 2)"#]
+        );
+    }
+
+    #[test]
+    fn did_you_mean_needs_a_close_candidate() {
+        assert_eq!(did_you_mean("raed", &["read", "write"]), Some("read"));
+        assert_eq!(did_you_mean("delete", &["read", "write"]), None);
+        assert_eq!(did_you_mean("x", &["ls"]), None);
+    }
+}
@@ -0,0 +1,13 @@
+//! Decision log configuration.
+//!
+//! Parsing is in [`crate::config::parse::log`]. Writing the log is the
+//! hook command's job.
+
+use std::path::PathBuf;
+
+/// Where to append one JSON line per evaluation.
+#[derive(Debug)]
+pub struct LogConfig {
+    /// Absolute path, with `~` already expanded.
+    pub path: PathBuf,
+}
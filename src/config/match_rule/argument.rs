@@ -0,0 +1,69 @@
+use serde_json::Value;
+
+use crate::config::argument::{ArgumentMatch, ArgumentPredicate, PathSegment};
+
+/// Whether any value the path selects satisfies every predicate.
+///
+/// A path that selects nothing never matches.
+pub fn matches(argument: &ArgumentMatch, tool_input: &Value) -> bool {
+    select(&argument.path, tool_input).into_iter().any(|value| {
+        argument
+            .predicates
+            .iter()
+            .all(|predicate| predicate_matches(predicate, value))
+    })
+}
+
+/// Collect every value the path selects.
+fn select<'a>(path: &[PathSegment], root: &'a Value) -> Vec<&'a Value> {
+    let mut current = vec![root];
+    for segment in path {
+        current = current
+            .into_iter()
+            .flat_map(|value| -> Vec<&'a Value> {
+                match (segment, value) {
+                    (PathSegment::Key(key), Value::Object(map)) => {
+                        map.get(key).into_iter().collect()
+                    }
+                    (PathSegment::Key(key), Value::Array(items)) => key
+                        .parse::<usize>()
+                        .ok()
+                        .and_then(|i| items.get(i))
+                        .into_iter()
+                        .collect(),
+                    (PathSegment::Index(i), Value::Array(items)) => {
+                        items.get(*i).into_iter().collect()
+                    }
+                    (PathSegment::Wildcard, Value::Array(items)) => items.iter().collect(),
+                    (PathSegment::Wildcard, Value::Object(map)) => map.values().collect(),
+                    _ => vec![],
+                }
+            })
+            .collect();
+    }
+    current
+}
+
+fn predicate_matches(predicate: &ArgumentPredicate, value: &Value) -> bool {
+    match predicate {
+        ArgumentPredicate::Equals(expected) => match (expected.as_f64(), value.as_f64()) {
+            (Some(a), Some(b)) => a == b,
+            _ => expected == value,
+        },
+        ArgumentPredicate::Glob(glob) => text(value).is_some_and(|t| glob.is_match(t.as_str())),
+        ArgumentPredicate::Regex(re) => text(value).is_some_and(|t| re.is_match(&t)),
+        ArgumentPredicate::Gt(n) => value.as_f64().is_some_and(|v| v > *n),
+        ArgumentPredicate::Gte(n) => value.as_f64().is_some_and(|v| v >= *n),
+        ArgumentPredicate::Lt(n) => value.as_f64().is_some_and(|v| v < *n),
+        ArgumentPredicate::Lte(n) => value.as_f64().is_some_and(|v| v <= *n),
+    }
+}
+
+/// Text of a scalar for glob and regex matching; `None` for arrays and objects.
+fn text(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(_) | Value::Bool(_) | Value::Null => Some(value.to_string()),
+        Value::Array(_) | Value::Object(_) => None,
+    }
+}
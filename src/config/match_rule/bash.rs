@@ -1,9 +1,103 @@
 use std::collections::HashSet;
 
 use crate::command::CommandSegment;
+use crate::config::flag_schema::ProgramFlags;
 use crate::config::rule::{ArgumentPattern, BashRule};
 
+/// One condition of a rule checked against a segment, as shown by `explain`.
+#[derive(Debug, PartialEq)]
+pub(crate) struct ConditionCheck {
+    /// Condition kind: `program`, `flags`, `any flag`, `no flag`,
+    /// `subcommand`, `positionals`, `arguments`, `subcommands`, or `except`.
+    pub(crate) kind: &'static str,
+    /// What the rule requires, as written.
+    pub(crate) expected: String,
+    pub(crate) passed: bool,
+}
+
 impl BashRule {
+    /// Check each of this rule's conditions separately.
+    ///
+    /// The program is always checked; other conditions only when the rule
+    /// sets them. The rule [`matches`](Self::matches) exactly when all pass.
+    pub(crate) fn check_conditions(&self, segment: &CommandSegment) -> Vec<ConditionCheck> {
+        let conditions = &self.conditions;
+        let mut checks = vec![ConditionCheck {
+            kind: "program",
+            expected: self.program.to_string(),
+            passed: self.program_matches(segment),
+        }];
+        let mut check = |kind, expected: Vec<String>, passed| {
+            if !expected.is_empty() {
+                checks.push(ConditionCheck {
+                    kind,
+                    expected: expected.join(" "),
+                    passed,
+                });
+            }
+        };
+        let sorted = |flags: &HashSet<crate::domain::Flag>| {
+            let mut flags: Vec<String> = flags.iter().map(|f| f.as_str().to_string()).collect();
+            flags.sort();
+            flags
+        };
+        check(
+            "flags",
+            sorted(&conditions.required_flags),
+            self.required_flags_match(segment),
+        );
+        check(
+            "any flag",
+            sorted(&conditions.optional_flags),
+            self.optional_flags_match(segment),
+        );
+        check(
+            "no flag",
+            sorted(&conditions.forbidden_flags),
+            self.forbidden_flags_absent(segment),
+        );
+        check(
+            "subcommand",
+            conditions.subcommand.clone(),
+            self.subcommand_matches(segment),
+        );
+        check(
+            "positionals",
+            conditions
+                .positionals
+                .iter()
+                .map(|p| p.raw.clone())
+                .collect(),
+            self.positionals_match(segment),
+        );
+        check(
+            "arguments",
+            conditions
+                .required_arguments
+                .iter()
+                .map(|a| format!("{} {}", a.flag, a.value.raw))
+                .collect(),
+            self.required_arguments_match(segment),
+        );
+        check(
+            "subcommands",
+            conditions
+                .subcommands
+                .iter()
+                .map(|chain| format!("[{}]", chain.join(" ")))
+                .collect(),
+            self.subcommands_match(segment),
+        );
+        for exception in &conditions.exceptions {
+            check(
+                "except",
+                vec![exception.raw.clone()],
+                !exception.matches(segment),
+            );
+        }
+        checks
+    }
+
     /// Check whether a parsed command segment satisfies this rule's conditions.
     ///
     /// All non-empty conditions must pass (AND semantics).
@@ -15,6 +109,7 @@ impl BashRule {
             && self.positionals_match(segment)
             && self.required_arguments_match(segment)
             && self.subcommands_match(segment)
+            && !self.excepted(segment)
     }
 
     /// Program name must match (both sides are already basename-normalized).
@@ -23,26 +118,53 @@ impl BashRule {
     }
 
     /// Required flags: ALL must be present. Optional flags: if non-empty, ANY one must be present.
+    /// Forbidden flags: NONE may be present.
     fn flags_match(&self, segment: &CommandSegment) -> bool {
-        let (actual_flags, _) = classify_args(&segment.args);
-        if !self
-            .conditions
+        self.required_flags_match(segment)
+            && self.optional_flags_match(segment)
+            && self.forbidden_flags_absent(segment)
+    }
+
+    fn required_flags_match(&self, segment: &CommandSegment) -> bool {
+        let schema = &self.conditions.schema;
+        let (actual_flags, _) = classify_args(&segment.args, schema);
+        self.conditions
             .required_flags
             .iter()
-            .all(|f| actual_flags.contains(f.as_str()))
-        {
-            return false;
+            .all(|f| actual_flags.contains(schema.canonical(f.as_str())))
+    }
+
+    fn optional_flags_match(&self, segment: &CommandSegment) -> bool {
+        if self.conditions.optional_flags.is_empty() {
+            return true;
         }
-        if !self.conditions.optional_flags.is_empty()
-            && !self
-                .conditions
-                .optional_flags
-                .iter()
-                .any(|f| actual_flags.contains(f.as_str()))
-        {
-            return false;
+        let schema = &self.conditions.schema;
+        let (actual_flags, _) = classify_args(&segment.args, schema);
+        self.conditions
+            .optional_flags
+            .iter()
+            .any(|f| actual_flags.contains(schema.canonical(f.as_str())))
+    }
+
+    fn forbidden_flags_absent(&self, segment: &CommandSegment) -> bool {
+        if self.conditions.forbidden_flags.is_empty() {
+            return true;
         }
-        true
+        let schema = &self.conditions.schema;
+        let (actual_flags, _) = classify_args(&segment.args, schema);
+        !self
+            .conditions
+            .forbidden_flags
+            .iter()
+            .any(|f| actual_flags.contains(schema.canonical(f.as_str())))
+    }
+
+    /// Whether an `except` carve-out matches the segment.
+    fn excepted(&self, segment: &CommandSegment) -> bool {
+        self.conditions
+            .exceptions
+            .iter()
+            .any(|exception| exception.matches(segment))
     }
 
     /// Subcommand chain from rule string: ordered prefix of actual non-flag args.
@@ -50,7 +172,7 @@ impl BashRule {
         if self.conditions.subcommand.is_empty() {
             return true;
         }
-        let (_, actual_positionals) = classify_args(&segment.args);
+        let (_, actual_positionals) = classify_args(&segment.args, &self.conditions.schema);
         if actual_positionals.len() < self.conditions.subcommand.len() {
             return false;
         }
@@ -66,7 +188,7 @@ impl BashRule {
         if self.conditions.positionals.is_empty() {
             return true;
         }
-        let (_, actual_positionals) = classify_args(&segment.args);
+        let (_, actual_positionals) = classify_args(&segment.args, &self.conditions.schema);
         self.conditions.positionals.iter().all(|pattern| {
             actual_positionals
                 .iter()
@@ -79,7 +201,7 @@ impl BashRule {
         self.conditions
             .required_arguments
             .iter()
-            .all(|req| find_argument_value(&segment.args, req))
+            .all(|req| find_argument_value(&segment.args, req, &self.conditions.schema))
     }
 
     /// Subcommands from children block: OR list of ordered prefix chains.
@@ -87,7 +209,7 @@ impl BashRule {
         if self.conditions.subcommands.is_empty() {
             return true;
         }
-        let (_, actual_positionals) = classify_args(&segment.args);
+        let (_, actual_positionals) = classify_args(&segment.args, &self.conditions.schema);
         self.conditions.subcommands.iter().any(|chain| {
             if actual_positionals.len() < chain.len() {
                 return false;
@@ -104,12 +226,18 @@ impl BashRule {
 ///
 /// Flags start with `-` (not `-` alone or `--`). `--` marks end-of-options:
 /// everything after it is positional regardless of dashes. `--` itself is
-/// excluded from both sets. `-` (stdin) is a positional.
-fn classify_args(args: &[String]) -> (HashSet<&str>, Vec<&str>) {
+/// excluded from both sets. `-` (stdin) is a positional. Flags are given by
+/// their canonical name in `schema`, without an `=value` suffix; the
+/// argument after a flag that takes a value is in neither set.
+fn classify_args<'a>(
+    args: &'a [String],
+    schema: &'a ProgramFlags,
+) -> (HashSet<&'a str>, Vec<&'a str>) {
     let mut flags = HashSet::new();
     let mut positionals = Vec::new();
     let mut end_of_options = false;
-    for arg in args {
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
         if end_of_options {
             positionals.push(arg.as_str());
             continue;
@@ -119,7 +247,17 @@ fn classify_args(args: &[String]) -> (HashSet<&str>, Vec<&str>) {
             continue;
         }
         if arg.starts_with('-') && arg != "-" {
-            flags.insert(arg.as_str());
+            match arg.split_once('=') {
+                Some((flag, _)) => {
+                    flags.insert(schema.canonical(flag));
+                }
+                None => {
+                    flags.insert(schema.canonical(arg));
+                    if schema.takes_value(arg) {
+                        args.next();
+                    }
+                }
+            }
         } else {
             positionals.push(arg.as_str());
         }
@@ -129,20 +267,20 @@ fn classify_args(args: &[String]) -> (HashSet<&str>, Vec<&str>) {
 
 /// Check if a required argument (flag+value) is present in the args.
 ///
-/// Looks for the flag in two forms:
+/// Looks for the flag, or another name `schema` gives it, in two forms:
 /// 1. Separate: `--flag value` (flag at position i, value at i+1 if not a flag)
 /// 2. Equals: `--flag=value` (split on first `=`)
 ///
 /// Honors `--` as end-of-options: tokens after `--` are positional and
 /// cannot satisfy flag-value requirements.
-fn find_argument_value(args: &[String], req: &ArgumentPattern) -> bool {
+fn find_argument_value(args: &[String], req: &ArgumentPattern, schema: &ProgramFlags) -> bool {
     for (i, arg) in args.iter().enumerate() {
         // Stop interpreting flags after --
         if *arg == "--" {
             return false;
         }
         // Form 1: separate args (--flag value)
-        if *arg == req.flag {
+        if schema.same(arg, &req.flag) {
             if let Some(next) = args.get(i + 1) {
                 if (!next.starts_with('-') || next == "-")
                     && req.value.matcher.is_match(next.as_str())
@@ -153,11 +291,9 @@ fn find_argument_value(args: &[String], req: &ArgumentPattern) -> bool {
             continue;
         }
         // Form 2: equals form (--flag=value)
-        if let Some(rest) = arg.strip_prefix(&req.flag) {
-            if let Some(value) = rest.strip_prefix('=') {
-                if req.value.matcher.is_match(value) {
-                    return true;
-                }
+        if let Some((flag, value)) = arg.split_once('=') {
+            if schema.same(flag, &req.flag) && req.value.matcher.is_match(value) {
+                return true;
             }
         }
     }
@@ -174,12 +310,15 @@ mod tests {
         CommandSegment {
             program: crate::domain::ProgramName::new(program),
             args: args.iter().map(|s| s.to_string()).collect(),
+            cwd: crate::command::WorkingDir::default(),
         }
     }
 
     fn rule(program: &str) -> BashRule {
         BashRule {
             program: crate::domain::ProgramName::new(program),
+            raw: program.to_string(),
+            line: 0,
             conditions: RuleConditions::default(),
         }
     }
@@ -193,6 +332,8 @@ mod tests {
         }
         BashRule {
             program: crate::domain::ProgramName::new(program),
+            raw: program.to_string(),
+            line: 0,
             conditions,
         }
     }
@@ -206,6 +347,8 @@ mod tests {
         }
         BashRule {
             program: crate::domain::ProgramName::new(program),
+            raw: program.to_string(),
+            line: 0,
             conditions,
         }
     }
@@ -213,6 +356,8 @@ mod tests {
     fn rule_subcommand(program: &str, subcmd: &[&str]) -> BashRule {
         BashRule {
             program: crate::domain::ProgramName::new(program),
+            raw: program.to_string(),
+            line: 0,
             conditions: RuleConditions {
                 subcommand: subcmd.iter().map(|s| s.to_string()).collect(),
                 ..Default::default()
@@ -227,6 +372,8 @@ mod tests {
         }
         BashRule {
             program: crate::domain::ProgramName::new(program),
+            raw: program.to_string(),
+            line: 0,
             conditions,
         }
     }
@@ -240,6 +387,8 @@ mod tests {
         }
         BashRule {
             program: crate::domain::ProgramName::new(program),
+            raw: program.to_string(),
+            line: 0,
             conditions,
         }
     }
@@ -254,6 +403,8 @@ mod tests {
         }
         BashRule {
             program: crate::domain::ProgramName::new(program),
+            raw: program.to_string(),
+            line: 0,
             conditions,
         }
     }
@@ -462,6 +613,8 @@ mod tests {
     ) -> BashRule {
         BashRule {
             program: crate::domain::ProgramName::new(program),
+            raw: program.to_string(),
+            line: 0,
             conditions: RuleConditions {
                 subcommand: subcmd.iter().map(|s| s.to_string()).collect(),
                 positionals: patterns.iter().map(|p| compile_glob(p).unwrap()).collect(),
@@ -612,6 +765,8 @@ mod tests {
     fn rule_subcommands_with_flags(program: &str, chains: &[&[&str]], flags: &[&str]) -> BashRule {
         BashRule {
             program: crate::domain::ProgramName::new(program),
+            raw: program.to_string(),
+            line: 0,
             conditions: RuleConditions {
                 subcommands: chains
                     .iter()
@@ -760,6 +915,8 @@ mod tests {
     ) -> BashRule {
         BashRule {
             program: crate::domain::ProgramName::new(program),
+            raw: program.to_string(),
+            line: 0,
             conditions: RuleConditions {
                 subcommands: chains
                     .iter()
@@ -773,7 +930,7 @@ mod tests {
 
     #[test]
     fn match_subcommands_and_positionals_both_pass() {
-        // deny "git" { subcommands "push"; remotes "origin" }
+        // deny "git" { subcommands "push"; positionals "origin" }
         let r = rule_subcommands_with_positionals("git", &[&["push"]], &["origin"]);
         assert!(r.matches(&seg("git", &["push", "origin", "main"])));
     }
@@ -808,6 +965,8 @@ mod tests {
     ) -> BashRule {
         BashRule {
             program: crate::domain::ProgramName::new(program),
+            raw: program.to_string(),
+            line: 0,
             conditions: RuleConditions {
                 subcommands: chains
                     .iter()
@@ -876,6 +1035,8 @@ mod tests {
         // Actual: "push origin main" → subcommand prefix ["push"] ✓, chain ["push","origin"] ✓
         let r = BashRule {
             program: crate::domain::ProgramName::new("git"),
+            raw: "git".to_string(),
+            line: 0,
             conditions: RuleConditions {
                 subcommand: vec!["push".to_string()],
                 subcommands: vec![vec!["push".to_string(), "origin".to_string()]],
@@ -890,6 +1051,8 @@ mod tests {
         // subcommand: ["push"] ✓, subcommands: [["push","origin"]] ✗ (upstream ≠ origin)
         let r = BashRule {
             program: crate::domain::ProgramName::new("git"),
+            raw: "git".to_string(),
+            line: 0,
             conditions: RuleConditions {
                 subcommand: vec!["push".to_string()],
                 subcommands: vec![vec!["push".to_string(), "origin".to_string()]],
@@ -904,6 +1067,8 @@ mod tests {
         // subcommand: ["push"] ✗ (pull ≠ push), subcommands: [["pull"]] would pass but moot
         let r = BashRule {
             program: crate::domain::ProgramName::new("git"),
+            raw: "git".to_string(),
+            line: 0,
             conditions: RuleConditions {
                 subcommand: vec!["push".to_string()],
                 subcommands: vec![vec!["pull".to_string()]],
@@ -912,4 +1077,179 @@ mod tests {
         };
         assert!(!r.matches(&seg("git", &["pull", "origin"])));
     }
+
+    // --- Group 15: Regex Positionals and Arguments ---
+
+    fn git_push_protected() -> BashRule {
+        let mut r = rule_subcommand("git", &["push"]);
+        r.conditions
+            .positionals
+            .push(crate::config::rule::compile_regex("^(main|release/.*)$").unwrap());
+        r
+    }
+
+    #[test]
+    fn match_regex_positional() {
+        let r = git_push_protected();
+        assert!(r.matches(&seg("git", &["push", "origin", "main"])));
+        assert!(r.matches(&seg("git", &["push", "origin", "release/2.1"])));
+    }
+
+    #[test]
+    fn no_match_regex_positional() {
+        let r = git_push_protected();
+        assert!(!r.matches(&seg("git", &["push", "origin", "feature/main"])));
+        assert!(!r.matches(&seg("git", &["push", "origin", "mainline"])));
+    }
+
+    #[test]
+    fn match_regex_argument_both_forms() {
+        let mut conditions = RuleConditions::default();
+        conditions.required_arguments.push(ArgumentPattern {
+            flag: "--url".to_string(),
+            value: crate::config::rule::compile_regex(r"^https?://\d+\.\d+\.\d+\.\d+").unwrap(),
+        });
+        let r = BashRule {
+            program: crate::domain::ProgramName::new("curl"),
+            raw: "curl".to_string(),
+            line: 0,
+            conditions,
+        };
+        assert!(r.matches(&seg("curl", &["--url", "http://10.0.0.1/x"])));
+        assert!(r.matches(&seg("curl", &["--url=https://192.168.1.1"])));
+        assert!(!r.matches(&seg("curl", &["--url", "https://example.com"])));
+    }
+
+    // --- Group 16: Forbidden Flags and Except Carve-outs ---
+
+    fn git_except_force_push() -> BashRule {
+        let mut r = rule("git");
+        r.conditions
+            .exceptions
+            .push(rule_required_flags("git", &["--force"]));
+        r.conditions.exceptions[0]
+            .conditions
+            .subcommand
+            .push("push".to_string());
+        r
+    }
+
+    #[test]
+    fn match_forbidden_flag_absent() {
+        let mut r = rule_subcommand("git", &["push"]);
+        r.conditions
+            .forbidden_flags
+            .insert(crate::domain::Flag::new("--force"));
+        assert!(r.matches(&seg("git", &["push", "origin"])));
+        assert!(!r.matches(&seg("git", &["push", "--force", "origin"])));
+    }
+
+    #[test]
+    fn match_except_leaves_other_commands() {
+        let r = git_except_force_push();
+        assert!(r.matches(&seg("git", &["push", "origin"])));
+        assert!(r.matches(&seg("git", &["status", "--force"])));
+    }
+
+    #[test]
+    fn no_match_when_except_matches() {
+        let r = git_except_force_push();
+        assert!(!r.matches(&seg("git", &["push", "--force", "origin"])));
+    }
+
+    #[test]
+    fn except_shows_as_a_condition_check() {
+        let mut r = git_except_force_push();
+        r.conditions.exceptions[0].raw = "git push --force".to_string();
+        let checks = r.check_conditions(&seg("git", &["push", "--force"]));
+        assert_eq!(
+            checks.last(),
+            Some(&ConditionCheck {
+                kind: "except",
+                expected: "git push --force".to_string(),
+                passed: false,
+            })
+        );
+    }
+
+    // --- Group 17: Flags That Take Values ---
+
+    fn with_value_flags(mut r: BashRule, flags: &[&str]) -> BashRule {
+        r.conditions.schema.value_flags =
+            flags.iter().map(|f| crate::domain::Flag::new(f)).collect();
+        r
+    }
+
+    #[test]
+    fn match_subcommand_after_flag_value() {
+        let r = with_value_flags(rule_subcommand("git", &["push"]), &["-C"]);
+        assert!(r.matches(&seg("git", &["-C", "/tmp", "push"])));
+        // Without the schema, the value reads as the subcommand.
+        assert!(!rule_subcommand("git", &["push"]).matches(&seg("git", &["-C", "/tmp", "push"])));
+    }
+
+    #[test]
+    fn flag_value_is_not_a_positional() {
+        let r = with_value_flags(rule_positionals("kubectl", &["prod"]), &["-n"]);
+        assert!(!r.matches(&seg("kubectl", &["-n", "prod", "get", "pods"])));
+        assert!(r.matches(&seg("kubectl", &["get", "prod"])));
+    }
+
+    #[test]
+    fn value_flag_still_counts_as_flag() {
+        let mut r = with_value_flags(rule_required_flags("kubectl", &["-n"]), &["-n"]);
+        r.conditions.subcommand.push("delete".to_string());
+        assert!(r.matches(&seg("kubectl", &["-n", "prod", "delete", "pod"])));
+    }
+
+    // --- Group 18: Flag Aliases, Abbreviations, and `=value` ---
+
+    fn with_schema(mut r: BashRule) -> BashRule {
+        r.conditions.schema =
+            crate::config::flag_schema::FlagSchema::default().for_program(r.program.as_str());
+        r
+    }
+
+    #[test]
+    fn match_long_aliases_of_short_flags() {
+        let r = with_schema(rule_required_flags("rm", &["-r", "-f"]));
+        assert!(r.matches(&seg("rm", &["--recursive", "--force", "/"])));
+        assert!(r.matches(&seg("rm", &["-R", "--force", "/"])));
+        assert!(!r.matches(&seg("rm", &["--recursive", "/"])));
+    }
+
+    #[test]
+    fn match_abbreviated_long_flags() {
+        let r = with_schema(rule_required_flags("rm", &["--force"]));
+        assert!(r.matches(&seg("rm", &["--forc", "x"])));
+        assert!(r.matches(&seg("rm", &["-f", "x"])));
+    }
+
+    #[test]
+    fn forbidden_flag_matches_its_aliases() {
+        let mut r = with_schema(rule_subcommand("git", &["push"]));
+        r.conditions
+            .forbidden_flags
+            .insert(crate::domain::Flag::new("-f"));
+        assert!(!r.matches(&seg("git", &["push", "--force"])));
+        assert!(r.matches(&seg("git", &["push", "--force-with-lease"])));
+    }
+
+    #[test]
+    fn equals_form_counts_as_the_flag() {
+        let r = with_schema(rule_required_flags("git", &["--force-with-lease"]));
+        assert!(r.matches(&seg("git", &["push", "--force-with-lease=main"])));
+    }
+
+    #[test]
+    fn required_argument_matches_alias() {
+        let mut r = with_schema(rule("kubectl"));
+        r.conditions.required_arguments.push(ArgumentPattern {
+            flag: "--namespace".to_string(),
+            value: compile_glob("prod").unwrap(),
+        });
+        assert!(r.matches(&seg("kubectl", &["-n", "prod", "delete"])));
+        assert!(r.matches(&seg("kubectl", &["-n=prod", "delete"])));
+        assert!(!r.matches(&seg("kubectl", &["-n", "dev", "delete"])));
+    }
 }
@@ -1,4 +1,5 @@
-use crate::config::files::{FileRule, FilesConfig};
+use super::RuleMatch;
+use crate::config::files::{FileRewrite, FileRule, FilesConfig};
 use crate::protocol::Decision;
 use crate::protocol::FileOperation;
 
@@ -13,68 +14,168 @@ use crate::protocol::FileOperation;
 ///
 /// Invalid glob patterns fail toward the more restrictive outcome:
 /// deny/ask tiers treat errors as matching, allow tier treats errors as non-matching.
+/// A rule does not match a path one of its `except` patterns matches.
+#[cfg(test)]
 pub fn lookup(
     config: &FilesConfig,
     normalized_path: &str,
     operation: FileOperation,
     cwd: &str,
 ) -> Option<Decision> {
+    lookup_match(config, normalized_path, operation, cwd).map(|m| m.decision)
+}
+
+/// Look up a path, reporting the deciding rule along with the decision.
+///
+/// For the fail-closed `Ask`, that is the rule whose pattern failed expansion.
+pub fn lookup_match(
+    config: &FilesConfig,
+    normalized_path: &str,
+    operation: FileOperation,
+    cwd: &str,
+) -> Option<RuleMatch> {
     // Fail-closed: if any rule for this operation has an expansion error
     // (e.g., $HOME not set), return Ask unconditionally to avoid silent deny.
-    if has_expansion_error(&config.deny, operation)
-        || has_expansion_error(&config.ask, operation)
-        || has_expansion_error(&config.allow, operation)
+    if let Some((tier, rule)) = [
+        (Decision::Deny, &config.deny),
+        (Decision::Ask, &config.ask),
+        (Decision::Allow, &config.allow),
+    ]
+    .into_iter()
+    .find_map(|(tier, rules)| find_expansion_error(rules, operation).map(|rule| (tier, rule)))
     {
-        return Some(Decision::Ask);
-    }
-    if matches_any_rule(&config.deny, normalized_path, operation, cwd, true) {
-        return Some(Decision::Deny);
-    }
-    if matches_any_rule(&config.ask, normalized_path, operation, cwd, true) {
-        return Some(Decision::Ask);
-    }
-    if matches_any_rule(&config.allow, normalized_path, operation, cwd, false) {
-        return Some(Decision::Allow);
+        return Some(RuleMatch {
+            decision: Decision::Ask,
+            tier,
+            rule: rule.raw_pattern.clone(),
+            line: rule.line,
+        });
     }
-    None
+    [
+        (Decision::Deny, &config.deny, true),
+        (Decision::Ask, &config.ask, true),
+        (Decision::Allow, &config.allow, false),
+    ]
+    .into_iter()
+    .find_map(|(decision, rules, error_means_match)| {
+        find_rule(rules, normalized_path, operation, cwd, error_means_match).map(|rule| RuleMatch {
+            decision: decision.clone(),
+            tier: decision,
+            rule: rule.raw_pattern.clone(),
+            line: rule.line,
+        })
+    })
+}
+
+/// Find the first rewrite whose rule matches the path and operation.
+///
+/// A pattern that cannot be decided does not match, so the call is left as
+/// it is and its decision stands.
+pub fn rewrite<'a>(
+    config: &'a FilesConfig,
+    normalized_path: &str,
+    operation: FileOperation,
+    cwd: &str,
+) -> Option<&'a FileRewrite> {
+    config.rewrites.iter().find(|rewrite| {
+        rewrite.rule.operations.contains(&operation)
+            && rule_matches(&rewrite.rule, normalized_path, cwd, false)
+    })
 }
 
-/// Returns `true` if any rule in the tier for the given operation has a
-/// pattern that failed home expansion.
-fn has_expansion_error(rules: &[FileRule], operation: FileOperation) -> bool {
+/// Find a rule in the tier for the given operation whose pattern failed
+/// home expansion.
+fn find_expansion_error(rules: &[FileRule], operation: FileOperation) -> Option<&FileRule> {
     rules
         .iter()
-        .any(|rule| rule.operations.contains(&operation) && rule.home_expanded_pattern.is_err())
+        .find(|rule| rule.operations.contains(&operation) && rule.home_expanded_pattern.is_err())
 }
 
-/// Check if any rule in a tier matches the given path and operation.
+/// Find the first rule in a tier that matches the given path and operation.
 ///
 /// `error_means_match`: when `true`, invalid glob patterns are treated as
 /// matching (fail-closed for deny/ask); when `false`, treated as non-matching
 /// (fail-closed for allow).
-fn matches_any_rule(
-    rules: &[FileRule],
+fn find_rule<'a>(
+    rules: &'a [FileRule],
     normalized_path: &str,
     operation: FileOperation,
     cwd: &str,
     error_means_match: bool,
+) -> Option<&'a FileRule> {
+    rules.iter().find(|rule| {
+        rule.operations.contains(&operation)
+            && rule_matches(rule, normalized_path, cwd, error_means_match)
+    })
+}
+
+/// Whether a rule's pattern matches the path and none of its `except`
+/// patterns do.
+///
+/// An exception that cannot be decided fails the other way from the rule:
+/// it carves the path out of an allow, but not out of a deny or ask.
+fn rule_matches(
+    rule: &FileRule,
+    normalized_path: &str,
+    cwd: &str,
+    error_means_match: bool,
 ) -> bool {
-    rules.iter().any(|rule| {
-        if !rule.operations.contains(&operation) {
-            return false;
-        }
-        let home_expanded = match &rule.home_expanded_pattern {
-            Ok(p) => p,
-            Err(_) => return error_means_match,
-        };
-        let expanded = home_expanded.replace("<cwd>", cwd);
-        crate::path::matches(normalized_path, &expanded).unwrap_or(error_means_match)
+    pattern_matches(rule, normalized_path, cwd, error_means_match)
+        && !rule
+            .exceptions
+            .iter()
+            .any(|exception| pattern_matches(exception, normalized_path, cwd, !error_means_match))
+}
+
+/// Whether a rule's pattern matches the path, with `error_means_match`
+/// deciding unexpandable and invalid patterns as in [`find_rule`].
+fn pattern_matches(
+    rule: &FileRule,
+    normalized_path: &str,
+    cwd: &str,
+    error_means_match: bool,
+) -> bool {
+    let home_expanded = match &rule.home_expanded_pattern {
+        Ok(p) => p,
+        Err(_) => return error_means_match,
+    };
+    if let Some(regex) = &rule.regex {
+        return regex.is_match(normalized_path, cwd);
+    }
+    let expanded = home_expanded.replace("<cwd>", cwd);
+    crate::path::matches(normalized_path, &expanded).unwrap_or(error_means_match)
+}
+
+/// Every rule covering `operation`, in lookup order (deny, ask, allow), with
+/// its tier and whether its pattern matches the path.
+pub fn considered<'a>(
+    config: &'a FilesConfig,
+    normalized_path: &str,
+    operation: FileOperation,
+    cwd: &str,
+) -> Vec<(Decision, &'a FileRule, bool)> {
+    [
+        (Decision::Deny, &config.deny, true),
+        (Decision::Ask, &config.ask, true),
+        (Decision::Allow, &config.allow, false),
+    ]
+    .into_iter()
+    .flat_map(|(tier, rules, error_means_match)| {
+        rules
+            .iter()
+            .filter(move |rule| rule.operations.contains(&operation))
+            .map(move |rule| {
+                let matched = rule_matches(rule, normalized_path, cwd, error_means_match);
+                (tier.clone(), rule, matched)
+            })
     })
+    .collect()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::define::Definitions;
     use crate::config::document::ConfigDocument;
     use crate::config::files::FilesConfig;
     use crate::config::parse::files::parse_files;
@@ -83,7 +184,7 @@ mod tests {
     fn parse_files_from_source(source: &str) -> Result<Option<FilesConfig>, ConfigError> {
         let wrapped = format!("files {{\n{source}\n}}");
         let doc = ConfigDocument::parse(&wrapped)?;
-        parse_files(&doc)
+        parse_files(&doc, &Definitions::default())
     }
 
     fn files(source: &str) -> FilesConfig {
@@ -161,13 +262,100 @@ mod tests {
         assert_eq!(result, None);
     }
 
+    #[test]
+    fn lookup_regex_pattern() {
+        let config = files(r#"deny "re:/\\.(env|pem)$" "read""#);
+        let result = lookup(&config, "/srv/app/.env", FileOperation::Read, "/");
+        assert_eq!(result, Some(Decision::Deny));
+        let result = lookup(&config, "/srv/app/.envrc", FileOperation::Read, "/");
+        assert_eq!(result, None);
+    }
+
+    #[test]
+    fn lookup_regex_after_cwd() {
+        let config = files(r#"allow "re:<cwd>/(src|tests)/.*\\.rs$" "write""#);
+        let result = lookup(&config, "/repo/src/lib.rs", FileOperation::Write, "/repo");
+        assert_eq!(result, Some(Decision::Allow));
+        // Anchored after the working directory, not anywhere in the path.
+        let result = lookup(
+            &config,
+            "/repo/vendor/src/lib.rs",
+            FileOperation::Write,
+            "/repo",
+        );
+        assert_eq!(result, None);
+        let result = lookup(&config, "/other/src/lib.rs", FileOperation::Write, "/repo");
+        assert_eq!(result, None);
+    }
+
+    #[test]
+    fn lookup_regex_home_prefix_is_anchored() {
+        let home = std::env::var("HOME").unwrap();
+        let config = files(r#"deny "re:~/\\.aws/" "read""#);
+        let inside = format!("{home}/.aws/credentials");
+        assert_eq!(
+            lookup(&config, &inside, FileOperation::Read, "/"),
+            Some(Decision::Deny)
+        );
+        let elsewhere = format!("/backup{home}/.aws/credentials");
+        assert_eq!(lookup(&config, &elsewhere, FileOperation::Read, "/"), None);
+    }
+
+    #[test]
+    fn lookup_except_carves_out_of_allow() {
+        let config = files(r#"allow "<cwd>/**" "read" "write" { except "<cwd>/.git/**"; }"#);
+        let result = lookup(&config, "/repo/src/main.rs", FileOperation::Write, "/repo");
+        assert_eq!(result, Some(Decision::Allow));
+        let result = lookup(&config, "/repo/.git/config", FileOperation::Write, "/repo");
+        assert_eq!(result, None);
+    }
+
+    #[test]
+    fn lookup_except_falls_through_to_later_tier() {
+        let config = files(
+            r#"
+            "/etc/**" {
+                deny "read"
+                except "/etc/hostname" "re:\\.conf$"
+            }
+            allow "/etc/**" "read"
+            "#,
+        );
+        let result = lookup(&config, "/etc/shadow", FileOperation::Read, "/");
+        assert_eq!(result, Some(Decision::Deny));
+        let result = lookup(&config, "/etc/hostname", FileOperation::Read, "/");
+        assert_eq!(result, Some(Decision::Allow));
+        let result = lookup(&config, "/etc/resolv.conf", FileOperation::Read, "/");
+        assert_eq!(result, Some(Decision::Allow));
+    }
+
+    #[test]
+    fn lookup_unexpandable_except_fails_closed() {
+        let mut deny = rule_with_expansion_error(&[]);
+        deny.raw_pattern = "/secrets/**".to_string();
+        deny.home_expanded_pattern = Ok("/secrets/**".to_string());
+        deny.operations = [FileOperation::Read].into_iter().collect();
+        deny.exceptions = vec![rule_with_expansion_error(&[FileOperation::Read])];
+        let config = FilesConfig {
+            deny: vec![deny],
+            ask: vec![],
+            allow: vec![],
+            rewrites: vec![],
+        };
+        // An exception that cannot be expanded does not lift the deny.
+        let result = lookup(&config, "/secrets/key", FileOperation::Read, "/");
+        assert_eq!(result, Some(Decision::Deny));
+    }
+
     // --- Expansion error → fail-closed Ask ---
 
     fn rule_with_expansion_error(operations: &[FileOperation]) -> FileRule {
         FileRule {
             raw_pattern: "~/.ssh/**".to_string(),
             home_expanded_pattern: Err(crate::domain::PathError::HomeNotSet("$HOME".to_string())),
+            regex: None,
             operations: operations.iter().cloned().collect(),
+            exceptions: vec![],
             line: 1,
         }
     }
@@ -178,6 +366,7 @@ mod tests {
             deny: vec![rule_with_expansion_error(&[FileOperation::Read])],
             ask: vec![],
             allow: vec![],
+            rewrites: vec![],
         };
         // Even for an unrelated path, expansion error forces Ask (fail-closed).
         let result = lookup(&config, "/tmp/foo.txt", FileOperation::Read, "/tmp");
@@ -191,6 +380,7 @@ mod tests {
             deny: vec![rule_with_expansion_error(&[FileOperation::Write])],
             ask: vec![],
             allow: vec![],
+            rewrites: vec![],
         };
         let result = lookup(&config, "/tmp/foo.txt", FileOperation::Read, "/tmp");
         assert_eq!(result, None);
@@ -0,0 +1,118 @@
+use serde_json::Value;
+
+use super::{first_match, RuleMatch};
+use crate::config::mcp::{McpConfig, McpRule};
+
+/// Look up an MCP tool call against the mcp section's rules.
+///
+/// Checks tiers in order: deny → ask → allow. First matching tier wins.
+/// Returns `None` if no rule in any tier matches.
+pub fn lookup(config: &McpConfig, tool_name: &str, tool_input: &Value) -> Option<RuleMatch> {
+    first_match(
+        &config.deny,
+        &config.ask,
+        &config.allow,
+        |rule| (&rule.raw_pattern, rule.line),
+        |rule: &McpRule| rule_matches(rule, tool_name, tool_input),
+    )
+}
+
+fn rule_matches(rule: &McpRule, tool_name: &str, tool_input: &Value) -> bool {
+    rule.matcher.is_match(tool_name)
+        && rule
+            .arguments
+            .iter()
+            .all(|argument| argument.matches(tool_input))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::protocol::Decision;
+    use serde_json::json;
+
+    fn mcp(source: &str) -> McpConfig {
+        let config = Config::parse(&format!("mcp {{\n{source}\n}}")).expect("parse should succeed");
+        config.mcp.expect("mcp config should be present")
+    }
+
+    #[test]
+    fn tool_glob_matches() {
+        let config = mcp(r#"deny "mcp__github__delete_*""#);
+        assert_eq!(
+            config.lookup("mcp__github__delete_repo", &json!({})),
+            Some(Decision::Deny)
+        );
+        assert_eq!(config.lookup("mcp__github__get_repo", &json!({})), None);
+    }
+
+    #[test]
+    fn server_glob_matches_every_tool() {
+        let config = mcp(r#"allow "mcp__linear__*""#);
+        assert_eq!(
+            config.lookup("mcp__linear__create_issue", &json!({})),
+            Some(Decision::Allow)
+        );
+        assert_eq!(config.lookup("mcp__linear_admin__create", &json!({})), None);
+    }
+
+    #[test]
+    fn deny_wins_over_allow() {
+        let config = mcp(r#"
+            allow "mcp__github__*"
+            deny "mcp__github__delete_*"
+            "#);
+        assert_eq!(
+            config.lookup("mcp__github__delete_repo", &json!({})),
+            Some(Decision::Deny)
+        );
+    }
+
+    #[test]
+    fn argument_condition_restricts_match() {
+        let config = mcp(r#"ask "mcp__github__*" { argument "repo" "acme/*" }"#);
+        assert_eq!(
+            config.lookup("mcp__github__merge", &json!({"repo": "acme/web"})),
+            Some(Decision::Ask)
+        );
+        assert_eq!(
+            config.lookup("mcp__github__merge", &json!({"repo": "other/web"})),
+            None
+        );
+        assert_eq!(config.lookup("mcp__github__merge", &json!({})), None);
+    }
+
+    #[test]
+    fn argument_nested_path_and_scalar_values() {
+        let config = mcp(r#"deny "mcp__db__*" {
+                argument "options.force" "true"
+                argument "targets.0" "prod-*"
+            }"#);
+        let input = json!({"options": {"force": true}, "targets": ["prod-eu", "dev"]});
+        assert_eq!(config.lookup("mcp__db__drop", &input), Some(Decision::Deny));
+        let input = json!({"options": {"force": false}, "targets": ["prod-eu"]});
+        assert_eq!(config.lookup("mcp__db__drop", &input), None);
+    }
+
+    #[test]
+    fn argument_object_value_never_matches() {
+        let config = mcp(r#"deny "mcp__db__*" { argument "options" "*" }"#);
+        assert_eq!(
+            config.lookup("mcp__db__drop", &json!({"options": {"force": true}})),
+            None
+        );
+    }
+
+    #[test]
+    fn argument_predicates_and_wildcard_paths() {
+        let config = mcp(r#"ask "mcp__db__*" {
+                argument "$.targets[*]" regex="^prod-"
+                argument "limit" gt=1000
+            }"#);
+        let input = json!({"targets": ["dev", "prod-eu"], "limit": 5000});
+        assert_eq!(config.lookup("mcp__db__query", &input), Some(Decision::Ask));
+        let input = json!({"targets": ["dev"], "limit": 5000});
+        assert_eq!(config.lookup("mcp__db__query", &input), None);
+    }
+}
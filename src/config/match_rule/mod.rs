@@ -1,2 +1,48 @@
+pub(super) mod argument;
 pub(super) mod bash;
 pub(super) mod files;
+pub(super) mod mcp;
+pub(super) mod tools;
+pub(super) mod web;
+
+use crate::protocol::Decision;
+
+/// A lookup result together with the rule that decided it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct RuleMatch {
+    pub(crate) decision: Decision,
+    /// Tier the matching rule is listed in. Differs from `decision` only
+    /// when the rule fails closed.
+    pub(crate) tier: Decision,
+    /// The matching rule's pattern as written in the config.
+    pub(crate) rule: String,
+    /// 1-based line number of the matching rule in the source file.
+    pub(crate) line: usize,
+}
+
+/// Find the first matching rule, checking tiers in order: deny → ask → allow.
+pub(super) fn first_match<R>(
+    deny: &[R],
+    ask: &[R],
+    allow: &[R],
+    source: impl Fn(&R) -> (&str, usize),
+    matches: impl Fn(&R) -> bool,
+) -> Option<RuleMatch> {
+    [
+        (Decision::Deny, deny),
+        (Decision::Ask, ask),
+        (Decision::Allow, allow),
+    ]
+    .into_iter()
+    .find_map(|(decision, rules)| {
+        rules.iter().find(|rule| matches(rule)).map(|rule| {
+            let (raw, line) = source(rule);
+            RuleMatch {
+                decision: decision.clone(),
+                tier: decision,
+                rule: raw.to_string(),
+                line,
+            }
+        })
+    })
+}
@@ -0,0 +1,152 @@
+use serde_json::Value;
+
+use super::{first_match, RuleMatch};
+use crate::config::tools::{ToolRule, ToolsConfig};
+
+/// Look up a tool call against the tools section's rules.
+///
+/// Checks tiers in order: deny → ask → allow. First matching tier wins.
+/// Returns `None` if no rule in any tier matches.
+pub fn lookup(config: &ToolsConfig, tool_name: &str, tool_input: &Value) -> Option<RuleMatch> {
+    first_match(
+        &config.deny,
+        &config.ask,
+        &config.allow,
+        |rule| (&rule.raw_pattern, rule.line),
+        |rule: &ToolRule| rule_matches(rule, tool_name, tool_input),
+    )
+}
+
+fn rule_matches(rule: &ToolRule, tool_name: &str, tool_input: &Value) -> bool {
+    rule.matcher.is_match(tool_name)
+        && rule
+            .arguments
+            .iter()
+            .all(|argument| argument.matches(tool_input))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::protocol::Decision;
+    use serde_json::json;
+
+    fn tools(source: &str) -> ToolsConfig {
+        let config =
+            Config::parse(&format!("tools {{\n{source}\n}}")).expect("parse should succeed");
+        config.tools.expect("tools config should be present")
+    }
+
+    #[test]
+    fn tool_name_glob() {
+        let config = tools(r#"ask "Todo*""#);
+        assert_eq!(config.lookup("TodoWrite", &json!({})), Some(Decision::Ask));
+        assert_eq!(config.lookup("Task", &json!({})), None);
+    }
+
+    #[test]
+    fn glob_predicate() {
+        let config = tools(r#"deny "Task" { argument "subagent_type" glob="*admin*"; }"#);
+        assert_eq!(
+            config.lookup("Task", &json!({"subagent_type": "db-admin"})),
+            Some(Decision::Deny)
+        );
+        assert_eq!(
+            config.lookup("Task", &json!({"subagent_type": "reviewer"})),
+            None
+        );
+        assert_eq!(config.lookup("Task", &json!({})), None);
+    }
+
+    #[test]
+    fn equals_predicate_compares_json_values() {
+        let config = tools(
+            r#"
+            deny "X" { argument "mode" equals="fast"; }
+            ask "Y" { argument "count" equals=1; }
+            allow "Z" { argument "dry_run" equals=#true; }
+            "#,
+        );
+        assert_eq!(
+            config.lookup("X", &json!({"mode": "fast"})),
+            Some(Decision::Deny)
+        );
+        assert_eq!(config.lookup("X", &json!({"mode": "slow"})), None);
+        assert_eq!(
+            config.lookup("Y", &json!({"count": 1.0})),
+            Some(Decision::Ask)
+        );
+        assert_eq!(config.lookup("Y", &json!({"count": "1"})), None);
+        assert_eq!(
+            config.lookup("Z", &json!({"dry_run": true})),
+            Some(Decision::Allow)
+        );
+    }
+
+    #[test]
+    fn regex_predicate_searches_text() {
+        let config = tools(r#"deny "Task" { argument "prompt" regex="(?i)rm\s+-rf"; }"#);
+        assert_eq!(
+            config.lookup("Task", &json!({"prompt": "then RM  -rf the dir"})),
+            Some(Decision::Deny)
+        );
+        assert_eq!(
+            config.lookup("Task", &json!({"prompt": "list files"})),
+            None
+        );
+    }
+
+    #[test]
+    fn numeric_comparisons() {
+        let config = tools(r#"ask "Task" { argument "max_turns" gt=10 lte=100; }"#);
+        assert_eq!(
+            config.lookup("Task", &json!({"max_turns": 50})),
+            Some(Decision::Ask)
+        );
+        assert_eq!(config.lookup("Task", &json!({"max_turns": 10})), None);
+        assert_eq!(config.lookup("Task", &json!({"max_turns": 101})), None);
+        assert_eq!(config.lookup("Task", &json!({"max_turns": "50"})), None);
+    }
+
+    #[test]
+    fn wildcard_path_matches_any_element() {
+        let config = tools(r#"ask "TodoWrite" { argument "$.todos[*].status" equals="blocked"; }"#);
+        let input = json!({"todos": [{"status": "done"}, {"status": "blocked"}]});
+        assert_eq!(config.lookup("TodoWrite", &input), Some(Decision::Ask));
+        let input = json!({"todos": [{"status": "done"}]});
+        assert_eq!(config.lookup("TodoWrite", &input), None);
+    }
+
+    #[test]
+    fn all_arguments_must_match() {
+        let config = tools(
+            r#"deny "Task" {
+                argument "subagent_type" equals="deployer"
+                argument "prompt" glob="*prod*"
+            }"#,
+        );
+        let input = json!({"subagent_type": "deployer", "prompt": "ship to prod"});
+        assert_eq!(config.lookup("Task", &input), Some(Decision::Deny));
+        let input = json!({"subagent_type": "deployer", "prompt": "ship to staging"});
+        assert_eq!(config.lookup("Task", &input), None);
+    }
+
+    #[test]
+    fn deny_wins_over_allow() {
+        let config = tools(
+            r#"
+            allow "Task"
+            deny "Task" { argument "subagent_type" glob="*admin*"; }
+            "#,
+        );
+        assert_eq!(
+            config.lookup("Task", &json!({"subagent_type": "admin"})),
+            Some(Decision::Deny)
+        );
+        assert_eq!(
+            config.lookup("Task", &json!({"subagent_type": "writer"})),
+            Some(Decision::Allow)
+        );
+    }
+}
@@ -0,0 +1,170 @@
+use super::{first_match, RuleMatch};
+use crate::config::web::{QueryRule, SearchRules, Url, UrlRule, WebConfig};
+
+/// Look up a URL against the web section's URL rules.
+///
+/// Checks tiers in order: deny → ask → allow. First matching tier wins.
+/// Returns `None` if no rule in any tier matches.
+pub fn lookup_url(config: &WebConfig, url: &Url) -> Option<RuleMatch> {
+    first_match(
+        &config.deny,
+        &config.ask,
+        &config.allow,
+        |rule| (&rule.raw_pattern, rule.line),
+        |rule| url_matches(rule, url),
+    )
+}
+
+/// Look up a search query against the `search` block's rules.
+///
+/// Same tier order as [`lookup_url`]. Matching is case-insensitive.
+pub fn lookup_query(rules: &SearchRules, query: &str) -> Option<RuleMatch> {
+    first_match(
+        &rules.deny,
+        &rules.ask,
+        &rules.allow,
+        |rule| (&rule.raw_pattern, rule.line),
+        |rule: &QueryRule| rule.matcher.is_match(query),
+    )
+}
+
+fn url_matches(rule: &UrlRule, url: &Url) -> bool {
+    let host_matches = rule.host.is_match(&url.host)
+        || rule
+            .apex
+            .as_ref()
+            .is_some_and(|apex| apex.is_match(&url.host));
+    rule.scheme.as_ref().is_none_or(|s| *s == url.scheme)
+        && host_matches
+        && rule.path.as_ref().is_none_or(|p| p.is_match(&url.path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::document::ConfigDocument;
+    use crate::config::parse::web::parse_web;
+    use crate::protocol::Decision;
+
+    fn web(source: &str) -> WebConfig {
+        let wrapped = format!("web {{\n{source}\n}}");
+        let doc = ConfigDocument::parse(&wrapped).expect("kdl should parse");
+        parse_web(&doc)
+            .expect("parse should succeed")
+            .expect("web section should be present")
+    }
+
+    fn lookup(config: &WebConfig, raw: &str) -> Option<Decision> {
+        lookup_url(config, &Url::parse(raw).unwrap()).map(|m| m.decision)
+    }
+
+    #[test]
+    fn bare_host_matches_any_scheme_and_path() {
+        let config = web(r#"allow "docs.rs""#);
+        assert_eq!(
+            lookup(&config, "https://docs.rs/serde"),
+            Some(Decision::Allow)
+        );
+        assert_eq!(lookup(&config, "http://docs.rs"), Some(Decision::Allow));
+    }
+
+    #[test]
+    fn host_match_is_case_insensitive() {
+        let config = web(r#"allow "Docs.rs""#);
+        assert_eq!(lookup(&config, "https://DOCS.rs/"), Some(Decision::Allow));
+    }
+
+    #[test]
+    fn wildcard_subdomain_matches_apex() {
+        let config = web(r#"allow "*.github.com""#);
+        assert_eq!(
+            lookup(&config, "https://api.github.com/repos"),
+            Some(Decision::Allow)
+        );
+        assert_eq!(
+            lookup(&config, "https://github.com/"),
+            Some(Decision::Allow)
+        );
+        assert_eq!(lookup(&config, "https://notgithub.com/"), None);
+    }
+
+    #[test]
+    fn lookalike_domain_not_matched() {
+        let config = web(r#"allow "docs.rs""#);
+        assert_eq!(lookup(&config, "https://docs.rs.evil.com/"), None);
+        assert_eq!(lookup(&config, "https://docs.rs@evil.com/"), None);
+    }
+
+    #[test]
+    fn scheme_restricts_match() {
+        let config = web(r#"allow "https://example.com""#);
+        assert_eq!(
+            lookup(&config, "https://example.com/"),
+            Some(Decision::Allow)
+        );
+        assert_eq!(lookup(&config, "http://example.com/"), None);
+    }
+
+    #[test]
+    fn path_glob_restricts_match() {
+        let config = web(r#"allow "github.com/rust-lang/**""#);
+        assert_eq!(
+            lookup(&config, "https://github.com/rust-lang/rust/issues"),
+            Some(Decision::Allow)
+        );
+        assert_eq!(lookup(&config, "https://github.com/other/repo"), None);
+    }
+
+    #[test]
+    fn deny_wins_over_allow() {
+        let config = web(r#"
+            allow "*"
+            deny "*.pastebin.com"
+            "#);
+        assert_eq!(
+            lookup(&config, "https://www.pastebin.com/raw/x"),
+            Some(Decision::Deny)
+        );
+        assert_eq!(
+            lookup(&config, "https://pastebin.com/raw/x"),
+            Some(Decision::Deny)
+        );
+        assert_eq!(lookup(&config, "https://docs.rs/"), Some(Decision::Allow));
+    }
+
+    #[test]
+    fn ask_wins_over_allow() {
+        let config = web(r#"
+            allow "*"
+            ask "*.internal.example.com"
+            "#);
+        assert_eq!(
+            lookup(&config, "https://wiki.internal.example.com/"),
+            Some(Decision::Ask)
+        );
+    }
+
+    #[test]
+    fn query_rules_case_insensitive() {
+        let config = web(r#"
+            search {
+                allow "*"
+                deny "*API KEY*"
+            }
+            "#);
+        assert_eq!(
+            config.lookup_query("leaked api key for acme"),
+            Some(Decision::Deny)
+        );
+        assert_eq!(
+            config.lookup_query("rust borrow checker"),
+            Some(Decision::Allow)
+        );
+    }
+
+    #[test]
+    fn query_without_search_rules_has_no_opinion() {
+        let config = web(r#"allow "docs.rs""#);
+        assert_eq!(config.lookup_query("anything"), None);
+    }
+}
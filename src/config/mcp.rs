@@ -0,0 +1,65 @@
+//! MCP tool configuration.
+//!
+//! Struct and lookup delegation. Parsing is in [`crate::config::parse::mcp`].
+//! Matching internals are in [`crate::config::match_rule::mcp`].
+
+use globset::GlobMatcher;
+use serde_json::Value;
+
+use super::argument::ArgumentMatch;
+use super::section::{ToolConfig, ToolSection};
+use super::{ConfigError, RuleMatch};
+
+/// MCP-specific configuration: rules for `mcp__<server>__<tool>` calls.
+#[derive(Debug, Default)]
+pub struct McpConfig {
+    pub allow: Vec<McpRule>,
+    pub deny: Vec<McpRule>,
+    pub ask: Vec<McpRule>,
+}
+
+/// A glob over the full MCP tool name with optional argument conditions.
+///
+/// Created from entries like `deny "mcp__github__delete_*"` or
+/// `ask "mcp__github__*" { argument "repo" "acme/*" }`.
+#[derive(Debug)]
+pub struct McpRule {
+    /// Pattern as written in the config.
+    pub raw_pattern: String,
+    /// Glob over the full tool name.
+    pub matcher: GlobMatcher,
+    /// Argument conditions that must ALL match (AND semantics).
+    pub arguments: Vec<ArgumentMatch>,
+    /// 1-based line number in the source file.
+    pub line: usize,
+}
+
+impl ToolConfig for McpConfig {
+    const SECTION: &'static str = "mcp";
+
+    fn from_section(section: ToolSection) -> Result<Self, ConfigError> {
+        super::parse::mcp::reject_settings(&section.settings)?;
+        Ok(McpConfig {
+            allow: super::parse::mcp::parse_rules(section.allow)?,
+            deny: super::parse::mcp::parse_rules(section.deny)?,
+            ask: super::parse::mcp::parse_rules(section.ask)?,
+        })
+    }
+}
+
+impl McpConfig {
+    /// Look up an MCP tool call by full tool name and `tool_input`, returning the decision
+    /// and the rule that decided it.
+    ///
+    /// Delegates to [`super::match_rule::mcp::lookup`].
+    /// Precedence: deny > ask > allow. Returns `None` if no rule matches.
+    pub(crate) fn lookup_match(&self, tool_name: &str, tool_input: &Value) -> Option<RuleMatch> {
+        super::match_rule::mcp::lookup(self, tool_name, tool_input)
+    }
+
+    /// The decision of [`lookup_match`](Self::lookup_match) alone.
+    #[cfg(test)]
+    pub fn lookup(&self, tool_name: &str, tool_input: &Value) -> Option<crate::protocol::Decision> {
+        self.lookup_match(tool_name, tool_input).map(|m| m.decision)
+    }
+}
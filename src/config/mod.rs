@@ -1,18 +1,41 @@
+pub(crate) mod argument;
 pub(crate) mod bash;
+pub(crate) mod define;
 mod document;
 pub(crate) mod files;
+pub(crate) mod flag_schema;
+pub(crate) mod lint;
+pub(crate) mod log;
 mod match_rule;
+pub(crate) mod mcp;
 pub(crate) mod normalize;
 pub(crate) mod parse;
+pub(crate) mod path_args;
+pub(crate) mod prompt;
+pub(crate) mod rewrite;
 pub(crate) mod rule;
 pub(crate) mod section;
+pub(crate) mod test_cases;
+pub(crate) mod tools;
+pub(crate) mod trust;
+pub(crate) mod web;
 
 use std::path::{Path, PathBuf};
 
 pub(crate) use bash::BashConfig;
 pub(crate) use files::FilesConfig;
+pub(crate) use lint::Warning;
+pub(crate) use log::LogConfig;
+pub(crate) use mcp::McpConfig;
+pub(crate) use prompt::PromptConfig;
+pub(crate) use test_cases::TestCase;
+pub(crate) use tools::ToolsConfig;
+pub(crate) use web::WebConfig;
+
+pub(crate) use match_rule::RuleMatch;
 
 use document::ConfigDocument;
+pub(crate) use document::SourceMap;
 
 /// Top-level configuration — facade for the rest of the codebase.
 ///
@@ -22,6 +45,16 @@ use document::ConfigDocument;
 pub struct Config {
     pub(crate) bash: Option<BashConfig>,
     pub(crate) files: Option<FilesConfig>,
+    pub(crate) web: Option<WebConfig>,
+    pub(crate) mcp: Option<McpConfig>,
+    pub(crate) tools: Option<ToolsConfig>,
+    pub(crate) prompt: Option<PromptConfig>,
+    pub(crate) log: Option<LogConfig>,
+    /// `expect` cases from the `tests` section, run by the `test` command.
+    pub(crate) tests: Vec<TestCase>,
+    /// Files the config was loaded from; empty when parsed from a string.
+    /// Rule lines count across them.
+    pub(crate) sources: SourceMap,
 }
 
 /// Errors that can occur when loading or parsing a config file.
@@ -36,7 +69,7 @@ pub enum ConfigError {
 }
 
 impl Config {
-    /// Load config from a file path.
+    /// Load config from a file path, with the files it includes.
     ///
     /// Returns `ConfigError::NotFound` if the file does not exist.
     ///
@@ -49,7 +82,18 @@ impl Config {
     /// let config = Config::load(Path::new("/path/to/config.kdl")).unwrap();
     /// ```
     pub fn load(path: &Path) -> Result<Self, ConfigError> {
-        let doc = ConfigDocument::load(path)?;
+        Self::load_layers(&[path.to_path_buf()])
+    }
+
+    /// Load config layered from several files, least specific first.
+    ///
+    /// Rules from every file are combined, so deny > ask > allow holds
+    /// across files. `max-nesting-depth` takes the value from the last file
+    /// that sets it. Settings a later layer could use to weaken an earlier
+    /// one are limited: a later layer's `path-args` only adds paths, and
+    /// the `log` path comes from the first layer that sets it.
+    pub fn load_layers(paths: &[PathBuf]) -> Result<Self, ConfigError> {
+        let doc = ConfigDocument::load(paths)?;
         Self::from_document(&doc)
     }
 
@@ -67,10 +111,44 @@ impl Config {
         Self::from_document(&doc)
     }
 
+    /// Load layered config files, along with lint warnings about them.
+    ///
+    /// Errors are the same as [`Config::load_layers`]; warnings never stop
+    /// loading.
+    pub(crate) fn load_with_warnings(
+        paths: &[PathBuf],
+    ) -> Result<(Self, Vec<Warning>), ConfigError> {
+        let doc = ConfigDocument::load(paths)?;
+        let config = Self::from_document(&doc)?;
+        let warnings = lint::lint(&doc, &config);
+        Ok((config, warnings))
+    }
+
+    /// Parse config from a KDL string, along with lint warnings about it.
+    #[cfg(test)]
+    pub(crate) fn parse_with_warnings(content: &str) -> Result<(Self, Vec<Warning>), ConfigError> {
+        let doc = ConfigDocument::parse(content)?;
+        let config = Self::from_document(&doc)?;
+        let warnings = lint::lint(&doc, &config);
+        Ok((config, warnings))
+    }
+
     fn from_document(doc: &ConfigDocument) -> Result<Self, ConfigError> {
+        Self::parse_sections(doc).map_err(|e| doc.locate_error(e))
+    }
+
+    fn parse_sections(doc: &ConfigDocument) -> Result<Self, ConfigError> {
+        let definitions = parse::define::parse_definitions(doc)?;
         Ok(Config {
-            bash: Some(section::parse_tool::<BashConfig>(doc)?),
-            files: parse::files::parse_files(doc)?,
+            bash: Some(section::parse_tool::<BashConfig>(doc, &definitions)?),
+            files: parse::files::parse_files(doc, &definitions)?,
+            web: parse::web::parse_web(doc)?,
+            mcp: Some(section::parse_tool::<McpConfig>(doc, &definitions)?),
+            tools: Some(section::parse_tool::<ToolsConfig>(doc, &definitions)?),
+            prompt: Some(section::parse_tool::<PromptConfig>(doc, &definitions)?),
+            log: parse::log::parse_log(doc)?,
+            tests: parse::test_cases::parse_test_cases(doc)?,
+            sources: doc.sources(),
         })
     }
 }
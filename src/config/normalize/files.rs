@@ -1,3 +1,6 @@
+use regex::Regex;
+
+use crate::config::files::PathRegex;
 use crate::domain::PathError;
 
 /// Expand `~` and `<home>` in a file pattern at load time.
@@ -21,6 +24,44 @@ pub(crate) fn expand_home(pattern: &str) -> Result<String, PathError> {
     }
 }
 
+/// Compile a `re:` file pattern (the part after `re:`) at load time.
+///
+/// A leading `<cwd>` is matched at match time: the rest of the regex is
+/// anchored to the start of the path after the working directory. A
+/// leading `~` becomes `^` and the escaped home directory; `<home>`
+/// anywhere becomes the escaped home directory.
+///
+/// The outer `Err` is an invalid pattern. The inner `Err` is
+/// `PathError::HomeNotSet`, as for [`expand_home`], when the pattern needs
+/// `$HOME` but it is not set.
+pub(crate) fn compile_regex(source: &str) -> Result<Result<PathRegex, PathError>, String> {
+    let (body, after_cwd) = match source.strip_prefix("<cwd>") {
+        Some(rest) => (rest, true),
+        None => (source, false),
+    };
+    if body.contains("<cwd>") {
+        return Err(format!(
+            "invalid regex '{source}': <cwd> can only start a re: pattern"
+        ));
+    }
+    let home = if body.starts_with('~') || body.contains("<home>") {
+        crate::path::home_dir().map(|home| regex::escape(&home))
+    } else {
+        Ok(String::new())
+    };
+    // Without $HOME the rule fails closed; compile anyway to report syntax errors.
+    let home_text = home.as_deref().unwrap_or("");
+    let mut expanded = body.replace("<home>", home_text);
+    if let Some(rest) = expanded.strip_prefix('~') {
+        expanded = format!("^{home_text}{rest}");
+    }
+    if after_cwd {
+        expanded = format!("^(?:{expanded})");
+    }
+    let regex = Regex::new(&expanded).map_err(|e| format!("invalid regex '{source}': {e}"))?;
+    Ok(home.map(|_| PathRegex { regex, after_cwd }))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,311 @@
+use globset::{GlobBuilder, GlobMatcher};
+use regex::Regex;
+use serde_json::Value;
+
+use crate::config::argument::{ArgumentMatch, ArgumentPredicate, PathSegment};
+use crate::config::section::ChildNode;
+use crate::config::ConfigError;
+
+/// Predicate names accepted as `argument` properties.
+const PREDICATES: &str = "equals, glob, regex, gt, gte, lt, lte";
+
+/// Parse a rule's children block into `argument` conditions.
+///
+/// `argument "<path>" "<glob>"` tests the selected values with a glob;
+/// properties add predicates (`argument "max_turns" gt=10 lte=100`), and
+/// all of them must hold. `section` names the section for errors.
+pub(crate) fn parse_arguments(
+    children: &[ChildNode],
+    section: &str,
+) -> Result<Vec<ArgumentMatch>, ConfigError> {
+    children
+        .iter()
+        .map(|child| parse_argument(child, section))
+        .collect()
+}
+
+fn parse_argument(child: &ChildNode, section: &str) -> Result<ArgumentMatch, ConfigError> {
+    let line = child.line;
+    let err = |msg: String| ConfigError::ParseError(format!("line {line}: {msg}"));
+
+    if child.name != "argument" {
+        return Err(err(format!(
+            "unexpected node \"{}\" in {section} rule; expected argument",
+            child.name
+        )));
+    }
+    let (raw_path, glob) = match child.values.as_slice() {
+        [path] => (path, None),
+        [path, glob] => (path, Some(glob)),
+        _ => {
+            return Err(err(
+                "argument requires a quoted field path and an optional glob".to_string(),
+            ))
+        }
+    };
+    if child.entry_count != child.values.len() + child.properties.len() {
+        return Err(err(format!(
+            "argument \"{raw_path}\" takes quoted strings and predicate properties only"
+        )));
+    }
+    let path = parse_path(raw_path)
+        .map_err(|e| err(format!("invalid argument path '{raw_path}': {e}")))?;
+
+    let mut predicates = Vec::new();
+    if let Some(glob) = glob {
+        predicates.push(compile_glob(glob).map_err(&err)?);
+    }
+    for (name, value) in &child.properties {
+        predicates.push(parse_predicate(name, value).map_err(&err)?);
+    }
+    if predicates.is_empty() {
+        return Err(err(format!(
+            "argument \"{raw_path}\" requires a glob or one of {PREDICATES}"
+        )));
+    }
+
+    Ok(ArgumentMatch { path, predicates })
+}
+
+fn parse_predicate(name: &str, value: &Value) -> Result<ArgumentPredicate, String> {
+    let number = |value: &Value| {
+        value
+            .as_f64()
+            .ok_or_else(|| format!("{name} requires a number"))
+    };
+    let string = |value: &Value| {
+        value
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| format!("{name} requires a quoted string"))
+    };
+    match name {
+        "equals" => Ok(ArgumentPredicate::Equals(value.clone())),
+        "glob" => compile_glob(&string(value)?),
+        "regex" => {
+            let pattern = string(value)?;
+            Regex::new(&pattern)
+                .map(ArgumentPredicate::Regex)
+                .map_err(|e| format!("invalid regex '{pattern}': {e}"))
+        }
+        "gt" => number(value).map(ArgumentPredicate::Gt),
+        "gte" => number(value).map(ArgumentPredicate::Gte),
+        "lt" => number(value).map(ArgumentPredicate::Lt),
+        "lte" => number(value).map(ArgumentPredicate::Lte),
+        other => Err(format!(
+            "unknown argument predicate '{other}'; expected {PREDICATES}"
+        )),
+    }
+}
+
+fn compile_glob(pattern: &str) -> Result<ArgumentPredicate, String> {
+    let glob: GlobMatcher = GlobBuilder::new(pattern)
+        .build()
+        .map_err(|e| format!("invalid glob pattern '{pattern}': {e}"))?
+        .compile_matcher();
+    Ok(ArgumentPredicate::Glob(glob))
+}
+
+/// Parse a JSONPath-like field path.
+///
+/// Accepts `name`, `a.b`, `a[0]`, `a[*].b`, `a["key with dots"]`, with an
+/// optional leading `$` or `$.`. A bare `$` selects the whole input.
+fn parse_path(raw: &str) -> Result<Vec<PathSegment>, String> {
+    let rest = raw.strip_prefix('$').unwrap_or(raw);
+    let mut chars = rest.chars().peekable();
+    let mut segments = Vec::new();
+    let mut expect_key = !rest.is_empty() && !rest.starts_with(['.', '[']);
+
+    loop {
+        if expect_key {
+            let mut key = String::new();
+            while let Some(&c) = chars.peek() {
+                if c == '.' || c == '[' {
+                    break;
+                }
+                if c == ']' {
+                    return Err("unexpected ']'".to_string());
+                }
+                key.push(c);
+                chars.next();
+            }
+            segments.push(match key.as_str() {
+                "" => return Err("empty path segment".to_string()),
+                "*" => PathSegment::Wildcard,
+                _ => PathSegment::Key(key),
+            });
+            expect_key = false;
+        }
+        match chars.next() {
+            None => return Ok(segments),
+            Some('.') => expect_key = true,
+            Some('[') => {
+                let mut inner = String::new();
+                loop {
+                    match chars.next() {
+                        Some(']') if !is_open_quote(&inner) => break,
+                        Some(c) => inner.push(c),
+                        None => return Err("unclosed '['".to_string()),
+                    }
+                }
+                segments.push(parse_bracket(&inner)?);
+            }
+            Some(c) => return Err(format!("unexpected '{c}'")),
+        }
+    }
+}
+
+/// Whether a bracket body so far is a quoted key still missing its closing quote.
+fn is_open_quote(inner: &str) -> bool {
+    let mut chars = inner.chars();
+    match chars.next() {
+        Some(q @ ('"' | '\'')) => !inner[1..].ends_with(q),
+        _ => false,
+    }
+}
+
+fn parse_bracket(inner: &str) -> Result<PathSegment, String> {
+    if inner == "*" {
+        return Ok(PathSegment::Wildcard);
+    }
+    if let Ok(index) = inner.parse::<usize>() {
+        return Ok(PathSegment::Index(index));
+    }
+    for quote in ['"', '\''] {
+        if let Some(key) = inner
+            .strip_prefix(quote)
+            .and_then(|s| s.strip_suffix(quote))
+        {
+            return Ok(PathSegment::Key(key.to_string()));
+        }
+    }
+    Err(format!("invalid index '[{inner}]'"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::section;
+
+    fn key(k: &str) -> PathSegment {
+        PathSegment::Key(k.to_string())
+    }
+
+    fn arguments(source: &str) -> Result<Vec<ArgumentMatch>, ConfigError> {
+        let ts = section::parse_from_source(source)?;
+        let children = ts.deny[0].children.as_deref().unwrap_or_default();
+        parse_arguments(children, "test")
+    }
+
+    fn arguments_err(source: &str) -> String {
+        arguments(source).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn glob_shorthand_and_predicates() {
+        let parsed = arguments(
+            r#"deny "X" {
+                argument "repo" "acme/*"
+                argument "max_turns" gte=5 lt=10
+                argument "$.todos[*].status" "*block*" regex="ed$"
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(parsed[0].path, vec![key("repo")]);
+        assert!(matches!(
+            parsed[0].predicates[..],
+            [ArgumentPredicate::Glob(_)]
+        ));
+        assert_eq!(parsed[1].predicates.len(), 2);
+        assert_eq!(
+            parsed[2].path,
+            vec![key("todos"), PathSegment::Wildcard, key("status")]
+        );
+        assert_eq!(parsed[2].predicates.len(), 2);
+    }
+
+    #[test]
+    fn equals_keeps_json_type() {
+        let parsed = arguments(r#"deny "X" { argument "n" equals=1; }"#).unwrap();
+        assert!(
+            matches!(&parsed[0].predicates[0], ArgumentPredicate::Equals(v) if v.as_i64() == Some(1))
+        );
+    }
+
+    #[test]
+    fn path_syntax() {
+        assert_eq!(parse_path("a").unwrap(), vec![key("a")]);
+        assert_eq!(parse_path("$.a.b").unwrap(), vec![key("a"), key("b")]);
+        assert_eq!(
+            parse_path("$.todos[*].status").unwrap(),
+            vec![key("todos"), PathSegment::Wildcard, key("status")]
+        );
+        assert_eq!(
+            parse_path("edits[0]").unwrap(),
+            vec![key("edits"), PathSegment::Index(0)]
+        );
+        assert_eq!(parse_path(r#"$["a.b"]"#).unwrap(), vec![key("a.b")]);
+        assert_eq!(parse_path("$['x]']").unwrap(), vec![key("x]")]);
+        assert!(parse_path("$").unwrap().is_empty());
+    }
+
+    #[test]
+    fn path_errors() {
+        assert!(parse_path("a..b").is_err());
+        assert!(parse_path("a[").is_err());
+        assert!(parse_path("a[x]").is_err());
+        assert!(parse_path("a]").is_err());
+    }
+
+    #[test]
+    fn error_unknown_child() {
+        let err = arguments_err("deny \"X\" {\n    field \"x\" equals=1\n}");
+        assert!(err.contains("line 3"), "got: {err}");
+        assert!(
+            err.contains("unexpected node \"field\" in test rule; expected argument"),
+            "got: {err}"
+        );
+    }
+
+    #[test]
+    fn error_argument_without_predicate() {
+        let err = arguments_err(r#"deny "X" { argument "x"; }"#);
+        assert!(err.contains("requires a glob or one of"), "got: {err}");
+    }
+
+    #[test]
+    fn error_argument_with_non_string_value() {
+        let err = arguments_err(r#"deny "X" { argument "x" 5; }"#);
+        assert!(
+            err.contains("quoted strings and predicate properties"),
+            "got: {err}"
+        );
+    }
+
+    #[test]
+    fn error_unknown_predicate() {
+        let err = arguments_err(r#"deny "X" { argument "x" contains="y"; }"#);
+        assert!(
+            err.contains("unknown argument predicate 'contains'"),
+            "got: {err}"
+        );
+    }
+
+    #[test]
+    fn error_numeric_predicate_with_string() {
+        let err = arguments_err(r#"deny "X" { argument "x" gt="5"; }"#);
+        assert!(err.contains("gt requires a number"), "got: {err}");
+    }
+
+    #[test]
+    fn error_invalid_regex() {
+        let err = arguments_err(r#"deny "X" { argument "x" regex="(unclosed"; }"#);
+        assert!(err.contains("invalid regex"), "got: {err}");
+    }
+
+    #[test]
+    fn error_invalid_path() {
+        let err = arguments_err(r#"deny "X" { argument "a..b" "*"; }"#);
+        assert!(err.contains("invalid argument path 'a..b'"), "got: {err}");
+    }
+}
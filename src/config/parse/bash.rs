@@ -1,10 +1,34 @@
+use crate::config::flag_schema::FlagSchema;
 use crate::config::normalize::bash::normalize_subcommand_chains;
-use crate::config::rule::{self, compile_glob};
-use crate::config::section::{ChildNode, RuleEntry};
+use crate::config::path_args::{PathArgs, PathArgsTable, PathRole};
+use crate::config::rewrite::{RewriteAction, RewriteRule};
+use crate::config::rule::{self, compile_glob, compile_regex};
+use crate::config::section::{ChildNode, RuleEntry, SettingEntry};
 use crate::config::ConfigError;
+use crate::domain::Flag;
+use crate::protocol::FileOperation;
+
+/// Condition names a rule's children block understands.
+const CONDITIONS: &[&str] = &[
+    "required-flags",
+    "optional-flags",
+    "forbidden-flags",
+    "not-flags",
+    "positionals",
+    "positionals-regex",
+    "required-arguments",
+    "argument-regex",
+    "subcommands",
+    "except",
+];
 
 /// Parse a tier's rule entries into BashRules.
-pub(crate) fn parse_rules(entries: Vec<RuleEntry>) -> Result<Vec<rule::BashRule>, ConfigError> {
+///
+/// `schema` tells which flags in a rule string take a value.
+pub(crate) fn parse_rules(
+    entries: Vec<RuleEntry>,
+    schema: &FlagSchema,
+) -> Result<Vec<rule::BashRule>, ConfigError> {
     let mut rules = Vec::new();
     for entry in entries {
         let at_line = |e: ConfigError| match e {
@@ -15,14 +39,14 @@ pub(crate) fn parse_rules(entries: Vec<RuleEntry>) -> Result<Vec<rule::BashRule>
         };
 
         for value in &entry.values {
-            let bash_rule = parse_rule_entry(value).map_err(&at_line)?;
+            let bash_rule = parse_rule_entry(value, entry.line, schema).map_err(&at_line)?;
             rules.push(bash_rule);
         }
 
         // Children extend the last parsed rule.
         if let Some(children) = &entry.children {
             if let Some(last_rule) = rules.last_mut() {
-                parse_children(children, &mut last_rule.conditions)?;
+                parse_children(children, last_rule, schema)?;
                 // When both inline subcommand and children subcommands exist,
                 // children chains are relative to the inline subcommand position.
                 // Prepend the inline subcommand to each chain, then clear it.
@@ -34,11 +58,276 @@ pub(crate) fn parse_rules(entries: Vec<RuleEntry>) -> Result<Vec<rule::BashRule>
     Ok(rules)
 }
 
+/// Parse the `max-nesting-depth` setting, falling back to the default.
+///
+/// The value must be a single non-negative integer: `max-nesting-depth 3`.
+/// A later config file's value replaces an earlier one's.
+pub(crate) fn parse_max_nesting_depth(settings: &[SettingEntry]) -> Result<usize, ConfigError> {
+    let mut depth = None;
+    let mut set_in = None;
+    for setting in settings.iter().filter(|s| s.name == "max-nesting-depth") {
+        let line = setting.line;
+        if set_in.replace(setting.file) == Some(setting.file) {
+            return Err(ConfigError::ParseError(format!(
+                "line {line}: max-nesting-depth is set more than once"
+            )));
+        }
+        let value = match setting.integers.as_slice() {
+            [value] if setting.entry_count == 1 => *value,
+            _ => {
+                return Err(ConfigError::ParseError(format!(
+                    "line {line}: max-nesting-depth requires a single integer value"
+                )))
+            }
+        };
+        let value = usize::try_from(value).map_err(|_| {
+            ConfigError::ParseError(format!(
+                "line {line}: max-nesting-depth must be a non-negative integer, got {value}"
+            ))
+        })?;
+        depth = Some(value);
+    }
+    Ok(depth.unwrap_or(crate::command::DEFAULT_MAX_NESTING_DEPTH))
+}
+
+/// Parse `path-args` settings on top of the built-in table.
+///
+/// Each node names one program. In the first config layer it replaces any
+/// built-in entry for the program, or an entry from a file that layer
+/// includes. A later layer's node adds to the program's entries instead, so
+/// a project config cannot stop paths an earlier layer relies on from being
+/// checked:
+///
+/// ```kdl
+/// path-args "mytool" {
+///     read "positionals"
+///     write "last" "-o" "out="
+///     ignore "first" "-n"
+///     script-options "-e"
+///     in-place-flags "-i"
+/// }
+/// ```
+pub(crate) fn parse_path_args(settings: &[SettingEntry]) -> Result<PathArgsTable, ConfigError> {
+    let mut table = PathArgsTable::default();
+    let mut seen = std::collections::HashSet::new();
+    for setting in settings.iter().filter(|s| s.name == "path-args") {
+        let line = setting.line;
+        let program = match setting.values.as_slice() {
+            [program] if setting.entry_count == 1 => program,
+            _ => {
+                return Err(ConfigError::ParseError(format!(
+                    "line {line}: path-args requires a single program name"
+                )))
+            }
+        };
+        let Some(children) = &setting.children else {
+            return Err(ConfigError::ParseError(format!(
+                "line {line}: path-args \"{program}\" requires a children block"
+            )));
+        };
+        if !seen.insert((program.clone(), setting.file)) {
+            return Err(ConfigError::ParseError(format!(
+                "line {line}: path-args \"{program}\" is defined more than once"
+            )));
+        }
+        let args = parse_path_args_children(children)?;
+        if setting.layer == 0 {
+            table.insert(program.clone(), args);
+        } else {
+            table.extend(program.clone(), args);
+        }
+    }
+    Ok(table)
+}
+
+fn parse_path_args_children(children: &[ChildNode]) -> Result<PathArgs, ConfigError> {
+    let mut args = PathArgs::default();
+    for child in children {
+        let line = child.line;
+        let role = match child.name.as_str() {
+            "read" => PathRole::Access(FileOperation::Read),
+            "write" => PathRole::Access(FileOperation::Write),
+            "ignore" => PathRole::Ignore,
+            "script-options" => {
+                args.script_options
+                    .extend(child.values.iter().map(|v| Flag::new(v)));
+                continue;
+            }
+            "in-place-flags" => {
+                args.in_place_flags
+                    .extend(child.values.iter().map(|v| Flag::new(v)));
+                continue;
+            }
+            other => {
+                return Err(ConfigError::ParseError(format!(
+                    "line {line}: unknown path-args child '{other}'; \
+                     expected read, write, ignore, script-options, or in-place-flags"
+                )))
+            }
+        };
+        for selector in &child.values {
+            match (selector.as_str(), role) {
+                ("positionals", PathRole::Access(op)) => args.positionals = Some(op),
+                ("positionals", PathRole::Ignore) => args.positionals = None,
+                ("first", role) => args.first = Some(role),
+                ("last", role) => args.last = Some(role),
+                (option, PathRole::Access(op)) if option.starts_with('-') => {
+                    args.option_paths.push((Flag::new(option), op));
+                }
+                (option, PathRole::Ignore) if option.starts_with('-') => {
+                    args.options_with_values.push(Flag::new(option));
+                }
+                (operand, PathRole::Access(op)) if operand.ends_with('=') => {
+                    args.operand_paths.push((operand.to_string(), op));
+                }
+                (other, _) => {
+                    return Err(ConfigError::ParseError(format!(
+                        "line {line}: invalid path-args selector '{other}' for {}; \
+                         expected positionals, first, last, an option, or a name= operand",
+                        child.name
+                    )))
+                }
+            }
+        }
+    }
+    Ok(args)
+}
+
+/// Parse `flags-with-values` and `flag-aliases` settings on top of the
+/// built-in flag schema.
+///
+/// Each node names a program, then the flags of it that take a value, or
+/// flags that mean the same. They add to the built-in entry and to those
+/// from other nodes and config files:
+///
+/// ```kdl
+/// flags-with-values "kubectl" "-n" "--namespace"
+/// flag-aliases "mytool" "-y" "--yes" "--assume-yes"
+/// ```
+pub(crate) fn parse_flag_schema(settings: &[SettingEntry]) -> Result<FlagSchema, ConfigError> {
+    let mut schema = FlagSchema::default();
+    for setting in settings {
+        let (least, what) = match setting.name.as_str() {
+            "flags-with-values" => (1, "at least one flag"),
+            "flag-aliases" => (2, "at least two flags"),
+            _ => continue,
+        };
+        let [program, flags @ ..] = setting.values.as_slice() else {
+            return Err(schema_error(setting, what));
+        };
+        if flags.len() < least || setting.entry_count != setting.values.len() {
+            return Err(schema_error(setting, what));
+        }
+        let flags = flags.iter().map(|f| Flag::new(f));
+        if setting.name == "flag-aliases" {
+            schema.add_aliases(program, flags.collect());
+        } else {
+            schema.extend_values(program, flags);
+        }
+    }
+    Ok(schema)
+}
+
+fn schema_error(setting: &SettingEntry, what: &str) -> ConfigError {
+    ConfigError::ParseError(format!(
+        "line {}: {} requires a program name and {what}",
+        setting.line, setting.name
+    ))
+}
+
+/// Parse `rewrite` settings into rewrite rules, in config order.
+///
+/// ```kdl
+/// rewrite "rm" { add-flags "-i"; }
+/// rewrite "git push --force" {
+///     replace-flag "--force" "--force-with-lease"
+/// }
+/// ```
+pub(crate) fn parse_rewrites(
+    settings: &[SettingEntry],
+    schema: &FlagSchema,
+) -> Result<Vec<RewriteRule>, ConfigError> {
+    let mut rewrites = Vec::new();
+    for setting in settings.iter().filter(|s| s.name == "rewrite") {
+        let line = setting.line;
+        let raw = match setting.values.as_slice() {
+            [raw] if setting.entry_count == 1 => raw,
+            _ => {
+                return Err(ConfigError::ParseError(format!(
+                    "line {line}: rewrite requires a single rule string"
+                )))
+            }
+        };
+        let rule = parse_rule_entry(raw, line, schema).map_err(|e| match e {
+            ConfigError::ParseError(msg) => ConfigError::ParseError(format!("line {line}: {msg}")),
+            other => other,
+        })?;
+        let actions = match &setting.children {
+            Some(children) if !children.is_empty() => parse_rewrite_actions(children)?,
+            _ => {
+                return Err(ConfigError::ParseError(format!(
+                    "line {line}: rewrite \"{raw}\" requires a children block with \
+                     add-flags, remove-flags, or replace-flag"
+                )))
+            }
+        };
+        rewrites.push(RewriteRule {
+            raw: raw.clone(),
+            rule,
+            actions,
+            line,
+        });
+    }
+    Ok(rewrites)
+}
+
+fn parse_rewrite_actions(children: &[ChildNode]) -> Result<Vec<RewriteAction>, ConfigError> {
+    children
+        .iter()
+        .map(|child| {
+            let line = child.line;
+            let flags = || {
+                child
+                    .values
+                    .iter()
+                    .map(|v| Flag::new(v))
+                    .collect::<Vec<_>>()
+            };
+            match (child.name.as_str(), child.values.as_slice()) {
+                ("add-flags", [_, ..]) => Ok(RewriteAction::AddFlags(flags())),
+                ("remove-flags", [_, ..]) => Ok(RewriteAction::RemoveFlags(flags())),
+                ("replace-flag", [from, to]) => Ok(RewriteAction::ReplaceFlag {
+                    from: Flag::new(from),
+                    to: Flag::new(to),
+                }),
+                ("add-flags" | "remove-flags", []) => Err(ConfigError::ParseError(format!(
+                    "line {line}: {} requires at least one flag",
+                    child.name
+                ))),
+                ("replace-flag", _) => Err(ConfigError::ParseError(format!(
+                    "line {line}: replace-flag requires the flag to replace and its replacement"
+                ))),
+                (other, _) => Err(ConfigError::ParseError(format!(
+                    "line {line}: unknown rewrite action \"{other}\"; \
+                     expected add-flags, remove-flags, or replace-flag"
+                ))),
+            }
+        })
+        .collect()
+}
+
 /// Parse a single rule entry string into a BashRule.
 ///
 /// Simple program name (no whitespace) -> BashRule with empty conditions.
 /// Rule with args -> parse with command::parse(), classify args into conditions.
-fn parse_rule_entry(value: &str) -> Result<rule::BashRule, ConfigError> {
+/// A flag the program's schema says takes a value requires that value:
+/// `kubectl -n prod delete` -> argument `-n prod`, subcommand `delete`, as
+/// does `--flag=value`.
+fn parse_rule_entry(
+    value: &str,
+    line: usize,
+    schema: &FlagSchema,
+) -> Result<rule::BashRule, ConfigError> {
     let trimmed = value.trim();
     if trimmed.is_empty() {
         return Err(ConfigError::ParseError("empty rule string".to_string()));
@@ -46,9 +335,15 @@ fn parse_rule_entry(value: &str) -> Result<rule::BashRule, ConfigError> {
 
     // Simple program name: no whitespace -> empty conditions
     if trimmed.split_whitespace().nth(1).is_none() {
+        let program = crate::domain::ProgramName::new(trimmed);
         return Ok(rule::BashRule {
-            program: crate::domain::ProgramName::new(trimmed),
-            conditions: rule::RuleConditions::default(),
+            conditions: rule::RuleConditions {
+                schema: schema.for_program(program.as_str()),
+                ..Default::default()
+            },
+            program,
+            raw: trimmed.to_string(),
+            line,
         });
     }
 
@@ -56,6 +351,16 @@ fn parse_rule_entry(value: &str) -> Result<rule::BashRule, ConfigError> {
     let segments = crate::command::parse(trimmed)
         .map_err(|e| ConfigError::ParseError(format!("invalid rule '{trimmed}': {e}")))?;
 
+    // Launchers expand to several segments; a combined rule could never match
+    if segments.len() > 1 && crate::command::is_launcher(segments[0].program.as_str()) {
+        return Err(ConfigError::ParseError(format!(
+            "rule '{trimmed}' runs '{}' through launcher '{}'; \
+             write separate rules for the launcher and the program",
+            segments[segments.len() - 1].program,
+            segments[0].program
+        )));
+    }
+
     // Require exactly one command segment
     if segments.len() > 1 {
         return Err(ConfigError::ParseError(format!(
@@ -68,9 +373,25 @@ fn parse_rule_entry(value: &str) -> Result<rule::BashRule, ConfigError> {
         .next()
         .ok_or_else(|| ConfigError::ParseError(format!("no program found in rule '{trimmed}'")))?;
 
+    let program_flags = schema.for_program(segment.program.as_str());
+    let mut args = segment.args.iter();
     let mut conditions = rule::RuleConditions::default();
-    for arg in &segment.args {
+    while let Some(arg) = args.next() {
         if arg.starts_with('-') {
+            let value = match arg.split_once('=') {
+                Some((flag, value)) => Some((flag, value)),
+                None if program_flags.takes_value(arg) => {
+                    args.next().map(|v| (arg.as_str(), v.as_str()))
+                }
+                None => None,
+            };
+            if let Some((flag, value)) = value {
+                conditions.required_arguments.push(rule::ArgumentPattern {
+                    flag: flag.to_string(),
+                    value: compile_glob(value).map_err(ConfigError::ParseError)?,
+                });
+                continue;
+            }
             conditions
                 .required_flags
                 .insert(crate::domain::Flag::new(arg));
@@ -79,8 +400,11 @@ fn parse_rule_entry(value: &str) -> Result<rule::BashRule, ConfigError> {
         }
     }
 
+    conditions.schema = program_flags;
     Ok(rule::BashRule {
         program: segment.program,
+        raw: trimmed.to_string(),
+        line,
         conditions,
     })
 }
@@ -90,8 +414,10 @@ fn parse_rule_entry(value: &str) -> Result<rule::BashRule, ConfigError> {
 /// Operates on the intermediate [`ChildNode`] representation — no KDL dependency.
 fn parse_children(
     children: &[ChildNode],
-    conditions: &mut rule::RuleConditions,
+    rule: &mut rule::BashRule,
+    schema: &FlagSchema,
 ) -> Result<(), ConfigError> {
+    let conditions = &mut rule.conditions;
     for child in children {
         let line = child.line;
         let glob_at_line = |msg: String| ConfigError::ParseError(format!("line {line}: {msg}"));
@@ -115,15 +441,40 @@ fn parse_children(
                         .insert(crate::domain::Flag::new(v));
                 }
             }
+            "forbidden-flags" | "not-flags" => {
+                for v in &child.values {
+                    conditions
+                        .forbidden_flags
+                        .insert(crate::domain::Flag::new(v));
+                }
+            }
+            "except" => {
+                let exceptions = parse_except(child, &rule.program, schema)?;
+                conditions.exceptions.extend(exceptions);
+            }
             "positionals" => {
                 for v in &child.values {
                     let pattern = compile_glob(v).map_err(&glob_at_line)?;
                     conditions.positionals.push(pattern);
                 }
             }
+            "positionals-regex" => {
+                for v in &child.values {
+                    let pattern = compile_regex(v).map_err(&glob_at_line)?;
+                    conditions.positionals.push(pattern);
+                }
+            }
             "required-arguments" => {
                 for v in &child.values {
-                    let pattern = parse_argument_pattern(v).map_err(&err_at_line)?;
+                    let pattern = parse_argument_pattern(v, &child.name, compile_glob)
+                        .map_err(&err_at_line)?;
+                    conditions.required_arguments.push(pattern);
+                }
+            }
+            "argument-regex" => {
+                for v in &child.values {
+                    let pattern = parse_argument_pattern(v, &child.name, compile_regex)
+                        .map_err(&err_at_line)?;
                     conditions.required_arguments.push(pattern);
                 }
             }
@@ -133,28 +484,111 @@ fn parse_children(
                     conditions.subcommands.push(chain);
                 }
             }
-            _ => {
-                // Named positional matcher (e.g., `files "/*"`, `remotes "linear"`)
-                for v in &child.values {
-                    let pattern = compile_glob(v).map_err(&glob_at_line)?;
-                    conditions.positionals.push(pattern);
-                }
+            unknown => {
+                let hint = crate::config::lint::did_you_mean(unknown, CONDITIONS)
+                    .map(|c| format!("did you mean \"{c}\"? "))
+                    .unwrap_or_default();
+                return Err(ConfigError::ParseError(format!(
+                    "line {line}: unknown condition \"{unknown}\"; {hint}\
+                     expected one of {}",
+                    CONDITIONS.join(", ")
+                )));
             }
         }
     }
     Ok(())
 }
 
-/// Parse a `required-arguments` entry: `"--upload-file *"` -> ArgumentPattern.
-fn parse_argument_pattern(value: &str) -> Result<rule::ArgumentPattern, ConfigError> {
+/// Parse an `except` child into the rules it carves out of a rule for
+/// `program`.
+///
+/// Values are rule strings for the same program: `except "git push --force"`.
+/// A children block adds conditions to a single rule string, or on its own
+/// excepts the program's commands that meet those conditions.
+fn parse_except(
+    child: &ChildNode,
+    program: &crate::domain::ProgramName,
+    schema: &FlagSchema,
+) -> Result<Vec<rule::BashRule>, ConfigError> {
+    let line = child.line;
+    let mut exceptions = Vec::new();
+    for value in &child.values {
+        let exception = parse_rule_entry(value, line, schema).map_err(|e| match e {
+            ConfigError::ParseError(msg) => ConfigError::ParseError(format!("line {line}: {msg}")),
+            other => other,
+        })?;
+        if exception.program != *program {
+            return Err(ConfigError::ParseError(format!(
+                "line {line}: except \"{value}\" names '{}', not '{program}'",
+                exception.program
+            )));
+        }
+        exceptions.push(exception);
+    }
+    let Some(children) = &child.children else {
+        if exceptions.is_empty() {
+            return Err(ConfigError::ParseError(format!(
+                "line {line}: except requires a rule string or a children block"
+            )));
+        }
+        return Ok(exceptions);
+    };
+    if exceptions.len() > 1 {
+        return Err(ConfigError::ParseError(format!(
+            "line {line}: except has a children block with multiple entries; \
+             use separate except nodes instead"
+        )));
+    }
+    if exceptions.is_empty() {
+        exceptions.push(rule::BashRule {
+            program: program.clone(),
+            raw: format!("{program} {{ {} }}", describe_children(children)),
+            line,
+            conditions: rule::RuleConditions {
+                schema: schema.for_program(program.as_str()),
+                ..Default::default()
+            },
+        });
+    }
+    let exception = &mut exceptions[0];
+    parse_children(children, exception, schema)?;
+    normalize_subcommand_chains(&mut exception.conditions);
+    Ok(exceptions)
+}
+
+/// Write a children block back out on one line, for display:
+/// `required-flags "--force"; positionals "main"`.
+fn describe_children(children: &[ChildNode]) -> String {
+    children
+        .iter()
+        .map(|child| {
+            let values: Vec<String> = child.values.iter().map(|v| format!("{v:?}")).collect();
+            [child.name.clone()]
+                .into_iter()
+                .chain(values)
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// Parse a `required-arguments` entry: `"--upload-file *"` -> ArgumentPattern,
+/// or an `argument-regex` entry: `"--url ^https?://[0-9.]+"`, compiling the
+/// value pattern with `compile`. `kind` names the condition for errors.
+fn parse_argument_pattern(
+    value: &str,
+    kind: &str,
+    compile: fn(&str) -> Result<rule::PositionalPattern, String>,
+) -> Result<rule::ArgumentPattern, ConfigError> {
     let parts: Vec<&str> = value.splitn(2, ' ').collect();
     if parts.len() != 2 {
         return Err(ConfigError::ParseError(format!(
-            "required-arguments entry must have flag and value pattern: '{value}'"
+            "{kind} entry must have flag and value pattern: '{value}'"
         )));
     }
     let flag = parts[0].to_string();
-    let pattern = compile_glob(parts[1]).map_err(ConfigError::ParseError)?;
+    let pattern = compile(parts[1]).map_err(ConfigError::ParseError)?;
     Ok(rule::ArgumentPattern {
         flag,
         value: pattern,
@@ -180,7 +614,7 @@ mod tests {
             "ask" => ts.ask,
             _ => panic!("unknown tier: {tier}"),
         };
-        parse_rules(entries).unwrap()
+        parse_rules(entries, &FlagSchema::default()).unwrap()
     }
 
     /// Parse raw KDL, attempt to collect bash rules, return the error string.
@@ -195,7 +629,9 @@ mod tests {
             "ask" => ts.ask,
             _ => panic!("unknown tier: {tier}"),
         };
-        parse_rules(entries).unwrap_err().to_string()
+        parse_rules(entries, &FlagSchema::default())
+            .unwrap_err()
+            .to_string()
     }
 
     #[test]
@@ -317,23 +753,28 @@ mod tests {
     }
 
     #[test]
-    fn rule_children_named_positional_matcher() {
-        let rules = rules_from_kdl(
-            r#"deny "rm" {
-                files "/*"
-            }"#,
+    fn error_unknown_condition() {
+        let err = rules_err(
+            "deny \"rm\" {\n    positionals \"/*\"\n    requred-flags \"-r\"\n}",
             "deny",
         );
-        assert_eq!(rules.len(), 1);
-        assert_eq!(rules[0].conditions.positionals.len(), 1);
-        assert_eq!(rules[0].conditions.positionals[0].raw, "/*");
+        assert!(err.contains("line 4"), "got: {err}");
+        assert!(
+            err.contains("unknown condition \"requred-flags\"; did you mean \"required-flags\"?"),
+            "got: {err}"
+        );
+        let err = rules_err("deny \"rm\" {\n    files \"/*\"\n}", "deny");
+        assert!(
+            err.contains("unknown condition \"files\"; expected one of"),
+            "got: {err}"
+        );
     }
 
     #[test]
     fn rule_inline_with_children_extends_conditions() {
         let rules = rules_from_kdl(
             r#"allow "claude mcp add" {
-                remotes "linear"
+                positionals "linear"
             }"#,
             "allow",
         );
@@ -396,6 +837,133 @@ mod tests {
         );
     }
 
+    #[test]
+    fn rule_children_regex_conditions() {
+        let rules = rules_from_kdl(
+            r#"deny "git push" {
+                positionals-regex "^(main|release/.*)$"
+                argument-regex "--repo ^git@"
+            }"#,
+            "deny",
+        );
+        let conditions = &rules[0].conditions;
+        assert_eq!(conditions.positionals[0].raw, "re:^(main|release/.*)$");
+        assert_eq!(conditions.required_arguments[0].flag, "--repo");
+        assert_eq!(conditions.required_arguments[0].value.raw, "re:^git@");
+    }
+
+    #[test]
+    fn rule_invalid_regex_returns_error() {
+        let err = rules_err(
+            r#"deny "git" {
+            positionals "main"
+            positionals-regex "(main"
+        }"#,
+            "deny",
+        );
+        assert!(err.contains("line 4: invalid regex '(main'"), "got: {err}");
+        let err = rules_err(
+            r#"deny "curl" {
+            argument-regex "--url"
+        }"#,
+            "deny",
+        );
+        assert!(
+            err.contains("line 3: argument-regex entry must have flag and value pattern"),
+            "got: {err}"
+        );
+    }
+
+    #[test]
+    fn rule_children_forbidden_flags() {
+        let rules = rules_from_kdl(
+            r#"allow "git push" {
+                forbidden-flags "force" "f"
+                not-flags "--mirror"
+            }"#,
+            "allow",
+        );
+        assert_eq!(
+            rules[0].conditions.forbidden_flags,
+            flag_set(&["--force", "-f", "--mirror"])
+        );
+    }
+
+    #[test]
+    fn rule_children_except_rule_strings() {
+        let rules = rules_from_kdl(
+            r#"allow "git" {
+                except "git push --force" "git reset --hard"
+            }"#,
+            "allow",
+        );
+        let exceptions = &rules[0].conditions.exceptions;
+        assert_eq!(exceptions.len(), 2);
+        assert_eq!(exceptions[0].raw, "git push --force");
+        assert_eq!(exceptions[0].conditions.subcommand, vec!["push"]);
+        assert_eq!(
+            exceptions[1].conditions.required_flags,
+            flag_set(&["--hard"])
+        );
+    }
+
+    #[test]
+    fn rule_children_except_block() {
+        let rules = rules_from_kdl(
+            r#"allow "git" {
+                except {
+                    subcommands "push"
+                    required-flags "--force"
+                }
+                except "git branch" { optional-flags "-D"; }
+            }"#,
+            "allow",
+        );
+        let exceptions = &rules[0].conditions.exceptions;
+        assert_eq!(exceptions[0].program, "git");
+        assert_eq!(
+            exceptions[0].raw,
+            r#"git { subcommands "push"; required-flags "--force" }"#
+        );
+        assert_eq!(exceptions[0].conditions.subcommands, vec![vec!["push"]]);
+        assert_eq!(exceptions[1].conditions.subcommand, vec!["branch"]);
+        assert_eq!(exceptions[1].conditions.optional_flags, flag_set(&["-D"]));
+    }
+
+    #[test]
+    fn rule_except_errors() {
+        let err = rules_err(
+            r#"allow "git" {
+            except "rm -rf"
+        }"#,
+            "allow",
+        );
+        assert!(
+            err.contains("line 3: except \"rm -rf\" names 'rm', not 'git'"),
+            "got: {err}"
+        );
+        let err = rules_err(
+            r#"allow "git" {
+            except
+        }"#,
+            "allow",
+        );
+        assert!(
+            err.contains("line 3: except requires a rule string or a children block"),
+            "got: {err}"
+        );
+        let err = rules_err(
+            r#"allow "git" {
+            except "git push" "git pull" { required-flags "--force"; }
+        }"#,
+            "allow",
+        );
+        assert!(
+            err.contains("line 3: except has a children block with multiple entries"),
+            "got: {err}"
+        );
+    }
+
     #[test]
     fn rule_invalid_required_arguments_format() {
         let err = rules_err(
@@ -420,6 +988,25 @@ mod tests {
         );
     }
 
+    #[test]
+    fn error_launcher_with_target_inline_rule() {
+        let err = rules_err(r#"deny "sudo rm""#, "deny");
+        assert!(err.contains("launcher 'sudo'"), "got: {err}");
+        assert!(
+            err.contains("line 2"),
+            "should include line number, got: {err}"
+        );
+    }
+
+    #[test]
+    fn rule_launcher_with_own_options() {
+        let rules = rules_from_kdl(r#"ask "sudo -u root""#, "ask");
+        assert_eq!(rules.len(), 1);
+        assert_eq!(rules[0].program, "sudo");
+        assert_eq!(rules[0].conditions.required_flags, flag_set(&["-u"]));
+        assert_eq!(rules[0].conditions.subcommand, vec!["root"]);
+    }
+
     #[test]
     fn error_children_without_entry() {
         let err = rules_err(
@@ -495,4 +1082,271 @@ mod tests {
             vec![vec!["push", "origin"], vec!["push", "upstream"]]
         );
     }
+
+    // --- max-nesting-depth setting ---
+
+    fn depth_from_kdl(source: &str) -> Result<usize, ConfigError> {
+        let ts = section::parse_from_source(source).unwrap();
+        parse_max_nesting_depth(&ts.settings)
+    }
+
+    #[test]
+    fn max_nesting_depth_defaults_when_absent() {
+        assert_eq!(
+            depth_from_kdl(r#"allow "git""#).unwrap(),
+            crate::command::DEFAULT_MAX_NESTING_DEPTH
+        );
+    }
+
+    #[test]
+    fn max_nesting_depth_parsed() {
+        assert_eq!(depth_from_kdl("max-nesting-depth 2").unwrap(), 2);
+    }
+
+    #[test]
+    fn error_max_nesting_depth_negative() {
+        let err = depth_from_kdl("max-nesting-depth -1")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("non-negative"), "got: {err}");
+        assert!(err.contains("line 2"), "got: {err}");
+    }
+
+    #[test]
+    fn error_max_nesting_depth_string_value() {
+        let err = depth_from_kdl(r#"max-nesting-depth "3""#)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("single integer"), "got: {err}");
+    }
+
+    #[test]
+    fn error_max_nesting_depth_repeated() {
+        let err = depth_from_kdl("max-nesting-depth 2\nmax-nesting-depth 3")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("more than once"), "got: {err}");
+    }
+
+    fn path_args_from_kdl(source: &str) -> Result<PathArgsTable, ConfigError> {
+        let ts = section::parse_from_source(source).unwrap();
+        parse_path_args(&ts.settings)
+    }
+
+    fn strings(items: &[&str]) -> Vec<String> {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn path_args_builtins_when_absent() {
+        let table = path_args_from_kdl(r#"allow "git""#).unwrap();
+        assert!(table.get("cat").is_some());
+    }
+
+    #[test]
+    fn path_args_adds_program() {
+        let table = path_args_from_kdl(
+            r#"path-args "mytool" {
+                read "positionals"
+                write "last" "-o" "out="
+                ignore "first" "-n"
+                script-options "-e"
+                in-place-flags "-i"
+            }"#,
+        )
+        .unwrap();
+        let args = &table.get("mytool").unwrap()[0];
+        assert_eq!(args.positionals, Some(FileOperation::Read));
+        assert_eq!(args.first, Some(PathRole::Ignore));
+        assert_eq!(args.last, Some(PathRole::Access(FileOperation::Write)));
+        assert_eq!(
+            args.option_paths,
+            vec![(Flag::new("-o"), FileOperation::Write)]
+        );
+        assert_eq!(
+            args.operand_paths,
+            vec![("out=".to_string(), FileOperation::Write)]
+        );
+        assert_eq!(args.options_with_values, vec![Flag::new("-n")]);
+        assert_eq!(args.script_options, vec![Flag::new("-e")]);
+        assert_eq!(args.in_place_flags, vec![Flag::new("-i")]);
+    }
+
+    #[test]
+    fn path_args_replaces_builtin() {
+        let table = path_args_from_kdl(r#"path-args "cat" { write "positionals" }"#).unwrap();
+        assert_eq!(
+            table.accesses("cat", &strings(&["x"])),
+            vec![("x".to_string(), FileOperation::Write)]
+        );
+    }
+
+    #[test]
+    fn error_path_args_without_children() {
+        let err = path_args_from_kdl(r#"path-args "mytool""#)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("requires a children block"), "got: {err}");
+    }
+
+    #[test]
+    fn error_path_args_without_program() {
+        let err = path_args_from_kdl(r#"path-args { read "positionals" }"#)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("single program name"), "got: {err}");
+    }
+
+    #[test]
+    fn error_path_args_unknown_child() {
+        let err = path_args_from_kdl("path-args \"mytool\" {\n    edit \"positionals\"\n}")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("line 3"), "got: {err}");
+        assert!(err.contains("unknown path-args child 'edit'"), "got: {err}");
+    }
+
+    #[test]
+    fn error_path_args_invalid_selector() {
+        let err = path_args_from_kdl(r#"path-args "mytool" { read "middle" }"#)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains("invalid path-args selector 'middle'"),
+            "got: {err}"
+        );
+    }
+
+    #[test]
+    fn error_path_args_repeated() {
+        let err = path_args_from_kdl(
+            r#"path-args "mytool" { read "positionals" }
+path-args "mytool" { write "positionals" }"#,
+        )
+        .unwrap_err()
+        .to_string();
+        assert!(err.contains("defined more than once"), "got: {err}");
+    }
+
+    fn schema_from_kdl(source: &str) -> Result<FlagSchema, ConfigError> {
+        let ts = section::parse_from_source(source).unwrap();
+        parse_flag_schema(&ts.settings)
+    }
+
+    #[test]
+    fn flags_with_values_extend_schema() {
+        let schema = schema_from_kdl(
+            r#"flags-with-values "mytool" "-x" "profile"
+flags-with-values "git" "--exec-path""#,
+        )
+        .unwrap();
+        assert_eq!(
+            schema.for_program("mytool").value_flags,
+            [Flag::new("-x"), Flag::new("--profile")]
+        );
+        assert!(schema.for_program("git").takes_value("-C"));
+        assert!(schema.for_program("git").takes_value("--exec-path"));
+    }
+
+    #[test]
+    fn flag_aliases_extend_schema() {
+        let schema = schema_from_kdl(r#"flag-aliases "mytool" "-y" "yes""#).unwrap();
+        assert!(schema.for_program("mytool").same("--yes", "-y"));
+        let err = schema_from_kdl(r#"flag-aliases "mytool" "-y""#)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains("line 2: flag-aliases requires a program name and at least two flags"),
+            "got: {err}"
+        );
+    }
+
+    #[test]
+    fn error_flags_with_values_needs_program_and_flag() {
+        let err = schema_from_kdl(r#"flags-with-values "mytool""#)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains("line 2: flags-with-values requires a program name and at least one flag"),
+            "got: {err}"
+        );
+    }
+
+    #[test]
+    fn rule_string_flag_value_becomes_argument() {
+        let rules = rules_from_kdl(r#"deny "kubectl -n prod delete""#, "deny");
+        let conditions = &rules[0].conditions;
+        assert_eq!(conditions.subcommand, vec!["delete"]);
+        assert!(conditions.required_flags.is_empty());
+        assert_eq!(conditions.required_arguments[0].flag, "-n");
+        assert_eq!(conditions.required_arguments[0].value.raw, "prod");
+        assert!(conditions.schema.takes_value("--namespace"));
+    }
+
+    #[test]
+    fn rule_string_equals_form_becomes_argument() {
+        let rules = rules_from_kdl(r#"deny "mytool --env=prod* deploy""#, "deny");
+        let conditions = &rules[0].conditions;
+        assert_eq!(conditions.subcommand, vec!["deploy"]);
+        assert!(conditions.required_flags.is_empty());
+        assert_eq!(conditions.required_arguments[0].flag, "--env");
+        assert_eq!(conditions.required_arguments[0].value.raw, "prod*");
+    }
+
+    fn rewrites_from_kdl(source: &str) -> Result<Vec<RewriteRule>, ConfigError> {
+        let ts = section::parse_from_source(source).unwrap();
+        parse_rewrites(&ts.settings, &FlagSchema::default())
+    }
+
+    #[test]
+    fn rewrite_rule_and_actions() {
+        let rewrites = rewrites_from_kdl(
+            r#"rewrite "git push --force" {
+                replace-flag "--force" "--force-with-lease"
+                add-flags "v"
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(rewrites[0].raw, "git push --force");
+        assert_eq!(rewrites[0].rule.conditions.subcommand, vec!["push"]);
+        assert_eq!(
+            rewrites[0].actions,
+            vec![
+                RewriteAction::ReplaceFlag {
+                    from: Flag::new("--force"),
+                    to: Flag::new("--force-with-lease"),
+                },
+                RewriteAction::AddFlags(vec![Flag::new("-v")]),
+            ]
+        );
+    }
+
+    #[test]
+    fn error_rewrite_without_actions() {
+        let err = rewrites_from_kdl(r#"rewrite "rm""#)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("line 2"), "got: {err}");
+        assert!(err.contains("requires a children block"), "got: {err}");
+    }
+
+    #[test]
+    fn error_rewrite_unknown_action() {
+        let err = rewrites_from_kdl("rewrite \"rm\" {\n    prepend \"sudo\"\n}")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("line 3"), "got: {err}");
+        assert!(
+            err.contains("unknown rewrite action \"prepend\""),
+            "got: {err}"
+        );
+    }
+
+    #[test]
+    fn error_rewrite_replace_flag_arity() {
+        let err = rewrites_from_kdl(r#"rewrite "rm" { replace-flag "-f"; }"#)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("its replacement"), "got: {err}");
+    }
 }
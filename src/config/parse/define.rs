@@ -0,0 +1,327 @@
+use std::collections::HashMap;
+
+use crate::config::define::{list_reference, path_reference, Definitions};
+use crate::config::document::{ConfigDocument, ParseNode};
+use crate::config::ConfigError;
+
+/// A definition as written, before its references are resolved.
+struct Raw {
+    values: Vec<String>,
+    line: usize,
+}
+
+/// Parse the top-level `define` and `path-var` nodes, resolving references
+/// between them.
+///
+/// ```kdl
+/// define "readonly-tools" "ls" "cat" "head"
+/// define "safe-tools" "@readonly-tools" "git status"
+/// path-var "secrets" "~/.ssh/**" "~/.aws/**"
+/// path-var "work" "~/work"
+/// ```
+///
+/// A name is defined once across all config files, so a later file cannot
+/// change the rules of an earlier one. A reference to an undefined name, or
+/// one that leads back to the definition it is in, is an error.
+pub(crate) fn parse_definitions(doc: &ConfigDocument) -> Result<Definitions, ConfigError> {
+    let mut lists = HashMap::new();
+    let mut paths = HashMap::new();
+    let mut order = Vec::new();
+    for node in doc.nodes() {
+        let kind = node.name();
+        let defined = match kind {
+            "define" => &mut lists,
+            "path-var" => &mut paths,
+            _ => continue,
+        };
+        let (name, raw) = parse_definition(&node)?;
+        if let Some(earlier) = defined.get(&name) {
+            let earlier: &Raw = earlier;
+            return Err(ConfigError::ParseError(format!(
+                "line {}: {kind} \"{name}\" is already defined at line {}",
+                raw.line, earlier.line
+            )));
+        }
+        order.push((kind == "define", name.clone()));
+        defined.insert(name, raw);
+    }
+
+    // Resolve in definition order, so errors are reported deterministically.
+    let mut definitions = Definitions::default();
+    for (is_list, name) in &order {
+        if *is_list {
+            resolve_list(name, &lists, &mut definitions.lists, &mut Vec::new())?;
+        } else {
+            resolve_path(name, &paths, &mut definitions.paths, &mut Vec::new())?;
+        }
+    }
+    Ok(definitions)
+}
+
+/// Parse one `define "name" "value"…` or `path-var "name" "value"…` node.
+fn parse_definition(node: &ParseNode<'_>) -> Result<(String, Raw), ConfigError> {
+    let kind = node.name();
+    let line = node.line();
+    let values = node.string_values();
+    if node.entry_count() != values.len() || node.has_children() {
+        return Err(ConfigError::ParseError(format!(
+            "line {line}: {kind} takes a name and values, all quoted strings"
+        )));
+    }
+    let Some((name, values)) = values.split_first() else {
+        return Err(ConfigError::ParseError(format!(
+            "line {line}: {kind} requires a name and at least one value"
+        )));
+    };
+    if values.is_empty() {
+        return Err(ConfigError::ParseError(format!(
+            "line {line}: {kind} \"{name}\" requires at least one value"
+        )));
+    }
+    if name.is_empty() || name.contains(['@', '$', '/']) {
+        return Err(ConfigError::ParseError(format!(
+            "line {line}: {kind} name \"{name}\" must not be empty or contain @, $, or /"
+        )));
+    }
+    let raw = Raw {
+        values: values.iter().map(|v| v.to_string()).collect(),
+        line,
+    };
+    Ok((name.to_string(), raw))
+}
+
+/// Resolve a `define`, expanding the `@name` values it contains.
+fn resolve_list(
+    name: &str,
+    raw: &HashMap<String, Raw>,
+    resolved: &mut HashMap<String, Vec<String>>,
+    stack: &mut Vec<String>,
+) -> Result<(), ConfigError> {
+    if resolved.contains_key(name) {
+        return Ok(());
+    }
+    let definition = &raw[name];
+    stack.push(name.to_string());
+    let mut values = Vec::new();
+    for value in &definition.values {
+        let Some(reference) = list_reference(value) else {
+            values.push(value.clone());
+            continue;
+        };
+        check_reference("define", "@", reference, raw, stack, definition.line)?;
+        resolve_list(reference, raw, resolved, stack)?;
+        values.extend(resolved[reference].iter().cloned());
+    }
+    stack.pop();
+    resolved.insert(name.to_string(), values);
+    Ok(())
+}
+
+/// Resolve a `path-var`, expanding the `$name` prefixes it contains.
+fn resolve_path(
+    name: &str,
+    raw: &HashMap<String, Raw>,
+    resolved: &mut HashMap<String, Vec<String>>,
+    stack: &mut Vec<String>,
+) -> Result<(), ConfigError> {
+    if resolved.contains_key(name) {
+        return Ok(());
+    }
+    let definition = &raw[name];
+    stack.push(name.to_string());
+    let mut values = Vec::new();
+    for value in &definition.values {
+        let Some((reference, rest)) = path_reference(value) else {
+            values.push(value.clone());
+            continue;
+        };
+        check_reference("path-var", "$", reference, raw, stack, definition.line)?;
+        resolve_path(reference, raw, resolved, stack)?;
+        values.extend(resolved[reference].iter().map(|v| format!("{v}{rest}")));
+    }
+    stack.pop();
+    resolved.insert(name.to_string(), values);
+    Ok(())
+}
+
+/// Reject a reference to an undefined name or one already being resolved.
+fn check_reference(
+    kind: &str,
+    sigil: &str,
+    reference: &str,
+    raw: &HashMap<String, Raw>,
+    stack: &[String],
+    line: usize,
+) -> Result<(), ConfigError> {
+    if !raw.contains_key(reference) {
+        return Err(ConfigError::ParseError(format!(
+            "line {line}: {kind} \"{}\" refers to undefined \"{sigil}{reference}\"",
+            stack[stack.len() - 1]
+        )));
+    }
+    if let Some(start) = stack.iter().position(|name| name == reference) {
+        let cycle: Vec<String> = stack[start..]
+            .iter()
+            .chain(std::iter::once(&reference.to_string()))
+            .map(|name| format!("{sigil}{name}"))
+            .collect();
+        return Err(ConfigError::ParseError(format!(
+            "line {line}: cyclic {kind} reference: {}",
+            cycle.join(" -> ")
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn definitions(source: &str) -> Result<Definitions, ConfigError> {
+        parse_definitions(&ConfigDocument::parse(source)?)
+    }
+
+    fn error(source: &str) -> String {
+        definitions(source).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn define_and_path_var_are_collected() {
+        let defs = definitions(
+            "define \"readonly\" \"ls\" \"cat\"\npath-var \"secrets\" \"~/.ssh/**\" \"~/.aws/**\"",
+        )
+        .unwrap();
+        assert_eq!(defs.lists["readonly"], vec!["ls", "cat"]);
+        assert_eq!(defs.paths["secrets"], vec!["~/.ssh/**", "~/.aws/**"]);
+    }
+
+    #[test]
+    fn references_between_definitions_resolve_in_any_order() {
+        let defs = definitions(
+            r#"
+            define "safe" "@readonly" "git status"
+            define "readonly" "ls" "cat"
+            path-var "keys" "$home-dirs/.ssh/**"
+            path-var "home-dirs" "/home/a" "/home/b"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(defs.lists["safe"], vec!["ls", "cat", "git status"]);
+        assert_eq!(
+            defs.paths["keys"],
+            vec!["/home/a/.ssh/**", "/home/b/.ssh/**"]
+        );
+    }
+
+    #[test]
+    fn undefined_reference_is_an_error() {
+        assert_eq!(
+            error("define \"safe\" \"@readonly\""),
+            "invalid KDL syntax: line 1: define \"safe\" refers to undefined \"@readonly\""
+        );
+    }
+
+    #[test]
+    fn cyclic_reference_is_an_error() {
+        assert_eq!(
+            error("path-var \"a\" \"$b/x\"\npath-var \"b\" \"$a\""),
+            "invalid KDL syntax: line 2: cyclic path-var reference: $a -> $b -> $a"
+        );
+        assert_eq!(
+            error("define \"a\" \"@a\""),
+            "invalid KDL syntax: line 1: cyclic define reference: @a -> @a"
+        );
+    }
+
+    #[test]
+    fn redefinition_is_an_error() {
+        assert_eq!(
+            error("define \"a\" \"ls\"\ndefine \"a\" \"cat\""),
+            "invalid KDL syntax: line 2: define \"a\" is already defined at line 1"
+        );
+    }
+
+    #[test]
+    fn list_reference_expands_in_rules() {
+        let config = crate::config::Config::parse(
+            r#"
+            define "readonly-tools" "ls" "cat"
+            bash {
+                allow "@readonly-tools" "git status"
+                deny "rm" {
+                    required-flags "-r"
+                }
+                ask "@readonly-tools" {
+                    positionals "/etc/**"
+                }
+            }
+            "#,
+        )
+        .unwrap();
+        let bash = config.bash.unwrap();
+        let raw = |rules: &[crate::config::rule::BashRule]| {
+            rules.iter().map(|r| r.raw.clone()).collect::<Vec<_>>()
+        };
+        assert_eq!(raw(&bash.allow), vec!["ls", "cat", "git status"]);
+        assert_eq!(raw(&bash.ask), vec!["ls", "cat"]);
+        assert!(bash
+            .ask
+            .iter()
+            .all(|r| !r.conditions.positionals.is_empty()));
+        assert_eq!(bash.allow[0].line, 4);
+    }
+
+    #[test]
+    fn path_var_expands_in_file_patterns() {
+        let config = crate::config::Config::parse(
+            r#"
+            path-var "secrets" "/etc/ssl/private/**" "/root/**"
+            path-var "work" "/srv/work"
+            files {
+                deny "$secrets" "read"
+                "$work/**" {
+                    allow "read" "write"
+                }
+            }
+            "#,
+        )
+        .unwrap();
+        let files = config.files.unwrap();
+        let patterns = |rules: &[crate::config::files::FileRule]| {
+            rules
+                .iter()
+                .map(|r| r.raw_pattern.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            patterns(&files.deny),
+            vec!["/etc/ssl/private/**", "/root/**"]
+        );
+        assert_eq!(patterns(&files.allow), vec!["/srv/work/**"]);
+    }
+
+    #[test]
+    fn undefined_reference_in_a_rule_suggests_a_name() {
+        let err = crate::config::Config::parse(
+            "define \"readonly-tools\" \"ls\"\nbash {\n    allow \"@readonly-tool\"\n}",
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid KDL syntax: line 3, column 11: undefined list \"@readonly-tool\"; did you mean \"@readonly-tools\"?"
+        );
+        let err =
+            crate::config::Config::parse("files {\n    deny \"$secrets\" \"read\"\n}").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid KDL syntax: line 2, column 10: undefined path variable \"$secrets\""
+        );
+    }
+
+    #[test]
+    fn definition_needs_values_and_a_plain_name() {
+        assert!(error("define \"a\"").contains("define \"a\" requires at least one value"));
+        assert!(error("path-var \"$a\" \"/x\"").contains("must not be empty or contain @, $, or /"));
+        assert!(error("define \"a\" 1").contains("all quoted strings"));
+    }
+}
@@ -1,14 +1,20 @@
 use std::collections::HashSet;
 
-use crate::config::document::ConfigDocument;
-use crate::config::files::{FileRule, FilesConfig};
+use crate::config::define::Definitions;
+use crate::config::document::{ConfigDocument, ParseNode};
+use crate::config::files::{FileRewrite, FileRule, FilesConfig};
+use crate::config::normalize;
 use crate::config::ConfigError;
 use crate::protocol::FileOperation;
 
 /// Parse the `files` section from a config document.
 ///
-/// Returns `None` when the `files` section is absent.
-pub(crate) fn parse_files(doc: &ConfigDocument) -> Result<Option<FilesConfig>, ConfigError> {
+/// Returns `None` when the `files` section is absent. A pattern starting
+/// with `$name` becomes one rule per value of the path variable.
+pub(crate) fn parse_files(
+    doc: &ConfigDocument,
+    definitions: &Definitions,
+) -> Result<Option<FilesConfig>, ConfigError> {
     let section = match doc.section("files") {
         Some(s) => s,
         None => return Ok(None),
@@ -19,10 +25,13 @@ pub(crate) fn parse_files(doc: &ConfigDocument) -> Result<Option<FilesConfig>, C
     for node in section.nodes() {
         match node.name() {
             "allow" | "deny" | "ask" => {
-                parse_flat_rule(&node, &mut config)?;
+                parse_flat_rule(&node, definitions, &mut config)?;
+            }
+            "rewrite" => {
+                parse_rewrite(&node, definitions, &mut config)?;
             }
             _ => {
-                parse_path_block(&node, &mut config)?;
+                parse_path_block(&node, definitions, &mut config)?;
             }
         }
     }
@@ -33,9 +42,11 @@ pub(crate) fn parse_files(doc: &ConfigDocument) -> Result<Option<FilesConfig>, C
 /// Parse a flat one-liner rule: `deny "~/.ssh/**" "read" "write"`.
 ///
 /// Node name determines the tier. First string value is the path pattern,
-/// remaining values are operations.
+/// remaining values are operations. An optional children block holds
+/// `except` nodes: `allow "<cwd>/**" "write" { except "<cwd>/.git/**"; }`.
 fn parse_flat_rule(
-    node: &crate::config::document::ParseNode<'_>,
+    node: &ParseNode<'_>,
+    definitions: &Definitions,
     config: &mut FilesConfig,
 ) -> Result<(), ConfigError> {
     let tier = node.name();
@@ -65,24 +76,25 @@ fn parse_flat_rule(
     }
 
     let operations = parse_operations(op_strings, line)?;
-    let home_expanded_pattern = crate::config::normalize::files::expand_home(&raw_pattern);
-    let rule = FileRule {
-        raw_pattern,
-        home_expanded_pattern,
-        operations,
-        line,
-    };
-
-    push_rule(config, tier, rule);
+    let context = format!("{tier} \"{raw_pattern}\"");
+    let except_nodes = except_children(node, &context)?;
+    for raw_pattern in definitions.expand_path(&raw_pattern, line)? {
+        let mut rule = file_rule(raw_pattern, operations.clone(), line)?;
+        rule.exceptions = except_rules(&except_nodes, &operations, definitions)?;
+        push_rule(config, tier, rule);
+    }
     Ok(())
 }
 
 /// Parse a path-first block: `"<cwd>/**" { allow "read" "write" }`.
 ///
 /// Node name is the path pattern. Children nodes named `allow`/`deny`/`ask`
-/// define the tier, with their string values parsed as operations.
+/// define the tier, with their string values parsed as operations. `except`
+/// children carve paths out of every tier in the block; a tier's own
+/// children block can hold more `except` nodes for that tier alone.
 fn parse_path_block(
-    node: &crate::config::document::ParseNode<'_>,
+    node: &ParseNode<'_>,
+    definitions: &Definitions,
     config: &mut FilesConfig,
 ) -> Result<(), ConfigError> {
     let raw_pattern = node.name().to_string();
@@ -111,10 +123,17 @@ fn parse_path_block(
         )));
     }
 
+    let patterns = definitions.expand_path(&raw_pattern, line)?;
+    let block_excepts: Vec<_> = child_nodes
+        .iter()
+        .filter(|child| child.name() == "except")
+        .cloned()
+        .collect();
     let mut found_tier = false;
     for child in &child_nodes {
         let child_tier = child.name();
         match child_tier {
+            "except" => {}
             "allow" | "deny" | "ask" => {
                 let op_strings = child.string_values();
                 if child.entry_count() != op_strings.len() {
@@ -132,21 +151,21 @@ fn parse_path_block(
                     )));
                 }
                 let operations = parse_operations(&op_strings, child.line())?;
-                let home_expanded_pattern =
-                    crate::config::normalize::files::expand_home(&raw_pattern);
-                let rule = FileRule {
-                    raw_pattern: raw_pattern.clone(),
-                    home_expanded_pattern,
-                    operations,
-                    line: child.line(),
-                };
-                push_rule(config, child_tier, rule);
+                let context = format!("{child_tier} node in path block \"{raw_pattern}\"");
+                let mut except_nodes = block_excepts.clone();
+                except_nodes.extend(except_children(child, &context)?);
+                for raw_pattern in &patterns {
+                    let mut rule =
+                        file_rule(raw_pattern.clone(), operations.clone(), child.line())?;
+                    rule.exceptions = except_rules(&except_nodes, &operations, definitions)?;
+                    push_rule(config, child_tier, rule);
+                }
                 found_tier = true;
             }
             other => {
                 return Err(ConfigError::ParseError(format!(
                     "line {}: unexpected node \"{other}\" in path block \"{raw_pattern}\"; \
-                     expected allow, deny, or ask",
+                     expected allow, deny, ask, or except",
                     child.line()
                 )));
             }
@@ -162,6 +181,156 @@ fn parse_path_block(
     Ok(())
 }
 
+/// Parse a path rewrite:
+/// `rewrite "~/.env" "read" { path "<cwd>/.env.example"; }`.
+///
+/// The values are a pattern and operations, as in a flat rule. The children
+/// block needs exactly one `path` node naming the replacement path, and can
+/// hold `except` nodes.
+fn parse_rewrite(
+    node: &ParseNode<'_>,
+    definitions: &Definitions,
+    config: &mut FilesConfig,
+) -> Result<(), ConfigError> {
+    let line = node.line();
+    let values = node.string_values();
+    if node.entry_count() != values.len() || values.len() < 2 {
+        return Err(ConfigError::ParseError(format!(
+            "line {line}: rewrite requires a quoted path pattern and at least one operation"
+        )));
+    }
+    let raw_pattern = values[0];
+    let operations = parse_operations(&values[1..], line)?;
+
+    let children = node.children().map(|c| c.nodes()).unwrap_or_default();
+    let (targets, others): (Vec<_>, Vec<_>) = children
+        .into_iter()
+        .partition(|child| child.name() == "path");
+    if let Some(other) = others.iter().find(|child| child.name() != "except") {
+        return Err(ConfigError::ParseError(format!(
+            "line {}: unexpected node \"{}\" in rewrite \"{raw_pattern}\"; \
+             expected path or except",
+            other.line(),
+            other.name()
+        )));
+    }
+    let raw_target = match targets.as_slice() {
+        [target] => match target.string_values().as_slice() {
+            [value] if target.entry_count() == 1 && !value.is_empty() => value.to_string(),
+            _ => {
+                return Err(ConfigError::ParseError(format!(
+                    "line {}: path in rewrite \"{raw_pattern}\" requires one quoted path",
+                    target.line()
+                )));
+            }
+        },
+        _ => {
+            return Err(ConfigError::ParseError(format!(
+                "line {line}: rewrite \"{raw_pattern}\" requires exactly one path child"
+            )));
+        }
+    };
+
+    for raw_pattern in definitions.expand_path(raw_pattern, line)? {
+        let mut rule = file_rule(raw_pattern, operations.clone(), line)?;
+        rule.exceptions = except_rules(&others, &operations, definitions)?;
+        config.rewrites.push(FileRewrite {
+            rule,
+            home_expanded_target: normalize::files::expand_home(&raw_target),
+        });
+    }
+    Ok(())
+}
+
+/// The `except` nodes in a rule's children block; any other child is an
+/// error. `context` names the rule for errors.
+fn except_children<'a>(
+    node: &ParseNode<'a>,
+    context: &str,
+) -> Result<Vec<ParseNode<'a>>, ConfigError> {
+    let Some(children) = node.children() else {
+        return Ok(vec![]);
+    };
+    let nodes = children.nodes();
+    if let Some(other) = nodes.iter().find(|child| child.name() != "except") {
+        return Err(ConfigError::ParseError(format!(
+            "line {}: unexpected node \"{}\" in {context}; expected except",
+            other.line(),
+            other.name()
+        )));
+    }
+    Ok(nodes)
+}
+
+/// Build the exception rules for `except "pattern" …` nodes, with path
+/// variables expanded.
+fn except_rules(
+    nodes: &[ParseNode<'_>],
+    operations: &HashSet<FileOperation>,
+    definitions: &Definitions,
+) -> Result<Vec<FileRule>, ConfigError> {
+    let mut exceptions = Vec::new();
+    for node in nodes {
+        let line = node.line();
+        let values = node.string_values();
+        if values.is_empty() || node.entry_count() != values.len() || node.has_children() {
+            return Err(ConfigError::ParseError(format!(
+                "line {line}: except requires one or more quoted path patterns"
+            )));
+        }
+        for value in values {
+            for raw_pattern in definitions.expand_path(value, line)? {
+                exceptions.push(file_rule(raw_pattern, operations.clone(), line)?);
+            }
+        }
+    }
+    Ok(exceptions)
+}
+
+/// Build a rule for one pattern: a glob with `~` and `<home>` expanded, or
+/// a `re:` regex compiled now so a bad one is reported at its line.
+fn file_rule(
+    raw_pattern: String,
+    operations: HashSet<FileOperation>,
+    line: usize,
+) -> Result<FileRule, ConfigError> {
+    let Some(source) = raw_pattern.strip_prefix("re:") else {
+        return Ok(FileRule {
+            home_expanded_pattern: normalize::files::expand_home(&raw_pattern),
+            raw_pattern,
+            regex: None,
+            operations,
+            exceptions: vec![],
+            line,
+        });
+    };
+    let compiled = normalize::files::compile_regex(source)
+        .map_err(|e| ConfigError::ParseError(format!("line {line}: {e}")))?;
+    let (home_expanded_pattern, regex) = match compiled {
+        Ok(regex) => (Ok(raw_pattern.clone()), Some(regex)),
+        Err(e) => (Err(e), None),
+    };
+    Ok(FileRule {
+        raw_pattern,
+        home_expanded_pattern,
+        regex,
+        operations,
+        exceptions: vec![],
+        line,
+    })
+}
+
+/// Operation names accepted in file rules.
+const OPERATIONS: &[&str] = &[
+    "read",
+    "write",
+    "edit",
+    "glob",
+    "grep",
+    "notebook-edit",
+    "list",
+];
+
 /// Parse operation strings into a `HashSet<FileOperation>`.
 fn parse_operations(ops: &[&str], line: usize) -> Result<HashSet<FileOperation>, ConfigError> {
     let mut set = HashSet::new();
@@ -172,10 +341,15 @@ fn parse_operations(ops: &[&str], line: usize) -> Result<HashSet<FileOperation>,
             "edit" => FileOperation::Edit,
             "glob" => FileOperation::Glob,
             "grep" => FileOperation::Grep,
+            "notebook-edit" => FileOperation::NotebookEdit,
+            "list" => FileOperation::List,
             unknown => {
+                let hint = crate::config::lint::did_you_mean(unknown, OPERATIONS)
+                    .map(|op| format!("did you mean \"{op}\"? "))
+                    .unwrap_or_default();
                 return Err(ConfigError::ParseError(format!(
-                    "line {line}: unknown file operation \"{unknown}\"; \
-                     expected read, write, edit, glob, or grep"
+                    "line {line}: unknown file operation \"{unknown}\"; {hint}\
+                     expected read, write, edit, glob, grep, notebook-edit, or list"
                 )));
             }
         };
@@ -202,7 +376,7 @@ mod tests {
     fn parse_files_from_source(source: &str) -> Result<Option<FilesConfig>, ConfigError> {
         let wrapped = format!("files {{\n{source}\n}}");
         let doc = ConfigDocument::parse(&wrapped)?;
-        parse_files(&doc)
+        parse_files(&doc, &Definitions::default())
     }
 
     fn files(source: &str) -> FilesConfig {
@@ -323,7 +497,7 @@ mod tests {
             "#,
         )
         .unwrap();
-        let result = parse_files(&doc).unwrap();
+        let result = parse_files(&doc, &Definitions::default()).unwrap();
         assert!(result.is_none());
     }
 
@@ -413,7 +587,7 @@ mod tests {
             "#,
         )
         .unwrap();
-        let result = parse_files(&doc).unwrap();
+        let result = parse_files(&doc, &Definitions::default()).unwrap();
         assert!(result.is_none());
     }
 
@@ -437,11 +611,12 @@ mod tests {
         );
     }
 
-    // --- All five operations parse correctly ---
+    // --- All operations parse correctly ---
 
     #[test]
     fn all_operations_parse() {
-        let config = files(r#"allow "/**" "read" "write" "edit" "glob" "grep""#);
+        let config =
+            files(r#"allow "/**" "read" "write" "edit" "glob" "grep" "notebook-edit" "list""#);
         assert_eq!(config.allow.len(), 1);
         assert_eq!(
             config.allow[0].operations,
@@ -451,6 +626,8 @@ mod tests {
                 FileOperation::Edit,
                 FileOperation::Glob,
                 FileOperation::Grep,
+                FileOperation::NotebookEdit,
+                FileOperation::List,
             ])
         );
     }
@@ -538,4 +715,114 @@ mod tests {
         );
         assert!(err.contains("inline values"), "got: {err}");
     }
+
+    #[test]
+    fn regex_pattern_compiles_at_load() {
+        let config = files(
+            r#"
+            deny "re:\\.(env|pem)$" "read"
+            "re:<cwd>/target/" {
+                allow "write"
+            }
+            "#,
+        );
+        assert_eq!(config.deny[0].raw_pattern, r"re:\.(env|pem)$");
+        assert!(config.deny[0].regex.is_some());
+        assert!(config.allow[0].regex.as_ref().unwrap().after_cwd);
+    }
+
+    #[test]
+    fn error_invalid_regex_pattern_names_line() {
+        let err = files_err("allow \"/tmp/**\" \"read\"\ndeny \"re:(unclosed\" \"read\"");
+        assert!(
+            err.contains("line 3: invalid regex '(unclosed'"),
+            "got: {err}"
+        );
+    }
+
+    #[test]
+    fn error_regex_cwd_must_lead() {
+        let err = files_err(r#"deny "re:/srv<cwd>" "read""#);
+        assert!(
+            err.contains("<cwd> can only start a re: pattern"),
+            "got: {err}"
+        );
+    }
+
+    #[test]
+    fn except_in_flat_rule_and_path_block() {
+        let config = files(
+            r#"
+            allow "<cwd>/**" "write" { except "<cwd>/.git/**" "<cwd>/target/**"; }
+            "/etc/**" {
+                except "/etc/hosts"
+                deny "read"
+                ask "write" { except "/etc/motd"; }
+            }
+            "#,
+        );
+        let patterns = |rule: &FileRule| -> Vec<String> {
+            rule.exceptions
+                .iter()
+                .map(|e| e.raw_pattern.clone())
+                .collect()
+        };
+        assert_eq!(
+            patterns(&config.allow[0]),
+            vec!["<cwd>/.git/**", "<cwd>/target/**"]
+        );
+        assert_eq!(patterns(&config.deny[0]), vec!["/etc/hosts"]);
+        assert_eq!(patterns(&config.ask[0]), vec!["/etc/hosts", "/etc/motd"]);
+    }
+
+    #[test]
+    fn error_except_needs_patterns() {
+        let err = files_err(r#"allow "/tmp/**" "write" { except; }"#);
+        assert!(
+            err.contains("line 2: except requires one or more quoted path patterns"),
+            "got: {err}"
+        );
+        let err = files_err(r#"allow "/tmp/**" "write" { deny "/tmp/x"; }"#);
+        assert!(
+            err.contains(r#"line 2: unexpected node "deny" in allow "/tmp/**"; expected except"#),
+            "got: {err}"
+        );
+    }
+
+    #[test]
+    fn rewrite_with_target_and_except() {
+        let config = files(
+            r#"
+            rewrite "<cwd>/.env*" "read" {
+                path "<cwd>/.env.example"
+                except "<cwd>/.env.example"
+            }
+            "#,
+        );
+        let rewrite = &config.rewrites[0];
+        assert_eq!(rewrite.rule.raw_pattern, "<cwd>/.env*");
+        assert_eq!(rewrite.rule.operations, ops_set(&[FileOperation::Read]));
+        assert_eq!(rewrite.rule.exceptions[0].raw_pattern, "<cwd>/.env.example");
+        assert_eq!(rewrite.target("/p").as_deref(), Some("/p/.env.example"));
+        assert!(config.allow.is_empty());
+    }
+
+    #[test]
+    fn error_rewrite_needs_one_path() {
+        let err = files_err(r#"rewrite "/tmp/x" "read""#);
+        assert!(
+            err.contains(r#"line 2: rewrite "/tmp/x" requires exactly one path child"#),
+            "got: {err}"
+        );
+        let err = files_err(r#"rewrite "/tmp/x" "read" { path "/a" "/b"; }"#);
+        assert!(
+            err.contains(r#"path in rewrite "/tmp/x" requires one quoted path"#),
+            "got: {err}"
+        );
+        let err = files_err(r#"rewrite "/tmp/x" { path "/a"; }"#);
+        assert!(
+            err.contains("rewrite requires a quoted path pattern and at least one operation"),
+            "got: {err}"
+        );
+    }
 }
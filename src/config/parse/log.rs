@@ -0,0 +1,116 @@
+use std::path::PathBuf;
+
+use crate::config::document::ConfigDocument;
+use crate::config::log::LogConfig;
+use crate::config::ConfigError;
+
+/// Parse the `log` section from a config document.
+///
+/// Returns `None` when the `log` section is absent. Within a config layer, a
+/// later file's path replaces an earlier one's; a later layer cannot move a
+/// path an earlier layer set, so a project config cannot point log appends
+/// at a file of its choosing once the user config names one.
+///
+/// ```kdl
+/// log {
+///     path "~/.local/state/claude-permissions-hook/decisions.jsonl"
+/// }
+/// ```
+pub(crate) fn parse_log(doc: &ConfigDocument) -> Result<Option<LogConfig>, ConfigError> {
+    let section = match doc.section("log") {
+        Some(s) => s,
+        None => return Ok(None),
+    };
+
+    let mut path = None;
+    let mut path_layer = None;
+    let mut set_in = None;
+    for node in section.nodes() {
+        let line = node.line();
+        if node.name() != "path" {
+            return Err(ConfigError::ParseError(format!(
+                "line {line}: unexpected node \"{}\" in log section; expected path",
+                node.name()
+            )));
+        }
+        if set_in.replace(node.part()) == Some(node.part()) {
+            return Err(ConfigError::ParseError(format!(
+                "line {line}: log path is set more than once"
+            )));
+        }
+        let raw = match node.string_values().as_slice() {
+            [raw] if node.entry_count() == 1 => raw.to_string(),
+            _ => {
+                return Err(ConfigError::ParseError(format!(
+                    "line {line}: path requires a single quoted file path"
+                )))
+            }
+        };
+        let expanded = crate::config::normalize::files::expand_home(&raw).map_err(|e| {
+            ConfigError::ParseError(format!("line {line}: cannot expand log path '{raw}': {e}"))
+        })?;
+        let expanded = PathBuf::from(expanded);
+        if !expanded.is_absolute() {
+            return Err(ConfigError::ParseError(format!(
+                "line {line}: log path '{raw}' must be absolute or start with ~"
+            )));
+        }
+        if path_layer.is_some_and(|layer| layer < node.layer()) {
+            continue;
+        }
+        path = Some(expanded);
+        path_layer = Some(node.layer());
+    }
+
+    match path {
+        Some(path) => Ok(Some(LogConfig { path })),
+        None => Err(ConfigError::ParseError(
+            "log section requires a path".to_string(),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn log(source: &str) -> Result<Option<LogConfig>, ConfigError> {
+        parse_log(&ConfigDocument::parse(source).expect("kdl should parse"))
+    }
+
+    fn log_err(source: &str) -> String {
+        log(source).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn absent_section() {
+        assert!(log(r#"bash { allow "git" }"#).unwrap().is_none());
+    }
+
+    #[test]
+    fn absolute_path() {
+        let config = log(r#"log { path "/var/log/hook.jsonl"; }"#)
+            .unwrap()
+            .unwrap();
+        assert_eq!(config.path, PathBuf::from("/var/log/hook.jsonl"));
+    }
+
+    #[test]
+    fn error_relative_path() {
+        let err = log_err(r#"log { path "hook.jsonl"; }"#);
+        assert!(err.contains("must be absolute"), "got: {err}");
+    }
+
+    #[test]
+    fn error_missing_path() {
+        let err = log_err("log {\n}");
+        assert!(err.contains("log section requires a path"), "got: {err}");
+    }
+
+    #[test]
+    fn error_unknown_node() {
+        let err = log_err("log {\n    level \"debug\"\n}");
+        assert!(err.contains("line 2"), "got: {err}");
+        assert!(err.contains("unexpected node \"level\""), "got: {err}");
+    }
+}
@@ -0,0 +1,138 @@
+use globset::{GlobBuilder, GlobMatcher};
+
+use crate::config::mcp::McpRule;
+use crate::config::parse::argument::parse_arguments;
+use crate::config::section::{RuleEntry, SettingEntry};
+use crate::config::ConfigError;
+
+/// Parse a tier's rule entries into McpRules.
+///
+/// Each value is a glob over the full tool name and must start with
+/// `mcp__`. A children block adds `argument` conditions to its rule.
+pub(crate) fn parse_rules(entries: Vec<RuleEntry>) -> Result<Vec<McpRule>, ConfigError> {
+    let mut rules = Vec::new();
+    for entry in entries {
+        let line = entry.line;
+        let arguments = match &entry.children {
+            Some(children) => parse_arguments(children, "mcp")?,
+            None => Vec::new(),
+        };
+        // `collect_entries` guarantees a single value when children are present.
+        let mut arguments = Some(arguments);
+        for pattern in &entry.values {
+            if !pattern.starts_with("mcp__") {
+                return Err(ConfigError::ParseError(format!(
+                    "line {line}: MCP pattern '{pattern}' must start with 'mcp__' \
+                     (e.g. \"mcp__github__*\")"
+                )));
+            }
+            let matcher = compile(pattern).map_err(|e| {
+                ConfigError::ParseError(format!(
+                    "line {line}: invalid MCP pattern '{pattern}': {e}"
+                ))
+            })?;
+            rules.push(McpRule {
+                raw_pattern: pattern.clone(),
+                matcher,
+                arguments: arguments.take().unwrap_or_default(),
+                line,
+            });
+        }
+    }
+    Ok(rules)
+}
+
+/// The `mcp` section only holds allow/deny/ask rules.
+pub(crate) fn reject_settings(settings: &[SettingEntry]) -> Result<(), ConfigError> {
+    match settings.first() {
+        Some(setting) => Err(ConfigError::ParseError(format!(
+            "line {}: unexpected node \"{}\" in mcp section; expected allow, deny, or ask",
+            setting.line, setting.name
+        ))),
+        None => Ok(()),
+    }
+}
+
+fn compile(pattern: &str) -> Result<GlobMatcher, globset::Error> {
+    Ok(GlobBuilder::new(pattern).build()?.compile_matcher())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::argument::PathSegment;
+    use crate::config::section;
+
+    fn rules_from_kdl(source: &str) -> Result<Vec<McpRule>, ConfigError> {
+        let ts = section::parse_from_source(source)?;
+        reject_settings(&ts.settings)?;
+        parse_rules(ts.deny)
+    }
+
+    fn rules_err(source: &str) -> String {
+        rules_from_kdl(source).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn rule_multiple_patterns() {
+        let rules = rules_from_kdl(r#"deny "mcp__github__delete_*" "mcp__slack__*""#).unwrap();
+        assert_eq!(rules.len(), 2);
+        assert_eq!(rules[0].raw_pattern, "mcp__github__delete_*");
+        assert!(rules[0].matcher.is_match("mcp__github__delete_repo"));
+        assert!(rules[0].arguments.is_empty());
+    }
+
+    #[test]
+    fn rule_with_argument_conditions() {
+        let rules = rules_from_kdl(
+            r#"deny "mcp__github__*" {
+                argument "repo" "acme/*"
+                argument "options.force" "true"
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(rules[0].arguments.len(), 2);
+        assert_eq!(
+            rules[0].arguments[1].path,
+            vec![
+                PathSegment::Key("options".to_string()),
+                PathSegment::Key("force".to_string())
+            ]
+        );
+        assert!(rules[0].arguments[0].matches(&serde_json::json!({"repo": "acme/web"})));
+    }
+
+    #[test]
+    fn error_pattern_without_prefix() {
+        let err = rules_err(r#"deny "github__delete_*""#);
+        assert!(err.contains("must start with 'mcp__'"), "got: {err}");
+    }
+
+    #[test]
+    fn error_unknown_child() {
+        let err = rules_err("deny \"mcp__github__*\" {\n    flag \"x\"\n}");
+        assert!(err.contains("line 3"), "got: {err}");
+        assert!(err.contains("unexpected node \"flag\""), "got: {err}");
+    }
+
+    #[test]
+    fn error_argument_missing_value() {
+        let err = rules_err(r#"deny "mcp__github__*" { argument "repo" }"#);
+        assert!(err.contains("requires a glob or one of"), "got: {err}");
+    }
+
+    #[test]
+    fn error_argument_empty_path_segment() {
+        let err = rules_err(r#"deny "mcp__github__*" { argument "repo..name" "*" }"#);
+        assert!(err.contains("invalid argument path"), "got: {err}");
+    }
+
+    #[test]
+    fn error_unknown_section_node() {
+        let err = rules_err(r#"servers "github""#);
+        assert!(
+            err.contains("unexpected node \"servers\" in mcp section"),
+            "got: {err}"
+        );
+    }
+}
@@ -1,2 +1,9 @@
+pub(super) mod argument;
 pub(super) mod bash;
+pub(super) mod define;
 pub(super) mod files;
+pub(super) mod log;
+pub(super) mod mcp;
+pub(super) mod test_cases;
+pub(super) mod tools;
+pub(super) mod web;
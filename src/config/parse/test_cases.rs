@@ -0,0 +1,230 @@
+use serde_json::{json, Value};
+
+use crate::config::document::{ConfigDocument, EntryValue, ParseNode};
+use crate::config::test_cases::TestCase;
+use crate::config::ConfigError;
+use crate::protocol::{Decision, PermissionMode};
+
+/// Tool properties: the property name, the tool it calls, and the input
+/// field its value fills.
+const TOOL_PROPERTIES: &[(&str, &str, &str)] = &[
+    ("bash", "Bash", "command"),
+    ("read", "Read", "file_path"),
+    ("write", "Write", "file_path"),
+    ("edit", "Edit", "file_path"),
+    ("list", "LS", "path"),
+    ("fetch", "WebFetch", "url"),
+    ("search", "WebSearch", "query"),
+];
+
+/// Parse the `tests` section from a config document.
+///
+/// Returns no cases when the `tests` section is absent.
+///
+/// ```kdl
+/// tests {
+///     expect "deny" bash="git push --force origin main"
+///     expect "allow" read="<cwd>/src/lib.rs" cwd="/repo"
+///     expect "ask" tool="mcp__github__merge_pr" input="{\"pr\": 1}" mode="default"
+/// }
+/// ```
+pub(crate) fn parse_test_cases(doc: &ConfigDocument) -> Result<Vec<TestCase>, ConfigError> {
+    let Some(section) = doc.section("tests") else {
+        return Ok(Vec::new());
+    };
+    section
+        .nodes()
+        .iter()
+        .map(|node| {
+            if node.name() != "expect" {
+                return Err(ConfigError::ParseError(format!(
+                    "line {}: unexpected node \"{}\" in tests section; expected expect",
+                    node.line(),
+                    node.name()
+                )));
+            }
+            parse_expect(node)
+        })
+        .collect()
+}
+
+/// Parse one `expect "<decision>" <tool>=... [cwd=...] [mode=...]` node.
+fn parse_expect(node: &ParseNode<'_>) -> Result<TestCase, ConfigError> {
+    let line = node.line();
+    let err = |msg: String| ConfigError::ParseError(format!("line {line}: {msg}"));
+
+    let expected = match node.arguments().as_slice() {
+        [EntryValue::String("allow")] => Some(Decision::Allow),
+        [EntryValue::String("ask")] => Some(Decision::Ask),
+        [EntryValue::String("deny")] => Some(Decision::Deny),
+        [EntryValue::String("none")] => None,
+        _ => {
+            return Err(err(
+                "expect requires one decision: \"allow\", \"ask\", \"deny\", or \"none\""
+                    .to_string(),
+            ))
+        }
+    };
+
+    let mut call: Option<(String, Value, String)> = None;
+    let mut input = None;
+    let mut cwd = None;
+    let mut mode = PermissionMode::default();
+    for (name, value) in node.properties() {
+        let EntryValue::String(value) = value else {
+            return Err(err(format!("{name} must be a quoted string")));
+        };
+        let tool = TOOL_PROPERTIES
+            .iter()
+            .find(|(property, _, _)| *property == name)
+            .map(|(_, tool, field)| (tool.to_string(), json!({ *field: value })));
+        let tool = match (name, tool) {
+            (_, Some(tool)) => Some(tool),
+            ("tool", None) => Some((value.to_string(), json!({}))),
+            ("input", None) => {
+                input = Some(
+                    serde_json::from_str::<Value>(value)
+                        .map_err(|e| err(format!("input is not valid JSON: {e}")))?,
+                );
+                None
+            }
+            ("cwd", None) => {
+                cwd = Some(value.to_string());
+                None
+            }
+            ("mode", None) => {
+                mode = PermissionMode::from_name(value)
+                    .ok_or_else(|| err(format!("unknown permission mode \"{value}\"")))?;
+                None
+            }
+            (other, None) => {
+                return Err(err(format!(
+                    "unknown expect property \"{other}\"; expected bash, read, write, edit, \
+                     list, fetch, search, tool, input, cwd, or mode"
+                )))
+            }
+        };
+        if let Some((tool_name, tool_input)) = tool {
+            if call.is_some() {
+                return Err(err("expect names more than one tool call".to_string()));
+            }
+            call = Some((tool_name, tool_input, format!("{name}=\"{value}\"")));
+        }
+    }
+
+    let Some((tool_name, mut tool_input, label)) = call else {
+        return Err(err(
+            "expect requires a tool call: bash=, read=, write=, edit=, list=, fetch=, \
+             search=, or tool="
+                .to_string(),
+        ));
+    };
+    if let Some(input) = input {
+        if !label.starts_with("tool=") {
+            return Err(err("input can only be given with tool=".to_string()));
+        }
+        tool_input = input;
+    }
+    Ok(TestCase {
+        expected,
+        tool_name,
+        tool_input,
+        cwd,
+        mode,
+        label,
+        line,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cases(source: &str) -> Result<Vec<TestCase>, ConfigError> {
+        parse_test_cases(&ConfigDocument::parse(source).expect("kdl should parse"))
+    }
+
+    fn cases_err(source: &str) -> String {
+        cases(source).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn absent_section() {
+        assert!(cases(r#"bash { allow "git" }"#).unwrap().is_empty());
+    }
+
+    #[test]
+    fn bash_case() {
+        let cases = cases(r#"tests { expect "deny" bash="git push --force"; }"#).unwrap();
+        assert_eq!(cases.len(), 1);
+        assert_eq!(cases[0].expected, Some(Decision::Deny));
+        assert_eq!(cases[0].tool_name, "Bash");
+        assert_eq!(cases[0].tool_input, json!({"command": "git push --force"}));
+        assert_eq!(cases[0].label, r#"bash="git push --force""#);
+        assert_eq!(cases[0].mode, PermissionMode::Default);
+    }
+
+    #[test]
+    fn file_case_with_cwd_and_mode() {
+        let cases = cases(
+            r#"tests { expect "allow" read="<cwd>/src/lib.rs" cwd="/repo" mode="acceptEdits"; }"#,
+        )
+        .unwrap();
+        assert_eq!(cases[0].tool_name, "Read");
+        assert_eq!(
+            cases[0].tool_input,
+            json!({"file_path": "<cwd>/src/lib.rs"})
+        );
+        assert_eq!(cases[0].cwd.as_deref(), Some("/repo"));
+        assert_eq!(cases[0].mode, PermissionMode::AcceptEdits);
+    }
+
+    #[test]
+    fn generic_tool_with_input() {
+        let cases =
+            cases(r#"tests { expect "none" tool="Task" input="{\"subagent_type\": \"x\"}"; }"#)
+                .unwrap();
+        assert_eq!(cases[0].expected, None);
+        assert_eq!(cases[0].tool_name, "Task");
+        assert_eq!(cases[0].tool_input, json!({"subagent_type": "x"}));
+    }
+
+    #[test]
+    fn error_unknown_decision() {
+        let err = cases_err("tests {\n    expect \"block\" bash=\"ls\"\n}");
+        assert!(err.contains("line 2"), "got: {err}");
+        assert!(err.contains("expect requires one decision"), "got: {err}");
+    }
+
+    #[test]
+    fn error_missing_tool_call() {
+        let err = cases_err(r#"tests { expect "allow" cwd="/repo"; }"#);
+        assert!(err.contains("requires a tool call"), "got: {err}");
+    }
+
+    #[test]
+    fn error_two_tool_calls() {
+        let err = cases_err(r#"tests { expect "allow" bash="ls" read="/x"; }"#);
+        assert!(err.contains("more than one tool call"), "got: {err}");
+    }
+
+    #[test]
+    fn error_unknown_property_and_mode() {
+        let err = cases_err(r#"tests { expect "allow" bsh="ls"; }"#);
+        assert!(
+            err.contains("unknown expect property \"bsh\""),
+            "got: {err}"
+        );
+        let err = cases_err(r#"tests { expect "allow" bash="ls" mode="yolo"; }"#);
+        assert!(
+            err.contains("unknown permission mode \"yolo\""),
+            "got: {err}"
+        );
+    }
+
+    #[test]
+    fn error_unknown_node() {
+        let err = cases_err("tests {\n    assert \"allow\" bash=\"ls\"\n}");
+        assert!(err.contains("unexpected node \"assert\""), "got: {err}");
+    }
+}
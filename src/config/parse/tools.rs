@@ -0,0 +1,140 @@
+use globset::GlobBuilder;
+
+use crate::config::parse::argument::parse_arguments;
+use crate::config::section::{RuleEntry, SettingEntry};
+use crate::config::tools::ToolRule;
+use crate::config::ConfigError;
+
+/// Parse a tier's rule entries into ToolRules.
+///
+/// Each value is a glob over the tool name. A children block adds
+/// `argument` conditions to its rule, with the same syntax as the `mcp`
+/// section:
+///
+/// ```kdl
+/// tools {
+///     allow "ExitPlanMode" "TodoWrite"
+///     deny "Task" {
+///         argument "subagent_type" "*admin*"
+///     }
+/// }
+/// ```
+pub(crate) fn parse_rules(entries: Vec<RuleEntry>) -> Result<Vec<ToolRule>, ConfigError> {
+    let mut rules = Vec::new();
+    for entry in entries {
+        let line = entry.line;
+        if entry.values.is_empty() {
+            return Err(ConfigError::ParseError(format!(
+                "line {line}: rule requires one or more quoted tool name patterns"
+            )));
+        }
+        let arguments = match &entry.children {
+            Some(children) => parse_arguments(children, "tools")?,
+            None => Vec::new(),
+        };
+        // `collect_entries` guarantees a single value when children are present.
+        let mut arguments = Some(arguments);
+        for pattern in &entry.values {
+            let matcher = GlobBuilder::new(pattern)
+                .build()
+                .map_err(|e| {
+                    ConfigError::ParseError(format!(
+                        "line {line}: invalid tool name pattern '{pattern}': {e}"
+                    ))
+                })?
+                .compile_matcher();
+            rules.push(ToolRule {
+                raw_pattern: pattern.clone(),
+                matcher,
+                arguments: arguments.take().unwrap_or_default(),
+                line,
+            });
+        }
+    }
+    Ok(rules)
+}
+
+/// The `tools` section only holds allow/deny/ask rules.
+pub(crate) fn reject_settings(settings: &[SettingEntry]) -> Result<(), ConfigError> {
+    match settings.first() {
+        Some(setting) => Err(ConfigError::ParseError(format!(
+            "line {}: unexpected node \"{}\" in tools section; expected allow, deny, or ask",
+            setting.line, setting.name
+        ))),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::section;
+    use crate::config::tools::ToolsConfig;
+
+    fn tools(source: &str) -> ToolsConfig {
+        tools_from_source(source).expect("parse should succeed")
+    }
+
+    fn tools_from_source(source: &str) -> Result<ToolsConfig, ConfigError> {
+        let ts = section::parse_from_source(source)?;
+        reject_settings(&ts.settings)?;
+        Ok(ToolsConfig {
+            allow: parse_rules(ts.allow)?,
+            deny: parse_rules(ts.deny)?,
+            ask: parse_rules(ts.ask)?,
+        })
+    }
+
+    fn tools_err(source: &str) -> String {
+        tools_from_source(source).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn tiers_with_multiple_tools() {
+        let config = tools(
+            r#"
+            allow "ExitPlanMode" "TodoWrite"
+            ask "Task"
+            "#,
+        );
+        assert_eq!(config.allow.len(), 2);
+        assert_eq!(config.allow[1].raw_pattern, "TodoWrite");
+        assert_eq!(config.ask.len(), 1);
+        assert!(config.deny.is_empty());
+    }
+
+    #[test]
+    fn rule_with_argument_conditions() {
+        let config = tools(r#"deny "Task" { argument "max_turns" gte=5 lt=10; }"#);
+        assert_eq!(config.deny[0].arguments[0].predicates.len(), 2);
+    }
+
+    #[test]
+    fn error_unknown_node() {
+        let err = tools_err(r#"block "Task""#);
+        assert!(err.contains("unexpected node \"block\""), "got: {err}");
+    }
+
+    #[test]
+    fn error_tier_without_tool() {
+        let err = tools_err("deny");
+        assert!(err.contains("line 2"), "got: {err}");
+        assert!(err.contains("tool name pattern"), "got: {err}");
+    }
+
+    #[test]
+    fn error_children_with_multiple_tools() {
+        let err = tools_err(r#"deny "A" "B" { argument "x" equals=1; }"#);
+        assert!(err.contains("multiple entries"), "got: {err}");
+    }
+
+    #[test]
+    fn error_unknown_child() {
+        let err = tools_err("deny \"Task\" {\n    field \"x\" equals=1\n}");
+        assert!(err.contains("line 3"), "got: {err}");
+        assert!(
+            err.contains("unexpected node \"field\" in tools rule; expected argument"),
+            "got: {err}"
+        );
+    }
+}
@@ -0,0 +1,287 @@
+use globset::{GlobBuilder, GlobMatcher};
+
+use crate::config::document::{ConfigDocument, ParseNode};
+use crate::config::web::{QueryRule, SearchRules, UrlRule, WebConfig};
+use crate::config::ConfigError;
+
+/// Parse the `web` section from a config document.
+///
+/// Returns `None` when the `web` section is absent.
+///
+/// ```kdl
+/// web {
+///     allow "docs.rs" "*.github.com"
+///     deny "*.pastebin.com"
+///     search {
+///         allow "*"
+///     }
+/// }
+/// ```
+pub(crate) fn parse_web(doc: &ConfigDocument) -> Result<Option<WebConfig>, ConfigError> {
+    let section = match doc.section("web") {
+        Some(s) => s,
+        None => return Ok(None),
+    };
+
+    let mut config = WebConfig::default();
+
+    for node in section.nodes() {
+        let line = node.line();
+        match node.name() {
+            tier @ ("allow" | "deny" | "ask") => {
+                let mut rules = Vec::new();
+                for pattern in tier_values(&node, "web")? {
+                    rules.push(parse_url_pattern(pattern, line)?);
+                }
+                match tier {
+                    "allow" => config.allow.extend(rules),
+                    "deny" => config.deny.extend(rules),
+                    _ => config.ask.extend(rules),
+                }
+            }
+            "search" => parse_search_block(&node, &mut config.search)?,
+            other => {
+                return Err(ConfigError::ParseError(format!(
+                    "line {line}: unexpected node \"{other}\" in web section; \
+                     expected allow, deny, ask, or search"
+                )))
+            }
+        }
+    }
+
+    Ok(Some(config))
+}
+
+/// Parse a `search { allow "..." }` block of query globs.
+fn parse_search_block(node: &ParseNode<'_>, search: &mut SearchRules) -> Result<(), ConfigError> {
+    let line = node.line();
+    let Some(children) = node.children().filter(|_| node.entry_count() == 0) else {
+        return Err(ConfigError::ParseError(format!(
+            "line {line}: search requires a children block of allow/deny/ask query patterns"
+        )));
+    };
+    for child in children.nodes() {
+        let child_line = child.line();
+        let tier = child.name();
+        if !matches!(tier, "allow" | "deny" | "ask") {
+            return Err(ConfigError::ParseError(format!(
+                "line {child_line}: unexpected node \"{tier}\" in search block; \
+                 expected allow, deny, or ask"
+            )));
+        }
+        let mut rules = Vec::new();
+        for pattern in tier_values(&child, "search")? {
+            let matcher = compile(pattern, false).map_err(|e| {
+                ConfigError::ParseError(format!(
+                    "line {child_line}: invalid query pattern '{pattern}': {e}"
+                ))
+            })?;
+            rules.push(QueryRule {
+                raw_pattern: pattern.to_string(),
+                matcher,
+                line: child_line,
+            });
+        }
+        match tier {
+            "allow" => search.allow.extend(rules),
+            "deny" => search.deny.extend(rules),
+            _ => search.ask.extend(rules),
+        }
+    }
+    Ok(())
+}
+
+/// Validate a tier node's entries: one or more strings, no children block.
+fn tier_values<'a>(node: &ParseNode<'a>, context: &str) -> Result<Vec<&'a str>, ConfigError> {
+    let tier = node.name();
+    let line = node.line();
+    let values = node.string_values();
+    if node.entry_count() != values.len() {
+        return Err(ConfigError::ParseError(format!(
+            "line {line}: {tier} node in {context} contains non-string values; \
+             all entries must be quoted strings"
+        )));
+    }
+    if values.is_empty() {
+        return Err(ConfigError::ParseError(format!(
+            "line {line}: {tier} node in {context} requires at least one pattern"
+        )));
+    }
+    if node.has_children() {
+        return Err(ConfigError::ParseError(format!(
+            "line {line}: {tier} node in {context} does not take a children block"
+        )));
+    }
+    Ok(values)
+}
+
+/// Parse a URL pattern: `[scheme://]host-glob[/path-glob]`.
+fn parse_url_pattern(raw: &str, line: usize) -> Result<UrlRule, ConfigError> {
+    let err = |msg: String| ConfigError::ParseError(format!("line {line}: {msg}"));
+
+    let (scheme, rest) = match raw.split_once("://") {
+        Some((scheme, rest)) => (Some(scheme.to_ascii_lowercase()), rest),
+        None => (None, raw),
+    };
+    if scheme.as_deref() == Some("") {
+        return Err(err(format!("URL pattern '{raw}' has an empty scheme")));
+    }
+    let (host, path) = match rest.find('/') {
+        Some(i) => (&rest[..i], Some(&rest[i..])),
+        None => (rest, None),
+    };
+    if host.is_empty() {
+        return Err(err(format!("URL pattern '{raw}' has no host")));
+    }
+    if host.contains(':') || host.contains('@') {
+        return Err(err(format!(
+            "URL pattern '{raw}' must not contain a port or user info"
+        )));
+    }
+
+    let host_err = |e: globset::Error| err(format!("invalid host pattern in '{raw}': {e}"));
+    let apex = host
+        .strip_prefix("*.")
+        .map(|apex| compile(apex, false))
+        .transpose()
+        .map_err(host_err)?;
+    let host = compile(host, false).map_err(host_err)?;
+    let path = path
+        .map(|p| compile(p, true))
+        .transpose()
+        .map_err(|e| err(format!("invalid path pattern in '{raw}': {e}")))?;
+
+    Ok(UrlRule {
+        raw_pattern: raw.to_string(),
+        scheme,
+        host,
+        apex,
+        path,
+        line,
+    })
+}
+
+/// Compile a glob. Hosts and queries are case-insensitive and `*` crosses
+/// dots and spaces; paths are case-sensitive and `*` stops at `/`.
+fn compile(pattern: &str, is_path: bool) -> Result<GlobMatcher, globset::Error> {
+    Ok(GlobBuilder::new(pattern)
+        .case_insensitive(!is_path)
+        .literal_separator(is_path)
+        .build()?
+        .compile_matcher())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_web_from_source(source: &str) -> Result<Option<WebConfig>, ConfigError> {
+        let wrapped = format!("web {{\n{source}\n}}");
+        let doc = ConfigDocument::parse(&wrapped)?;
+        parse_web(&doc)
+    }
+
+    fn web(source: &str) -> WebConfig {
+        parse_web_from_source(source)
+            .expect("parse should succeed")
+            .expect("web section should be present")
+    }
+
+    fn web_err(source: &str) -> String {
+        parse_web_from_source(source).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn absent_section_returns_none() {
+        let doc = ConfigDocument::parse(r#"bash { allow "git" }"#).unwrap();
+        assert!(parse_web(&doc).unwrap().is_none());
+    }
+
+    #[test]
+    fn tiers_with_multiple_patterns() {
+        let config = web(r#"
+            allow "docs.rs" "*.github.com"
+            deny "*.pastebin.com"
+            ask "example.com"
+            "#);
+        assert_eq!(config.allow.len(), 2);
+        assert_eq!(config.allow[1].raw_pattern, "*.github.com");
+        assert_eq!(config.deny.len(), 1);
+        assert_eq!(config.ask.len(), 1);
+    }
+
+    #[test]
+    fn scheme_and_path_split() {
+        let config = web(r#"allow "HTTPS://github.com/rust-lang/**""#);
+        let rule = &config.allow[0];
+        assert_eq!(rule.scheme.as_deref(), Some("https"));
+        assert!(rule.host.is_match("github.com"));
+        assert!(rule.path.as_ref().unwrap().is_match("/rust-lang/rust"));
+    }
+
+    #[test]
+    fn search_block_parsed() {
+        let config = web(r#"
+            search {
+                allow "*"
+                deny "*password*"
+            }
+            "#);
+        assert_eq!(config.search.allow.len(), 1);
+        assert_eq!(config.search.deny.len(), 1);
+        assert!(config.allow.is_empty());
+    }
+
+    #[test]
+    fn error_empty_tier() {
+        let err = web_err("deny");
+        assert!(err.contains("line 2"), "got: {err}");
+        assert!(err.contains("requires at least one pattern"), "got: {err}");
+    }
+
+    #[test]
+    fn error_non_string_value() {
+        let err = web_err("allow 443");
+        assert!(err.contains("non-string"), "got: {err}");
+    }
+
+    #[test]
+    fn error_port_in_pattern() {
+        let err = web_err(r#"allow "localhost:8080""#);
+        assert!(err.contains("port"), "got: {err}");
+    }
+
+    #[test]
+    fn error_missing_host() {
+        let err = web_err(r#"allow "https:///path""#);
+        assert!(err.contains("no host"), "got: {err}");
+    }
+
+    #[test]
+    fn error_invalid_glob() {
+        let err = web_err(r#"allow "[docs.rs""#);
+        assert!(err.contains("invalid host pattern"), "got: {err}");
+    }
+
+    #[test]
+    fn error_unknown_node() {
+        let err = web_err(r#"fetch "docs.rs""#);
+        assert!(err.contains("unexpected node \"fetch\""), "got: {err}");
+    }
+
+    #[test]
+    fn error_search_without_children() {
+        let err = web_err(r#"search "*""#);
+        assert!(
+            err.contains("search requires a children block"),
+            "got: {err}"
+        );
+    }
+
+    #[test]
+    fn error_unknown_node_in_search() {
+        let err = web_err("search {\n    block \"*\"\n}");
+        assert!(err.contains("line 3"), "got: {err}");
+        assert!(err.contains("unexpected node \"block\""), "got: {err}");
+    }
+}
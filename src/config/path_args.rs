@@ -0,0 +1,566 @@
+//! Program argument semantics: which arguments of a program name files.
+//!
+//! Lets bash evaluation check `cat ~/.ssh/id_rsa` or `cp x ~/.bashrc` against
+//! `files` rules. Built-in entries cover common file utilities; `path-args`
+//! nodes in the `bash` section add programs, replace built-in entries in the
+//! first config layer, and add to existing entries in later layers.
+//! Parsing is in [`crate::config::parse::bash`].
+
+use std::collections::HashMap;
+
+use crate::domain::Flag;
+use crate::protocol::FileOperation;
+
+/// What a program does with the file named by one of its arguments.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PathRole {
+    /// The argument is not a path (`chmod 755`, the `sed` script).
+    Ignore,
+    /// The argument is a path accessed with this operation.
+    Access(FileOperation),
+}
+
+/// How one program's arguments map onto file paths.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PathArgs {
+    /// Operation on positionals not covered by `first` or `last`.
+    /// `None` means plain positionals are not paths.
+    pub positionals: Option<FileOperation>,
+    /// Role of the first positional, unless a `script_options` flag is given.
+    pub first: Option<PathRole>,
+    /// Role of the last positional when there are at least two (`cp SRC DEST`).
+    pub last: Option<PathRole>,
+    /// Options whose value is a path (`sort -o FILE`, `--output=FILE`).
+    pub option_paths: Vec<(Flag, FileOperation)>,
+    /// Operands of the form `name=PATH` (`dd if=... of=...`), keyed by `name=`.
+    pub operand_paths: Vec<(String, FileOperation)>,
+    /// Options that consume a following value which is not a path.
+    pub options_with_values: Vec<Flag>,
+    /// Options that supply the script or pattern (`sed -e`, `grep -e`), so
+    /// the first positional is an ordinary path.
+    pub script_options: Vec<Flag>,
+    /// Flags that turn read positionals into writes (`sed -i`).
+    pub in_place_flags: Vec<Flag>,
+}
+
+/// Argument semantics for every known program, keyed by program basename.
+///
+/// A program can have several entries: one from the built-in table or the
+/// first config layer, and one more from each later layer that adds to it.
+/// Every entry's paths are checked, so a later layer can only add paths.
+#[derive(Debug)]
+pub struct PathArgsTable {
+    programs: HashMap<String, Vec<PathArgs>>,
+}
+
+impl Default for PathArgsTable {
+    /// The built-in table.
+    fn default() -> Self {
+        PathArgsTable {
+            programs: builtin_programs()
+                .into_iter()
+                .map(|(program, args)| (program, vec![args]))
+                .collect(),
+        }
+    }
+}
+
+impl PathArgsTable {
+    /// Look up the argument semantics for a program basename.
+    pub(crate) fn get(&self, program: &str) -> Option<&[PathArgs]> {
+        self.programs.get(program).map(Vec::as_slice)
+    }
+
+    /// Extract the paths named in a segment's args under every entry for
+    /// the program. See [`PathArgs::accesses`].
+    pub(crate) fn accesses(&self, program: &str, args: &[String]) -> Vec<(String, FileOperation)> {
+        let mut accesses = Vec::new();
+        for entry in self.get(program).unwrap_or_default() {
+            for access in entry.accesses(args) {
+                if !accesses.contains(&access) {
+                    accesses.push(access);
+                }
+            }
+        }
+        accesses
+    }
+
+    /// Add a program, replacing any built-in entry for it.
+    pub(crate) fn insert(&mut self, program: String, args: PathArgs) {
+        self.programs.insert(program, vec![args]);
+    }
+
+    /// Add an entry for a program alongside the ones it already has.
+    pub(crate) fn extend(&mut self, program: String, args: PathArgs) {
+        self.programs.entry(program).or_default().push(args);
+    }
+}
+
+impl PathArgs {
+    /// Extract the paths named in a segment's (flag-expanded) args, paired
+    /// with the operation the program performs on each.
+    ///
+    /// Bare `-` (stdin/stdout) is never a path.
+    pub(crate) fn accesses(&self, args: &[String]) -> Vec<(String, FileOperation)> {
+        let mut accesses = Vec::new();
+        let mut positionals: Vec<&String> = Vec::new();
+        let mut script_given = false;
+        let mut in_place = false;
+        let mut end_of_options = false;
+
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            if end_of_options || arg == "-" || !arg.starts_with('-') {
+                if !end_of_options {
+                    if let Some((path, op)) = self.operand_path(arg) {
+                        accesses.push((path.to_string(), op));
+                        continue;
+                    }
+                }
+                if arg != "-" {
+                    positionals.push(arg);
+                }
+                continue;
+            }
+            if arg == "--" {
+                end_of_options = true;
+                continue;
+            }
+            // Long option with an attached value: `--output=FILE`
+            if let Some((name, value)) = arg.split_once('=') {
+                script_given |= contains_flag(&self.script_options, name);
+                if let Some(op) = self.option_path(name) {
+                    accesses.push((value.to_string(), op));
+                }
+                continue;
+            }
+            in_place |= contains_flag(&self.in_place_flags, arg);
+            script_given |= contains_flag(&self.script_options, arg);
+            if let Some(op) = self.option_path(arg) {
+                if let Some(value) = args.next() {
+                    accesses.push((value.clone(), op));
+                }
+            } else if contains_flag(&self.options_with_values, arg) {
+                args.next();
+            }
+        }
+
+        let count = positionals.len();
+        for (i, path) in positionals.into_iter().enumerate() {
+            let role = match (i, self.first, self.last) {
+                (0, Some(first), _) if !script_given => Some(first),
+                (i, _, Some(last)) if count > 1 && i == count - 1 => Some(last),
+                _ => self.positionals.map(|op| match op {
+                    FileOperation::Read if in_place => PathRole::Access(FileOperation::Write),
+                    op => PathRole::Access(op),
+                }),
+            };
+            if let Some(PathRole::Access(op)) = role {
+                accesses.push((path.clone(), op));
+            }
+        }
+        accesses
+    }
+
+    fn option_path(&self, option: &str) -> Option<FileOperation> {
+        self.option_paths
+            .iter()
+            .find(|(flag, _)| flag == option)
+            .map(|(_, op)| *op)
+    }
+
+    fn operand_path<'a>(&self, arg: &'a str) -> Option<(&'a str, FileOperation)> {
+        self.operand_paths
+            .iter()
+            .find_map(|(prefix, op)| arg.strip_prefix(prefix.as_str()).map(|path| (path, *op)))
+    }
+}
+
+fn contains_flag(flags: &[Flag], arg: &str) -> bool {
+    flags.iter().any(|flag| flag == arg)
+}
+
+// ---- Built-in table ----
+
+use FileOperation::{Read, Write};
+
+fn flags(names: &[&str]) -> Vec<Flag> {
+    names.iter().map(|name| Flag::new(name)).collect()
+}
+
+fn option_paths(names: &[&str], op: FileOperation) -> Vec<(Flag, FileOperation)> {
+    names.iter().map(|name| (Flag::new(name), op)).collect()
+}
+
+/// Every positional is accessed with `op`.
+fn all(op: FileOperation) -> PathArgs {
+    PathArgs {
+        positionals: Some(op),
+        ..Default::default()
+    }
+}
+
+/// Sources are accessed with `op`, the last positional is written
+/// (`cp`, `mv`, `ln`, `install`), and `-t DIR` names the destination.
+fn copy_like(op: FileOperation, options_with_values: &[&str]) -> PathArgs {
+    PathArgs {
+        positionals: Some(op),
+        last: Some(PathRole::Access(Write)),
+        option_paths: option_paths(&["-t", "--target-directory"], Write),
+        options_with_values: flags(options_with_values),
+        ..Default::default()
+    }
+}
+
+/// The first positional is a mode or owner, the rest are written
+/// (`chmod`, `chown`, `chgrp`).
+fn attribute_like() -> PathArgs {
+    PathArgs {
+        positionals: Some(Write),
+        first: Some(PathRole::Ignore),
+        option_paths: option_paths(&["--reference"], Read),
+        ..Default::default()
+    }
+}
+
+/// The first positional is a script or pattern unless one is given by
+/// option; the rest are read (`grep`, `sed`, `awk`).
+fn script_like(script_options: &[&str], script_files: &[&str], values: &[&str]) -> PathArgs {
+    PathArgs {
+        positionals: Some(Read),
+        first: Some(PathRole::Ignore),
+        option_paths: option_paths(script_files, Read),
+        script_options: flags(script_options),
+        options_with_values: flags(values),
+        ..Default::default()
+    }
+}
+
+fn builtin_programs() -> HashMap<String, PathArgs> {
+    let mut table = HashMap::new();
+    let mut add = |programs: &[&str], args: PathArgs| {
+        for program in programs {
+            table.insert(program.to_string(), args.clone());
+        }
+    };
+
+    add(
+        &[
+            "cat",
+            "less",
+            "more",
+            "nl",
+            "wc",
+            "strings",
+            "xxd",
+            "hexdump",
+            "md5sum",
+            "sha1sum",
+            "sha256sum",
+            "sha512sum",
+            "b2sum",
+            "cksum",
+            "base64",
+            "diff",
+            "cmp",
+            "file",
+            "stat",
+        ],
+        all(Read),
+    );
+    add(
+        &["head", "tail"],
+        PathArgs {
+            options_with_values: flags(&["-n", "-c", "--lines", "--bytes"]),
+            ..all(Read)
+        },
+    );
+    add(
+        &["sort"],
+        PathArgs {
+            option_paths: option_paths(&["-o", "--output"], Write),
+            options_with_values: flags(&[
+                "-k",
+                "-t",
+                "-T",
+                "-S",
+                "--key",
+                "--field-separator",
+                "--temporary-directory",
+                "--buffer-size",
+            ]),
+            ..all(Read)
+        },
+    );
+    add(
+        &["grep", "egrep", "fgrep"],
+        script_like(
+            &["-e", "-f", "--regexp", "--file"],
+            &["-f", "--file"],
+            &[
+                "-e",
+                "--regexp",
+                "-m",
+                "--max-count",
+                "-A",
+                "--after-context",
+                "-B",
+                "--before-context",
+                "-C",
+                "--context",
+            ],
+        ),
+    );
+    add(
+        &["rg"],
+        script_like(
+            &["-e", "-f", "--regexp", "--file"],
+            &["-f", "--file"],
+            &[
+                "-e",
+                "--regexp",
+                "-g",
+                "--glob",
+                "-t",
+                "--type",
+                "-T",
+                "--type-not",
+                "-m",
+                "--max-count",
+                "-A",
+                "-B",
+                "-C",
+                "-j",
+                "--threads",
+            ],
+        ),
+    );
+    add(
+        &["sed"],
+        PathArgs {
+            in_place_flags: flags(&["-i", "--in-place"]),
+            ..script_like(
+                &["-e", "-f", "--expression", "--file"],
+                &["-f", "--file"],
+                &["-e", "--expression", "-l", "--line-length"],
+            )
+        },
+    );
+    add(
+        &["awk", "gawk"],
+        script_like(
+            &["-f", "--file"],
+            &["-f", "--file"],
+            &["-v", "--assign", "-F"],
+        ),
+    );
+    add(&["cp"], copy_like(Read, &["-S", "--suffix"]));
+    add(&["mv"], copy_like(Write, &["-S", "--suffix"]));
+    add(&["ln"], copy_like(Read, &["-S", "--suffix"]));
+    add(
+        &["install"],
+        copy_like(
+            Read,
+            &[
+                "-m", "-o", "-g", "-S", "--mode", "--owner", "--group", "--suffix",
+            ],
+        ),
+    );
+    add(&["tee", "rm", "rmdir", "unlink"], all(Write));
+    add(
+        &["shred"],
+        PathArgs {
+            options_with_values: flags(&["-n", "-s", "--iterations", "--size"]),
+            ..all(Write)
+        },
+    );
+    add(
+        &["touch"],
+        PathArgs {
+            option_paths: option_paths(&["-r", "--reference"], Read),
+            options_with_values: flags(&["-d", "-t", "--date"]),
+            ..all(Write)
+        },
+    );
+    add(
+        &["mkdir"],
+        PathArgs {
+            options_with_values: flags(&["-m", "--mode"]),
+            ..all(Write)
+        },
+    );
+    add(
+        &["truncate"],
+        PathArgs {
+            option_paths: option_paths(&["-r", "--reference"], Read),
+            options_with_values: flags(&["-s", "--size"]),
+            ..all(Write)
+        },
+    );
+    add(&["chmod", "chown", "chgrp"], attribute_like());
+    add(
+        &["dd"],
+        PathArgs {
+            operand_paths: vec![("if=".to_string(), Read), ("of=".to_string(), Write)],
+            ..Default::default()
+        },
+    );
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn accesses(program: &str, args: &[&str]) -> Vec<(String, FileOperation)> {
+        let table = PathArgsTable::default();
+        let args: Vec<String> = args.iter().map(|a| a.to_string()).collect();
+        table.accesses(program, &args)
+    }
+
+    fn paths(pairs: &[(&str, FileOperation)]) -> Vec<(String, FileOperation)> {
+        pairs.iter().map(|(p, op)| (p.to_string(), *op)).collect()
+    }
+
+    #[test]
+    fn cat_reads_every_positional() {
+        assert_eq!(
+            accesses("cat", &["-n", "a.txt", "b.txt"]),
+            paths(&[("a.txt", Read), ("b.txt", Read)])
+        );
+    }
+
+    #[test]
+    fn head_skips_option_value() {
+        assert_eq!(
+            accesses("head", &["-n", "5", "log.txt"]),
+            paths(&[("log.txt", Read)])
+        );
+    }
+
+    #[test]
+    fn stdin_dash_is_not_a_path() {
+        assert!(accesses("cat", &["-"]).is_empty());
+    }
+
+    #[test]
+    fn cp_reads_sources_and_writes_destination() {
+        assert_eq!(
+            accesses("cp", &["-r", "a", "b", "dest"]),
+            paths(&[("a", Read), ("b", Read), ("dest", Write)])
+        );
+    }
+
+    #[test]
+    fn cp_target_directory_option_written() {
+        assert_eq!(
+            accesses("cp", &["-t", "dest", "a"]),
+            paths(&[("dest", Write), ("a", Read)])
+        );
+    }
+
+    #[test]
+    fn mv_writes_sources_and_destination() {
+        assert_eq!(
+            accesses("mv", &["a", "b"]),
+            paths(&[("a", Write), ("b", Write)])
+        );
+    }
+
+    #[test]
+    fn chmod_mode_is_not_a_path() {
+        assert_eq!(
+            accesses("chmod", &["-R", "600", "secrets"]),
+            paths(&[("secrets", Write)])
+        );
+    }
+
+    #[test]
+    fn sed_script_is_not_a_path() {
+        assert_eq!(
+            accesses("sed", &["s/a/b/", "file"]),
+            paths(&[("file", Read)])
+        );
+    }
+
+    #[test]
+    fn sed_in_place_writes_files() {
+        assert_eq!(
+            accesses("sed", &["-i", "s/a/b/", "/etc/hosts"]),
+            paths(&[("/etc/hosts", Write)])
+        );
+    }
+
+    #[test]
+    fn sed_expression_option_makes_first_positional_a_path() {
+        assert_eq!(
+            accesses("sed", &["-e", "s/a/b/", "file"]),
+            paths(&[("file", Read)])
+        );
+    }
+
+    #[test]
+    fn grep_pattern_is_not_a_path() {
+        assert_eq!(
+            accesses("grep", &["-r", "secret", "/home/user/.aws"]),
+            paths(&[("/home/user/.aws", Read)])
+        );
+    }
+
+    #[test]
+    fn sort_output_option_written() {
+        assert_eq!(
+            accesses("sort", &["-o", "out.txt", "in.txt"]),
+            paths(&[("out.txt", Write), ("in.txt", Read)])
+        );
+        assert_eq!(
+            accesses("sort", &["--output=out.txt", "in.txt"]),
+            paths(&[("out.txt", Write), ("in.txt", Read)])
+        );
+    }
+
+    #[test]
+    fn dd_operands() {
+        assert_eq!(
+            accesses("dd", &["if=/dev/sda", "of=disk.img", "bs=1M"]),
+            paths(&[("/dev/sda", Read), ("disk.img", Write)])
+        );
+    }
+
+    #[test]
+    fn positionals_after_double_dash() {
+        assert_eq!(accesses("rm", &["--", "-rf"]), paths(&[("-rf", Write)]));
+    }
+
+    #[test]
+    fn unknown_program_has_no_entry() {
+        assert!(PathArgsTable::default().get("git").is_none());
+    }
+
+    #[test]
+    fn insert_replaces_builtin() {
+        let mut table = PathArgsTable::default();
+        table.insert("cat".to_string(), all(Write));
+        assert_eq!(
+            table.accesses("cat", &["x".to_string()]),
+            paths(&[("x", Write)])
+        );
+    }
+
+    #[test]
+    fn extend_keeps_builtin_paths() {
+        let mut table = PathArgsTable::default();
+        table.extend(
+            "cat".to_string(),
+            PathArgs {
+                positionals: Some(Write),
+                first: Some(PathRole::Ignore),
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            table.accesses("cat", &["x".to_string(), "y".to_string()]),
+            paths(&[("x", Read), ("y", Read), ("y", Write)])
+        );
+    }
+}
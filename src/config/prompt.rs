@@ -0,0 +1,125 @@
+//! User prompt configuration.
+//!
+//! Deny-only glob rules over the text of a submitted prompt, used for the
+//! `UserPromptSubmit` hook event. Small enough that parsing and matching
+//! live here.
+
+use globset::{GlobBuilder, GlobMatcher};
+
+use super::section::{RuleEntry, ToolConfig, ToolSection};
+use super::ConfigError;
+
+/// Prompt configuration: patterns that block a prompt before it is processed.
+#[derive(Debug, Default)]
+pub struct PromptConfig {
+    pub deny: Vec<PromptRule>,
+}
+
+/// A glob over the full prompt text, created from `deny "*prod*"`.
+#[derive(Debug)]
+pub struct PromptRule {
+    /// Pattern as written in the config.
+    pub raw_pattern: String,
+    pub matcher: GlobMatcher,
+    /// 1-based line number in the source file.
+    pub line: usize,
+}
+
+impl ToolConfig for PromptConfig {
+    const SECTION: &'static str = "prompt";
+
+    fn from_section(section: ToolSection) -> Result<Self, ConfigError> {
+        let unsupported = [("allow", &section.allow), ("ask", &section.ask)]
+            .into_iter()
+            .find_map(|(tier, entries)| entries.first().map(|entry| (tier, entry.line)));
+        if let Some((tier, line)) = unsupported {
+            return Err(ConfigError::ParseError(format!(
+                "line {line}: prompt section only supports deny; \
+                 a prompt cannot be {tier}ed ahead of time"
+            )));
+        }
+        if let Some(setting) = section.settings.first() {
+            return Err(ConfigError::ParseError(format!(
+                "line {}: unexpected node \"{}\" in prompt section; expected deny",
+                setting.line, setting.name
+            )));
+        }
+        Ok(PromptConfig {
+            deny: parse_rules(section.deny)?,
+        })
+    }
+}
+
+impl PromptConfig {
+    /// Find the first deny rule matching the prompt text.
+    pub fn lookup(&self, prompt: &str) -> Option<&PromptRule> {
+        self.deny.iter().find(|rule| rule.matcher.is_match(prompt))
+    }
+}
+
+fn parse_rules(entries: Vec<RuleEntry>) -> Result<Vec<PromptRule>, ConfigError> {
+    let mut rules = Vec::new();
+    for entry in entries {
+        let line = entry.line;
+        if entry.children.is_some() {
+            return Err(ConfigError::ParseError(format!(
+                "line {line}: prompt rules do not take a children block"
+            )));
+        }
+        for pattern in entry.values {
+            let matcher = GlobBuilder::new(&pattern)
+                .case_insensitive(true)
+                .build()
+                .map_err(|e| {
+                    ConfigError::ParseError(format!(
+                        "line {line}: invalid prompt pattern '{pattern}': {e}"
+                    ))
+                })?
+                .compile_matcher();
+            rules.push(PromptRule {
+                raw_pattern: pattern,
+                matcher,
+                line,
+            });
+        }
+    }
+    Ok(rules)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::Config;
+
+    fn parse_err(source: &str) -> String {
+        Config::parse(source).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn deny_pattern_matches_case_insensitively_across_lines() {
+        let config = Config::parse(r#"prompt { deny "*drop table*" }"#).unwrap();
+        let prompt = config.prompt.expect("prompt config should be present");
+        let rule = prompt
+            .lookup("first line\nthen DROP TABLE users")
+            .expect("pattern should match");
+        assert_eq!(rule.raw_pattern, "*drop table*");
+        assert!(prompt.lookup("select * from users").is_none());
+    }
+
+    #[test]
+    fn error_allow_tier() {
+        let err = parse_err(r#"prompt { allow "*" }"#);
+        assert!(err.contains("only supports deny"), "got: {err}");
+    }
+
+    #[test]
+    fn error_children_block() {
+        let err = parse_err(r#"prompt { deny "*x*" { field "y" } }"#);
+        assert!(err.contains("do not take a children block"), "got: {err}");
+    }
+
+    #[test]
+    fn error_unknown_node() {
+        let err = parse_err(r#"prompt { max-length 10 }"#);
+        assert!(err.contains("unexpected node \"max-length\""), "got: {err}");
+    }
+}
@@ -0,0 +1,253 @@
+//! Bash command rewrite rules.
+//!
+//! A rewrite pairs a rule string (the same syntax as allow/deny entries) with
+//! flag edits applied to commands it matches. Parsing is in
+//! [`crate::config::parse::bash`].
+
+use super::rule::BashRule;
+use crate::command::{split_short_cluster, CommandSegment};
+use crate::domain::Flag;
+
+/// A rewrite rule: `rewrite "git push --force" { replace-flag "--force" "--force-with-lease" }`.
+#[derive(Debug)]
+pub(crate) struct RewriteRule {
+    /// Rule string as written in the config.
+    pub(crate) raw: String,
+    /// Which commands the rewrite applies to.
+    pub(crate) rule: BashRule,
+    /// Edits applied in order.
+    pub(crate) actions: Vec<RewriteAction>,
+    /// 1-based line number in the source file.
+    pub(crate) line: usize,
+}
+
+/// One edit to a command's arguments.
+///
+/// Only arguments before `--` are treated as flags. A flag stands for all
+/// its names in the program's flag schema: removing `-f` also removes
+/// `--force`, and a bundle holding it is split first (`-rf` → `-r`).
+#[derive(Debug, PartialEq)]
+pub(crate) enum RewriteAction {
+    /// Insert flags that are not already present, right after the program
+    /// and the rule's subcommand (`terraform apply` → `terraform apply --dry-run`).
+    AddFlags(Vec<Flag>),
+    /// Drop every occurrence of the flags.
+    RemoveFlags(Vec<Flag>),
+    /// Swap one flag for another, keeping an `=value` suffix.
+    ReplaceFlag { from: Flag, to: Flag },
+}
+
+impl RewriteRule {
+    /// Whether the rewrite applies to a command segment.
+    pub(crate) fn matches(&self, segment: &CommandSegment) -> bool {
+        self.rule.matches(segment)
+    }
+
+    /// Apply every action to a segment's arguments.
+    ///
+    /// Returns the arguments as given when no action changes anything, so a
+    /// bundle split only to look inside it is not reported as an edit.
+    pub(crate) fn apply(&self, original: &[String]) -> Vec<String> {
+        let schema = &self.rule.conditions.schema;
+        let is = |flag: &Flag, arg: &str| schema.same(flag.as_str(), arg);
+        let expanded = self.split_bundles(original);
+        let mut args = expanded.clone();
+        for action in &self.actions {
+            match action {
+                RewriteAction::AddFlags(flags) => {
+                    let at = self.insertion_point(&args);
+                    let missing: Vec<String> = flags
+                        .iter()
+                        .filter(|flag| !options(&args).any(|arg| is(flag, arg)))
+                        .map(|flag| flag.as_str().to_string())
+                        .collect();
+                    args.splice(at..at, missing);
+                }
+                RewriteAction::RemoveFlags(flags) => {
+                    let end = options_end(&args);
+                    let mut index = 0;
+                    args.retain(|arg| {
+                        index += 1;
+                        index > end || !flags.iter().any(|flag| is(flag, arg))
+                    });
+                }
+                RewriteAction::ReplaceFlag { from, to } => {
+                    let end = options_end(&args);
+                    for arg in &mut args[..end] {
+                        if is(from, arg) {
+                            *arg = to.as_str().to_string();
+                        } else if let Some((_, value)) =
+                            arg.split_once('=').filter(|(flag, _)| is(from, flag))
+                        {
+                            *arg = format!("{to}={value}");
+                        }
+                    }
+                }
+            }
+        }
+        if args == expanded {
+            return original.to_vec();
+        }
+        args
+    }
+
+    /// Split the short-flag bundles before `--` that hold a flag an action
+    /// names, so `-fu` can lose its `-f`.
+    ///
+    /// Other words stay as written: a single-dash long option such as
+    /// `-auto-approve` is not a bundle.
+    fn split_bundles(&self, args: &[String]) -> Vec<String> {
+        let schema = &self.rule.conditions.schema;
+        let consuming: Vec<&str> = schema
+            .value_flags
+            .iter()
+            .map(Flag::as_str)
+            .filter(|flag| !flag.starts_with("--"))
+            .collect();
+        let named: Vec<&Flag> = self
+            .actions
+            .iter()
+            .flat_map(|action| match action {
+                RewriteAction::AddFlags(flags) | RewriteAction::RemoveFlags(flags) => {
+                    flags.iter().collect::<Vec<_>>()
+                }
+                RewriteAction::ReplaceFlag { from, .. } => vec![from],
+            })
+            .collect();
+        let end = options_end(args);
+        let mut out = Vec::with_capacity(args.len());
+        for (i, arg) in args.iter().enumerate() {
+            if i < end && arg.starts_with('-') && !arg.starts_with("--") && arg.len() > 2 {
+                let (flags, _) = split_short_cluster(arg, &consuming);
+                let touched = flags
+                    .iter()
+                    .any(|part| named.iter().any(|flag| schema.same(flag.as_str(), part)));
+                if touched {
+                    out.extend(flags);
+                    continue;
+                }
+            }
+            out.push(arg.clone());
+        }
+        out
+    }
+
+    /// Index just past the rule's subcommand words.
+    fn insertion_point(&self, args: &[String]) -> usize {
+        let wanted = self.rule.conditions.subcommand.len();
+        if wanted == 0 {
+            return 0;
+        }
+        let mut seen = 0;
+        for (i, arg) in args[..options_end(args)].iter().enumerate() {
+            if !arg.starts_with('-') || arg == "-" {
+                seen += 1;
+                if seen == wanted {
+                    return i + 1;
+                }
+            }
+        }
+        0
+    }
+}
+
+/// Arguments before the first `--`.
+fn options(args: &[String]) -> impl Iterator<Item = &String> {
+    args[..options_end(args)].iter()
+}
+
+fn options_end(args: &[String]) -> usize {
+    args.iter()
+        .position(|arg| arg == "--")
+        .unwrap_or(args.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::Config;
+
+    fn rewrite(source: &str) -> Config {
+        Config::parse(&format!("bash {{\n{source}\n}}")).expect("parse should succeed")
+    }
+
+    fn apply(config: &Config, args: &[&str]) -> Vec<String> {
+        let args: Vec<String> = args.iter().map(|a| a.to_string()).collect();
+        config.bash.as_ref().unwrap().rewrites[0].apply(&args)
+    }
+
+    #[test]
+    fn add_flags_after_program() {
+        let config = rewrite(r#"rewrite "rm" { add-flags "-i"; }"#);
+        assert_eq!(apply(&config, &["-r", "build"]), ["-i", "-r", "build"]);
+    }
+
+    #[test]
+    fn add_flags_skips_present_flags() {
+        let config = rewrite(r#"rewrite "rm" { add-flags "-i"; }"#);
+        assert_eq!(apply(&config, &["-i", "x"]), ["-i", "x"]);
+    }
+
+    #[test]
+    fn add_flags_after_subcommand() {
+        let config = rewrite(r#"rewrite "terraform apply" { add-flags "--dry-run"; }"#);
+        assert_eq!(
+            apply(&config, &["-chdir=infra", "apply", "-auto-approve"]),
+            ["-chdir=infra", "apply", "--dry-run", "-auto-approve"]
+        );
+    }
+
+    #[test]
+    fn replace_flag_keeps_value() {
+        let config = rewrite(
+            r#"rewrite "git push --force" { replace-flag "--force" "--force-with-lease"; }"#,
+        );
+        assert_eq!(
+            apply(&config, &["push", "--force", "origin"]),
+            ["push", "--force-with-lease", "origin"]
+        );
+        assert_eq!(
+            apply(&config, &["push", "--force=main"]),
+            ["push", "--force-with-lease=main"]
+        );
+    }
+
+    #[test]
+    fn remove_flags_stops_at_double_dash() {
+        let config = rewrite(r#"rewrite "rm" { remove-flags "-f"; }"#);
+        assert_eq!(apply(&config, &["-f", "--", "-f"]), ["--", "-f"]);
+    }
+
+    #[test]
+    fn flag_edits_cover_every_name_of_the_flag() {
+        let config =
+            rewrite(r#"rewrite "git push" { replace-flag "--force" "--force-with-lease"; }"#);
+        assert_eq!(
+            apply(&config, &["push", "-f", "origin"]),
+            ["push", "--force-with-lease", "origin"]
+        );
+        let config = rewrite(r#"rewrite "rm" { remove-flags "-f"; add-flags "-i"; }"#);
+        assert_eq!(
+            apply(&config, &["--force", "--interactive", "x"]),
+            ["--interactive", "x"]
+        );
+    }
+
+    #[test]
+    fn flag_edits_split_bundles() {
+        let config = rewrite(r#"rewrite "rm -f" { remove-flags "-f"; }"#);
+        assert_eq!(apply(&config, &["-rf", "x"]), ["-r", "x"]);
+        let config = rewrite(
+            r#"rewrite "git push --force" { replace-flag "--force" "--force-with-lease"; }"#,
+        );
+        assert_eq!(
+            apply(&config, &["push", "-fu", "origin", "main"]),
+            ["push", "--force-with-lease", "-u", "origin", "main"]
+        );
+    }
+
+    #[test]
+    fn bundle_left_alone_when_nothing_changes() {
+        let config = rewrite(r#"rewrite "rm" { add-flags "-i"; }"#);
+        assert_eq!(apply(&config, &["-ri", "x"]), ["-ri", "x"]);
+    }
+}
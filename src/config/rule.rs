@@ -1,7 +1,9 @@
 use std::collections::HashSet;
 
 use globset::GlobMatcher;
+use regex::Regex;
 
+use super::flag_schema::ProgramFlags;
 use crate::domain::{Flag, ProgramName};
 
 /// A parsed rule for a bash program with optional conditions.
@@ -12,6 +14,10 @@ use crate::domain::{Flag, ProgramName};
 pub(crate) struct BashRule {
     pub(crate) program: ProgramName,
     pub(crate) conditions: RuleConditions,
+    /// Rule string as written in the config (`"rm -rf"`).
+    pub(crate) raw: String,
+    /// 1-based line number in the source file.
+    pub(crate) line: usize,
 }
 
 /// Conditions that must be met for a rule to match a command.
@@ -24,24 +30,51 @@ pub(crate) struct RuleConditions {
     pub(crate) required_flags: HashSet<Flag>,
     /// Flags where ANY one triggers the rule (OR semantics).
     pub(crate) optional_flags: HashSet<Flag>,
+    /// Flags where ANY one keeps the rule from matching.
+    pub(crate) forbidden_flags: HashSet<Flag>,
     /// Ordered prefix from rule string non-flag args (e.g., `["push"]` from `git push --force`).
     pub(crate) subcommand: Vec<String>,
-    /// Glob patterns for positional arguments (any order).
+    /// Glob or regex patterns for positional arguments (any order).
     pub(crate) positionals: Vec<PositionalPattern>,
     /// Flag+value pairs that must be present (e.g., `--upload-file *.txt`).
     pub(crate) required_arguments: Vec<ArgumentPattern>,
     /// OR list of ordered subcommand chains from children blocks.
     pub(crate) subcommands: Vec<Vec<String>>,
+    /// Carve-outs from `except` children: the rule does not match a command
+    /// that any of these rules match.
+    pub(crate) exceptions: Vec<BashRule>,
+    /// The program's flag schema. Not a condition: it says which flags take
+    /// a value and which flags are the same when checking the others.
+    pub(crate) schema: ProgramFlags,
 }
 
-/// A glob pattern for matching positional arguments.
+/// A glob or regex pattern for matching positional arguments.
 #[derive(Debug)]
 pub(crate) struct PositionalPattern {
-    /// Original pattern string for display/debugging.
-    #[allow(dead_code)]
+    /// Original pattern string for display/debugging; regexes are shown
+    /// with a `re:` prefix.
     pub(crate) raw: String,
-    /// Compiled glob matcher.
-    pub(crate) matcher: GlobMatcher,
+    /// Compiled matcher.
+    pub(crate) matcher: ArgMatcher,
+}
+
+/// How a [`PositionalPattern`] matches an argument.
+#[derive(Debug)]
+pub(crate) enum ArgMatcher {
+    /// The whole argument matches the glob.
+    Glob(GlobMatcher),
+    /// The regex matches somewhere in the argument; anchor it with `^…$`
+    /// to match the whole argument.
+    Regex(Regex),
+}
+
+impl ArgMatcher {
+    pub(crate) fn is_match(&self, arg: &str) -> bool {
+        match self {
+            ArgMatcher::Glob(glob) => glob.is_match(arg),
+            ArgMatcher::Regex(regex) => regex.is_match(arg),
+        }
+    }
 }
 
 /// A flag+value pattern for matching arguments like `--upload-file *.txt`.
@@ -49,7 +82,7 @@ pub(crate) struct PositionalPattern {
 pub(crate) struct ArgumentPattern {
     /// The flag (e.g., `"--upload-file"`).
     pub(crate) flag: String,
-    /// Glob pattern for the value.
+    /// Glob or regex pattern for the value.
     pub(crate) value: PositionalPattern,
 }
 
@@ -59,10 +92,12 @@ impl BashRule {
     pub(crate) fn is_unconditional(&self) -> bool {
         self.conditions.required_flags.is_empty()
             && self.conditions.optional_flags.is_empty()
+            && self.conditions.forbidden_flags.is_empty()
             && self.conditions.subcommand.is_empty()
             && self.conditions.positionals.is_empty()
             && self.conditions.required_arguments.is_empty()
             && self.conditions.subcommands.is_empty()
+            && self.conditions.exceptions.is_empty()
     }
 }
 
@@ -79,7 +114,18 @@ pub(crate) fn compile_glob(raw: &str) -> Result<PositionalPattern, String> {
         .map_err(|e| format!("invalid glob pattern '{raw}': {e}"))?;
     Ok(PositionalPattern {
         raw: raw.to_string(),
-        matcher: glob.compile_matcher(),
+        matcher: ArgMatcher::Glob(glob.compile_matcher()),
+    })
+}
+
+/// Compile a regular expression into a `PositionalPattern`.
+///
+/// Returns `Err` with a message if the regex is invalid.
+pub(crate) fn compile_regex(raw: &str) -> Result<PositionalPattern, String> {
+    let regex = Regex::new(raw).map_err(|e| format!("invalid regex '{raw}': {e}"))?;
+    Ok(PositionalPattern {
+        raw: format!("re:{raw}"),
+        matcher: ArgMatcher::Regex(regex),
     })
 }
 
@@ -91,6 +137,8 @@ mod tests {
     fn unconditional_rule_with_empty_conditions() {
         let rule = BashRule {
             program: crate::domain::ProgramName::new("rm"),
+            raw: "rm".to_string(),
+            line: 0,
             conditions: RuleConditions::default(),
         };
         assert!(rule.is_unconditional());
@@ -104,6 +152,8 @@ mod tests {
             .insert(crate::domain::Flag::new("-r"));
         let rule = BashRule {
             program: crate::domain::ProgramName::new("rm"),
+            raw: "rm".to_string(),
+            line: 0,
             conditions,
         };
         assert!(!rule.is_unconditional());
@@ -121,4 +171,18 @@ mod tests {
         let result = compile_glob("[invalid");
         assert!(result.is_err());
     }
+
+    #[test]
+    fn compile_valid_regex() {
+        let pattern = compile_regex("^(main|release/.*)$").unwrap();
+        assert_eq!(pattern.raw, "re:^(main|release/.*)$");
+        assert!(pattern.matcher.is_match("release/1.2"));
+        assert!(!pattern.matcher.is_match("feature/main"));
+    }
+
+    #[test]
+    fn compile_invalid_regex_returns_error() {
+        let err = compile_regex("(unclosed").unwrap_err();
+        assert!(err.starts_with("invalid regex '(unclosed': "), "{err}");
+    }
 }
@@ -3,7 +3,10 @@
 //! Converts KDL sections into tool-agnostic intermediate types.
 //! Tool modules consume these without any KDL dependency.
 
-use super::document::{ConfigDocument, ConfigSection};
+use serde_json::Value;
+
+use super::define::Definitions;
+use super::document::{ConfigDocument, ConfigSection, EntryValue};
 use super::ConfigError;
 
 /// Trait for tool-specific configuration.
@@ -20,11 +23,13 @@ pub(crate) trait ToolConfig: Default + std::fmt::Debug {
 
 /// Intermediate representation of a tool's configuration section.
 ///
-/// Contains tiered rule entries (allow/deny/ask) with no KDL dependency.
+/// Contains tiered rule entries (allow/deny/ask) and any other top-level
+/// nodes as settings, with no KDL dependency.
 pub(crate) struct ToolSection {
     pub allow: Vec<RuleEntry>,
     pub deny: Vec<RuleEntry>,
     pub ask: Vec<RuleEntry>,
+    pub settings: Vec<SettingEntry>,
 }
 
 /// A single rule entry from a config node.
@@ -43,44 +48,110 @@ pub(crate) struct RuleEntry {
 /// A child node within a rule's children block.
 ///
 /// Represents nodes like `required-flags "r" "f"` or `positionals "/*"`.
+#[derive(Clone)]
 pub(crate) struct ChildNode {
     /// Node name (e.g., `"required-flags"`, `"positionals"`).
     pub name: String,
+    /// Positional string values from the node.
+    pub values: Vec<String>,
+    /// Named entries (`key=value`), in order, as JSON values.
+    pub properties: Vec<(String, Value)>,
+    /// Total number of entries (all types, named or not).
+    pub entry_count: usize,
+    /// The child's own children block, as in `except { … }`.
+    pub children: Option<Vec<ChildNode>>,
+    /// 1-based line number in the source file.
+    pub line: usize,
+}
+
+/// A non-rule node in a tool section.
+///
+/// Represents settings like `max-nesting-depth 3` or `path-args "tool" { … }`.
+/// Each tool decides which setting names it understands and validates their
+/// values.
+pub(crate) struct SettingEntry {
+    /// Node name (e.g., `"max-nesting-depth"`).
+    pub name: String,
     /// String values from the node.
     pub values: Vec<String>,
+    /// Integer values from the node.
+    pub integers: Vec<i128>,
+    /// Parsed children block, if present.
+    pub children: Option<Vec<ChildNode>>,
+    /// Total number of entries (all types).
+    pub entry_count: usize,
     /// 1-based line number in the source file.
     pub line: usize,
+    /// Which of the layered config files the setting is in, in load order.
+    /// A later file's value replaces an earlier one's.
+    pub file: usize,
+    /// Which layer the setting is in: the layered config file it was loaded
+    /// from, directly or through an include.
+    pub layer: usize,
 }
 
 /// Parse a tool section from KDL into the tool's config type.
 ///
 /// Looks up the section by `T::SECTION`, parses it into [`ToolSection`],
 /// and delegates to [`ToolConfig::from_section`]. Returns `T::default()`
-/// when the section is absent.
-pub(super) fn parse_tool<T: ToolConfig>(kdl: &ConfigDocument) -> Result<T, ConfigError> {
+/// when the section is absent. `@name` rule values are expanded from
+/// `definitions`.
+pub(super) fn parse_tool<T: ToolConfig>(
+    kdl: &ConfigDocument,
+    definitions: &Definitions,
+) -> Result<T, ConfigError> {
     match kdl.section(T::SECTION) {
         Some(section_kdl) => {
-            let section = parse_section(&section_kdl)?;
+            let section = parse_section(&section_kdl, definitions)?;
             T::from_section(section)
         }
         None => Ok(T::default()),
     }
 }
 
-fn parse_section(kdl: &ConfigSection) -> Result<ToolSection, ConfigError> {
+fn parse_section(
+    kdl: &ConfigSection,
+    definitions: &Definitions,
+) -> Result<ToolSection, ConfigError> {
     Ok(ToolSection {
-        allow: collect_entries(kdl, "allow")?,
-        deny: collect_entries(kdl, "deny")?,
-        ask: collect_entries(kdl, "ask")?,
+        allow: collect_entries(kdl, "allow", definitions)?,
+        deny: collect_entries(kdl, "deny", definitions)?,
+        ask: collect_entries(kdl, "ask", definitions)?,
+        settings: collect_settings(kdl),
     })
 }
 
+/// Collect every node that is not an allow/deny/ask tier as a setting.
+fn collect_settings(kdl: &ConfigSection) -> Vec<SettingEntry> {
+    kdl.nodes()
+        .into_iter()
+        .filter(|node| !matches!(node.name(), "allow" | "deny" | "ask"))
+        .map(|node| SettingEntry {
+            name: node.name().to_string(),
+            values: node.string_values().into_iter().map(String::from).collect(),
+            integers: node.integer_values(),
+            children: node.children().as_ref().map(collect_children),
+            entry_count: node.entry_count(),
+            line: node.line(),
+            file: node.part(),
+            layer: node.layer(),
+        })
+        .collect()
+}
+
 /// Collect all rule entries from nodes with the given tier name.
 ///
 /// Validates structural constraints common to all tools:
 /// - Children block requires exactly one string entry
 /// - Children block without any entry is rejected
-fn collect_entries(kdl: &ConfigSection, tier: &str) -> Result<Vec<RuleEntry>, ConfigError> {
+///
+/// An `@name` value expands to the named list; with a children block, each
+/// of its values becomes a rule with those children.
+fn collect_entries(
+    kdl: &ConfigSection,
+    tier: &str,
+    definitions: &Definitions,
+) -> Result<Vec<RuleEntry>, ConfigError> {
     let mut entries = Vec::new();
     for node in kdl.nodes_named(tier) {
         let line = node.line();
@@ -100,29 +171,66 @@ fn collect_entries(kdl: &ConfigSection, tier: &str) -> Result<Vec<RuleEntry>, Co
             )));
         }
 
-        let children = node.children().map(|children_kdl| {
-            children_kdl
-                .nodes()
+        let children = node.children().as_ref().map(collect_children);
+        let mut expanded = Vec::new();
+        for value in &values {
+            expanded.extend(definitions.expand_list(value, line)?);
+        }
+
+        if children.is_some() {
+            entries.extend(expanded.into_iter().map(|value| RuleEntry {
+                values: vec![value],
+                children: children.clone(),
+                line,
+            }));
+        } else {
+            entries.push(RuleEntry {
+                values: expanded,
+                children,
+                line,
+            });
+        }
+    }
+    Ok(entries)
+}
+
+/// Convert a children block into [`ChildNode`]s.
+fn collect_children(kdl: &ConfigSection) -> Vec<ChildNode> {
+    kdl.nodes()
+        .into_iter()
+        .map(|child| ChildNode {
+            name: child.name().to_string(),
+            values: child
+                .arguments()
                 .into_iter()
-                .map(|child| ChildNode {
-                    name: child.name().to_string(),
-                    values: child
-                        .string_values()
-                        .into_iter()
-                        .map(String::from)
-                        .collect(),
-                    line: child.line(),
+                .filter_map(|value| match value {
+                    EntryValue::String(s) => Some(s.to_string()),
+                    _ => None,
                 })
-                .collect()
-        });
-
-        entries.push(RuleEntry {
-            values,
-            children,
-            line,
-        });
+                .collect(),
+            properties: child
+                .properties()
+                .into_iter()
+                .map(|(name, value)| (name.to_string(), json_value(value)))
+                .collect(),
+            entry_count: child.entry_count(),
+            children: child.children().as_ref().map(collect_children),
+            line: child.line(),
+        })
+        .collect()
+}
+
+/// A KDL value as JSON. Integers outside the `i64` range become floats.
+fn json_value(value: EntryValue<'_>) -> Value {
+    match value {
+        EntryValue::String(s) => Value::from(s),
+        EntryValue::Integer(i) => i64::try_from(i)
+            .map(Value::from)
+            .unwrap_or_else(|_| Value::from(i as f64)),
+        EntryValue::Float(f) => Value::from(f),
+        EntryValue::Bool(b) => Value::from(b),
+        EntryValue::Null => Value::Null,
     }
-    Ok(entries)
 }
 
 /// Test-only: parse raw KDL source directly into a ToolSection.
@@ -136,5 +244,5 @@ pub(super) fn parse_from_source(source: &str) -> Result<ToolSection, ConfigError
     let section = kdl
         .section("test")
         .expect("synthetic test section must exist");
-    parse_section(&section)
+    parse_section(&section, &Definitions::default())
 }
@@ -0,0 +1,25 @@
+//! Inline rule tests (`tests` section).
+//!
+//! Parsing is in [`crate::config::parse::test_cases`]. Running the cases
+//! is the `test` command's job; they never affect the hook's decisions.
+
+use serde_json::Value;
+
+use crate::protocol::{Decision, PermissionMode};
+
+/// One `expect` case: a tool call and the decision the config should reach.
+#[derive(Debug)]
+pub struct TestCase {
+    /// Expected decision; `None` expects no opinion.
+    pub expected: Option<Decision>,
+    pub tool_name: String,
+    /// Tool input; string values may contain `<cwd>`, replaced when run.
+    pub tool_input: Value,
+    /// Working directory for the case; the current one when absent.
+    pub cwd: Option<String>,
+    pub mode: PermissionMode,
+    /// The tool property as written (`bash="git push"`), for reports.
+    pub label: String,
+    /// 1-based line number in the source file.
+    pub line: usize,
+}
@@ -0,0 +1,68 @@
+//! Generic tool configuration types.
+//!
+//! Rules for tools without a dedicated section, matched by tool name glob
+//! and [`argument`](super::argument) conditions over `tool_input`, as in
+//! the `mcp` section. Parsing is in
+//! [`crate::config::parse::tools`]. Matching internals are in
+//! [`crate::config::match_rule::tools`].
+
+use globset::GlobMatcher;
+use serde_json::Value;
+
+use super::argument::ArgumentMatch;
+use super::section::{ToolConfig, ToolSection};
+use super::{ConfigError, RuleMatch};
+
+/// Generic tool configuration: rules for allow, deny, or ask decisions.
+#[derive(Debug, Default)]
+pub struct ToolsConfig {
+    pub deny: Vec<ToolRule>,
+    pub ask: Vec<ToolRule>,
+    pub allow: Vec<ToolRule>,
+}
+
+/// A tool name glob with optional argument conditions.
+///
+/// Created from entries like `ask "Todo*"` or
+/// `deny "Task" { argument "subagent_type" "*admin*" }`.
+#[derive(Debug)]
+pub struct ToolRule {
+    /// Pattern as written in the config.
+    pub raw_pattern: String,
+    /// Glob over the tool name.
+    pub matcher: GlobMatcher,
+    /// Argument conditions that must ALL match (AND semantics).
+    pub arguments: Vec<ArgumentMatch>,
+    /// 1-based line number in the source file.
+    pub line: usize,
+}
+
+impl ToolConfig for ToolsConfig {
+    const SECTION: &'static str = "tools";
+
+    fn from_section(section: ToolSection) -> Result<Self, ConfigError> {
+        super::parse::tools::reject_settings(&section.settings)?;
+        Ok(ToolsConfig {
+            allow: super::parse::tools::parse_rules(section.allow)?,
+            deny: super::parse::tools::parse_rules(section.deny)?,
+            ask: super::parse::tools::parse_rules(section.ask)?,
+        })
+    }
+}
+
+impl ToolsConfig {
+    /// Look up a tool call by name and `tool_input`, returning the decision
+    /// and the rule that decided it.
+    ///
+    /// Delegates to [`super::match_rule::tools::lookup`].
+    /// Precedence: deny > ask > allow. Returns `None` if no rule matches.
+    pub(crate) fn lookup_match(&self, tool_name: &str, tool_input: &Value) -> Option<RuleMatch> {
+        super::match_rule::tools::lookup(self, tool_name, tool_input)
+    }
+
+    /// The decision of [`lookup_match`](Self::lookup_match) alone.
+    #[cfg(test)]
+    pub fn lookup(&self, tool_name: &str, tool_input: &Value) -> Option<crate::protocol::Decision> {
+        self.lookup_match(tool_name, tool_input).map(|m| m.decision)
+    }
+}
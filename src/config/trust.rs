@@ -0,0 +1,286 @@
+//! Trusted project roots: which `.claude/permissions.kdl` files the hook
+//! loads.
+//!
+//! A project config comes with the repository, so anyone who can commit to
+//! a repository could otherwise allow whatever they like on the machines of
+//! everyone who opens it. The hook only loads a project config whose root
+//! the user has trusted, either outright or pinned to the exact content
+//! that was reviewed.
+//!
+//! The store is a KDL file of `project` nodes, written by the `trust`
+//! command:
+//!
+//! ```kdl
+//! project "/home/me/work/monorepo"
+//! project "/home/me/scratch" sha256="9f86d08…"
+//! ```
+
+use std::path::{Path, PathBuf};
+
+use sha2::{Digest, Sha256};
+
+use super::document::{ConfigDocument, EntryValue};
+use super::ConfigError;
+
+/// The user's trusted project roots.
+#[derive(Debug, Default)]
+pub(crate) struct TrustStore {
+    entries: Vec<TrustEntry>,
+}
+
+/// One trusted project root.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct TrustEntry {
+    /// Directory holding `.claude/permissions.kdl`, with `~` expanded.
+    root: PathBuf,
+    /// Digest of the project config (and its includes) the trust is pinned
+    /// to; `None` trusts whatever the config says.
+    sha256: Option<String>,
+}
+
+impl TrustStore {
+    /// Read the store. A missing file trusts nothing.
+    pub(crate) fn load(path: &Path) -> Result<Self, ConfigError> {
+        match std::fs::read_to_string(path) {
+            Ok(content) => Self::parse(&content),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(ConfigError::ReadError(e)),
+        }
+    }
+
+    fn parse(content: &str) -> Result<Self, ConfigError> {
+        let doc = ConfigDocument::parse(content)?;
+        let mut entries = Vec::new();
+        for node in doc.nodes() {
+            let line = node.line();
+            if node.name() != "project" {
+                return Err(ConfigError::ParseError(format!(
+                    "line {line}: unknown node \"{}\" in trust file",
+                    node.name()
+                )));
+            }
+            let raw = match node.arguments().as_slice() {
+                [EntryValue::String(raw)] if !node.has_children() => *raw,
+                _ => {
+                    return Err(ConfigError::ParseError(format!(
+                        "line {line}: project requires a single quoted directory path"
+                    )))
+                }
+            };
+            let mut sha256 = None;
+            for (name, value) in node.properties() {
+                match (name, value) {
+                    ("sha256", EntryValue::String(digest)) => sha256 = Some(digest.to_string()),
+                    _ => {
+                        return Err(ConfigError::ParseError(format!(
+                            "line {line}: unknown project property \"{name}\"; \
+                             only sha256 is supported"
+                        )))
+                    }
+                }
+            }
+            let root = super::normalize::files::expand_home(raw).map_err(|e| {
+                ConfigError::ParseError(format!(
+                    "line {line}: cannot expand project path '{raw}': {e}"
+                ))
+            })?;
+            entries.push(TrustEntry {
+                root: PathBuf::from(root),
+                sha256,
+            });
+        }
+        Ok(Self { entries })
+    }
+
+    /// Write the store, replacing the file.
+    pub(crate) fn save(&self, path: &Path) -> Result<(), ConfigError> {
+        let mut out = String::from(
+            "// Project roots whose .claude/permissions.kdl claude-permissions-hook loads.\n\
+             // Written by `claude-permissions-hook trust`.\n",
+        );
+        for entry in &self.entries {
+            out.push_str(&format!(
+                "project {}",
+                quoted(&entry.root.to_string_lossy())
+            ));
+            if let Some(digest) = &entry.sha256 {
+                out.push_str(&format!(" sha256={}", quoted(digest)));
+            }
+            out.push('\n');
+        }
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(ConfigError::ReadError)?;
+        }
+        std::fs::write(path, out).map_err(ConfigError::ReadError)
+    }
+
+    /// Trust a project root, pinned to `sha256` when given. Replaces any
+    /// earlier entry for the root.
+    pub(crate) fn trust(&mut self, root: &Path, sha256: Option<String>) {
+        self.remove(root);
+        self.entries.push(TrustEntry {
+            root: canonical(root),
+            sha256,
+        });
+    }
+
+    /// Stop trusting a project root. Returns whether it was trusted.
+    pub(crate) fn remove(&mut self, root: &Path) -> bool {
+        let root = canonical(root);
+        let before = self.entries.len();
+        self.entries.retain(|entry| canonical(&entry.root) != root);
+        self.entries.len() != before
+    }
+
+    /// Whether the project config at `config`, under `root`, may be loaded.
+    ///
+    /// A pinned entry only trusts the content it was pinned to; a config
+    /// that no longer loads is not trusted by a pinned entry.
+    pub(crate) fn trusts(&self, root: &Path, config: &Path) -> bool {
+        let root = canonical(root);
+        let mut digest = None;
+        self.entries
+            .iter()
+            .filter(|entry| canonical(&entry.root) == root)
+            .any(|entry| match &entry.sha256 {
+                None => true,
+                Some(pinned) => digest
+                    .get_or_insert_with(|| project_digest(config).ok())
+                    .as_ref()
+                    .is_some_and(|digest| digest == pinned),
+            })
+    }
+}
+
+/// SHA-256 of a project config and the files it includes, hex-encoded.
+pub(crate) fn project_digest(config: &Path) -> Result<String, ConfigError> {
+    let doc = ConfigDocument::load(&[config.to_path_buf()])?;
+    let mut hasher = Sha256::new();
+    for content in doc.contents() {
+        hasher.update((content.len() as u64).to_le_bytes());
+        hasher.update(content.as_bytes());
+    }
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect())
+}
+
+/// The path with symlinks resolved, or as given when it does not exist.
+fn canonical(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// A KDL quoted string.
+fn quoted(value: &str) -> String {
+    let mut out = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(dir: &Path, name: &str, content: &str) -> PathBuf {
+        let path = dir.join(name);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn missing_store_trusts_nothing() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = write(dir.path(), ".claude/permissions.kdl", "");
+        let store = TrustStore::load(&dir.path().join("trusted.kdl")).unwrap();
+        assert!(!store.trusts(dir.path(), &config));
+    }
+
+    #[test]
+    fn trusted_root_survives_save_and_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = dir.path().join("my \"project\"");
+        let config = write(&project, ".claude/permissions.kdl", "bash {\n}\n");
+        let store_path = dir.path().join("config/trusted.kdl");
+        let mut store = TrustStore::default();
+        store.trust(&project, None);
+        store.save(&store_path).unwrap();
+
+        let store = TrustStore::load(&store_path).unwrap();
+        assert!(store.trusts(&project, &config));
+        assert!(!store.trusts(dir.path(), &config));
+    }
+
+    #[test]
+    fn pinned_trust_ends_when_content_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = write(
+            dir.path(),
+            ".claude/permissions.kdl",
+            "bash {\n    allow \"ls\"\n}\n",
+        );
+        let mut store = TrustStore::default();
+        store.trust(dir.path(), Some(project_digest(&config).unwrap()));
+        assert!(store.trusts(dir.path(), &config));
+
+        std::fs::write(&config, "bash {\n    allow \"rm\"\n}\n").unwrap();
+        assert!(!store.trusts(dir.path(), &config));
+    }
+
+    #[test]
+    fn pinned_digest_covers_included_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let base = write(
+            dir.path(),
+            ".claude/base.kdl",
+            "bash {\n    allow \"ls\"\n}\n",
+        );
+        let config = write(
+            dir.path(),
+            ".claude/permissions.kdl",
+            "include \"base.kdl\"\n",
+        );
+        let before = project_digest(&config).unwrap();
+        std::fs::write(base, "bash {\n    allow \"rm\"\n}\n").unwrap();
+        assert_ne!(project_digest(&config).unwrap(), before);
+    }
+
+    #[test]
+    fn trusting_again_replaces_the_entry() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = write(dir.path(), ".claude/permissions.kdl", "");
+        let mut store = TrustStore::default();
+        store.trust(dir.path(), Some("0000".to_string()));
+        assert!(!store.trusts(dir.path(), &config));
+        store.trust(dir.path(), None);
+        assert!(store.trusts(dir.path(), &config));
+        assert!(store.remove(dir.path()));
+        assert!(!store.trusts(dir.path(), &config));
+    }
+
+    #[test]
+    fn store_rejects_unknown_nodes_and_properties() {
+        let err = TrustStore::parse("projects \"/a\"").unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("line 1: unknown node \"projects\""),
+            "{err}"
+        );
+        let err = TrustStore::parse("project \"/a\" sha1=\"x\"").unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("unknown project property \"sha1\""),
+            "{err}"
+        );
+    }
+}
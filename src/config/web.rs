@@ -0,0 +1,202 @@
+//! Web tool configuration types.
+//!
+//! Struct definitions and public lookup delegation. Parsing is in
+//! [`crate::config::parse::web`]. Matching internals are in
+//! [`crate::config::match_rule::web`].
+
+use globset::GlobMatcher;
+
+use super::RuleMatch;
+
+/// Web tool configuration: rules for `WebFetch` URLs and `WebSearch` queries.
+#[derive(Debug, Default)]
+pub struct WebConfig {
+    pub deny: Vec<UrlRule>,
+    pub ask: Vec<UrlRule>,
+    pub allow: Vec<UrlRule>,
+    /// Query rules from the `search { … }` block.
+    pub search: SearchRules,
+}
+
+/// Tiered `WebSearch` query rules.
+#[derive(Debug, Default)]
+pub struct SearchRules {
+    pub deny: Vec<QueryRule>,
+    pub ask: Vec<QueryRule>,
+    pub allow: Vec<QueryRule>,
+}
+
+/// A URL pattern: `[scheme://]host-glob[/path-glob]`.
+///
+/// `"docs.rs"` matches any scheme and path on `docs.rs`;
+/// `"https://*.github.com/rust-lang/**"` restricts scheme, host, and path.
+/// A `*.` host also matches the domain itself: `*.github.com` covers
+/// `github.com`.
+#[derive(Debug)]
+pub struct UrlRule {
+    /// Pattern as written in the config.
+    pub raw_pattern: String,
+    /// Required scheme (lowercase), or `None` for any.
+    pub scheme: Option<String>,
+    /// Host glob, matched case-insensitively.
+    pub host: GlobMatcher,
+    /// For a `*.` host glob, the glob after `*.`, so the domain itself
+    /// matches too.
+    pub apex: Option<GlobMatcher>,
+    /// Path glob, or `None` for any path.
+    pub path: Option<GlobMatcher>,
+    /// 1-based line number in the source file.
+    pub line: usize,
+}
+
+/// A glob over `WebSearch` query text, matched case-insensitively.
+#[derive(Debug)]
+pub struct QueryRule {
+    /// Pattern as written in the config.
+    pub raw_pattern: String,
+    pub matcher: GlobMatcher,
+    /// 1-based line number in the source file.
+    pub line: usize,
+}
+
+/// The parts of a fetched URL that rules match against.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Url {
+    /// Lowercase scheme (`"https"`).
+    pub scheme: String,
+    /// Lowercase host without userinfo, port, or trailing dot.
+    pub host: String,
+    /// Path starting with `/`, without query or fragment.
+    pub path: String,
+}
+
+impl WebConfig {
+    /// Look up a URL against the URL rules, returning the decision and the
+    /// rule that decided it.
+    ///
+    /// Delegates to [`super::match_rule::web::lookup_url`].
+    /// Precedence: deny > ask > allow. Returns `None` if no rule matches.
+    pub(crate) fn lookup_url_match(&self, url: &Url) -> Option<RuleMatch> {
+        super::match_rule::web::lookup_url(self, url)
+    }
+
+    /// Look up a search query against the `search` rules, returning the
+    /// decision and the rule that decided it.
+    ///
+    /// Delegates to [`super::match_rule::web::lookup_query`].
+    /// Precedence: deny > ask > allow. Returns `None` if no rule matches.
+    pub(crate) fn lookup_query_match(&self, query: &str) -> Option<RuleMatch> {
+        super::match_rule::web::lookup_query(&self.search, query)
+    }
+
+    /// The decision of [`lookup_query_match`](Self::lookup_query_match) alone.
+    #[cfg(test)]
+    pub fn lookup_query(&self, query: &str) -> Option<crate::protocol::Decision> {
+        self.lookup_query_match(query).map(|m| m.decision)
+    }
+}
+
+impl Url {
+    /// Split a URL into scheme, host, and path.
+    ///
+    /// Parsed the way WHATWG clients do: `\` separates the authority like
+    /// `/`, and the host is percent-decoded and IDNA-normalized. The path
+    /// keeps its percent-encoding. Returns `None` for input the `url` crate
+    /// rejects and for URLs without a host.
+    pub fn parse(raw: &str) -> Option<Self> {
+        let parsed = ::url::Url::parse(raw.trim()).ok()?;
+        let host = parsed
+            .host_str()?
+            .trim_end_matches('.')
+            .to_ascii_lowercase();
+        if host.is_empty() {
+            return None;
+        }
+        let path = match parsed.path() {
+            "" => "/".to_string(),
+            path => path.to_string(),
+        };
+        Some(Url {
+            scheme: parsed.scheme().to_string(),
+            host,
+            path,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn url(raw: &str) -> Url {
+        Url::parse(raw).expect("url should parse")
+    }
+
+    #[test]
+    fn parse_splits_scheme_host_path() {
+        assert_eq!(
+            url("https://docs.rs/serde/latest"),
+            Url {
+                scheme: "https".to_string(),
+                host: "docs.rs".to_string(),
+                path: "/serde/latest".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn parse_lowercases_scheme_and_host() {
+        let url = url("HTTPS://Docs.RS/Serde");
+        assert_eq!(url.scheme, "https");
+        assert_eq!(url.host, "docs.rs");
+        assert_eq!(url.path, "/Serde");
+    }
+
+    #[test]
+    fn parse_strips_userinfo_port_query_fragment() {
+        let url = url("http://user:pw@example.com:8080/a/b?q=1#top");
+        assert_eq!(url.host, "example.com");
+        assert_eq!(url.path, "/a/b");
+    }
+
+    #[test]
+    fn parse_defaults_empty_path_to_root() {
+        assert_eq!(url("https://example.com?q=1").path, "/");
+    }
+
+    #[test]
+    fn parse_strips_trailing_dot() {
+        assert_eq!(url("https://example.com./").host, "example.com");
+    }
+
+    #[test]
+    fn parse_ipv6_host() {
+        assert_eq!(url("http://[::1]:8080/").host, "[::1]");
+    }
+
+    #[test]
+    fn parse_treats_backslash_as_path_separator() {
+        let url = url("https://evil.com\\@docs.rs/");
+        assert_eq!(url.host, "evil.com");
+    }
+
+    #[test]
+    fn parse_percent_decodes_host() {
+        assert_eq!(url("https://pastebin%2ecom/raw").host, "pastebin.com");
+    }
+
+    #[test]
+    fn parse_normalizes_dot_segments() {
+        assert_eq!(url("https://docs.rs/a/../b").path, "/b");
+    }
+
+    #[test]
+    fn parse_rejects_missing_scheme() {
+        assert!(Url::parse("docs.rs/serde").is_none());
+    }
+
+    #[test]
+    fn parse_rejects_missing_host() {
+        assert!(Url::parse("file:///etc/passwd").is_none());
+    }
+}
@@ -1,6 +1,17 @@
 use crate::protocol::output::Decision;
 use crate::protocol::PermissionMode;
 
+use super::reason::build_uncovered_rewrite_reason;
+
+/// A decision the rules reached, with the permission mode applied.
+pub(super) struct Verdict {
+    /// The decision before the permission mode.
+    pub(super) decision: Decision,
+    /// The decision after the permission mode.
+    pub(super) modified: Decision,
+    pub(super) reason: String,
+}
+
 /// Aggregate multiple per-program decisions into a single decision.
 ///
 /// - All None → None (no opinion on any program)
@@ -39,3 +50,28 @@ pub(crate) fn apply_mode_modifier(decision: Decision, mode: &PermissionMode) ->
         },
     }
 }
+
+/// The verdict that stands once a rewritten input has been looked up again.
+///
+/// The rewritten input's verdict wins when it is stricter, so a rewrite can
+/// never loosen a decision. A rewritten input no rule covers is an `Ask`;
+/// `subject` names it in the reason (`"command"`, `"path"`).
+pub(super) fn after_rewrite(
+    before: Verdict,
+    after: Option<Verdict>,
+    mode: &PermissionMode,
+    subject: &str,
+) -> Verdict {
+    match after {
+        Some(after) if after.decision.severity() > before.decision.severity() => after,
+        Some(_) => before,
+        None => {
+            let modified = apply_mode_modifier(Decision::Ask, mode);
+            Verdict {
+                reason: build_uncovered_rewrite_reason(&modified, subject),
+                decision: Decision::Ask,
+                modified,
+            }
+        }
+    }
+}
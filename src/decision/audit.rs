@@ -0,0 +1,107 @@
+use serde::Serialize;
+
+use crate::config::{RuleMatch, SourceMap};
+use crate::protocol::output::Decision;
+use crate::protocol::FileOperation;
+
+use super::reason::operation_str;
+
+/// One thing the hook looked up while evaluating an input, for the decision log.
+///
+/// `decision` and `line` are the raw rule lookup, before aggregation and
+/// permission-mode adjustment; `file` names the config file the line is in.
+/// An item with a decision but no line failed closed without a rule (an
+/// unresolvable path, an unparseable URL).
+#[derive(Debug, Serialize, PartialEq)]
+pub(crate) struct AuditItem {
+    /// What was looked up: `program`, `path`, `url`, `query`, `tool`, `prompt`.
+    pub(crate) kind: &'static str,
+    /// The looked-up value: a command segment, a path, a URL, a tool name.
+    pub(crate) subject: String,
+    /// File operation, for `path` items.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) operation: Option<&'static str>,
+    pub(crate) decision: Option<Decision>,
+    pub(crate) line: Option<usize>,
+    /// Config file of the deciding rule; absent for configs parsed from a string.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) file: Option<String>,
+}
+
+impl AuditItem {
+    fn new(kind: &'static str, subject: impl Into<String>, found: Option<&RuleMatch>) -> Self {
+        Self {
+            kind,
+            subject: subject.into(),
+            operation: None,
+            decision: found.map(|found| found.decision.clone()),
+            line: found.map(|found| found.line),
+            file: None,
+        }
+    }
+}
+
+/// The lookups an evaluation makes, recorded as it makes them.
+///
+/// Every command segment, file tool path, URL, or tool name gets an item,
+/// matched or not, and so does the command or path a `rewrite` produced
+/// when it is looked up again. Files a bash command opens only appear when
+/// a `files` rule matches them or they fail closed. Nothing is recorded
+/// without config, for events without a tool call, or for commands that
+/// fail to parse.
+#[derive(Debug, Default)]
+pub(super) struct Audit {
+    items: Vec<AuditItem>,
+}
+
+impl Audit {
+    /// A rule lookup of `kind` for `subject`; `found` is `None` when no
+    /// rule matched.
+    pub(super) fn looked_up(
+        &mut self,
+        kind: &'static str,
+        subject: impl Into<String>,
+        found: Option<&RuleMatch>,
+    ) {
+        self.items.push(AuditItem::new(kind, subject, found));
+    }
+
+    /// A value that could not be looked up and failed closed to ask.
+    pub(super) fn unresolved(&mut self, kind: &'static str, subject: impl Into<String>) {
+        self.items.push(AuditItem {
+            decision: Some(Decision::Ask),
+            ..AuditItem::new(kind, subject, None)
+        });
+    }
+
+    /// A path looked up for a file operation. `decision` is the path's own
+    /// decision, `Ask` with no rule when it failed closed.
+    pub(super) fn path(
+        &mut self,
+        path: impl Into<String>,
+        operation: FileOperation,
+        decision: Option<Decision>,
+        found: Option<&RuleMatch>,
+    ) {
+        self.items.push(AuditItem {
+            operation: Some(operation_str(operation)),
+            decision,
+            ..AuditItem::new("path", path, found)
+        });
+    }
+
+    /// The recorded items, with each line mapped to its config file.
+    pub(super) fn into_items(self, sources: &SourceMap) -> Vec<AuditItem> {
+        self.items
+            .into_iter()
+            .map(|mut item| {
+                if let Some(line) = item.line {
+                    let (file, line) = sources.locate(line);
+                    item.line = Some(line);
+                    item.file = file.map(|path| path.display().to_string());
+                }
+                item
+            })
+            .collect()
+    }
+}
@@ -1,19 +1,41 @@
-use crate::command;
-use crate::config::Config;
+use serde_json::Value;
+
+use crate::command::{
+    self, CommandSegment, ParsedCommand, Redirect, RedirectDirection, WorkingDir,
+};
+use crate::config::path_args::PathArgsTable;
+use crate::config::rewrite::RewriteRule;
+use crate::config::{BashConfig, Config, RuleMatch};
+use crate::domain::ProgramName;
 use crate::protocol::output::Decision;
-use crate::protocol::{HookInput, HookOutput};
+use crate::protocol::{FileOperation, HookInput, HookOutput};
 
-use super::aggregation::{aggregate_decisions, apply_mode_modifier};
-use super::reason::build_reason;
+use super::aggregation::{after_rewrite, aggregate_decisions, apply_mode_modifier, Verdict};
+use super::audit::Audit;
+use super::reason::{
+    build_file_reason, build_reason, build_rewrite_reason, build_unrewritable_reason, operation_str,
+};
 
 /// Evaluate a Bash tool invocation against bash config rules.
 ///
 /// Receives the already-extracted `command` from `ToolUse::parse()`.
 /// `None` means the command field was missing from tool_input.
+///
+/// Files the command opens through redirections, and paths passed to
+/// programs with known argument semantics (`cat`, `cp`, `sed -i`, …), are
+/// also checked against the `files` section. A file decision stricter than
+/// the programs' decision wins; a file `allow` never grants an otherwise
+/// unlisted command.
+///
+/// Allowed and asked commands then go through the rewrite stage: a matching
+/// `rewrite` rule turns into `updatedInput`, and a command it cannot be
+/// applied to is asked about instead of allowed. The rewritten command is
+/// looked up again, and the stricter of the two decisions stands.
 pub(super) fn evaluate_bash(
     command: Option<&str>,
     input: &HookInput,
     config: &Config,
+    audit: &mut Audit,
 ) -> Option<HookOutput> {
     let command = match command {
         Some(cmd) => cmd,
@@ -24,33 +46,350 @@ pub(super) fn evaluate_bash(
         return Some(HookOutput::ask("Empty bash command"));
     }
 
-    let segments = match command::parse(command) {
-        Ok(segs) => segs,
+    let parsed = match parse_command(command, config) {
+        Ok(parsed) => parsed,
         Err(e) => return Some(HookOutput::ask(format!("Failed to parse command: {e}"))),
     };
 
-    if segments.is_empty() {
+    if parsed.segments.is_empty() {
         return Some(HookOutput::ask(
             "No programs extracted from command".to_string(),
         ));
     }
 
-    let bash = config.bash.as_ref()?;
-    let per_program: Vec<Option<Decision>> = segments.iter().map(|seg| bash.lookup(seg)).collect();
+    let verdict = decide_command(&parsed, input, config, audit)?;
+
+    let rewrite = match (&config.bash, &verdict.modified) {
+        (Some(bash), Decision::Allow | Decision::Ask) => rewrite_command(command, &parsed, bash),
+        _ => Rewrite::Unchanged,
+    };
+    let sources = &config.sources;
+    let (modified, reason, updated_input) = match rewrite {
+        Rewrite::Unchanged => (verdict.modified, verdict.reason, None),
+        Rewrite::Rewritten(rewritten, rule) => {
+            let recheck = parse_command(&rewritten, config)
+                .ok()
+                .filter(|parsed| !parsed.segments.is_empty())
+                .and_then(|parsed| decide_command(&parsed, input, config, audit));
+            let verdict = after_rewrite(verdict, recheck, &input.permission_mode, "command");
+            let reason = build_rewrite_reason(
+                &verdict.reason,
+                command.trim(),
+                &rewritten,
+                &rule.raw,
+                rule.line,
+                sources,
+            );
+            let updated = (verdict.modified != Decision::Deny).then(|| {
+                let mut updated = input.tool_input.clone();
+                updated["command"] = Value::String(rewritten);
+                updated
+            });
+            (verdict.modified, reason, updated)
+        }
+        Rewrite::Unsupported(rule) if verdict.modified == Decision::Allow => {
+            match apply_mode_modifier(Decision::Ask, &input.permission_mode) {
+                Decision::Allow => (Decision::Allow, verdict.reason, None),
+                fallback => {
+                    let reason = build_unrewritable_reason(
+                        &fallback, "command", &rule.raw, rule.line, sources,
+                    );
+                    (fallback, reason, None)
+                }
+            }
+        }
+        Rewrite::Unsupported(_) => (verdict.modified, verdict.reason, None),
+    };
+
+    let output = match modified {
+        Decision::Allow => HookOutput::allow(reason),
+        Decision::Ask => HookOutput::ask(reason),
+        Decision::Deny => HookOutput::deny(reason),
+    };
+    Some(match updated_input {
+        Some(updated) => output.with_updated_input(updated),
+        None => output,
+    })
+}
 
-    let aggregated = aggregate_decisions(&per_program);
+/// Parse a command with the configured nesting depth limit.
+fn parse_command(command: &str, config: &Config) -> Result<ParsedCommand, command::ParseError> {
+    let max_depth = config
+        .bash
+        .as_ref()
+        .map_or(command::DEFAULT_MAX_NESTING_DEPTH, |b| b.max_nesting_depth);
+    command::parse_with_depth_limit(command, max_depth)
+}
 
-    match aggregated {
-        Some(decision) => {
+/// Look up a parsed command's programs against the `bash` section and the
+/// files it opens against the `files` section.
+///
+/// Returns `None` when no rule has an opinion.
+fn decide_command(
+    parsed: &ParsedCommand,
+    input: &HookInput,
+    config: &Config,
+    audit: &mut Audit,
+) -> Option<Verdict> {
+    let segments = &parsed.segments;
+    let rules: Vec<Option<RuleMatch>> = match &config.bash {
+        Some(bash) => segments.iter().map(|seg| bash.lookup_match(seg)).collect(),
+        None => vec![None; segments.len()],
+    };
+    for (segment, found) in segments.iter().zip(&rules) {
+        audit.looked_up("program", segment.to_string(), found.as_ref());
+    }
+    let per_program: Vec<Option<Decision>> = rules
+        .iter()
+        .map(|found| found.as_ref().map(|m| m.decision.clone()))
+        .collect();
+    let sources = &config.sources;
+    let program_decision = aggregate_decisions(&per_program);
+
+    let accesses = file_accesses(parsed, config);
+    let file_checks = check_file_accesses(&accesses, input, config);
+    for check in &file_checks {
+        audit.path(
+            &check.path,
+            check.operation,
+            Some(check.decision.clone()),
+            check.rule.as_ref(),
+        );
+    }
+    let file_trigger = file_checks
+        .iter()
+        .filter(|check| check.decision != Decision::Allow)
+        .max_by_key(|check| check.decision.severity())
+        .filter(|check| {
+            program_decision
+                .as_ref()
+                .is_none_or(|d| check.decision.severity() > d.severity())
+        });
+
+    match (file_trigger, program_decision) {
+        (Some(check), _) => {
+            let modified = apply_mode_modifier(check.decision.clone(), &input.permission_mode);
+            let reason = build_file_reason(
+                &modified,
+                std::slice::from_ref(&check.path),
+                &[Some(check.decision.clone())],
+                std::slice::from_ref(&check.rule),
+                &check.decision,
+                operation_str(check.operation),
+                sources,
+            );
+            Some(Verdict {
+                decision: check.decision.clone(),
+                modified,
+                reason,
+            })
+        }
+        (None, Some(decision)) => {
             let modified = apply_mode_modifier(decision.clone(), &input.permission_mode);
             let programs: Vec<&str> = segments.iter().map(|s| s.program.as_str()).collect();
-            let reason = build_reason(&modified, &programs, &per_program, &decision);
-            Some(match modified {
-                Decision::Allow => HookOutput::allow(reason),
-                Decision::Ask => HookOutput::ask(reason),
-                Decision::Deny => HookOutput::deny(reason),
+            let reason = build_reason(
+                &modified,
+                &programs,
+                &per_program,
+                &rules,
+                &decision,
+                sources,
+            );
+            Some(Verdict {
+                decision,
+                modified,
+                reason,
             })
         }
-        None => None,
+        (None, None) => None,
     }
 }
+
+/// Outcome of the rewrite stage.
+enum Rewrite<'a> {
+    /// No rewrite rule matches, or its edits change nothing.
+    Unchanged,
+    /// The full rewritten command string, and the rule that rewrote it.
+    Rewritten(String, &'a RewriteRule),
+    /// A rule matches but the command cannot be rebuilt safely.
+    Unsupported(&'a RewriteRule),
+}
+
+/// Apply the first rewrite rule matching any segment of the command.
+///
+/// Edits apply to the command's words as written, since parsed arguments
+/// are normalized (`-rf` becomes `-r -f`); [`RewriteRule::apply`] splits
+/// only the flag bundles it edits. Only a single simple command of
+/// plain words is rewritten. Pipelines, lists, redirections, wrappers,
+/// assignments, quoting, and substitutions are `Unsupported`, so the caller can
+/// fail closed rather than drop or misread part of the command.
+fn rewrite_command<'a>(command: &str, parsed: &ParsedCommand, bash: &'a BashConfig) -> Rewrite<'a> {
+    let Some((segment, rule)) = parsed
+        .segments
+        .iter()
+        .find_map(|segment| bash.rewrite(segment).map(|rule| (segment, rule)))
+    else {
+        return Rewrite::Unchanged;
+    };
+
+    let words: Vec<&str> = command.split_whitespace().collect();
+    let rebuildable = parsed.segments.len() == 1
+        && parsed.redirects.is_empty()
+        && words.iter().all(|word| is_plain_word(word))
+        && words
+            .first()
+            .is_some_and(|program| ProgramName::new(program) == segment.program);
+    if !rebuildable {
+        return Rewrite::Unsupported(rule);
+    }
+
+    let original: Vec<String> = words[1..].iter().map(|word| word.to_string()).collect();
+    let args = rule.apply(&original);
+    if args == original {
+        return Rewrite::Unchanged;
+    }
+    let rewritten: Vec<String> = std::iter::once(words[0].to_string()).chain(args).collect();
+    Rewrite::Rewritten(rewritten.join(" "), rule)
+}
+
+/// A word whitespace splitting reads the same way the shell does: no
+/// quoting, escapes, substitutions, comments, or operators.
+fn is_plain_word(word: &str) -> bool {
+    !word.contains(|c| "'\"\\$`#();&|<>{}".contains(c))
+}
+
+/// A file a bash command opens, to be checked against `files` rules.
+pub(super) struct FileAccess {
+    /// The path, resolved against the directory the command runs in; as
+    /// written when that directory is not known.
+    pub(super) path: String,
+    pub(super) operation: FileOperation,
+    /// A relative path in a directory only the shell can tell (`cd "$DIR"`).
+    pub(super) unknown_dir: bool,
+}
+
+impl FileAccess {
+    /// One access per directory the command may run in.
+    fn in_dir(path: &str, operation: FileOperation, cwd: &WorkingDir) -> Vec<FileAccess> {
+        match cwd.resolve(path) {
+            Some(paths) => paths
+                .into_iter()
+                .map(|path| FileAccess {
+                    path,
+                    operation,
+                    unknown_dir: false,
+                })
+                .collect(),
+            None => vec![FileAccess {
+                path: path.to_string(),
+                operation,
+                unknown_dir: true,
+            }],
+        }
+    }
+
+    /// Whether the path can only be resolved by running the shell.
+    ///
+    /// Paths are unquoted before they get here, so quotes or backslashes
+    /// left over mean quoting [`command::unquote`] could not resolve.
+    pub(super) fn is_dynamic(&self) -> bool {
+        self.unknown_dir || self.path.contains(['$', '`', '\'', '"', '\\'])
+    }
+}
+
+/// Result of checking one [`FileAccess`] against `files` rules.
+struct FileCheck {
+    path: String,
+    operation: FileOperation,
+    decision: Decision,
+    /// The deciding rule; `None` when the path itself failed closed.
+    rule: Option<RuleMatch>,
+}
+
+/// Every file the command opens: redirection targets, plus path arguments
+/// of programs in the path-args table.
+///
+/// Relative paths are resolved against the directory the command runs in
+/// after `cd`, `pushd`, and `popd`, once for each directory it may be in.
+pub(super) fn file_accesses(parsed: &ParsedCommand, config: &Config) -> Vec<FileAccess> {
+    let mut accesses = redirect_accesses(&parsed.redirects);
+    if let Some(bash) = &config.bash {
+        accesses.extend(argument_accesses(&parsed.segments, &bash.path_args));
+    }
+    accesses
+}
+
+/// Map redirections onto the file operations they perform.
+///
+/// `<` reads, `>`/`>>`/`&>` write, and `<>` does both.
+fn redirect_accesses(redirects: &[Redirect]) -> Vec<FileAccess> {
+    redirects
+        .iter()
+        .flat_map(|redirect| {
+            let operations: &[FileOperation] = match redirect.direction {
+                RedirectDirection::Read => &[FileOperation::Read],
+                RedirectDirection::Write => &[FileOperation::Write],
+                RedirectDirection::ReadWrite => &[FileOperation::Read, FileOperation::Write],
+            };
+            operations.iter().flat_map(|&operation| {
+                FileAccess::in_dir(&redirect.target, operation, &redirect.cwd)
+            })
+        })
+        .collect()
+}
+
+/// Map the path arguments of programs in the path-args table onto the file
+/// operations the programs perform on them.
+fn argument_accesses(segments: &[CommandSegment], table: &PathArgsTable) -> Vec<FileAccess> {
+    segments
+        .iter()
+        .flat_map(|segment| {
+            table
+                .accesses(segment.program.as_str(), &segment.args)
+                .into_iter()
+                .flat_map(|(path, operation)| {
+                    let path = command::unquote(&path).unwrap_or(path);
+                    FileAccess::in_dir(&path, operation, &segment.cwd)
+                })
+        })
+        .collect()
+}
+
+/// Look up each file access against the `files` section.
+///
+/// Accesses no rule matches are dropped — they carry no opinion. Paths that
+/// cannot be resolved statically (`$OUT`, `` `mktemp` ``, or a relative path
+/// after `cd "$DIR"`) or normalized (`~` without `$HOME`) fail closed to
+/// `Ask`. Returns nothing when there is no `files` section.
+fn check_file_accesses(
+    accesses: &[FileAccess],
+    input: &HookInput,
+    config: &Config,
+) -> Vec<FileCheck> {
+    let Some(files) = config.files.as_ref() else {
+        return vec![];
+    };
+    accesses
+        .iter()
+        .filter_map(|access| {
+            let (decision, rule) = if access.is_dynamic() {
+                (Decision::Ask, None)
+            } else {
+                match crate::path::normalize(&access.path, &input.cwd) {
+                    Ok(normalized) => {
+                        let found =
+                            files.lookup_match(&normalized, access.operation, &input.cwd)?;
+                        (found.decision.clone(), Some(found))
+                    }
+                    Err(_) => (Decision::Ask, None),
+                }
+            };
+            Some(FileCheck {
+                path: access.path.clone(),
+                operation: access.operation,
+                decision,
+                rule,
+            })
+        })
+        .collect()
+}
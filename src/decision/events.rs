@@ -0,0 +1,71 @@
+use crate::config::{Config, RuleMatch};
+use crate::protocol::output::Decision;
+use crate::protocol::{EventOutput, HookInput};
+
+use super::audit::Audit;
+use super::evaluate_tool_audited;
+use super::reason::build_prompt_reason;
+
+/// Answer a permission dialog with the tool decision, carrying over any
+/// rewritten input.
+///
+/// `ask` (including every call without a config) returns `None` so the
+/// dialog is shown as usual.
+pub(super) fn permission_request(
+    input: &HookInput,
+    config: Option<&Config>,
+    audit: &mut Audit,
+) -> Option<EventOutput> {
+    let output = evaluate_tool_audited(input, config, audit)?.hook_specific_output;
+    match output.permission_decision {
+        Decision::Allow => Some(EventOutput::permission_allow(output.updated_input)),
+        Decision::Deny => Some(EventOutput::permission_deny(
+            output.permission_decision_reason,
+        )),
+        Decision::Ask => None,
+    }
+}
+
+/// Audit a completed tool call: a call the rules deny is reported back to
+/// Claude as blocking feedback.
+pub(super) fn post_tool_use(
+    input: &HookInput,
+    config: Option<&Config>,
+    audit: &mut Audit,
+) -> Option<EventOutput> {
+    let output = evaluate_tool_audited(input, config, audit)?.hook_specific_output;
+    match output.permission_decision {
+        Decision::Deny => Some(EventOutput::block(
+            "PostToolUse",
+            output.permission_decision_reason,
+        )),
+        Decision::Allow | Decision::Ask => None,
+    }
+}
+
+/// Block a submitted prompt that matches a `prompt` deny rule.
+pub(super) fn user_prompt_submit(
+    input: &HookInput,
+    config: Option<&Config>,
+    audit: &mut Audit,
+) -> Option<EventOutput> {
+    let prompt = input.prompt.as_deref()?;
+    let config = config?;
+    let rule = config.prompt.as_ref().and_then(|p| p.lookup(prompt));
+    audit.looked_up(
+        "prompt",
+        prompt,
+        rule.map(|rule| RuleMatch {
+            decision: Decision::Deny,
+            tier: Decision::Deny,
+            rule: rule.raw_pattern.clone(),
+            line: rule.line,
+        })
+        .as_ref(),
+    );
+    let rule = rule?;
+    Some(EventOutput::block(
+        "UserPromptSubmit",
+        build_prompt_reason(&rule.raw_pattern, rule.line, &config.sources),
+    ))
+}
@@ -0,0 +1,323 @@
+use std::fmt::Write;
+
+use crate::command::{self, CommandSegment, ParsedCommand};
+use crate::config::rule::BashRule;
+use crate::config::{BashConfig, Config, FilesConfig};
+use crate::protocol::output::Decision;
+use crate::protocol::{FileOperation, HookInput, ToolUse};
+
+use super::aggregation::{aggregate_decisions, apply_mode_modifier};
+use super::bash::file_accesses;
+use super::reason::{decision_str, describe_rule, operation_str};
+
+/// Trace the evaluation of a tool call step by step, as human-readable text.
+///
+/// Shows what [`super::evaluate_tool`] looks at: for Bash, the parsed
+/// segments, the wrappers unwrapped to reach them, every rule naming each
+/// segment's program with pass/fail per condition, and the files the command
+/// opens; for file tools, each path and the rules covering the operation.
+/// Both end with the aggregation and the mode modifier. The final decision
+/// and reason come from `evaluate_tool` itself, so they always agree with the
+/// hook.
+pub(crate) fn explain(input: &HookInput, config: Option<&Config>) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "Tool: {}", input.tool_name);
+    let _ = writeln!(out, "Mode: {}", input.permission_mode.as_str());
+
+    if let Some(config) = config {
+        let tool_use = ToolUse::parse(&input.tool_name, &input.tool_input);
+        match &tool_use {
+            ToolUse::Bash {
+                command: Some(command),
+            } => explain_bash(&mut out, command, input, config),
+            _ => {
+                if let (Some(operation), Some(paths)) =
+                    (tool_use.file_operation(), tool_use.file_paths(&input.cwd))
+                {
+                    explain_file_tool(&mut out, operation, &paths, input, config);
+                }
+            }
+        }
+    }
+
+    let _ = writeln!(out);
+    match super::evaluate_tool(input, config) {
+        Some(output) => {
+            let specific = output.hook_specific_output;
+            let _ = writeln!(
+                out,
+                "Decision: {}",
+                decision_str(&specific.permission_decision)
+            );
+            let _ = writeln!(out, "Reason: {}", specific.permission_decision_reason);
+            if let Some(updated) = specific.updated_input {
+                let _ = writeln!(out, "Updated input: {updated}");
+            }
+        }
+        None => {
+            let _ = writeln!(
+                out,
+                "Decision: no opinion (Claude Code's own permission settings apply)"
+            );
+        }
+    }
+    out
+}
+
+fn explain_bash(out: &mut String, command: &str, input: &HookInput, config: &Config) {
+    let _ = writeln!(out, "Command: {command}");
+    let max_depth = config
+        .bash
+        .as_ref()
+        .map_or(command::DEFAULT_MAX_NESTING_DEPTH, |b| b.max_nesting_depth);
+    let parsed = match command::parse_with_depth_limit(command, max_depth) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            let _ = writeln!(out, "\nParse error: {e}");
+            return;
+        }
+    };
+
+    let _ = writeln!(out, "\nSegments:");
+    for (i, segment) in parsed.segments.iter().enumerate() {
+        if segment.cwd.is_start() {
+            let _ = writeln!(out, "  {}. {}", i + 1, segment);
+        } else {
+            let _ = writeln!(out, "  {}. {} (in {})", i + 1, segment, segment.cwd);
+        }
+    }
+    if !parsed.unwraps.is_empty() {
+        let _ = writeln!(out, "\nUnwrapped:");
+        for unwrap in &parsed.unwraps {
+            let kind = if unwrap.launcher {
+                "launcher, kept as a segment"
+            } else {
+                "wrapper"
+            };
+            let consumed = if unwrap.consumed.is_empty() {
+                String::new()
+            } else {
+                format!(", consumed: {}", unwrap.consumed.join(" "))
+            };
+            let _ = writeln!(out, "  {} ({kind}{consumed})", unwrap.wrapper);
+        }
+    }
+
+    let mut per_program = Vec::new();
+    for (i, segment) in parsed.segments.iter().enumerate() {
+        let _ = writeln!(out, "\nSegment {}: {}", i + 1, segment);
+        let found = match &config.bash {
+            Some(bash) => {
+                explain_segment_rules(out, bash, segment, config);
+                bash.lookup_match(segment)
+            }
+            None => None,
+        };
+        match &found {
+            Some(found) => {
+                let _ = writeln!(out, "  => {}", describe_rule(found, &config.sources, None));
+            }
+            None => {
+                let _ = writeln!(out, "  => unlisted (no opinion)");
+            }
+        }
+        per_program.push(found.map(|m| m.decision));
+    }
+
+    let per_file = explain_bash_files(out, &parsed, input, config);
+    let program_decision = explain_aggregation(out, &per_program, input);
+    let strictest_file = per_file
+        .into_iter()
+        .flatten()
+        .filter(|d| *d != Decision::Allow)
+        .max_by_key(Decision::severity);
+    if let Some(file_decision) = strictest_file {
+        if program_decision
+            .as_ref()
+            .is_none_or(|d| file_decision.severity() > d.severity())
+        {
+            let _ = writeln!(
+                out,
+                "Files: {} is stricter and takes precedence",
+                decision_str(&file_decision)
+            );
+        }
+    }
+}
+
+/// List every rule naming the segment's program, in lookup order.
+fn explain_segment_rules(
+    out: &mut String,
+    bash: &BashConfig,
+    segment: &CommandSegment,
+    config: &Config,
+) {
+    let tiers: [(Decision, &[BashRule]); 3] = [
+        (Decision::Deny, &bash.deny),
+        (Decision::Ask, &bash.ask),
+        (Decision::Allow, &bash.allow),
+    ];
+    let mut any = false;
+    for (tier, rules) in tiers {
+        for rule in rules.iter().filter(|rule| rule.program == segment.program) {
+            any = true;
+            let checks = rule.check_conditions(segment);
+            let matched = checks.iter().all(|check| check.passed);
+            let _ = writeln!(
+                out,
+                "  {} \"{}\" ({}): {}",
+                decision_str(&tier),
+                rule.raw,
+                config.sources.location(rule.line),
+                if matched { "match" } else { "no match" }
+            );
+            for check in checks {
+                let _ = writeln!(
+                    out,
+                    "    {} {}: {}",
+                    if check.passed { "pass" } else { "FAIL" },
+                    check.kind,
+                    check.expected
+                );
+            }
+        }
+    }
+    if !any {
+        let _ = writeln!(out, "  no rules for '{}'", segment.program);
+    }
+}
+
+/// Files the command opens, each with the `files` rules covering it.
+/// Returns each file's decision.
+fn explain_bash_files(
+    out: &mut String,
+    parsed: &ParsedCommand,
+    input: &HookInput,
+    config: &Config,
+) -> Vec<Option<Decision>> {
+    let Some(files) = &config.files else {
+        return vec![];
+    };
+    let accesses = file_accesses(parsed, config);
+    if !accesses.is_empty() {
+        let _ = writeln!(out, "\nFiles opened:");
+    }
+    accesses
+        .iter()
+        .map(|access| {
+            if access.is_dynamic() {
+                let _ = writeln!(
+                    out,
+                    "  {} ({})\n    => ask (cannot be resolved without running the shell)",
+                    access.path,
+                    operation_str(access.operation)
+                );
+                return Some(Decision::Ask);
+            }
+            explain_path(out, files, &access.path, access.operation, input, config)
+        })
+        .collect()
+}
+
+fn explain_file_tool(
+    out: &mut String,
+    operation: FileOperation,
+    paths: &[String],
+    input: &HookInput,
+    config: &Config,
+) {
+    let _ = writeln!(out, "Operation: {}", operation_str(operation));
+    let Some(files) = &config.files else {
+        let _ = writeln!(out, "\nNo files section: file tools get no opinion");
+        return;
+    };
+    let _ = writeln!(out, "\nPaths:");
+    let per_path: Vec<Option<Decision>> = paths
+        .iter()
+        .map(|path| explain_path(out, files, path, operation, input, config))
+        .collect();
+    explain_aggregation(out, &per_path, input);
+}
+
+/// Show how one path resolves against `files` rules; returns its decision.
+fn explain_path(
+    out: &mut String,
+    files: &FilesConfig,
+    path: &str,
+    operation: FileOperation,
+    input: &HookInput,
+    config: &Config,
+) -> Option<Decision> {
+    let _ = writeln!(out, "  {path} ({})", operation_str(operation));
+    let normalized = match crate::path::normalize(path, &input.cwd) {
+        Ok(normalized) => normalized,
+        Err(e) => {
+            let _ = writeln!(out, "    => ask (cannot normalize: {e})");
+            return Some(Decision::Ask);
+        }
+    };
+    if normalized != path {
+        let _ = writeln!(out, "    normalized: {normalized}");
+    }
+    for (tier, rule, matched) in files.considered(&normalized, operation, &input.cwd) {
+        let _ = writeln!(
+            out,
+            "    {} \"{}\" ({}): {}",
+            decision_str(&tier),
+            rule.raw_pattern,
+            config.sources.location(rule.line),
+            if matched { "match" } else { "no match" }
+        );
+    }
+    let found = files.lookup_match(&normalized, operation, &input.cwd);
+    match &found {
+        Some(found) => {
+            let _ = writeln!(
+                out,
+                "    => {} by {}",
+                decision_str(&found.decision),
+                describe_rule(found, &config.sources, None)
+            );
+        }
+        None => {
+            let _ = writeln!(out, "    => no rule matches (no opinion)");
+        }
+    }
+    found.map(|m| m.decision)
+}
+
+/// Show the aggregated decision and what the permission mode makes of it.
+/// Returns the aggregated decision.
+fn explain_aggregation(
+    out: &mut String,
+    decisions: &[Option<Decision>],
+    input: &HookInput,
+) -> Option<Decision> {
+    let _ = writeln!(out);
+    let Some(aggregated) = aggregate_decisions(decisions) else {
+        let _ = writeln!(out, "Aggregate: no opinion (nothing listed)");
+        return None;
+    };
+    let listed: Vec<&str> = decisions
+        .iter()
+        .map(|d| d.as_ref().map_or("unlisted", decision_str))
+        .collect();
+    let _ = writeln!(
+        out,
+        "Aggregate: {} (most restrictive of: {}; unlisted counts as ask)",
+        decision_str(&aggregated),
+        listed.join(", ")
+    );
+    let modified = apply_mode_modifier(aggregated.clone(), &input.permission_mode);
+    let effect = if modified == aggregated {
+        "unchanged".to_string()
+    } else {
+        format!(
+            "{} -> {}",
+            decision_str(&aggregated),
+            decision_str(&modified)
+        )
+    };
+    let _ = writeln!(out, "Mode {}: {effect}", input.permission_mode.as_str());
+    Some(aggregated)
+}
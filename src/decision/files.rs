@@ -1,20 +1,31 @@
-use crate::config::Config;
+use serde_json::Value;
+
+use crate::config::files::{FileRewrite, FilesConfig};
+use crate::config::{Config, RuleMatch};
 use crate::protocol::output::Decision;
-use crate::protocol::{HookInput, HookOutput, ToolUse};
+use crate::protocol::{FileOperation, HookInput, HookOutput, ToolUse};
 
-use super::aggregation::{aggregate_decisions, apply_mode_modifier};
-use super::reason::{build_file_reason, operation_str};
+use super::aggregation::{after_rewrite, aggregate_decisions, apply_mode_modifier, Verdict};
+use super::audit::Audit;
+use super::reason::{
+    build_file_reason, build_rewrite_reason, build_unrewritable_reason, operation_str,
+};
 use super::APP_NAME;
 
 /// Evaluate a file tool invocation against file config rules.
 ///
 /// Receives the already-parsed `ToolUse` variant with typed path data.
 /// Flow: check files config → extract paths → fail-closed on empty → normalize
-/// → lookup per-path → aggregate → apply mode → build reason.
+/// → lookup per-path → aggregate → apply mode → build reason → rewrite.
+///
+/// An allowed or asked call whose path a `rewrite` rule matches gets the
+/// replacement path through `updatedInput`. The replacement is looked up
+/// again, and the stricter of the two decisions stands.
 pub(super) fn evaluate_file_tool(
     tool_use: &ToolUse,
     input: &HookInput,
     config: &Config,
+    audit: &mut Audit,
 ) -> Option<HookOutput> {
     // No files config → no opinion on file tools (backwards compat)
     let files_config = config.files.as_ref()?;
@@ -30,28 +41,146 @@ pub(super) fn evaluate_file_tool(
         )));
     }
 
+    let verdict = decide_paths(&paths, operation, input, config, audit)?;
+
+    let rewrite = match verdict.modified {
+        Decision::Allow | Decision::Ask => rewrite_path(&paths, operation, input, files_config),
+        Decision::Deny => Rewrite::Unchanged,
+    };
+    let sources = &config.sources;
+    let (modified, reason, updated_input) = match (rewrite, tool_use.path_field()) {
+        (Rewrite::Rewritten(target, rewrite), Some(field)) => {
+            let recheck = decide_paths(
+                std::slice::from_ref(&target),
+                operation,
+                input,
+                config,
+                audit,
+            );
+            let verdict = after_rewrite(verdict, recheck, &input.permission_mode, "path");
+            let reason = build_rewrite_reason(
+                &verdict.reason,
+                &paths[0],
+                &target,
+                &rewrite.rule.raw_pattern,
+                rewrite.rule.line,
+                sources,
+            );
+            let updated = (verdict.modified != Decision::Deny).then(|| {
+                let mut updated = input.tool_input.clone();
+                updated[field] = Value::String(target);
+                updated
+            });
+            (verdict.modified, reason, updated)
+        }
+        (Rewrite::Unsupported(rewrite), _) if verdict.modified == Decision::Allow => {
+            match apply_mode_modifier(Decision::Ask, &input.permission_mode) {
+                Decision::Allow => (Decision::Allow, verdict.reason, None),
+                fallback => {
+                    let reason = build_unrewritable_reason(
+                        &fallback,
+                        "path",
+                        &rewrite.rule.raw_pattern,
+                        rewrite.rule.line,
+                        sources,
+                    );
+                    (fallback, reason, None)
+                }
+            }
+        }
+        _ => (verdict.modified, verdict.reason, None),
+    };
+
+    let output = match modified {
+        Decision::Allow => HookOutput::allow(reason),
+        Decision::Ask => HookOutput::ask(reason),
+        Decision::Deny => HookOutput::deny(reason),
+    };
+    Some(match updated_input {
+        Some(updated) => output.with_updated_input(updated),
+        None => output,
+    })
+}
+
+/// Look up each path, aggregate, and apply the permission mode.
+///
+/// Returns `None` when no rule matches any path.
+fn decide_paths(
+    paths: &[String],
+    operation: FileOperation,
+    input: &HookInput,
+    config: &Config,
+    audit: &mut Audit,
+) -> Option<Verdict> {
+    let files_config = config.files.as_ref()?;
+
     // Per-path lookup
-    let per_path: Vec<Option<Decision>> = paths
+    let (per_path, rules): (Vec<Option<Decision>>, Vec<Option<RuleMatch>>) = paths
         .iter()
-        .map(|p| match crate::path::normalize(p, &input.cwd) {
-            Ok(normalized) => files_config.lookup(&normalized, operation, &input.cwd),
-            Err(_) => Some(Decision::Ask), // fail-closed: $HOME not set
+        .map(|p| {
+            let (decision, found) = match crate::path::normalize(p, &input.cwd) {
+                Ok(normalized) => {
+                    let found = files_config.lookup_match(&normalized, operation, &input.cwd);
+                    (found.as_ref().map(|m| m.decision.clone()), found)
+                }
+                Err(_) => (Some(Decision::Ask), None), // fail-closed: $HOME not set
+            };
+            audit.path(p, operation, decision.clone(), found.as_ref());
+            (decision, found)
         })
-        .collect();
-
-    let aggregated = aggregate_decisions(&per_path);
-
-    match aggregated {
-        Some(decision) => {
-            let modified = apply_mode_modifier(decision.clone(), &input.permission_mode);
-            let op_str = operation_str(operation);
-            let reason = build_file_reason(&modified, &paths, &per_path, &decision, op_str);
-            Some(match modified {
-                Decision::Allow => HookOutput::allow(reason),
-                Decision::Ask => HookOutput::ask(reason),
-                Decision::Deny => HookOutput::deny(reason),
-            })
+        .unzip();
+
+    let decision = aggregate_decisions(&per_path)?;
+    let modified = apply_mode_modifier(decision.clone(), &input.permission_mode);
+    let reason = build_file_reason(
+        &modified,
+        paths,
+        &per_path,
+        &rules,
+        &decision,
+        operation_str(operation),
+        &config.sources,
+    );
+    Some(Verdict {
+        decision,
+        modified,
+        reason,
+    })
+}
+
+/// Outcome of the rewrite stage.
+enum Rewrite<'a> {
+    /// No rewrite rule matches, or the replacement is the same path.
+    Unchanged,
+    /// The replacement path, and the rule that produced it.
+    Rewritten(String, &'a FileRewrite),
+    /// A rule matches but its replacement needs `$HOME`, which is not set.
+    Unsupported(&'a FileRewrite),
+}
+
+/// Apply the first rewrite rule matching the call's path.
+///
+/// Only single-path calls are rewritten, which covers every file tool.
+fn rewrite_path<'a>(
+    paths: &[String],
+    operation: FileOperation,
+    input: &HookInput,
+    files: &'a FilesConfig,
+) -> Rewrite<'a> {
+    let [path] = paths else {
+        return Rewrite::Unchanged;
+    };
+    let Ok(normalized) = crate::path::normalize(path, &input.cwd) else {
+        return Rewrite::Unchanged;
+    };
+    let Some(rewrite) = files.rewrite(&normalized, operation, &input.cwd) else {
+        return Rewrite::Unchanged;
+    };
+    match rewrite.target(&input.cwd) {
+        Some(target) if crate::path::normalize(&target, &input.cwd).ok() == Some(normalized) => {
+            Rewrite::Unchanged
         }
-        None => None,
+        Some(target) => Rewrite::Rewritten(target, rewrite),
+        None => Rewrite::Unsupported(rewrite),
     }
 }
@@ -0,0 +1,34 @@
+use crate::config::Config;
+use crate::protocol::output::Decision;
+use crate::protocol::{HookInput, HookOutput};
+
+use super::aggregation::apply_mode_modifier;
+use super::audit::Audit;
+use super::reason::build_mcp_reason;
+
+/// Evaluate an MCP tool call against mcp config rules.
+///
+/// Rules match the full tool name (`mcp__<server>__<tool>`) and, optionally,
+/// values in `tool_input`. Returns `None` when no rule matches.
+pub(super) fn evaluate_mcp_tool(
+    server: &str,
+    tool: &str,
+    input: &HookInput,
+    config: &Config,
+    audit: &mut Audit,
+) -> Option<HookOutput> {
+    let found = config
+        .mcp
+        .as_ref()?
+        .lookup_match(&input.tool_name, &input.tool_input);
+    audit.looked_up("tool", &input.tool_name, found.as_ref());
+    let found = found?;
+
+    let modified = apply_mode_modifier(found.decision.clone(), &input.permission_mode);
+    let reason = build_mcp_reason(&modified, server, tool, &found, &config.sources);
+    Some(match modified {
+        Decision::Allow => HookOutput::allow(reason),
+        Decision::Ask => HookOutput::ask(reason),
+        Decision::Deny => HookOutput::deny(reason),
+    })
+}
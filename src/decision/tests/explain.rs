@@ -0,0 +1,129 @@
+use super::{bash_input, make_input};
+use crate::config::Config;
+use crate::decision::explain;
+use serde_json::json;
+
+fn explain_config() -> Config {
+    Config::parse(
+        r#"
+        bash {
+            allow "git status"
+            deny "git push --force"
+            ask "git push"
+        }
+        files {
+            deny "/etc/**" "read"
+        }
+        "#,
+    )
+    .unwrap()
+}
+
+#[test]
+fn explain_shows_segments_and_unwrapping() {
+    let config = explain_config();
+    let out = explain(
+        &bash_input("sudo -u root env -i git push --force", "default"),
+        Some(&config),
+    );
+    assert!(
+        out.contains("  1. sudo -u root\n  2. git push --force\n"),
+        "{out}"
+    );
+    assert!(
+        out.contains("  sudo (launcher, kept as a segment, consumed: -u root)"),
+        "{out}"
+    );
+    assert!(out.contains("  env (wrapper, consumed: -i)"), "{out}");
+}
+
+#[test]
+fn explain_shows_each_condition_of_each_rule() {
+    let config = explain_config();
+    let out = explain(&bash_input("git push --force", "default"), Some(&config));
+    assert!(
+        out.contains(
+            "  deny \"git push --force\" (line 4): match\n    pass program: git\n    pass flags: --force\n    pass subcommand: push\n"
+        ),
+        "{out}"
+    );
+    assert!(
+        out.contains("  allow \"git status\" (line 3): no match\n    pass program: git\n    FAIL subcommand: status\n"),
+        "{out}"
+    );
+    assert!(
+        out.contains("  => deny \"git push --force\" (line 4)"),
+        "{out}"
+    );
+}
+
+#[test]
+fn explain_shows_unlisted_programs_and_aggregation() {
+    let config = explain_config();
+    let out = explain(&bash_input("git status && make", "default"), Some(&config));
+    assert!(
+        out.contains("  no rules for 'make'\n  => unlisted (no opinion)"),
+        "{out}"
+    );
+    assert!(
+        out.contains(
+            "Aggregate: ask (most restrictive of: allow, unlisted; unlisted counts as ask)"
+        ),
+        "{out}"
+    );
+    assert!(out.contains("Mode default: unchanged"), "{out}");
+    assert!(out.contains("Decision: ask"), "{out}");
+}
+
+#[test]
+fn explain_shows_mode_modifier() {
+    let config = explain_config();
+    let out = explain(&bash_input("git push", "dontAsk"), Some(&config));
+    assert!(out.contains("Mode dontAsk: ask -> deny"), "{out}");
+    assert!(out.contains("Decision: deny"), "{out}");
+}
+
+#[test]
+fn explain_shows_files_opened_by_command() {
+    let config = explain_config();
+    let out = explain(
+        &bash_input("git status < /etc/hosts", "default"),
+        Some(&config),
+    );
+    assert!(
+        out.contains("Files opened:\n  /etc/hosts (read)\n    deny \"/etc/**\" (line 8): match\n"),
+        "{out}"
+    );
+    assert!(
+        out.contains("Files: deny is stricter and takes precedence"),
+        "{out}"
+    );
+    assert!(out.contains("Decision: deny"), "{out}");
+}
+
+#[test]
+fn explain_file_tool_shows_normalized_path_and_rules() {
+    let config = explain_config();
+    let input = make_input(
+        "Read",
+        "default",
+        json!({"file_path": "/etc/../etc/passwd"}),
+    );
+    let out = explain(&input, Some(&config));
+    assert!(out.contains("Operation: read"), "{out}");
+    assert!(
+        out.contains("  /etc/../etc/passwd (read)\n    normalized: /etc/passwd\n"),
+        "{out}"
+    );
+    assert!(
+        out.contains("    => deny by deny \"/etc/**\" (line 8)"),
+        "{out}"
+    );
+}
+
+#[test]
+fn explain_without_config_has_no_trace() {
+    let out = explain(&bash_input("git status", "default"), None);
+    assert!(!out.contains("Segments:"), "{out}");
+    assert!(out.contains("Decision: ask"), "{out}");
+}
@@ -1,7 +1,7 @@
 use super::{make_config, make_input};
 use crate::config::files::{FileRule, FilesConfig};
 use crate::config::Config;
-use crate::decision::evaluate;
+use crate::decision::evaluate_tool;
 use crate::protocol::output::Decision;
 use crate::protocol::FileOperation;
 use serde_json::json;
@@ -20,7 +20,9 @@ fn file_rule(pattern: &str, ops: &[FileOperation]) -> FileRule {
     FileRule {
         home_expanded_pattern: crate::config::normalize::files::expand_home(pattern),
         raw_pattern: pattern.to_string(),
+        regex: None,
         operations: ops.iter().copied().collect::<HashSet<_>>(),
+        exceptions: vec![],
         line: 0,
     }
 }
@@ -31,7 +33,7 @@ fn file_input(tool: &str, mode: &str, tool_input: serde_json::Value) -> crate::p
 
 /// Extract decision from evaluate result.
 fn file_decision(input: &crate::protocol::HookInput, config: &Config) -> Decision {
-    evaluate(input, Some(config))
+    evaluate_tool(input, Some(config))
         .unwrap()
         .hook_specific_output
         .permission_decision
@@ -136,7 +138,7 @@ fn file_no_files_config_returns_none() {
     // Config with bash only, no files section
     let config = make_config(&["git"], &[], &[]);
     let input = file_input("Read", "default", json!({"file_path": "/tmp/test.txt"}));
-    assert!(evaluate(&input, Some(&config)).is_none());
+    assert!(evaluate_tool(&input, Some(&config)).is_none());
 }
 
 // ---- Deny > ask > allow precedence ----
@@ -147,6 +149,7 @@ fn file_deny_beats_ask_beats_allow() {
         allow: vec![file_rule("/**", &[FileOperation::Read])],
         ask: vec![file_rule("/**", &[FileOperation::Read])],
         deny: vec![file_rule("/**", &[FileOperation::Read])],
+        ..Default::default()
     });
     let input = file_input("Read", "default", json!({"file_path": "/any/path"}));
     assert_eq!(file_decision(&input, &config), Decision::Deny);
@@ -224,7 +227,7 @@ fn file_operation_mismatch_returns_none() {
     });
     // Write tool but allow rule only has read → no match → None
     let input = file_input("Write", "default", json!({"file_path": "/tmp/test.txt"}));
-    assert!(evaluate(&input, Some(&config)).is_none());
+    assert!(evaluate_tool(&input, Some(&config)).is_none());
 }
 
 // ---- Variable expansion in rules ----
@@ -334,11 +337,12 @@ fn file_config_with_both_bash_and_files() {
             allow: vec![file_rule("<cwd>/**", &[FileOperation::Read])],
             ..Default::default()
         }),
+        ..Default::default()
     };
     // Bash still evaluates independently
     let bash_in = super::bash_input("git status", "default");
     assert_eq!(
-        evaluate(&bash_in, Some(&config))
+        evaluate_tool(&bash_in, Some(&config))
             .unwrap()
             .hook_specific_output
             .permission_decision,
@@ -353,6 +357,78 @@ fn file_config_with_both_bash_and_files() {
     assert_eq!(file_decision(&read_in, &config), Decision::Allow);
 }
 
+// ---- MultiEdit, NotebookEdit and LS ----
+
+#[test]
+fn multi_edit_checked_as_edit() {
+    let config = make_files_config(FilesConfig {
+        deny: vec![file_rule("/etc/**", &[FileOperation::Edit])],
+        ..Default::default()
+    });
+    let input = file_input(
+        "MultiEdit",
+        "default",
+        json!({"file_path": "/etc/hosts", "edits": []}),
+    );
+    assert_eq!(file_decision(&input, &config), Decision::Deny);
+}
+
+#[test]
+fn notebook_edit_uses_notebook_edit_operation() {
+    let config = make_files_config(FilesConfig {
+        deny: vec![file_rule("/secret/**", &[FileOperation::NotebookEdit])],
+        allow: vec![file_rule("<cwd>/**", &[FileOperation::NotebookEdit])],
+        ..Default::default()
+    });
+    let input = file_input(
+        "NotebookEdit",
+        "default",
+        json!({"notebook_path": "/secret/keys.ipynb", "new_source": "x"}),
+    );
+    assert_eq!(file_decision(&input, &config), Decision::Deny);
+    let input = file_input(
+        "NotebookEdit",
+        "default",
+        json!({"notebook_path": "/home/user/project/a.ipynb", "new_source": "x"}),
+    );
+    assert_eq!(file_decision(&input, &config), Decision::Allow);
+}
+
+#[test]
+fn notebook_edit_not_covered_by_edit_rule() {
+    let config = make_files_config(FilesConfig {
+        deny: vec![file_rule("/secret/**", &[FileOperation::Edit])],
+        ..Default::default()
+    });
+    let input = file_input(
+        "NotebookEdit",
+        "default",
+        json!({"notebook_path": "/secret/keys.ipynb"}),
+    );
+    assert!(evaluate_tool(&input, Some(&config)).is_none());
+}
+
+#[test]
+fn ls_uses_list_operation() {
+    let home = std::env::var("HOME").unwrap();
+    let config = make_files_config(FilesConfig {
+        deny: vec![file_rule("~/.ssh", &[FileOperation::List])],
+        ..Default::default()
+    });
+    let input = file_input("LS", "default", json!({"path": format!("{home}/.ssh")}));
+    assert_eq!(file_decision(&input, &config), Decision::Deny);
+}
+
+#[test]
+fn ls_missing_path_fails_closed() {
+    let config = make_files_config(FilesConfig {
+        allow: vec![file_rule("/**", &[FileOperation::List])],
+        ..Default::default()
+    });
+    let input = file_input("LS", "default", json!({}));
+    assert_eq!(file_decision(&input, &config), Decision::Ask);
+}
+
 // ---- Unknown tool still returns None ----
 
 #[test]
@@ -361,8 +437,8 @@ fn unknown_tool_with_files_config_returns_none() {
         allow: vec![file_rule("/**", &[FileOperation::Read])],
         ..Default::default()
     });
-    let input = make_input("NotebookEdit", "default", json!({}));
-    assert!(evaluate(&input, Some(&config)).is_none());
+    let input = make_input("TodoWrite", "default", json!({}));
+    assert!(evaluate_tool(&input, Some(&config)).is_none());
 }
 
 // ---- Path normalization in evaluation ----
@@ -378,3 +454,350 @@ fn file_relative_path_normalized_to_cwd() {
     // Normalized: /home/user/project/src/main.rs → matches <cwd>/**
     assert_eq!(file_decision(&input, &config), Decision::Allow);
 }
+
+// ---- Bash redirect targets checked against file rules ----
+
+fn bash_and_files_config(allow: &[&str], files: FilesConfig) -> Config {
+    Config {
+        bash: Some(crate::config::BashConfig {
+            allow: super::rules_of(allow),
+            ..Default::default()
+        }),
+        files: Some(files),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn bash_write_redirect_to_denied_path() {
+    let config = bash_and_files_config(
+        &["echo"],
+        FilesConfig {
+            deny: vec![file_rule("~/.ssh/**", &[FileOperation::Write])],
+            ..Default::default()
+        },
+    );
+    let input = super::bash_input("echo key >> ~/.ssh/authorized_keys", "default");
+    assert_eq!(file_decision(&input, &config), Decision::Deny);
+}
+
+#[test]
+fn bash_read_redirect_to_denied_path() {
+    let config = bash_and_files_config(
+        &["mail"],
+        FilesConfig {
+            deny: vec![file_rule("~/.aws/**", &[FileOperation::Read])],
+            ..Default::default()
+        },
+    );
+    let input = super::bash_input("mail root < ~/.aws/credentials", "default");
+    assert_eq!(file_decision(&input, &config), Decision::Deny);
+}
+
+#[test]
+fn bash_redirect_operation_mismatch_keeps_program_decision() {
+    // Rule denies writes only; `<` reads the file
+    let config = bash_and_files_config(
+        &["sort"],
+        FilesConfig {
+            deny: vec![file_rule("/etc/**", &[FileOperation::Write])],
+            ..Default::default()
+        },
+    );
+    let input = super::bash_input("sort < /etc/hosts", "default");
+    assert_eq!(file_decision(&input, &config), Decision::Allow);
+}
+
+#[test]
+fn bash_redirect_ask_overrides_allowed_program() {
+    let config = bash_and_files_config(
+        &["echo"],
+        FilesConfig {
+            ask: vec![file_rule("/etc/**", &[FileOperation::Write])],
+            ..Default::default()
+        },
+    );
+    let input = super::bash_input("echo 127.0.0.1 > /etc/hosts", "default");
+    assert_eq!(file_decision(&input, &config), Decision::Ask);
+}
+
+#[test]
+fn bash_redirect_unmatched_path_has_no_effect() {
+    let config = bash_and_files_config(
+        &["echo"],
+        FilesConfig {
+            deny: vec![file_rule("~/.ssh/**", &[FileOperation::Write])],
+            ..Default::default()
+        },
+    );
+    let input = super::bash_input("echo hi > out.txt", "default");
+    assert_eq!(file_decision(&input, &config), Decision::Allow);
+}
+
+#[test]
+fn bash_redirect_allow_does_not_grant_unlisted_program() {
+    let config = bash_and_files_config(
+        &[],
+        FilesConfig {
+            allow: vec![file_rule("<cwd>/**", &[FileOperation::Write])],
+            ..Default::default()
+        },
+    );
+    let input = super::bash_input("python gen.py > out.txt", "default");
+    assert!(evaluate_tool(&input, Some(&config)).is_none());
+}
+
+#[test]
+fn bash_redirect_deny_applies_to_unlisted_program() {
+    let config = bash_and_files_config(
+        &[],
+        FilesConfig {
+            deny: vec![file_rule("/etc/**", &[FileOperation::Write])],
+            ..Default::default()
+        },
+    );
+    let input = super::bash_input("python gen.py > /etc/passwd", "default");
+    assert_eq!(file_decision(&input, &config), Decision::Deny);
+}
+
+#[test]
+fn bash_dynamic_redirect_target_fails_closed() {
+    let config = bash_and_files_config(
+        &["echo"],
+        FilesConfig {
+            deny: vec![file_rule("~/.ssh/**", &[FileOperation::Write])],
+            ..Default::default()
+        },
+    );
+    let input = super::bash_input("echo hi > \"$TARGET\"", "default");
+    assert_eq!(file_decision(&input, &config), Decision::Ask);
+}
+
+#[test]
+fn bash_quoted_redirect_target_is_unquoted() {
+    let config = bash_and_files_config(
+        &["echo"],
+        FilesConfig {
+            deny: vec![file_rule("~/.ssh/**", &[FileOperation::Write])],
+            ..Default::default()
+        },
+    );
+    for command in [
+        "echo k > ~/.ss''h/authorized_keys",
+        "echo k > ~/.ss\"h\"/authorized_keys",
+        "echo k > ~/.s\\sh/authorized_keys",
+    ] {
+        let input = super::bash_input(command, "default");
+        assert_eq!(file_decision(&input, &config), Decision::Deny, "{command}");
+    }
+}
+
+#[test]
+fn bash_unresolvable_quoting_in_redirect_target_fails_closed() {
+    let config = bash_and_files_config(
+        &["echo"],
+        FilesConfig {
+            deny: vec![file_rule("~/.ssh/**", &[FileOperation::Write])],
+            ..Default::default()
+        },
+    );
+    let input = super::bash_input("echo k > ~/$'.ssh'/authorized_keys", "default");
+    assert_eq!(file_decision(&input, &config), Decision::Ask);
+}
+
+#[test]
+fn bash_redirect_ignored_without_files_config() {
+    let config = make_config(&["echo"], &[], &[]);
+    let input = super::bash_input("echo hi > \"$TARGET\"", "default");
+    assert_eq!(file_decision(&input, &config), Decision::Allow);
+}
+
+// ---- Bash program path arguments checked against file rules ----
+
+#[test]
+fn bash_cat_of_denied_path() {
+    let config = bash_and_files_config(
+        &["cat"],
+        FilesConfig {
+            deny: vec![file_rule("~/.ssh/**", &[FileOperation::Read])],
+            ..Default::default()
+        },
+    );
+    let input = super::bash_input("cat ~/.ssh/id_rsa", "default");
+    assert_eq!(file_decision(&input, &config), Decision::Deny);
+}
+
+#[test]
+fn bash_quoted_path_argument_is_unquoted() {
+    let config = bash_and_files_config(
+        &["cat", "cp"],
+        FilesConfig {
+            deny: vec![file_rule(
+                "~/.ssh/**",
+                &[FileOperation::Read, FileOperation::Write],
+            )],
+            ..Default::default()
+        },
+    );
+    for command in [
+        "cat ~/.ss\"\"h/id_rsa",
+        "cat ~/.ss''h/id_rsa",
+        "cat '~'/.ssh/id_rsa",
+        "cat ~/.s\\sh/id_rsa",
+        "cp key ~/\".ssh\"/authorized_keys",
+    ] {
+        let input = super::bash_input(command, "default");
+        assert_eq!(file_decision(&input, &config), Decision::Deny, "{command}");
+    }
+}
+
+#[test]
+fn bash_unresolvable_quoting_in_path_argument_fails_closed() {
+    let config = bash_and_files_config(
+        &["cat"],
+        FilesConfig {
+            deny: vec![file_rule("~/.ssh/**", &[FileOperation::Read])],
+            ..Default::default()
+        },
+    );
+    let input = super::bash_input("cat ~/$'.ssh'/id_rsa", "default");
+    assert_eq!(file_decision(&input, &config), Decision::Ask);
+}
+
+#[test]
+fn bash_cp_destination_checked_as_write() {
+    let config = bash_and_files_config(
+        &["cp"],
+        FilesConfig {
+            deny: vec![file_rule("~/.bashrc", &[FileOperation::Write])],
+            ..Default::default()
+        },
+    );
+    let input = super::bash_input("cp x ~/.bashrc", "default");
+    assert_eq!(file_decision(&input, &config), Decision::Deny);
+}
+
+#[test]
+fn bash_cp_source_checked_as_read() {
+    // Rule denies writes only; the source is read
+    let config = bash_and_files_config(
+        &["cp"],
+        FilesConfig {
+            deny: vec![file_rule("/etc/**", &[FileOperation::Write])],
+            ..Default::default()
+        },
+    );
+    let input = super::bash_input("cp /etc/hosts hosts.bak", "default");
+    assert_eq!(file_decision(&input, &config), Decision::Allow);
+}
+
+#[test]
+fn bash_sed_in_place_checked_as_write() {
+    let config = bash_and_files_config(
+        &["sed"],
+        FilesConfig {
+            deny: vec![file_rule("/etc/**", &[FileOperation::Write])],
+            ..Default::default()
+        },
+    );
+    let input = super::bash_input("sed -i 's/a/b/' /etc/hosts", "default");
+    assert_eq!(file_decision(&input, &config), Decision::Deny);
+
+    let input = super::bash_input("sed 's/a/b/' /etc/hosts", "default");
+    assert_eq!(file_decision(&input, &config), Decision::Allow);
+}
+
+#[test]
+fn bash_option_value_not_checked_as_path() {
+    let config = bash_and_files_config(
+        &["head"],
+        FilesConfig {
+            deny: vec![file_rule("<cwd>/5", &[FileOperation::Read])],
+            ..Default::default()
+        },
+    );
+    let input = super::bash_input("head -n 5 log.txt", "default");
+    assert_eq!(file_decision(&input, &config), Decision::Allow);
+}
+
+#[test]
+fn bash_path_argument_in_nested_command() {
+    let config = bash_and_files_config(
+        &["echo", "cat"],
+        FilesConfig {
+            deny: vec![file_rule("~/.aws/**", &[FileOperation::Read])],
+            ..Default::default()
+        },
+    );
+    let input = super::bash_input("echo $(cat ~/.aws/credentials)", "default");
+    assert_eq!(file_decision(&input, &config), Decision::Deny);
+}
+
+#[test]
+fn bash_configured_path_args_checked() {
+    let config = crate::config::Config::parse(
+        r#"
+        bash {
+            allow "upload"
+            path-args "upload" { read "positionals" }
+        }
+        files {
+            deny "~/.ssh/**" "read"
+        }
+        "#,
+    )
+    .unwrap();
+    let input = super::bash_input("upload ~/.ssh/id_rsa", "default");
+    assert_eq!(file_decision(&input, &config), Decision::Deny);
+}
+
+// ---- Bash paths resolved in the directory after cd ----
+
+#[test]
+fn bash_relative_path_after_cd_checked_in_new_directory() {
+    let config = bash_and_files_config(
+        &["cd", "cat", "echo"],
+        FilesConfig {
+            deny: vec![file_rule(
+                "/etc/**",
+                &[FileOperation::Read, FileOperation::Write],
+            )],
+            ..Default::default()
+        },
+    );
+    let input = super::bash_input("cd /etc && cat passwd", "default");
+    assert_eq!(file_decision(&input, &config), Decision::Deny);
+    let input = super::bash_input("cd /etc && echo x > hosts", "default");
+    assert_eq!(file_decision(&input, &config), Decision::Deny);
+    // The subshell's cd is gone by the time cat runs.
+    let input = super::bash_input("(cd /etc) && cat passwd", "default");
+    assert_eq!(file_decision(&input, &config), Decision::Allow);
+}
+
+#[test]
+fn bash_path_after_pushd_checked_in_every_possible_directory() {
+    let config = bash_and_files_config(
+        &["pushd", "cat"],
+        FilesConfig {
+            deny: vec![file_rule("~/.ssh/**", &[FileOperation::Read])],
+            ..Default::default()
+        },
+    );
+    let input = super::bash_input("pushd ~/.ssh; cat id_rsa", "default");
+    assert_eq!(file_decision(&input, &config), Decision::Deny);
+}
+
+#[test]
+fn bash_path_after_dynamic_cd_fails_closed() {
+    let config = bash_and_files_config(
+        &["cd", "cat"],
+        FilesConfig {
+            allow: vec![file_rule("<cwd>/**", &[FileOperation::Read])],
+            ..Default::default()
+        },
+    );
+    let input = super::bash_input("cat notes.txt", "default");
+    assert_eq!(file_decision(&input, &config), Decision::Allow);
+    let input = super::bash_input(r#"cd "$DIR" && cat notes.txt"#, "default");
+    assert_eq!(file_decision(&input, &config), Decision::Ask);
+}
@@ -0,0 +1,127 @@
+use super::make_input;
+use crate::config::Config;
+use crate::decision::evaluate_tool;
+use crate::protocol::output::Decision;
+use crate::protocol::HookInput;
+use serde_json::json;
+
+fn mcp_config() -> Config {
+    Config::parse(
+        r#"
+        mcp {
+            allow "mcp__github__get_*" "mcp__linear__*"
+            deny "mcp__github__delete_*"
+            ask "mcp__github__merge_pull_request" {
+                argument "repo" "acme/*"
+            }
+        }
+        "#,
+    )
+    .unwrap()
+}
+
+fn mcp_input(tool_name: &str, mode: &str, tool_input: serde_json::Value) -> HookInput {
+    make_input(tool_name, mode, tool_input)
+}
+
+fn mcp_decision(input: &HookInput, config: &Config) -> Decision {
+    evaluate_tool(input, Some(config))
+        .unwrap()
+        .hook_specific_output
+        .permission_decision
+}
+
+fn mcp_reason(input: &HookInput, config: &Config) -> String {
+    evaluate_tool(input, Some(config))
+        .unwrap()
+        .hook_specific_output
+        .permission_decision_reason
+}
+
+#[test]
+fn mcp_allowed_tool() {
+    let input = mcp_input("mcp__github__get_issue", "default", json!({"number": 1}));
+    assert_eq!(mcp_decision(&input, &mcp_config()), Decision::Allow);
+}
+
+#[test]
+fn mcp_allowed_server() {
+    let input = mcp_input("mcp__linear__create_issue", "default", json!({}));
+    assert_eq!(mcp_decision(&input, &mcp_config()), Decision::Allow);
+}
+
+#[test]
+fn mcp_denied_tool() {
+    let input = mcp_input("mcp__github__delete_repo", "bypassPermissions", json!({}));
+    assert_eq!(mcp_decision(&input, &mcp_config()), Decision::Deny);
+}
+
+#[test]
+fn mcp_argument_condition_modulated_by_mode() {
+    let config = mcp_config();
+    let tool_input = json!({"repo": "acme/web", "number": 7});
+    let input = mcp_input(
+        "mcp__github__merge_pull_request",
+        "default",
+        tool_input.clone(),
+    );
+    assert_eq!(mcp_decision(&input, &config), Decision::Ask);
+    let input = mcp_input("mcp__github__merge_pull_request", "dontAsk", tool_input);
+    assert_eq!(mcp_decision(&input, &config), Decision::Deny);
+}
+
+#[test]
+fn mcp_argument_mismatch_returns_none() {
+    let input = mcp_input(
+        "mcp__github__merge_pull_request",
+        "default",
+        json!({"repo": "other/web"}),
+    );
+    assert!(evaluate_tool(&input, Some(&mcp_config())).is_none());
+}
+
+#[test]
+fn mcp_unlisted_tool_returns_none() {
+    let input = mcp_input("mcp__slack__post_message", "default", json!({}));
+    assert!(evaluate_tool(&input, Some(&mcp_config())).is_none());
+}
+
+#[test]
+fn mcp_without_section_returns_none() {
+    let config = Config::parse(r#"bash { allow "git" }"#).unwrap();
+    let input = mcp_input("mcp__github__delete_repo", "default", json!({}));
+    assert!(evaluate_tool(&input, Some(&config)).is_none());
+}
+
+#[test]
+fn mcp_deny_reason_names_server_and_tool() {
+    let input = mcp_input("mcp__github__delete_repo", "default", json!({}));
+    assert_eq!(
+        mcp_reason(&input, &mcp_config()),
+        "claude-permissions-hook: MCP tool 'delete_repo' on server 'github' \
+         denied by deny \"mcp__github__delete_*\" (line 4)"
+    );
+}
+
+#[test]
+fn mcp_ask_reason_names_rule() {
+    let input = mcp_input(
+        "mcp__github__merge_pull_request",
+        "dontAsk",
+        json!({"repo": "acme/web"}),
+    );
+    assert_eq!(
+        mcp_reason(&input, &mcp_config()),
+        "claude-permissions-hook: MCP tool 'merge_pull_request' on server 'github' \
+         denied by dontAsk mode for ask \"mcp__github__merge_pull_request\" (line 5)"
+    );
+}
+
+#[test]
+fn mcp_allow_reason_names_server_and_tool() {
+    let input = mcp_input("mcp__linear__create_issue", "default", json!({}));
+    assert_eq!(
+        mcp_reason(&input, &mcp_config()),
+        "claude-permissions-hook: allowed MCP tool 'create_issue' on server 'linear'"
+    );
+}
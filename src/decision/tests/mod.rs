@@ -1,7 +1,14 @@
 mod aggregation;
+mod audit;
 mod bash;
+mod events;
+mod explain;
 mod files;
+mod mcp;
 mod reason;
+mod rewrite;
+mod tools;
+mod web;
 
 use crate::config::Config;
 use crate::protocol::HookInput;
@@ -30,6 +37,8 @@ fn rules_of(programs: &[&str]) -> Vec<crate::config::rule::BashRule> {
         .iter()
         .map(|p| crate::config::rule::BashRule {
             program: crate::domain::ProgramName::new(p),
+            raw: p.to_string(),
+            line: 0,
             conditions: crate::config::rule::RuleConditions::default(),
         })
         .collect()
@@ -41,6 +50,7 @@ fn make_config(allow: &[&str], deny: &[&str], ask: &[&str]) -> Config {
             allow: rules_of(allow),
             deny: rules_of(deny),
             ask: rules_of(ask),
+            ..Default::default()
         }),
         ..Default::default()
     }
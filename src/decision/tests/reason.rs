@@ -1,14 +1,14 @@
 use super::{bash_input, make_config, make_input};
 use crate::config::files::{FileRule, FilesConfig};
 use crate::config::Config;
-use crate::decision::evaluate;
+use crate::decision::evaluate_tool;
 use crate::protocol::FileOperation;
 use serde_json::json;
 use std::collections::HashSet;
 
-/// Helper to extract the reason string from an evaluate() result.
+/// Helper to extract the reason string from an evaluate_tool() result.
 fn reason_of(input: &crate::protocol::HookInput, config: &Config) -> String {
-    evaluate(input, Some(config))
+    evaluate_tool(input, Some(config))
         .unwrap()
         .hook_specific_output
         .permission_decision_reason
@@ -19,7 +19,9 @@ fn file_rule(pattern: &str, ops: &[FileOperation]) -> FileRule {
     FileRule {
         home_expanded_pattern: crate::config::normalize::files::expand_home(pattern),
         raw_pattern: pattern.to_string(),
+        regex: None,
         operations: ops.iter().copied().collect::<HashSet<_>>(),
+        exceptions: vec![],
         line: 0,
     }
 }
@@ -36,7 +38,7 @@ fn file_input(tool: &str, mode: &str, tool_input: serde_json::Value) -> crate::p
 }
 
 fn file_reason(input: &crate::protocol::HookInput, config: &Config) -> String {
-    evaluate(input, Some(config))
+    evaluate_tool(input, Some(config))
         .unwrap()
         .hook_specific_output
         .permission_decision_reason
@@ -70,7 +72,7 @@ fn reason_single_deny() {
     let input = bash_input("rm -rf /", "default");
     assert_eq!(
         reason_of(&input, &config),
-        "claude-permissions-hook: 'rm' is in your deny list"
+        r#"claude-permissions-hook: 'rm' denied by deny "rm" (line 0)"#
     );
 }
 
@@ -80,7 +82,7 @@ fn reason_multi_deny() {
     let input = bash_input("git add && rm -rf /", "default");
     assert_eq!(
         reason_of(&input, &config),
-        "claude-permissions-hook: 'rm' is denied (in: git, rm)"
+        r#"claude-permissions-hook: 'rm' denied by deny "rm" (line 0; in: git, rm)"#
     );
 }
 
@@ -90,7 +92,7 @@ fn reason_single_ask() {
     let input = bash_input("docker run ubuntu", "default");
     assert_eq!(
         reason_of(&input, &config),
-        "claude-permissions-hook: 'docker' requires confirmation"
+        r#"claude-permissions-hook: 'docker' requires confirmation per ask "docker" (line 0)"#
     );
 }
 
@@ -100,7 +102,7 @@ fn reason_multi_ask() {
     let input = bash_input("git pull && docker run ubuntu", "default");
     assert_eq!(
         reason_of(&input, &config),
-        "claude-permissions-hook: 'docker' requires confirmation (in: git, docker)"
+        r#"claude-permissions-hook: 'docker' requires confirmation per ask "docker" (line 0; in: git, docker)"#
     );
 }
 
@@ -132,7 +134,7 @@ fn reason_dont_ask_converts_ask_to_deny() {
     let input = bash_input("docker run", "dontAsk");
     assert_eq!(
         reason_of(&input, &config),
-        "claude-permissions-hook: 'docker' denied by dontAsk mode"
+        r#"claude-permissions-hook: 'docker' denied by dontAsk mode for ask "docker" (line 0)"#
     );
 }
 
@@ -142,18 +144,18 @@ fn reason_dont_ask_multi_converts_ask_to_deny() {
     let input = bash_input("git pull && docker run", "dontAsk");
     assert_eq!(
         reason_of(&input, &config),
-        "claude-permissions-hook: 'docker' denied by dontAsk mode (in: git, docker)"
+        r#"claude-permissions-hook: 'docker' denied by dontAsk mode for ask "docker" (line 0; in: git, docker)"#
     );
 }
 
 #[test]
 fn reason_explicit_deny_not_affected_by_mode_text() {
-    // An explicit deny in dontAsk mode should still say "deny list", not "dontAsk mode"
+    // An explicit deny in dontAsk mode should still name the rule, not "dontAsk mode"
     let config = make_config(&[], &["rm"], &[]);
     let input = bash_input("rm -rf /", "dontAsk");
     assert_eq!(
         reason_of(&input, &config),
-        "claude-permissions-hook: 'rm' is in your deny list"
+        r#"claude-permissions-hook: 'rm' denied by deny "rm" (line 0)"#
     );
 }
 
@@ -183,7 +185,7 @@ fn file_reason_deny() {
     let input = file_input("Write", "default", json!({"file_path": path.clone()}));
     assert_eq!(
         file_reason(&input, &config),
-        format!("claude-permissions-hook: '{path}' denied by file rules (write)")
+        format!(r#"claude-permissions-hook: '{path}' denied by deny "~/.ssh/**" (line 0; write)"#)
     );
 }
 
@@ -196,7 +198,24 @@ fn file_reason_ask() {
     let input = file_input("Edit", "default", json!({"file_path": "/etc/hosts"}));
     assert_eq!(
         file_reason(&input, &config),
-        "claude-permissions-hook: '/etc/hosts' requires confirmation (edit)"
+        r#"claude-permissions-hook: '/etc/hosts' requires confirmation per ask "/**" (line 0; edit)"#
+    );
+}
+
+#[test]
+fn file_reason_names_notebook_edit_operation() {
+    let config = make_files_config(FilesConfig {
+        ask: vec![file_rule("/**", &[FileOperation::NotebookEdit])],
+        ..Default::default()
+    });
+    let input = file_input(
+        "NotebookEdit",
+        "default",
+        json!({"notebook_path": "/tmp/a.ipynb"}),
+    );
+    assert_eq!(
+        file_reason(&input, &config),
+        r#"claude-permissions-hook: '/tmp/a.ipynb' requires confirmation per ask "/**" (line 0; notebook-edit)"#
     );
 }
 
@@ -209,7 +228,7 @@ fn file_reason_dont_ask_mode() {
     let input = file_input("Read", "dontAsk", json!({"file_path": "/tmp/secret.txt"}));
     assert_eq!(
         file_reason(&input, &config),
-        "claude-permissions-hook: '/tmp/secret.txt' denied by dontAsk mode (read)"
+        r#"claude-permissions-hook: '/tmp/secret.txt' denied by dontAsk mode for ask "/**" (line 0; read)"#
     );
 }
 
@@ -226,3 +245,88 @@ fn file_reason_fail_closed() {
         "expected fail-closed reason, got: {reason}"
     );
 }
+
+#[test]
+fn bash_redirect_deny_reason_names_path() {
+    let config = Config {
+        bash: Some(crate::config::BashConfig {
+            allow: super::rules_of(&["echo"]),
+            ..Default::default()
+        }),
+        files: Some(FilesConfig {
+            deny: vec![file_rule("/etc/**", &[FileOperation::Write])],
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    let input = bash_input("echo hi > /etc/hosts", "default");
+    assert_eq!(
+        reason_of(&input, &config),
+        r#"claude-permissions-hook: '/etc/hosts' denied by deny "/etc/**" (line 0; write)"#
+    );
+}
+
+// ---- Rule provenance ----
+
+#[test]
+fn reason_names_rule_text_and_line() {
+    let config = Config::parse(
+        r#"
+bash {
+    allow "git"
+    deny "rm -rf"
+}
+files {
+    ask "/etc/**" "write"
+}
+"#,
+    )
+    .unwrap();
+    let input = bash_input("rm -rf build", "default");
+    assert_eq!(
+        reason_of(&input, &config),
+        r#"claude-permissions-hook: 'rm' denied by deny "rm -rf" (line 4)"#
+    );
+    let input = file_input("Write", "default", json!({"file_path": "/etc/hosts"}));
+    assert_eq!(
+        file_reason(&input, &config),
+        r#"claude-permissions-hook: '/etc/hosts' requires confirmation per ask "/etc/**" (line 7; write)"#
+    );
+}
+
+#[test]
+fn reason_names_config_file() {
+    use std::io::Write;
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config.kdl");
+    std::fs::File::create(&path)
+        .unwrap()
+        .write_all(b"bash {\n    deny \"rm\"\n}\n")
+        .unwrap();
+    let config = Config::load(&path).unwrap();
+    let input = bash_input("rm -rf /", "default");
+    assert_eq!(
+        reason_of(&input, &config),
+        r#"claude-permissions-hook: 'rm' denied by deny "rm" (config.kdl:2)"#
+    );
+}
+
+#[test]
+fn reason_unresolved_path_has_no_rule() {
+    let config = make_files_config(FilesConfig {
+        deny: vec![file_rule("/etc/**", &[FileOperation::Write])],
+        ..Default::default()
+    });
+    let config = Config {
+        bash: Some(crate::config::BashConfig {
+            allow: super::rules_of(&["echo"]),
+            ..Default::default()
+        }),
+        ..config
+    };
+    let input = bash_input("echo hi > $OUT", "default");
+    assert_eq!(
+        reason_of(&input, &config),
+        "claude-permissions-hook: '$OUT' requires confirmation (write)"
+    );
+}
@@ -0,0 +1,321 @@
+use super::{bash_input, make_input};
+use crate::config::Config;
+use crate::decision::evaluate_tool;
+use crate::protocol::output::Decision;
+use crate::protocol::{HookInput, PreToolUseOutput};
+use serde_json::json;
+
+fn rewrite_config() -> Config {
+    Config::parse(
+        r#"
+        bash {
+            allow "rm" "git" "ls"
+            ask "terraform"
+            deny "shred"
+            rewrite "rm" { add-flags "-i"; }
+            rewrite "terraform apply" { add-flags "--dry-run"; }
+            rewrite "git push --force" { replace-flag "--force" "--force-with-lease"; }
+            rewrite "shred" { add-flags "-n1"; }
+        }
+        "#,
+    )
+    .unwrap()
+}
+
+fn output(input: &HookInput) -> PreToolUseOutput {
+    evaluate_tool(input, Some(&rewrite_config()))
+        .unwrap()
+        .hook_specific_output
+}
+
+#[test]
+fn allowed_command_is_rewritten() {
+    let out = output(&bash_input("rm -r build", "default"));
+    assert_eq!(out.permission_decision, Decision::Allow);
+    assert_eq!(
+        out.updated_input,
+        Some(json!({"command": "rm -i -r build"}))
+    );
+    assert!(
+        out.permission_decision_reason
+            .ends_with("; rewrote 'rm -r build' to 'rm -i -r build' per rewrite \"rm\" (line 6)"),
+        "got: {}",
+        out.permission_decision_reason
+    );
+}
+
+#[test]
+fn rewrite_keeps_other_tool_input_fields() {
+    let mut input = bash_input("git push --force origin main", "default");
+    input.tool_input["description"] = json!("Push");
+    let out = output(&input);
+    assert_eq!(
+        out.updated_input,
+        Some(json!({
+            "command": "git push --force-with-lease origin main",
+            "description": "Push"
+        }))
+    );
+}
+
+#[test]
+fn asked_command_is_rewritten_after_subcommand() {
+    let out = output(&bash_input("terraform apply -auto-approve", "default"));
+    assert_eq!(out.permission_decision, Decision::Ask);
+    assert_eq!(
+        out.updated_input,
+        Some(json!({"command": "terraform apply --dry-run -auto-approve"}))
+    );
+}
+
+#[test]
+fn bundled_force_flag_is_rewritten() {
+    let out = output(&bash_input("git push -fu origin main", "default"));
+    assert_eq!(out.permission_decision, Decision::Allow);
+    assert_eq!(
+        out.updated_input,
+        Some(json!({"command": "git push --force-with-lease -u origin main"}))
+    );
+}
+
+#[test]
+fn bundled_flag_is_removed() {
+    let config = Config::parse(
+        r#"
+        bash {
+            allow "rm"
+            rewrite "rm -f" { remove-flags "-f"; }
+        }
+        "#,
+    )
+    .unwrap();
+    let out = evaluate_tool(&bash_input("rm -rf x", "default"), Some(&config))
+        .unwrap()
+        .hook_specific_output;
+    assert_eq!(out.permission_decision, Decision::Allow);
+    assert_eq!(out.updated_input, Some(json!({"command": "rm -r x"})));
+}
+
+#[test]
+fn denied_command_is_not_rewritten() {
+    let out = output(&bash_input("shred secrets.txt", "default"));
+    assert_eq!(out.permission_decision, Decision::Deny);
+    assert_eq!(out.updated_input, None);
+}
+
+#[test]
+fn non_matching_or_noop_rewrite_leaves_input() {
+    assert_eq!(
+        output(&bash_input("git push origin", "default")).updated_input,
+        None
+    );
+    assert_eq!(
+        output(&bash_input("rm -i x", "default")).updated_input,
+        None
+    );
+}
+
+#[test]
+fn compound_command_fails_closed_to_ask() {
+    let out = output(&bash_input("ls && rm -r build", "default"));
+    assert_eq!(out.permission_decision, Decision::Ask);
+    assert_eq!(out.updated_input, None);
+    assert!(
+        out.permission_decision_reason
+            .contains("rewrite \"rm\" (line 6) cannot be applied to this command"),
+        "got: {}",
+        out.permission_decision_reason
+    );
+}
+
+#[test]
+fn redirected_command_fails_closed_to_ask() {
+    let out = output(&bash_input("rm -r build 2> err.log", "default"));
+    assert_eq!(out.permission_decision, Decision::Ask);
+}
+
+#[test]
+fn unrewritable_command_follows_mode() {
+    let out = output(&bash_input("ls && rm -r build", "dontAsk"));
+    assert_eq!(out.permission_decision, Decision::Deny);
+    let out = output(&bash_input("ls && rm -r build", "bypassPermissions"));
+    assert_eq!(out.permission_decision, Decision::Allow);
+    assert_eq!(out.updated_input, None);
+}
+
+#[test]
+fn quoted_words_fail_closed_to_ask() {
+    let out = output(&bash_input(r#"git push --force -o "ci skip""#, "default"));
+    assert_eq!(out.permission_decision, Decision::Ask);
+    assert_eq!(out.updated_input, None);
+}
+
+#[test]
+fn wrapped_command_fails_closed_to_ask() {
+    let out = output(&bash_input("nice rm -r build", "default"));
+    assert_eq!(out.permission_decision, Decision::Ask);
+}
+
+fn recheck_config() -> Config {
+    Config::parse(
+        r#"
+        bash {
+            allow "git" "curl" "wget -q"
+            deny "git push --force"
+            rewrite "git push" { add-flags "--force"; }
+            rewrite "curl" { remove-flags "-s"; }
+            rewrite "wget" { remove-flags "-q"; }
+        }
+        "#,
+    )
+    .unwrap()
+}
+
+#[test]
+fn rewritten_command_is_looked_up_again() {
+    let input = bash_input("git push origin", "default");
+    let out = evaluate_tool(&input, Some(&recheck_config()))
+        .unwrap()
+        .hook_specific_output;
+    assert_eq!(out.permission_decision, Decision::Deny);
+    assert_eq!(out.updated_input, None);
+    assert!(
+        out.permission_decision_reason
+            .contains("denied by deny \"git push --force\" (line 4)"),
+        "got: {}",
+        out.permission_decision_reason
+    );
+    assert!(
+        out.permission_decision_reason
+            .ends_with("per rewrite \"git push\" (line 5)"),
+        "got: {}",
+        out.permission_decision_reason
+    );
+}
+
+#[test]
+fn rewrite_that_keeps_the_decision_is_applied() {
+    let input = bash_input("curl -s example.com", "default");
+    let out = evaluate_tool(&input, Some(&recheck_config()))
+        .unwrap()
+        .hook_specific_output;
+    assert_eq!(out.permission_decision, Decision::Allow);
+    assert_eq!(
+        out.updated_input,
+        Some(json!({"command": "curl example.com"}))
+    );
+}
+
+#[test]
+fn rewritten_command_no_rule_covers_asks() {
+    let input = bash_input("wget -q example.com", "default");
+    let out = evaluate_tool(&input, Some(&recheck_config()))
+        .unwrap()
+        .hook_specific_output;
+    assert_eq!(out.permission_decision, Decision::Ask);
+    assert_eq!(
+        out.updated_input,
+        Some(json!({"command": "wget example.com"}))
+    );
+    assert!(
+        out.permission_decision_reason
+            .contains("rewritten command matches no rule; requires confirmation"),
+        "got: {}",
+        out.permission_decision_reason
+    );
+}
+
+// --- File tool rewrites ---
+
+fn file_rewrite_config() -> Config {
+    Config::parse(
+        r#"
+        files {
+            allow "<cwd>/**" "read" "grep"
+            deny "/etc/shadow" "read"
+            rewrite "<cwd>/.env" "read" { path "<cwd>/.env.example"; }
+            rewrite "<cwd>/secrets/**" "grep" { path "<cwd>/src"; }
+            rewrite "<cwd>/shadow" "read" { path "/etc/shadow"; }
+            rewrite "<cwd>/elsewhere" "read" { path "/srv/elsewhere"; }
+        }
+        "#,
+    )
+    .unwrap()
+}
+
+fn file_output(tool: &str, tool_input: serde_json::Value) -> PreToolUseOutput {
+    let input = make_input(tool, "default", tool_input);
+    evaluate_tool(&input, Some(&file_rewrite_config()))
+        .unwrap()
+        .hook_specific_output
+}
+
+#[test]
+fn file_read_is_clamped_to_target() {
+    let out = file_output("Read", json!({"file_path": "/home/user/project/.env"}));
+    assert_eq!(out.permission_decision, Decision::Allow);
+    assert_eq!(
+        out.updated_input,
+        Some(json!({"file_path": "/home/user/project/.env.example"}))
+    );
+    assert!(
+        out.permission_decision_reason.ends_with(
+            "; rewrote '/home/user/project/.env' to '/home/user/project/.env.example' \
+             per rewrite \"<cwd>/.env\" (line 5)"
+        ),
+        "got: {}",
+        out.permission_decision_reason
+    );
+}
+
+#[test]
+fn file_rewrite_keeps_other_tool_input_fields() {
+    let out = file_output(
+        "Grep",
+        json!({"pattern": "key", "path": "/home/user/project/secrets/prod"}),
+    );
+    assert_eq!(out.permission_decision, Decision::Allow);
+    assert_eq!(
+        out.updated_input,
+        Some(json!({"pattern": "key", "path": "/home/user/project/src"}))
+    );
+}
+
+#[test]
+fn file_rewrite_to_denied_path_is_denied() {
+    let out = file_output("Read", json!({"file_path": "/home/user/project/shadow"}));
+    assert_eq!(out.permission_decision, Decision::Deny);
+    assert_eq!(out.updated_input, None);
+}
+
+#[test]
+fn file_rewrite_to_unlisted_path_asks() {
+    let out = file_output("Read", json!({"file_path": "/home/user/project/elsewhere"}));
+    assert_eq!(out.permission_decision, Decision::Ask);
+    assert_eq!(
+        out.updated_input,
+        Some(json!({"file_path": "/srv/elsewhere"}))
+    );
+    assert!(
+        out.permission_decision_reason
+            .contains("rewritten path matches no rule; requires confirmation"),
+        "got: {}",
+        out.permission_decision_reason
+    );
+}
+
+#[test]
+fn file_rewrite_ignores_other_operations_and_paths() {
+    let input = make_input(
+        "Edit",
+        "default",
+        json!({"file_path": "/home/user/project/.env"}),
+    );
+    assert!(evaluate_tool(&input, Some(&file_rewrite_config())).is_none());
+    let out = file_output(
+        "Read",
+        json!({"file_path": "/home/user/project/src/main.rs"}),
+    );
+    assert_eq!(out.permission_decision, Decision::Allow);
+    assert_eq!(out.updated_input, None);
+}
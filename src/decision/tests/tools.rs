@@ -0,0 +1,96 @@
+use super::make_input;
+use crate::config::Config;
+use crate::decision::evaluate_tool;
+use crate::protocol::output::Decision;
+use crate::protocol::HookInput;
+use serde_json::json;
+
+fn tools_config() -> Config {
+    Config::parse(
+        r#"
+        tools {
+            allow "Task" "ExitPlanMode"
+            deny "Task" {
+                argument "subagent_type" "*admin*"
+            }
+            ask "Todo*"
+        }
+        "#,
+    )
+    .unwrap()
+}
+
+fn tool_decision(input: &HookInput, config: &Config) -> Decision {
+    evaluate_tool(input, Some(config))
+        .unwrap()
+        .hook_specific_output
+        .permission_decision
+}
+
+#[test]
+fn generic_tool_allowed() {
+    let input = make_input("ExitPlanMode", "default", json!({"plan": "x"}));
+    assert_eq!(tool_decision(&input, &tools_config()), Decision::Allow);
+}
+
+#[test]
+fn generic_tool_argument_condition_denies() {
+    let input = make_input(
+        "Task",
+        "bypassPermissions",
+        json!({"subagent_type": "cluster-admin", "prompt": "x"}),
+    );
+    assert_eq!(tool_decision(&input, &tools_config()), Decision::Deny);
+}
+
+#[test]
+fn generic_tool_argument_mismatch_falls_to_allow() {
+    let input = make_input("Task", "default", json!({"subagent_type": "reviewer"}));
+    assert_eq!(tool_decision(&input, &tools_config()), Decision::Allow);
+}
+
+#[test]
+fn generic_tool_ask_modulated_by_mode() {
+    let config = tools_config();
+    let input = make_input("TodoWrite", "default", json!({"todos": []}));
+    assert_eq!(tool_decision(&input, &config), Decision::Ask);
+    let input = make_input("TodoWrite", "dontAsk", json!({"todos": []}));
+    assert_eq!(tool_decision(&input, &config), Decision::Deny);
+}
+
+#[test]
+fn generic_tool_unlisted_returns_none() {
+    let input = make_input("SlashCommand", "default", json!({}));
+    assert!(evaluate_tool(&input, Some(&tools_config())).is_none());
+}
+
+#[test]
+fn generic_tool_rules_do_not_apply_to_dedicated_tools() {
+    let config = Config::parse(r#"tools { deny "*" }"#).unwrap();
+    let input = make_input("Read", "default", json!({"file_path": "/tmp/x"}));
+    assert!(evaluate_tool(&input, Some(&config)).is_none());
+}
+
+#[test]
+fn generic_tool_deny_reason_names_tool() {
+    let input = make_input("Task", "default", json!({"subagent_type": "admin"}));
+    assert_eq!(
+        evaluate_tool(&input, Some(&tools_config()))
+            .unwrap()
+            .hook_specific_output
+            .permission_decision_reason,
+        "claude-permissions-hook: tool 'Task' denied by deny \"Task\" (line 4)"
+    );
+}
+
+#[test]
+fn generic_tool_ask_reason_names_rule() {
+    let input = make_input("TodoWrite", "default", json!({}));
+    assert_eq!(
+        evaluate_tool(&input, Some(&tools_config()))
+            .unwrap()
+            .hook_specific_output
+            .permission_decision_reason,
+        "claude-permissions-hook: tool 'TodoWrite' requires confirmation per ask \"Todo*\" (line 7)"
+    );
+}
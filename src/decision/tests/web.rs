@@ -0,0 +1,169 @@
+use super::make_input;
+use crate::config::Config;
+use crate::decision::evaluate_tool;
+use crate::protocol::output::Decision;
+use crate::protocol::HookInput;
+use serde_json::json;
+
+fn web_config() -> Config {
+    Config::parse(
+        r#"
+        web {
+            allow "docs.rs" "*.github.com"
+            deny "*.pastebin.com"
+            ask "https://example.com/admin/**"
+            search {
+                allow "*"
+                deny "*password*"
+            }
+        }
+        "#,
+    )
+    .unwrap()
+}
+
+fn fetch_input(url: &str, mode: &str) -> HookInput {
+    make_input("WebFetch", mode, json!({"url": url, "prompt": "summarize"}))
+}
+
+fn search_input(query: &str, mode: &str) -> HookInput {
+    make_input("WebSearch", mode, json!({"query": query}))
+}
+
+fn web_decision(input: &HookInput, config: &Config) -> Decision {
+    evaluate_tool(input, Some(config))
+        .unwrap()
+        .hook_specific_output
+        .permission_decision
+}
+
+fn web_reason(input: &HookInput, config: &Config) -> String {
+    evaluate_tool(input, Some(config))
+        .unwrap()
+        .hook_specific_output
+        .permission_decision_reason
+}
+
+// ---- WebFetch ----
+
+#[test]
+fn fetch_allowed_domain() {
+    let input = fetch_input("https://docs.rs/serde/latest/serde/", "default");
+    assert_eq!(web_decision(&input, &web_config()), Decision::Allow);
+}
+
+#[test]
+fn fetch_allowed_subdomain_glob() {
+    let input = fetch_input("https://raw.github.com/x/y", "default");
+    assert_eq!(web_decision(&input, &web_config()), Decision::Allow);
+}
+
+#[test]
+fn fetch_denied_domain() {
+    let input = fetch_input("https://www.pastebin.com/raw/abc", "default");
+    assert_eq!(web_decision(&input, &web_config()), Decision::Deny);
+}
+
+#[test]
+fn fetch_backslash_authority_is_not_the_allowed_host() {
+    let input = fetch_input("https://evil.com\\@docs.rs/", "default");
+    assert!(evaluate_tool(&input, Some(&web_config())).is_none());
+}
+
+#[test]
+fn fetch_percent_encoded_host_is_decoded() {
+    let input = fetch_input("https://www.pastebin%2ecom/raw", "default");
+    assert_eq!(web_decision(&input, &web_config()), Decision::Deny);
+}
+
+#[test]
+fn fetch_ask_path_modulated_by_mode() {
+    let config = web_config();
+    let input = fetch_input("https://example.com/admin/users", "default");
+    assert_eq!(web_decision(&input, &config), Decision::Ask);
+    let input = fetch_input("https://example.com/admin/users", "bypassPermissions");
+    assert_eq!(web_decision(&input, &config), Decision::Allow);
+    let input = fetch_input("https://example.com/admin/users", "dontAsk");
+    assert_eq!(web_decision(&input, &config), Decision::Deny);
+}
+
+#[test]
+fn fetch_unlisted_domain_returns_none() {
+    let input = fetch_input("https://example.org/", "default");
+    assert!(evaluate_tool(&input, Some(&web_config())).is_none());
+}
+
+#[test]
+fn fetch_missing_url_fails_closed() {
+    let input = make_input("WebFetch", "default", json!({"prompt": "summarize"}));
+    assert_eq!(web_decision(&input, &web_config()), Decision::Ask);
+}
+
+#[test]
+fn fetch_unparseable_url_fails_closed() {
+    let input = fetch_input("docs.rs/serde", "default");
+    assert_eq!(web_decision(&input, &web_config()), Decision::Ask);
+}
+
+#[test]
+fn fetch_without_web_config_returns_none() {
+    let config = Config::parse(r#"bash { allow "git" }"#).unwrap();
+    let input = fetch_input("https://docs.rs/", "default");
+    assert!(evaluate_tool(&input, Some(&config)).is_none());
+}
+
+#[test]
+fn fetch_deny_reason_names_host() {
+    let input = fetch_input("https://www.pastebin.com/raw/abc", "default");
+    assert_eq!(
+        web_reason(&input, &web_config()),
+        "claude-permissions-hook: 'www.pastebin.com' denied by deny \"*.pastebin.com\" (line 4; fetch)"
+    );
+}
+
+#[test]
+fn fetch_ask_reason_names_rule() {
+    let input = fetch_input("https://example.com/admin/users", "default");
+    assert_eq!(
+        web_reason(&input, &web_config()),
+        "claude-permissions-hook: 'example.com' requires confirmation per \
+         ask \"https://example.com/admin/**\" (line 5; fetch)"
+    );
+}
+
+#[test]
+fn search_dont_ask_reason_names_rule() {
+    let config = Config::parse(r#"web { search { ask "*"; }; }"#).unwrap();
+    let input = search_input("rust", "dontAsk");
+    assert_eq!(
+        web_reason(&input, &config),
+        "claude-permissions-hook: 'rust' denied by dontAsk mode for ask \"*\" (line 1; search)"
+    );
+}
+
+// ---- WebSearch ----
+
+#[test]
+fn search_allowed_query() {
+    let input = search_input("rust globset case insensitive", "default");
+    assert_eq!(web_decision(&input, &web_config()), Decision::Allow);
+}
+
+#[test]
+fn search_denied_query() {
+    let input = search_input("acme admin Password dump", "default");
+    assert_eq!(web_decision(&input, &web_config()), Decision::Deny);
+}
+
+#[test]
+fn search_missing_query_fails_closed() {
+    let input = make_input("WebSearch", "default", json!({}));
+    assert_eq!(web_decision(&input, &web_config()), Decision::Ask);
+}
+
+#[test]
+fn search_without_search_rules_returns_none() {
+    let config = Config::parse(r#"web { allow "docs.rs" }"#).unwrap();
+    let input = search_input("anything", "default");
+    assert!(evaluate_tool(&input, Some(&config)).is_none());
+}
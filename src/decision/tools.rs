@@ -0,0 +1,33 @@
+use crate::config::Config;
+use crate::protocol::output::Decision;
+use crate::protocol::{HookInput, HookOutput};
+
+use super::aggregation::apply_mode_modifier;
+use super::audit::Audit;
+use super::reason::build_tool_reason;
+
+/// Evaluate a tool without a dedicated engine against the `tools` section.
+///
+/// Rules match the tool name and, optionally, `argument` conditions over
+/// `tool_input`. Returns `None` without a `tools` section or when no rule matches.
+pub(super) fn evaluate_generic_tool(
+    tool_name: &str,
+    input: &HookInput,
+    config: &Config,
+    audit: &mut Audit,
+) -> Option<HookOutput> {
+    let found = config
+        .tools
+        .as_ref()?
+        .lookup_match(tool_name, &input.tool_input);
+    audit.looked_up("tool", tool_name, found.as_ref());
+    let found = found?;
+
+    let modified = apply_mode_modifier(found.decision.clone(), &input.permission_mode);
+    let reason = build_tool_reason(&modified, tool_name, &found, &config.sources);
+    Some(match modified {
+        Decision::Allow => HookOutput::allow(reason),
+        Decision::Ask => HookOutput::ask(reason),
+        Decision::Deny => HookOutput::deny(reason),
+    })
+}
@@ -0,0 +1,105 @@
+use crate::config::web::Url;
+use crate::config::{Config, RuleMatch, WebConfig};
+use crate::protocol::output::Decision;
+use crate::protocol::{HookInput, HookOutput, ToolUse};
+
+use super::aggregation::apply_mode_modifier;
+use super::audit::Audit;
+use super::reason::build_web_reason;
+use super::APP_NAME;
+
+/// A web tool call looked up against the `web` section.
+enum WebLookup {
+    /// The input has no URL or query; names the missing field.
+    Missing(&'static str),
+    /// The URL could not be parsed.
+    Unparseable(String),
+    /// A URL or query was looked up.
+    Looked {
+        /// `url` or `query`.
+        kind: &'static str,
+        /// The URL or query as given.
+        subject: String,
+        /// What reasons name: the host of a URL, or the query.
+        target: String,
+        /// `fetch` or `search`.
+        action: &'static str,
+        found: Option<RuleMatch>,
+    },
+}
+
+/// Look up a WebFetch URL or a WebSearch query. Returns `None` for other
+/// tools.
+///
+/// URLs are matched by scheme, host, and path; queries against the `search`
+/// block.
+fn lookup_web(tool_use: &ToolUse, web_config: &WebConfig) -> Option<WebLookup> {
+    Some(match tool_use {
+        ToolUse::WebFetch { url: None } => WebLookup::Missing("URL"),
+        ToolUse::WebFetch { url: Some(raw) } => match Url::parse(raw) {
+            Some(url) => WebLookup::Looked {
+                kind: "url",
+                subject: raw.clone(),
+                found: web_config.lookup_url_match(&url),
+                target: url.host,
+                action: "fetch",
+            },
+            None => WebLookup::Unparseable(raw.clone()),
+        },
+        ToolUse::WebSearch { query: None } => WebLookup::Missing("query"),
+        ToolUse::WebSearch { query: Some(query) } => WebLookup::Looked {
+            kind: "query",
+            subject: query.clone(),
+            target: query.clone(),
+            action: "search",
+            found: web_config.lookup_query_match(query),
+        },
+        _ => return None,
+    })
+}
+
+/// Evaluate a WebFetch or WebSearch invocation against web config rules.
+///
+/// A missing or unparseable URL fails closed to ask. Returns `None` without
+/// a `web` section or when no rule matches.
+pub(super) fn evaluate_web_tool(
+    tool_use: &ToolUse,
+    input: &HookInput,
+    config: &Config,
+    audit: &mut Audit,
+) -> Option<HookOutput> {
+    let web_config = config.web.as_ref()?;
+
+    let (found, target, action) = match lookup_web(tool_use, web_config)? {
+        WebLookup::Missing(field) => {
+            return Some(HookOutput::ask(format!(
+                "{APP_NAME}: no {field} provided for {} tool",
+                input.tool_name
+            )));
+        }
+        WebLookup::Unparseable(raw) => {
+            audit.unresolved("url", &raw);
+            return Some(HookOutput::ask(format!(
+                "{APP_NAME}: could not parse URL '{raw}'"
+            )));
+        }
+        WebLookup::Looked {
+            kind,
+            subject,
+            found,
+            target,
+            action,
+        } => {
+            audit.looked_up(kind, subject, found.as_ref());
+            (found?, target, action)
+        }
+    };
+
+    let modified = apply_mode_modifier(found.decision.clone(), &input.permission_mode);
+    let reason = build_web_reason(&modified, &target, &found, action, &config.sources);
+    Some(match modified {
+        Decision::Allow => HookOutput::allow(reason),
+        Decision::Ask => HookOutput::ask(reason),
+        Decision::Deny => HookOutput::deny(reason),
+    })
+}
@@ -12,6 +12,49 @@ pub(crate) mod path;
 /// This is the binary entry point. It exists to bridge the binary crate (`main.rs`)
 /// to the library without exposing `cli` internals. Not a stable integration API —
 /// callers should use [`decision::evaluate`] and [`config::Config`] directly.
-pub fn run_hook(config_path: Option<&std::path::Path>) {
-    cli::hook::run(config_path)
+pub fn run_hook(config_path: Option<&std::path::Path>, log_path: Option<&std::path::Path>) {
+    cli::hook::run(config_path, log_path)
+}
+
+/// Run the check subcommand: report a config's errors and lint warnings.
+///
+/// Bridges the binary crate to the library like [`run_hook`].
+pub fn run_check(config_path: Option<&std::path::Path>) {
+    cli::check::run(config_path)
+}
+
+/// Run the test subcommand: check the config's `tests` cases.
+///
+/// Bridges the binary crate to the library like [`run_hook`].
+pub fn run_test(config_path: Option<&std::path::Path>) {
+    cli::test_cases::run(config_path)
+}
+
+/// Run the trust subcommand: let the hook load a project's config.
+///
+/// Bridges the binary crate to the library like [`run_hook`].
+pub fn run_trust(dir: Option<&std::path::Path>, pin: bool, remove: bool) {
+    cli::trust::run(dir, pin, remove)
+}
+
+/// Run the explain subcommand: print how a tool call would be decided.
+///
+/// Bridges the binary crate to the library like [`run_hook`]. `command` is
+/// the Bash command's words; `path` the path given to a file tool.
+pub fn run_explain(
+    config_path: Option<&std::path::Path>,
+    tool: Option<&str>,
+    command: &[String],
+    path: Option<&str>,
+    mode: Option<&str>,
+    cwd: Option<&std::path::Path>,
+) {
+    let request = cli::explain::ExplainRequest {
+        tool,
+        command,
+        path,
+        mode,
+        cwd,
+    };
+    cli::explain::run(config_path, &request)
 }
@@ -11,11 +11,63 @@ struct Cli {
 
 #[derive(Debug, Subcommand)]
 enum Commands {
-    /// Run as a Claude Code PreToolUse hook (reads stdin, writes stdout)
+    /// Run as a Claude Code hook (reads stdin, writes stdout)
     Hook {
         /// Path to the KDL config file
         #[arg(long)]
         config: Option<PathBuf>,
+        /// Append a JSON line per evaluation to this file (overrides the config's `log`)
+        #[arg(long)]
+        log: Option<PathBuf>,
+    },
+    /// Validate a config file and warn about rules that cannot work as written
+    #[command(alias = "lint")]
+    Check {
+        /// Path to the KDL config file
+        #[arg(long)]
+        config: Option<PathBuf>,
+    },
+    /// Run the `expect` cases in the config's `tests` section
+    Test {
+        /// Path to the KDL config file
+        #[arg(long)]
+        config: Option<PathBuf>,
+    },
+    /// Trust the project config (`.claude/permissions.kdl`) found from a directory
+    ///
+    /// The hook ignores a project config until its project is trusted.
+    Trust {
+        /// Directory in the project (default: the current one)
+        dir: Option<PathBuf>,
+        /// Trust only the config's current content, and the files it includes
+        #[arg(long, conflicts_with = "remove")]
+        pin: bool,
+        /// Stop trusting the project
+        #[arg(long)]
+        remove: bool,
+    },
+    /// Trace how a tool call would be decided, step by step
+    ///
+    /// Bash: `explain -- git push --force`. File tools: `explain --tool Read --path FILE`.
+    Explain {
+        /// Path to the KDL config file
+        #[arg(long)]
+        config: Option<PathBuf>,
+        /// Tool name (default: Bash)
+        #[arg(long)]
+        tool: Option<String>,
+        /// Path for a file tool
+        #[arg(long)]
+        path: Option<String>,
+        /// Permission mode (default, plan, acceptEdits, dontAsk, bypassPermissions)
+        #[arg(long)]
+        mode: Option<String>,
+        /// Working directory (default: the current one)
+        #[arg(long)]
+        cwd: Option<PathBuf>,
+        /// The Bash command, after `--`
+        #[arg(last = true)]
+        command: Vec<String>,
     },
 }
 
@@ -23,6 +75,28 @@ fn main() {
     let cli = Cli::parse();
 
     match cli.command {
-        Commands::Hook { config } => claude_permissions_hook::run_hook(config.as_deref()),
+        Commands::Hook { config, log } => {
+            claude_permissions_hook::run_hook(config.as_deref(), log.as_deref())
+        }
+        Commands::Check { config } => claude_permissions_hook::run_check(config.as_deref()),
+        Commands::Test { config } => claude_permissions_hook::run_test(config.as_deref()),
+        Commands::Trust { dir, pin, remove } => {
+            claude_permissions_hook::run_trust(dir.as_deref(), pin, remove)
+        }
+        Commands::Explain {
+            config,
+            tool,
+            path,
+            mode,
+            cwd,
+            command,
+        } => claude_permissions_hook::run_explain(
+            config.as_deref(),
+            tool.as_deref(),
+            &command,
+            path.as_deref(),
+            mode.as_deref(),
+            cwd.as_deref(),
+        ),
     }
 }
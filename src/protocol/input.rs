@@ -1,26 +1,70 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
-/// The input received from Claude Code on stdin for a PreToolUse hook.
+/// The input received from Claude Code on stdin for a hook event.
 ///
 /// Field names match the snake_case JSON that Claude Code sends.
 /// Unknown fields are silently ignored for forward compatibility.
+/// Tool fields are empty for events without a tool (`UserPromptSubmit`),
+/// and `prompt` is only present for that event.
 #[derive(Debug, Deserialize)]
 pub struct HookInput {
     pub session_id: String,
     pub transcript_path: String,
     pub cwd: String,
+    #[serde(default)]
     pub permission_mode: PermissionMode,
     pub hook_event_name: String,
+    #[serde(default)]
     pub tool_name: String,
+    #[serde(default)]
     pub tool_input: Value,
+    #[serde(default)]
     pub tool_use_id: String,
+    #[serde(default)]
+    pub prompt: Option<String>,
+}
+
+impl HookInput {
+    /// The hook event this input was sent for.
+    pub fn event(&self) -> HookEvent {
+        HookEvent::from_name(&self.hook_event_name)
+    }
+}
+
+/// Hook events the binary knows how to answer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookEvent {
+    /// Before a tool runs: allow, ask, or deny the call.
+    PreToolUse,
+    /// When Claude Code is about to show a permission dialog.
+    PermissionRequest,
+    /// After a tool ran: feed a block reason back to Claude.
+    PostToolUse,
+    /// Before a user prompt is processed.
+    UserPromptSubmit,
+    /// Any other event; the hook has no opinion.
+    Other,
+}
+
+impl HookEvent {
+    /// Map a `hook_event_name` value to an event.
+    pub fn from_name(name: &str) -> Self {
+        match name {
+            "PreToolUse" => HookEvent::PreToolUse,
+            "PermissionRequest" => HookEvent::PermissionRequest,
+            "PostToolUse" => HookEvent::PostToolUse,
+            "UserPromptSubmit" => HookEvent::UserPromptSubmit,
+            _ => HookEvent::Other,
+        }
+    }
 }
 
 /// Claude Code's permission modes.
-#[derive(Debug, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
 pub enum PermissionMode {
+    #[default]
     Default,
     Plan,
     AcceptEdits,
@@ -28,6 +72,31 @@ pub enum PermissionMode {
     BypassPermissions,
 }
 
+impl PermissionMode {
+    /// The mode's name as Claude Code sends it.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PermissionMode::Default => "default",
+            PermissionMode::Plan => "plan",
+            PermissionMode::AcceptEdits => "acceptEdits",
+            PermissionMode::DontAsk => "dontAsk",
+            PermissionMode::BypassPermissions => "bypassPermissions",
+        }
+    }
+
+    /// Look up a mode by the name Claude Code sends.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "default" => Some(PermissionMode::Default),
+            "plan" => Some(PermissionMode::Plan),
+            "acceptEdits" => Some(PermissionMode::AcceptEdits),
+            "dontAsk" => Some(PermissionMode::DontAsk),
+            "bypassPermissions" => Some(PermissionMode::BypassPermissions),
+            _ => None,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -91,6 +160,41 @@ mod tests {
         assert_eq!(parsed.session_id, "sess-123");
     }
 
+    #[test]
+    fn event_names_map_to_events() {
+        let events = [
+            ("PreToolUse", HookEvent::PreToolUse),
+            ("PermissionRequest", HookEvent::PermissionRequest),
+            ("PostToolUse", HookEvent::PostToolUse),
+            ("UserPromptSubmit", HookEvent::UserPromptSubmit),
+            ("SessionStart", HookEvent::Other),
+        ];
+        for (name, expected) in events {
+            let mut input = minimal_input_json();
+            input["hook_event_name"] = json!(name);
+            let parsed: HookInput = serde_json::from_value(input).expect("should parse event");
+            assert_eq!(parsed.event(), expected, "failed for {name}");
+        }
+    }
+
+    #[test]
+    fn user_prompt_submit_without_tool_fields() {
+        let input = json!({
+            "session_id": "sess-123",
+            "transcript_path": "/tmp/transcript.json",
+            "cwd": "/tmp/test",
+            "permission_mode": "default",
+            "hook_event_name": "UserPromptSubmit",
+            "prompt": "deploy to prod"
+        });
+        let parsed: HookInput =
+            serde_json::from_value(input).expect("prompt events carry no tool fields");
+        assert_eq!(parsed.event(), HookEvent::UserPromptSubmit);
+        assert_eq!(parsed.prompt.as_deref(), Some("deploy to prod"));
+        assert_eq!(parsed.tool_name, "");
+        assert!(parsed.tool_input.is_null());
+    }
+
     #[test]
     fn extra_fields_in_tool_input_are_preserved() {
         let input = json!({
@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
 /// The output returned to Claude Code on stdout.
 #[derive(Debug, Serialize)]
@@ -50,12 +51,30 @@ impl HookOutput {
         Self::with_decision(Decision::Deny, reason)
     }
 
+    /// Replace the tool call's input with `updated_input` when it runs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use claude_permissions_hook::protocol::HookOutput;
+    /// use serde_json::json;
+    ///
+    /// let output = HookOutput::allow("rewritten").with_updated_input(json!({"command": "rm -i x"}));
+    /// let value = serde_json::to_value(&output).unwrap();
+    /// assert_eq!(value["hookSpecificOutput"]["updatedInput"]["command"], "rm -i x");
+    /// ```
+    pub fn with_updated_input(mut self, updated_input: Value) -> Self {
+        self.hook_specific_output.updated_input = Some(updated_input);
+        self
+    }
+
     fn with_decision(decision: Decision, reason: impl Into<String>) -> Self {
         Self {
             hook_specific_output: PreToolUseOutput {
                 hook_event_name: "PreToolUse".to_string(),
                 permission_decision: decision,
                 permission_decision_reason: reason.into(),
+                updated_input: None,
             },
         }
     }
@@ -68,6 +87,143 @@ pub struct PreToolUseOutput {
     pub hook_event_name: String,
     pub permission_decision: Decision,
     pub permission_decision_reason: String,
+    /// Full replacement `tool_input`, sent only when a rule rewrote the call.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated_input: Option<Value>,
+}
+
+/// The output for any hook event, in that event's shape.
+///
+/// Serializes untagged: each variant produces exactly the JSON Claude Code
+/// expects for its event.
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum EventOutput {
+    /// `PreToolUse`: allow, ask, or deny the tool call.
+    PreToolUse(HookOutput),
+    /// `PermissionRequest`: answer the permission dialog.
+    PermissionRequest(PermissionRequestOutput),
+    /// `PostToolUse` or `UserPromptSubmit`: block with a reason.
+    Block(BlockOutput),
+}
+
+impl EventOutput {
+    /// Approve a permission dialog without showing it, optionally running
+    /// the tool with `updated_input` instead of the original input.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use claude_permissions_hook::protocol::EventOutput;
+    ///
+    /// let json = serde_json::to_value(EventOutput::permission_allow(None)).unwrap();
+    /// assert_eq!(json["hookSpecificOutput"]["decision"]["behavior"], "allow");
+    /// ```
+    pub fn permission_allow(updated_input: Option<Value>) -> Self {
+        Self::permission(Behavior::Allow, None, updated_input)
+    }
+
+    /// Reject a permission dialog without showing it; `message` tells Claude why.
+    pub fn permission_deny(message: impl Into<String>) -> Self {
+        Self::permission(Behavior::Deny, Some(message.into()), None)
+    }
+
+    /// Block a `PostToolUse` or `UserPromptSubmit` event with a reason.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use claude_permissions_hook::protocol::EventOutput;
+    ///
+    /// let json = serde_json::to_value(EventOutput::block("PostToolUse", "denied")).unwrap();
+    /// assert_eq!(json["decision"], "block");
+    /// assert_eq!(json["hookSpecificOutput"]["hookEventName"], "PostToolUse");
+    /// ```
+    pub fn block(hook_event_name: impl Into<String>, reason: impl Into<String>) -> Self {
+        EventOutput::Block(BlockOutput {
+            decision: BlockDecision::Block,
+            reason: reason.into(),
+            hook_specific_output: EventName {
+                hook_event_name: hook_event_name.into(),
+            },
+        })
+    }
+
+    fn permission(
+        behavior: Behavior,
+        message: Option<String>,
+        updated_input: Option<Value>,
+    ) -> Self {
+        EventOutput::PermissionRequest(PermissionRequestOutput {
+            hook_specific_output: PermissionRequestDecision {
+                hook_event_name: "PermissionRequest".to_string(),
+                decision: PermissionBehavior {
+                    behavior,
+                    message,
+                    updated_input,
+                },
+            },
+        })
+    }
+}
+
+/// The output for a `PermissionRequest` event.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PermissionRequestOutput {
+    pub hook_specific_output: PermissionRequestDecision,
+}
+
+/// PermissionRequest-specific output containing the dialog decision.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PermissionRequestDecision {
+    pub hook_event_name: String,
+    pub decision: PermissionBehavior,
+}
+
+/// How to answer a permission dialog.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PermissionBehavior {
+    pub behavior: Behavior,
+    /// Reason shown to Claude; only sent with `deny`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// Replacement `tool_input`; only sent with `allow`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated_input: Option<Value>,
+}
+
+/// A permission dialog answer: there is no "ask", since the dialog is the ask.
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Behavior {
+    Allow,
+    Deny,
+}
+
+/// The output for a blocked `PostToolUse` or `UserPromptSubmit` event.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockOutput {
+    pub decision: BlockDecision,
+    pub reason: String,
+    pub hook_specific_output: EventName,
+}
+
+/// The only top-level decision these events accept.
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum BlockDecision {
+    Block,
+}
+
+/// Event-specific output that carries only the event name.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventName {
+    pub hook_event_name: String,
 }
 
 /// The permission decision: allow, ask, or deny.
@@ -115,6 +271,71 @@ mod tests {
         );
     }
 
+    #[test]
+    fn pre_tool_use_event_output_serializes_unwrapped() {
+        let output = EventOutput::PreToolUse(HookOutput::deny("blocked"));
+        let json = serde_json::to_value(&output).expect("should serialize");
+        assert_eq!(
+            json,
+            json!({
+                "hookSpecificOutput": {
+                    "hookEventName": "PreToolUse",
+                    "permissionDecision": "deny",
+                    "permissionDecisionReason": "blocked"
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn permission_request_outputs_serialize_to_expected_json() {
+        let allow = serde_json::to_value(EventOutput::permission_allow(None)).unwrap();
+        assert_eq!(
+            allow,
+            json!({
+                "hookSpecificOutput": {
+                    "hookEventName": "PermissionRequest",
+                    "decision": {"behavior": "allow"}
+                }
+            })
+        );
+        let deny = serde_json::to_value(EventOutput::permission_deny("no")).unwrap();
+        assert_eq!(
+            deny,
+            json!({
+                "hookSpecificOutput": {
+                    "hookEventName": "PermissionRequest",
+                    "decision": {"behavior": "deny", "message": "no"}
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn block_output_serializes_to_expected_json() {
+        let output = EventOutput::block("UserPromptSubmit", "not allowed");
+        assert_eq!(
+            serde_json::to_value(&output).unwrap(),
+            json!({
+                "decision": "block",
+                "reason": "not allowed",
+                "hookSpecificOutput": {"hookEventName": "UserPromptSubmit"}
+            })
+        );
+    }
+
+    #[test]
+    fn updated_input_serializes_only_when_set() {
+        let output = HookOutput::ask("rewritten").with_updated_input(json!({"command": "rm -i x"}));
+        let json = serde_json::to_value(&output).expect("should serialize");
+        assert_eq!(
+            json["hookSpecificOutput"]["updatedInput"],
+            json!({"command": "rm -i x"})
+        );
+        let json = serde_json::to_value(HookOutput::ask("plain")).unwrap();
+        assert!(json["hookSpecificOutput"].get("updatedInput").is_none());
+    }
+
     #[test]
     fn decision_severity_allow_less_than_ask_less_than_deny() {
         assert!(Decision::Allow.severity() < Decision::Ask.severity());
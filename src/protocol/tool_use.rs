@@ -8,6 +8,10 @@ pub enum FileOperation {
     Edit,
     Glob,
     Grep,
+    /// Jupyter notebook cell edit (`NotebookEdit`).
+    NotebookEdit,
+    /// Directory listing (`LS`).
+    List,
 }
 
 /// Typed representation of a tool invocation, parsed at the protocol boundary.
@@ -27,6 +31,12 @@ pub enum ToolUse {
     Write { file_path: Option<String> },
     /// File edit operation.
     Edit { file_path: Option<String> },
+    /// Several edits to one file, checked as an edit.
+    MultiEdit { file_path: Option<String> },
+    /// Jupyter notebook cell edit.
+    NotebookEdit { notebook_path: Option<String> },
+    /// Directory listing.
+    Ls { path: Option<String> },
     /// Glob pattern search.
     Glob {
         /// Search directory (None = use cwd).
@@ -37,6 +47,18 @@ pub enum ToolUse {
         /// Search directory (None = use cwd).
         path: Option<String>,
     },
+    /// Web page fetch.
+    WebFetch {
+        /// URL from tool_input["url"].
+        url: Option<String>,
+    },
+    /// Web search.
+    WebSearch {
+        /// Query text from tool_input["query"].
+        query: Option<String>,
+    },
+    /// MCP server tool, named `mcp__<server>__<tool>`.
+    Mcp { server: String, tool: String },
     /// Unrecognized tool — hook has no opinion.
     Unknown { tool_name: String },
 }
@@ -84,14 +106,35 @@ impl ToolUse {
             "Edit" => ToolUse::Edit {
                 file_path: extract_string(tool_input, "file_path"),
             },
+            "MultiEdit" => ToolUse::MultiEdit {
+                file_path: extract_string(tool_input, "file_path"),
+            },
+            "NotebookEdit" => ToolUse::NotebookEdit {
+                notebook_path: extract_string(tool_input, "notebook_path"),
+            },
+            "LS" => ToolUse::Ls {
+                path: extract_string(tool_input, "path"),
+            },
             "Glob" => ToolUse::Glob {
                 path: extract_string(tool_input, "path"),
             },
             "Grep" => ToolUse::Grep {
                 path: extract_string(tool_input, "path"),
             },
-            _ => ToolUse::Unknown {
-                tool_name: tool_name.to_string(),
+            "WebFetch" => ToolUse::WebFetch {
+                url: extract_string(tool_input, "url"),
+            },
+            "WebSearch" => ToolUse::WebSearch {
+                query: extract_string(tool_input, "query"),
+            },
+            _ => match split_mcp_name(tool_name) {
+                Some((server, tool)) => ToolUse::Mcp {
+                    server: server.to_string(),
+                    tool: tool.to_string(),
+                },
+                None => ToolUse::Unknown {
+                    tool_name: tool_name.to_string(),
+                },
             },
         }
     }
@@ -101,23 +144,46 @@ impl ToolUse {
         match self {
             ToolUse::Read { .. } => Some(FileOperation::Read),
             ToolUse::Write { .. } => Some(FileOperation::Write),
-            ToolUse::Edit { .. } => Some(FileOperation::Edit),
+            ToolUse::Edit { .. } | ToolUse::MultiEdit { .. } => Some(FileOperation::Edit),
+            ToolUse::NotebookEdit { .. } => Some(FileOperation::NotebookEdit),
+            ToolUse::Ls { .. } => Some(FileOperation::List),
             ToolUse::Glob { .. } => Some(FileOperation::Glob),
             ToolUse::Grep { .. } => Some(FileOperation::Grep),
             _ => None,
         }
     }
 
+    /// The `tool_input` field holding a file tool's path.
+    ///
+    /// Non-file tools: returns None.
+    pub fn path_field(&self) -> Option<&'static str> {
+        match self {
+            ToolUse::Read { .. }
+            | ToolUse::Write { .. }
+            | ToolUse::Edit { .. }
+            | ToolUse::MultiEdit { .. } => Some("file_path"),
+            ToolUse::NotebookEdit { .. } => Some("notebook_path"),
+            ToolUse::Ls { .. } | ToolUse::Glob { .. } | ToolUse::Grep { .. } => Some("path"),
+            _ => None,
+        }
+    }
+
     /// Extract file paths from a file tool variant.
     ///
-    /// - Read/Write/Edit: returns the file_path if present, or empty vec (fail-closed).
+    /// - Read/Write/Edit/MultiEdit/NotebookEdit/LS: returns the path if present,
+    ///   or empty vec (fail-closed).
     /// - Glob/Grep: returns the path if present, or cwd as default.
     /// - Non-file tools: returns None.
     pub fn file_paths(&self, cwd: &str) -> Option<Vec<String>> {
         match self {
             ToolUse::Read { file_path }
             | ToolUse::Write { file_path }
-            | ToolUse::Edit { file_path } => {
+            | ToolUse::Edit { file_path }
+            | ToolUse::MultiEdit { file_path }
+            | ToolUse::NotebookEdit {
+                notebook_path: file_path,
+            }
+            | ToolUse::Ls { path: file_path } => {
                 let paths = match file_path {
                     Some(p) => vec![p.clone()],
                     None => vec![],
@@ -133,6 +199,12 @@ impl ToolUse {
     }
 }
 
+/// Split `mcp__<server>__<tool>` into server and tool names.
+fn split_mcp_name(tool_name: &str) -> Option<(&str, &str)> {
+    let (server, tool) = tool_name.strip_prefix("mcp__")?.split_once("__")?;
+    (!server.is_empty() && !tool.is_empty()).then_some((server, tool))
+}
+
 /// Extract a non-empty string field from JSON.
 fn extract_string(value: &Value, field: &str) -> Option<String> {
     value
@@ -210,6 +282,44 @@ mod tests {
         assert_eq!(tool_use.file_operation(), Some(FileOperation::Edit));
     }
 
+    #[test]
+    fn multi_edit_extracts_file_path() {
+        let tool_use = ToolUse::parse(
+            "MultiEdit",
+            &json!({"file_path": "/foo/lib.rs", "edits": [{"old_string": "a", "new_string": "b"}]}),
+        );
+        assert_eq!(tool_use.file_operation(), Some(FileOperation::Edit));
+        assert_eq!(tool_use.file_paths(CWD).unwrap(), vec!["/foo/lib.rs"]);
+    }
+
+    #[test]
+    fn notebook_edit_extracts_notebook_path() {
+        let tool_use = ToolUse::parse(
+            "NotebookEdit",
+            &json!({"notebook_path": "/foo/analysis.ipynb", "new_source": "print(1)"}),
+        );
+        match &tool_use {
+            ToolUse::NotebookEdit { notebook_path } => {
+                assert_eq!(notebook_path.as_deref(), Some("/foo/analysis.ipynb"))
+            }
+            _ => panic!("expected NotebookEdit variant"),
+        }
+        assert_eq!(tool_use.file_operation(), Some(FileOperation::NotebookEdit));
+    }
+
+    #[test]
+    fn ls_extracts_path() {
+        let tool_use = ToolUse::parse("LS", &json!({"path": "/foo"}));
+        assert_eq!(tool_use.file_operation(), Some(FileOperation::List));
+        assert_eq!(tool_use.file_paths(CWD).unwrap(), vec!["/foo"]);
+    }
+
+    #[test]
+    fn ls_missing_path_returns_empty_paths() {
+        let tool_use = ToolUse::parse("LS", &json!({}));
+        assert!(tool_use.file_paths(CWD).unwrap().is_empty());
+    }
+
     #[test]
     fn glob_extracts_explicit_path() {
         let tool_use = ToolUse::parse("Glob", &json!({"pattern": "**/*.rs", "path": "/src"}));
@@ -244,24 +354,74 @@ mod tests {
         assert_eq!(paths, vec![CWD.to_string()]);
     }
 
+    // ---- Web tool parsing ----
+
+    #[test]
+    fn web_fetch_extracts_url() {
+        let tool_use = ToolUse::parse(
+            "WebFetch",
+            &json!({"url": "https://docs.rs", "prompt": "summarize"}),
+        );
+        match &tool_use {
+            ToolUse::WebFetch { url } => assert_eq!(url.as_deref(), Some("https://docs.rs")),
+            _ => panic!("expected WebFetch variant"),
+        }
+        assert!(tool_use.file_operation().is_none());
+        assert!(tool_use.file_paths(CWD).is_none());
+    }
+
+    #[test]
+    fn web_search_extracts_query() {
+        let tool_use = ToolUse::parse("WebSearch", &json!({"query": "rust globset"}));
+        match tool_use {
+            ToolUse::WebSearch { query } => assert_eq!(query.as_deref(), Some("rust globset")),
+            _ => panic!("expected WebSearch variant"),
+        }
+    }
+
+    #[test]
+    fn web_fetch_missing_url_yields_none() {
+        let tool_use = ToolUse::parse("WebFetch", &json!({"prompt": "summarize"}));
+        match tool_use {
+            ToolUse::WebFetch { url } => assert!(url.is_none()),
+            _ => panic!("expected WebFetch variant"),
+        }
+    }
+
     // ---- Unknown tools ----
 
     #[test]
     fn unknown_tool() {
-        let tool_use = ToolUse::parse("NotebookEdit", &json!({}));
+        let tool_use = ToolUse::parse("TodoWrite", &json!({}));
         assert_eq!(tool_use.file_operation(), None);
         match tool_use {
-            ToolUse::Unknown { tool_name } => assert_eq!(tool_name, "NotebookEdit"),
+            ToolUse::Unknown { tool_name } => assert_eq!(tool_name, "TodoWrite"),
             _ => panic!("expected Unknown variant"),
         }
     }
 
     #[test]
-    fn mcp_tool_is_unknown() {
-        let tool_use = ToolUse::parse("mcp__test__run", &json!({"server": "test"}));
+    fn mcp_tool_splits_server_and_tool() {
+        let tool_use = ToolUse::parse("mcp__github__create_pull_request", &json!({}));
         match tool_use {
-            ToolUse::Unknown { tool_name } => assert_eq!(tool_name, "mcp__test__run"),
-            _ => panic!("expected Unknown variant"),
+            ToolUse::Mcp { server, tool } => {
+                assert_eq!(server, "github");
+                assert_eq!(tool, "create_pull_request");
+            }
+            _ => panic!("expected Mcp variant"),
+        }
+        assert!(ToolUse::parse("mcp__github__x", &json!({}))
+            .file_operation()
+            .is_none());
+    }
+
+    #[test]
+    fn malformed_mcp_name_is_unknown() {
+        for name in ["mcp__github", "mcp____run", "mcp__github__"] {
+            match ToolUse::parse(name, &json!({})) {
+                ToolUse::Unknown { tool_name } => assert_eq!(tool_name, name),
+                _ => panic!("expected Unknown variant for {name}"),
+            }
         }
     }
 
@@ -313,7 +473,7 @@ mod tests {
 
     #[test]
     fn unknown_file_paths_returns_none() {
-        let tool_use = ToolUse::parse("NotebookEdit", &json!({}));
+        let tool_use = ToolUse::parse("TodoWrite", &json!({}));
         assert!(tool_use.file_paths(CWD).is_none());
     }
 }
@@ -28,13 +28,13 @@ fn contract_output_has_hook_specific_output() {
 }
 
 #[test]
-fn contract_hook_event_name_is_pre_tool_use() {
+fn contract_hook_event_name_echoes_pre_tool_use() {
     let input = bash_input_json("ls", "default");
     let (stdout, _, _) = run_hook(&input);
     let value = parse_hook_output(&stdout);
     assert_eq!(
         value["hookSpecificOutput"]["hookEventName"], "PreToolUse",
-        "hookEventName must always be PreToolUse"
+        "hookEventName must echo the PreToolUse event"
     );
 }
 
@@ -194,3 +194,23 @@ fn contract_no_opinion_returns_empty_json_object() {
         "no-opinion must return empty JSON object"
     );
 }
+
+// ---- Plugin registration ----
+
+#[test]
+fn contract_plugin_registers_every_handled_event() {
+    let hooks: serde_json::Value = serde_json::from_str(include_str!("../hooks/hooks.json"))
+        .expect("hooks.json must be valid JSON");
+    for event in [
+        "PreToolUse",
+        "PermissionRequest",
+        "PostToolUse",
+        "UserPromptSubmit",
+    ] {
+        let command = &hooks["hooks"][event][0]["hooks"][0]["command"];
+        assert!(
+            command.as_str().is_some_and(|c| c.ends_with(" hook")),
+            "hooks.json must run the hook for {event}, got: {command}"
+        );
+    }
+}
@@ -4,8 +4,12 @@
 
 mod common;
 
+use std::io::Write;
+use std::path::Path;
+
 use common::{
-    bash_input_json, binary_path, make_input_json, parse_hook_output, run_hook_with_config,
+    bash_input_json, binary_path, make_input_json, parse_hook_output, run_hook_args,
+    run_hook_with_config,
 };
 
 const FLOW_CONFIG: &str = r#"
@@ -136,6 +140,129 @@ fn flow_multi_command_pipe_highest_severity_wins() {
     assert_decision(&stdout, "deny");
 }
 
+// ---- Hook event flows ----
+
+fn event_input_json(event: &str, command: &str) -> String {
+    let mut value: serde_json::Value =
+        serde_json::from_str(&bash_input_json(command, "default")).unwrap();
+    value["hook_event_name"] = serde_json::json!(event);
+    value.to_string()
+}
+
+#[test]
+fn flow_permission_request_allow() {
+    let input = event_input_json("PermissionRequest", "git status");
+    let (stdout, _, exit_code) = run_hook_with_config(&input, FLOW_CONFIG);
+    assert_eq!(exit_code, 0);
+    let value = parse_hook_output(&stdout);
+    assert_eq!(
+        value["hookSpecificOutput"],
+        serde_json::json!({"hookEventName": "PermissionRequest", "decision": {"behavior": "allow"}})
+    );
+}
+
+#[test]
+fn flow_permission_request_ask_leaves_dialog() {
+    let input = event_input_json("PermissionRequest", "docker run nginx");
+    let (stdout, _, exit_code) = run_hook_with_config(&input, FLOW_CONFIG);
+    assert_eq!(exit_code, 0);
+    assert_empty(&stdout);
+}
+
+#[test]
+fn flow_post_tool_use_deny_blocks() {
+    let input = event_input_json("PostToolUse", "rm -rf /");
+    let (stdout, _, exit_code) = run_hook_with_config(&input, FLOW_CONFIG);
+    assert_eq!(exit_code, 0);
+    let value = parse_hook_output(&stdout);
+    assert_eq!(value["decision"], "block");
+    assert_eq!(value["hookSpecificOutput"]["hookEventName"], "PostToolUse");
+}
+
+#[test]
+fn flow_user_prompt_submit_block() {
+    let input = serde_json::json!({
+        "session_id": "sess-e2e-test",
+        "transcript_path": "/tmp/transcript.json",
+        "cwd": "/tmp/test",
+        "permission_mode": "default",
+        "hook_event_name": "UserPromptSubmit",
+        "prompt": "please deploy to PROD now"
+    })
+    .to_string();
+    let config = r#"prompt { deny "*deploy to prod*" }"#;
+    let (stdout, _, exit_code) = run_hook_with_config(&input, config);
+    assert_eq!(exit_code, 0);
+    let value = parse_hook_output(&stdout);
+    assert_eq!(value["decision"], "block");
+    assert_eq!(
+        value["hookSpecificOutput"]["hookEventName"],
+        "UserPromptSubmit"
+    );
+}
+
+// ---- Decision log ----
+
+fn run_hook_with_log(stdin_input: &str, log_path: &str) -> (String, String, i32) {
+    use std::io::Write;
+    let mut config = tempfile::NamedTempFile::new().expect("failed to create temp config");
+    config
+        .write_all(FLOW_CONFIG.as_bytes())
+        .expect("failed to write config");
+    let config_path = config.path().to_str().unwrap().to_string();
+    run_hook_args(stdin_input, &["--config", &config_path, "--log", log_path])
+}
+
+#[test]
+fn flow_log_appends_one_line_per_evaluation() {
+    let dir = tempfile::tempdir().unwrap();
+    let log_path = dir.path().join("decisions.jsonl");
+    let log_path = log_path.to_str().unwrap();
+
+    let (stdout, _, _) =
+        run_hook_with_log(&bash_input_json("git status | rm x", "default"), log_path);
+    assert_decision(&stdout, "deny");
+    let (stdout, _, _) = run_hook_with_log(&bash_input_json("make", "default"), log_path);
+    assert_empty(&stdout);
+
+    let log = std::fs::read_to_string(log_path).unwrap();
+    let records: Vec<serde_json::Value> = log
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("each line is JSON"))
+        .collect();
+    assert_eq!(records.len(), 2);
+
+    let first = &records[0];
+    assert_eq!(first["session_id"], "sess-e2e-test");
+    assert_eq!(first["event"], "PreToolUse");
+    assert_eq!(first["tool"], "Bash");
+    assert_eq!(first["mode"], "default");
+    assert_eq!(first["decision"], "deny");
+    assert!(first["reason"].as_str().unwrap().contains("rm"));
+    assert!(first["timestamp"].as_str().unwrap().ends_with('Z'));
+    let items = first["items"].as_array().unwrap();
+    assert_eq!(items[0]["subject"], "git status");
+    assert_eq!(items[0]["decision"], "allow");
+    assert_eq!(items[0]["line"], 3);
+    assert_eq!(items[1]["subject"], "rm x");
+    assert_eq!(items[1]["decision"], "deny");
+    assert_eq!(items[1]["line"], 4);
+
+    assert_eq!(records[1]["decision"], serde_json::Value::Null);
+    assert_eq!(records[1]["items"][0]["decision"], serde_json::Value::Null);
+}
+
+#[test]
+fn flow_log_failure_leaves_output_unchanged() {
+    let dir = tempfile::tempdir().unwrap();
+    let unwritable = dir.path().join("missing-dir/decisions.jsonl");
+    let input = bash_input_json("git status", "default");
+    let (stdout, stderr, exit_code) = run_hook_with_log(&input, unwritable.to_str().unwrap());
+    assert_eq!(exit_code, 0);
+    assert_eq!(stderr, "");
+    assert_decision(&stdout, "allow");
+}
+
 // ---- Regression: $HOME unset must not panic ----
 
 /// When $HOME is not set, file-tool lookups must return a graceful JSON
@@ -193,3 +320,263 @@ fn flow_home_unset_file_tool_returns_valid_json() {
         "expected fail-closed ask when $HOME is unset"
     );
 }
+
+// ---- Layered config: user layer plus a trusted project layer ----
+
+/// Runs the hook for a Bash command with `cwd` as the input's working
+/// directory, and `home` and the user config in place of the real ones.
+fn run_hook_in(home: &Path, user_config: &Path, cwd: &Path, command: &str) -> serde_json::Value {
+    let mut input: serde_json::Value =
+        serde_json::from_str(&bash_input_json(command, "default")).unwrap();
+    input["cwd"] = cwd.to_string_lossy().into();
+    let mut child = std::process::Command::new(binary_path())
+        .arg("hook")
+        .env("HOME", home)
+        .env("CLAUDE_PERMISSIONS_HOOK_CONFIG", user_config)
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .spawn()
+        .expect("failed to execute binary");
+    let mut stdin = child.stdin.take().unwrap();
+    stdin.write_all(input.to_string().as_bytes()).unwrap();
+    drop(stdin);
+    let output = child.wait_with_output().unwrap();
+    parse_hook_output(&String::from_utf8(output.stdout).unwrap())
+}
+
+fn run_trust(home: &Path, args: &[&str]) -> (String, Option<i32>) {
+    let output = std::process::Command::new(binary_path())
+        .arg("trust")
+        .args(args)
+        .env("HOME", home)
+        .output()
+        .expect("failed to execute binary");
+    (
+        String::from_utf8(output.stdout).unwrap(),
+        output.status.code(),
+    )
+}
+
+#[test]
+fn flow_layered_configs_combine() {
+    let dir = tempfile::tempdir().unwrap();
+    let user_config = dir.path().join("user.kdl");
+    std::fs::write(
+        &user_config,
+        "bash {\n    allow \"git\"\n    deny \"rm\"\n}\n",
+    )
+    .unwrap();
+    let project = dir.path().join("project");
+    std::fs::create_dir_all(project.join(".claude")).unwrap();
+    std::fs::create_dir_all(project.join("src")).unwrap();
+    std::fs::write(
+        project.join(".claude/team-base.kdl"),
+        "bash {\n    allow \"cargo\"\n}\n",
+    )
+    .unwrap();
+    let log_path = dir.path().join("decisions.jsonl");
+    std::fs::write(
+        project.join(".claude/permissions.kdl"),
+        format!(
+            "include \"team-base.kdl\"\nlog {{\n    path \"{}\"\n}}\n",
+            log_path.display()
+        ),
+    )
+    .unwrap();
+    let (_, code) = run_trust(dir.path(), &[project.to_str().unwrap()]);
+    assert_eq!(code, Some(0));
+
+    let cwd = project.join("src");
+    let allowed = run_hook_in(dir.path(), &user_config, &cwd, "git status && cargo build");
+    assert_eq!(allowed["hookSpecificOutput"]["permissionDecision"], "allow");
+    let denied = run_hook_in(
+        dir.path(),
+        &user_config,
+        &cwd,
+        "cargo build && rm -rf target",
+    );
+    assert_eq!(denied["hookSpecificOutput"]["permissionDecision"], "deny");
+    assert_eq!(
+        denied["hookSpecificOutput"]["permissionDecisionReason"],
+        "claude-permissions-hook: 'rm' denied by deny \"rm\" (user.kdl:3; in: cargo, rm)"
+    );
+
+    let log = std::fs::read_to_string(log_path).unwrap();
+    let record: serde_json::Value = serde_json::from_str(log.lines().nth(1).unwrap()).unwrap();
+    let items = record["items"].as_array().unwrap();
+    assert_eq!(items[0]["line"], 2);
+    assert!(items[0]["file"]
+        .as_str()
+        .unwrap()
+        .ends_with("team-base.kdl"));
+    assert_eq!(items[1]["line"], 3);
+    assert!(items[1]["file"].as_str().unwrap().ends_with("user.kdl"));
+}
+
+#[test]
+fn flow_project_config_needs_trust() {
+    let dir = tempfile::tempdir().unwrap();
+    let user_config = dir.path().join("user.kdl");
+    std::fs::write(&user_config, "bash {\n    allow \"git\"\n}\n").unwrap();
+    let project = dir.path().join("project");
+    std::fs::create_dir_all(project.join(".claude")).unwrap();
+    let project_config = project.join(".claude/permissions.kdl");
+    std::fs::write(&project_config, "bash {\n    allow \"terraform plan\"\n}\n").unwrap();
+    let decision =
+        |value: serde_json::Value| value["hookSpecificOutput"]["permissionDecision"].clone();
+
+    // Untrusted: the project config is ignored.
+    let untrusted = run_hook_in(dir.path(), &user_config, &project, "terraform plan");
+    assert_eq!(untrusted, serde_json::json!({}));
+
+    // Pinned: trusted until the config changes.
+    let (stdout, code) = run_trust(dir.path(), &["--pin", project.to_str().unwrap()]);
+    assert_eq!(code, Some(0));
+    assert!(stdout.starts_with("trusted: "), "{stdout}");
+    assert!(stdout.contains("(pinned to sha256 "), "{stdout}");
+    let pinned = run_hook_in(dir.path(), &user_config, &project, "terraform plan");
+    assert_eq!(decision(pinned), "allow");
+    std::fs::write(&project_config, "bash {\n    allow \"terraform\"\n}\n").unwrap();
+    let changed = run_hook_in(dir.path(), &user_config, &project, "terraform apply");
+    assert_eq!(changed, serde_json::json!({}));
+
+    // Removed: ignored again, even unpinned.
+    run_trust(dir.path(), &[project.to_str().unwrap()]);
+    let trusted = run_hook_in(dir.path(), &user_config, &project, "terraform apply");
+    assert_eq!(decision(trusted), "allow");
+    let (stdout, code) = run_trust(dir.path(), &["--remove", project.to_str().unwrap()]);
+    assert_eq!(code, Some(0));
+    assert!(stdout.starts_with("no longer trusted: "), "{stdout}");
+    let removed = run_hook_in(dir.path(), &user_config, &project, "terraform apply");
+    assert_eq!(removed, serde_json::json!({}));
+}
+
+#[test]
+fn flow_trust_without_project_config_fails() {
+    let dir = tempfile::tempdir().unwrap();
+    let (stdout, code) = run_trust(dir.path(), &[dir.path().to_str().unwrap()]);
+    assert_eq!(code, Some(1));
+    assert!(
+        stdout.starts_with("no .claude/permissions.kdl in "),
+        "{stdout}"
+    );
+}
+
+#[test]
+fn flow_explain_traces_command() {
+    let dir = tempfile::tempdir().unwrap();
+    let config_path = dir.path().join("config.kdl");
+    std::fs::write(&config_path, FLOW_CONFIG).unwrap();
+
+    let output = std::process::Command::new(binary_path())
+        .args(["explain", "--config", config_path.to_str().unwrap(), "--"])
+        .arg("sudo env FOO=1 rm -rf build")
+        .output()
+        .expect("failed to execute binary");
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!(output.status.code(), Some(0));
+    assert!(stdout.contains("Unwrapped:\n  sudo (launcher"), "{stdout}");
+    assert!(
+        stdout.contains("  => deny \"rm\" (config.kdl:4)"),
+        "{stdout}"
+    );
+    assert!(stdout.contains("Decision: deny"), "{stdout}");
+}
+
+#[test]
+fn flow_explain_reports_config_error() {
+    let output = std::process::Command::new(binary_path())
+        .args(["explain", "--config", "/nonexistent/config.kdl", "--", "ls"])
+        .output()
+        .expect("failed to execute binary");
+    let stderr = String::from_utf8(output.stderr).unwrap();
+
+    assert_ne!(output.status.code(), Some(0));
+    assert!(stderr.contains("Config error"), "{stderr}");
+}
+
+fn run_check(config: &str) -> (String, Option<i32>) {
+    let dir = tempfile::tempdir().unwrap();
+    let config_path = dir.path().join("config.kdl");
+    std::fs::write(&config_path, config).unwrap();
+    let output = std::process::Command::new(binary_path())
+        .args(["check", "--config", config_path.to_str().unwrap()])
+        .output()
+        .expect("failed to execute binary");
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let name = config_path.display().to_string();
+    (stdout.replace(&name, "config.kdl"), output.status.code())
+}
+
+#[test]
+fn flow_check_reports_warnings_and_succeeds() {
+    let (stdout, code) =
+        run_check("bash {\n    deny \"git\"\n    allow \"git status\"\n}\nbsah {\n}\n");
+    assert_eq!(code, Some(0));
+    assert_eq!(
+        stdout,
+        "config.kdl:3: warning: allow \"git status\" can never allow anything: \
+         deny \"git\" (config.kdl:2) matches everything it does first\n\
+         config.kdl:5: warning: unknown section \"bsah\" is ignored; did you mean \"bash\"?\n\
+         config.kdl: ok, 2 warnings\n"
+    );
+}
+
+#[test]
+fn flow_check_clean_config() {
+    let (stdout, code) = run_check(FLOW_CONFIG);
+    assert_eq!(code, Some(0));
+    assert_eq!(stdout, "config.kdl: ok\n");
+}
+
+#[test]
+fn flow_check_reports_syntax_error_with_line_and_column() {
+    let (stdout, code) = run_check("bash {\n    deny \"git\n}\n");
+    assert_eq!(code, Some(1));
+    assert!(
+        stdout.starts_with("config.kdl: error: invalid KDL syntax: line 2, column 10:"),
+        "{stdout}"
+    );
+}
+
+fn run_test_subcommand(config: &str) -> (String, Option<i32>) {
+    let dir = tempfile::tempdir().unwrap();
+    let config_path = dir.path().join("config.kdl");
+    std::fs::write(&config_path, config).unwrap();
+    let output = std::process::Command::new(binary_path())
+        .args(["test", "--config", config_path.to_str().unwrap()])
+        .output()
+        .expect("failed to execute binary");
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let name = config_path.display().to_string();
+    (stdout.replace(&name, "config.kdl"), output.status.code())
+}
+
+#[test]
+fn flow_test_passing_cases() {
+    let config = format!(
+        "{FLOW_CONFIG}\ntests {{\n    expect \"deny\" bash=\"rm -rf /\"\n    expect \"allow\" read=\"<cwd>/src/lib.rs\" cwd=\"/repo\"\n}}\n"
+    );
+    let (stdout, code) = run_test_subcommand(&config);
+    assert_eq!(code, Some(0), "{stdout}");
+    assert_eq!(stdout, "config.kdl: 2 passed, 0 failed\n");
+}
+
+#[test]
+fn flow_test_failing_case_exits_non_zero() {
+    let config = format!("{FLOW_CONFIG}\ntests {{\n    expect \"allow\" bash=\"docker ps\"\n}}\n");
+    let line = config.lines().count() - 1;
+    let (stdout, code) = run_test_subcommand(&config);
+    assert_eq!(code, Some(1));
+    assert!(
+        stdout.starts_with(&format!(
+            "config.kdl:{line}: FAIL bash=\"docker ps\": expected allow, got ask\n    reason: "
+        )),
+        "{stdout}"
+    );
+    assert!(
+        stdout.ends_with("config.kdl: 0 passed, 1 failed\n"),
+        "{stdout}"
+    );
+}
@@ -5,7 +5,6 @@
 use std::io::Write;
 use std::path::PathBuf;
 use std::process::Command;
-use tempfile::NamedTempFile;
 
 pub fn binary_path() -> PathBuf {
     let path = PathBuf::from(env!("CARGO_BIN_EXE_claude-permissions-hook"));
@@ -17,13 +16,15 @@ pub fn run_hook(stdin_input: &str) -> (String, String, i32) {
     run_hook_args(stdin_input, &[])
 }
 
+/// Runs the binary with the config written to `config.kdl` in a temp dir,
+/// so reasons that name the config file are stable.
 pub fn run_hook_with_config(stdin_input: &str, config_content: &str) -> (String, String, i32) {
-    let mut tmpfile = NamedTempFile::new().expect("failed to create temp config");
-    tmpfile
-        .write_all(config_content.as_bytes())
+    let dir = tempfile::tempdir().expect("failed to create temp dir");
+    let config_path = dir.path().join("config.kdl");
+    std::fs::File::create(&config_path)
+        .and_then(|mut file| file.write_all(config_content.as_bytes()))
         .expect("failed to write config");
-    let config_path = tmpfile.path().to_str().unwrap().to_string();
-    run_hook_args(stdin_input, &["--config", &config_path])
+    run_hook_args(stdin_input, &["--config", config_path.to_str().unwrap()])
 }
 
 /// Runs the binary with the given stdin and extra args.